@@ -0,0 +1,230 @@
+//! LaTeX engines behind one interface: how each is invoked, what it leaves
+//! behind and which PDF settings it can apply.
+//!
+//! Every [`LatexEngine`] has a [`RenderBackend`], found with
+//! [`LatexEngine::backend`]; compiling, the commands shown by
+//! `--print-commands` and the capability checks before a batch all go
+//! through it.
+//!
+//! ```
+//! use equation_processor::render::PdfOptions;
+//! use equation_processor::LatexEngine;
+//!
+//! let pdf = PdfOptions {
+//!     compression: Some(9),
+//!     ..PdfOptions::default()
+//! };
+//! let backend = LatexEngine::Xelatex.backend();
+//! assert_eq!(backend.program(), "xelatex");
+//! assert_eq!(
+//!     backend.pdf_setup(&pdf).unwrap(),
+//!     "\\special{dvipdfmx:config z 9}"
+//! );
+//! ```
+
+use crate::error::EquationError;
+use crate::model::LatexEngine;
+use crate::render::{PdfOptions, TectonicConfig};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// One LaTeX engine: compiling a `.tex` file into a PDF.
+pub trait RenderBackend: Sync {
+    /// The engine this backend runs.
+    fn engine(&self) -> LatexEngine;
+
+    /// The program run, looked up on `PATH`.
+    fn program(&self) -> &'static str {
+        self.engine().program()
+    }
+
+    /// The command compiling `tex_path` into `output_dir` as `config` says.
+    fn command(&self, config: &TectonicConfig, tex_path: &Path, output_dir: &Path) -> Command;
+
+    /// Extensions of the files the engine leaves next to the PDF, removed
+    /// after a successful run.
+    fn leftovers(&self) -> &'static [&'static str] {
+        &["aux", "log"]
+    }
+
+    /// The error for a failed run of `tex`, given what the engine printed.
+    fn failure(&self, _config: &TectonicConfig, tex: PathBuf, log: String) -> EquationError {
+        EquationError::LatexCompileFailed {
+            engine: self.engine(),
+            tex,
+            log,
+        }
+    }
+
+    /// LaTeX applying `pdf` to the output, placed at the start of the
+    /// document; an error saying what the engine cannot do otherwise.
+    fn pdf_setup(&self, pdf: &PdfOptions) -> Result<String, String>;
+}
+
+/// Tectonic, with its own bundle and cache, writing PDFs with `xdvipdfmx`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TectonicBackend;
+
+/// The system `pdflatex`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PdflatexBackend;
+
+/// The system `xelatex`, writing PDFs with `xdvipdfmx`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct XelatexBackend;
+
+/// The system `lualatex`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LualatexBackend;
+
+impl LatexEngine {
+    /// The backend compiling with this engine.
+    pub fn backend(self) -> &'static dyn RenderBackend {
+        match self {
+            LatexEngine::Tectonic => &TectonicBackend,
+            LatexEngine::Pdflatex => &PdflatexBackend,
+            LatexEngine::Xelatex => &XelatexBackend,
+            LatexEngine::Lualatex => &LualatexBackend,
+        }
+    }
+}
+
+/// A TeX Live engine's command: stop at the first error, and write into
+/// `output_dir`. Only the environment variables of `config` apply.
+fn tex_live_command(
+    program: &str,
+    config: &TectonicConfig,
+    tex_path: &Path,
+    output_dir: &Path,
+) -> Command {
+    let mut cmd = Command::new(program);
+    let mut output_arg = OsString::from("-output-directory=");
+    output_arg.push(output_dir);
+    cmd.args(["-interaction=nonstopmode", "-halt-on-error"])
+        .arg(output_arg);
+    config.set_env(&mut cmd);
+    cmd.arg(tex_path);
+    cmd
+}
+
+/// The `\special`s configuring `xdvipdfmx`, which tectonic and xelatex
+/// write their PDFs with.
+fn xdvipdfmx_setup(pdf: &PdfOptions) -> Result<String, String> {
+    if pdf.full_fonts {
+        return Err("cannot embed complete fonts; xdvipdfmx always subsets them".into());
+    }
+    Ok(pdf.specials())
+}
+
+/// Compression and version for pdfTeX's and LuaTeX's PDF writer: `set`
+/// turns a parameter name such as `compresslevel` and its value into an
+/// assignment. Versions before 1.5 have no object streams.
+fn pdf_parameters(pdf: &PdfOptions, set: impl Fn(&str, u8) -> String) -> String {
+    let mut setup = String::new();
+    if let Some(level) = pdf.compression {
+        setup.push_str(&set("compresslevel", level));
+    }
+    if let Some(minor) = pdf.minor_version {
+        if minor < 5 {
+            setup.push_str(&set("objcompresslevel", 0));
+        }
+        setup.push_str(&set("minorversion", minor));
+    }
+    setup
+}
+
+impl RenderBackend for TectonicBackend {
+    fn engine(&self) -> LatexEngine {
+        LatexEngine::Tectonic
+    }
+
+    fn command(&self, config: &TectonicConfig, tex_path: &Path, output_dir: &Path) -> Command {
+        let mut cmd = config.command();
+        cmd.arg(tex_path).arg("--outdir").arg(output_dir);
+        cmd
+    }
+
+    fn leftovers(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// In offline mode a failure is reported as
+    /// [`EquationError::ResourcesNotCached`], since it usually means a
+    /// resource is missing from the cache and would have to be downloaded.
+    fn failure(&self, config: &TectonicConfig, tex: PathBuf, log: String) -> EquationError {
+        if config.offline {
+            return EquationError::ResourcesNotCached { tex, log };
+        }
+        EquationError::LatexCompileFailed {
+            engine: self.engine(),
+            tex,
+            log,
+        }
+    }
+
+    fn pdf_setup(&self, pdf: &PdfOptions) -> Result<String, String> {
+        xdvipdfmx_setup(pdf)
+    }
+}
+
+impl RenderBackend for PdflatexBackend {
+    fn engine(&self) -> LatexEngine {
+        LatexEngine::Pdflatex
+    }
+
+    fn command(&self, config: &TectonicConfig, tex_path: &Path, output_dir: &Path) -> Command {
+        tex_live_command(self.program(), config, tex_path, output_dir)
+    }
+
+    /// `\pdfcompresslevel` and `\pdfminorversion`; pdfTeX's font map
+    /// decides about subsetting, so complete fonts cannot be asked for.
+    fn pdf_setup(&self, pdf: &PdfOptions) -> Result<String, String> {
+        if pdf.full_fonts {
+            return Err("cannot embed complete fonts; its font map subsets them".into());
+        }
+        Ok(pdf_parameters(pdf, |name, value| {
+            format!("\\pdf{name}={value}\\relax")
+        }))
+    }
+}
+
+impl RenderBackend for XelatexBackend {
+    fn engine(&self) -> LatexEngine {
+        LatexEngine::Xelatex
+    }
+
+    fn command(&self, config: &TectonicConfig, tex_path: &Path, output_dir: &Path) -> Command {
+        tex_live_command(self.program(), config, tex_path, output_dir)
+    }
+
+    fn pdf_setup(&self, pdf: &PdfOptions) -> Result<String, String> {
+        xdvipdfmx_setup(pdf)
+    }
+}
+
+impl RenderBackend for LualatexBackend {
+    fn engine(&self) -> LatexEngine {
+        LatexEngine::Lualatex
+    }
+
+    fn command(&self, config: &TectonicConfig, tex_path: &Path, output_dir: &Path) -> Command {
+        tex_live_command(self.program(), config, tex_path, output_dir)
+    }
+
+    /// `\pdfvariable` assignments, and for complete fonts a luaotfload
+    /// hook marking every font loaded from then on for full embedding.
+    fn pdf_setup(&self, pdf: &PdfOptions) -> Result<String, String> {
+        let mut setup = pdf_parameters(pdf, |name, value| {
+            format!("\\pdfvariable {name}={value}\\relax")
+        });
+        if pdf.full_fonts {
+            setup.push_str(
+                "\\directlua{luatexbase.add_to_callback(\"luaotfload.patch_font\", \
+                 function(tfmdata) tfmdata.embedding = \"full\" end, \
+                 \"equation_processor.full_fonts\")}",
+            );
+        }
+        Ok(setup)
+    }
+}
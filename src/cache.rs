@@ -0,0 +1,154 @@
+//! Incremental rendering: equations whose inputs have not changed since the
+//! last run, and whose artifacts are still there, are not rendered again.
+//!
+//! [`render_equations_parallel`](crate::render::render_equations_parallel)
+//! keeps a [`RenderCache`] in `.eqcache.json` in the output directory. It
+//! maps each equation to a key hashed from everything that shapes its
+//! artifacts (see [`cache_key`]) and to the files the render left behind.
+//!
+//! ```
+//! use equation_processor::cache::cache_key;
+//! use equation_processor::model::Equation;
+//! use equation_processor::render::{RenderOptions, TectonicConfig};
+//!
+//! let eq = Equation::new(true, "energy", "E = mc^2");
+//! let (tectonic, options) = (TectonicConfig::default(), RenderOptions::default());
+//! let black = cache_key(&eq, "#000000", &tectonic, &options);
+//! assert_eq!(black, cache_key(&eq, "#000000", &tectonic, &options));
+//! assert_ne!(black, cache_key(&eq, "#FF0000", &tectonic, &options));
+//! ```
+
+use crate::model::Equation;
+use crate::render::{OutputFormat, RenderOptions, TectonicConfig};
+use crate::report::ContentManifest;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// File name of the render cache kept inside the output directory.
+pub const RENDER_CACHE_FILE: &str = ".eqcache.json";
+
+/// Version of the [`RENDER_CACHE_FILE`] schema, bumped on incompatible changes.
+pub const RENDER_CACHE_VERSION: u32 = 1;
+
+/// Bumped when the rendering pipeline changes in a way that alters artifacts,
+/// so caches written by older versions are not trusted.
+const KEY_VERSION: &str = "2";
+
+/// Key of everything that shapes the artifacts of `eq` rendered with `color`,
+/// `tectonic` and `options`: the generated LaTeX (body, color, template and
+/// class options), the description embedded in the SVG, the engine, the
+/// output formats, the environment including the reproducible build date,
+/// and the contents of the assets.
+pub fn cache_key(
+    eq: &Equation,
+    color: &str,
+    tectonic: &TectonicConfig,
+    options: &RenderOptions,
+) -> String {
+    let mut hasher = Sha256::new();
+    let mut field = |data: &[u8]| {
+        hasher.update((data.len() as u64).to_le_bytes());
+        hasher.update(data);
+    };
+    field(KEY_VERSION.as_bytes());
+    field(eq.generate_latex_with(color, options).as_bytes());
+    field(eq.description.as_deref().unwrap_or_default().as_bytes());
+    field(eq.engine_with(options).program().as_bytes());
+    for format in &options.formats {
+        field(format.extension().as_bytes());
+    }
+    for (key, value) in tectonic.env.iter().chain(&eq.env) {
+        field(format!("{key}={value}").as_bytes());
+    }
+    if let Some(epoch) = tectonic.source_date_epoch {
+        field(format!("SOURCE_DATE_EPOCH={epoch}").as_bytes());
+    }
+    for asset in &eq.assets {
+        field(asset.to_string_lossy().as_bytes());
+        field(&fs::read(asset).unwrap_or_default());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// What an equation was last rendered from and what it produced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Key of the inputs, see [`cache_key`]
+    pub key: String,
+    /// Artifacts the render left in the output directory, by file name
+    pub files: Vec<String>,
+}
+
+/// The render cache of an output directory.
+///
+/// Entries of equations missing from a batch are kept, so several inputs
+/// can share an output directory.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RenderCache {
+    /// Schema version, see [`RENDER_CACHE_VERSION`]
+    pub version: u32,
+    /// Equation name → entry
+    pub equations: BTreeMap<String, CacheEntry>,
+}
+
+impl RenderCache {
+    /// Load the cache of `output_dir`; empty if there is none, it cannot be
+    /// read or it has another version, which only means everything is
+    /// rendered again.
+    pub fn read(output_dir: &Path) -> Self {
+        fs::read_to_string(output_dir.join(RENDER_CACHE_FILE))
+            .ok()
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .filter(|cache| cache.version == RENDER_CACHE_VERSION)
+            .unwrap_or_default()
+    }
+
+    /// Whether the equation `name` was last rendered from inputs with `key`
+    /// and all of its artifacts still exist in `output_dir`, under their own
+    /// name or the content-addressed one `manifest` maps it to.
+    pub fn is_fresh(
+        &self,
+        name: &str,
+        key: &str,
+        output_dir: &Path,
+        manifest: Option<&ContentManifest>,
+    ) -> bool {
+        let exists = |file: &str| {
+            output_dir.join(file).is_file()
+                || manifest
+                    .zip(file.strip_prefix(name).and_then(|f| f.strip_prefix('.')))
+                    .and_then(|(manifest, ext)| manifest.artifact(name, ext))
+                    .is_some_and(|file| output_dir.join(file).is_file())
+        };
+        self.equations.get(name).is_some_and(|entry| {
+            entry.key == key && !entry.files.is_empty() && entry.files.iter().all(|f| exists(f))
+        })
+    }
+
+    /// Record that `name` was just rendered from inputs with `key`, noting
+    /// which of its artifacts are in `output_dir`.
+    pub fn record(&mut self, name: &str, key: String, output_dir: &Path) {
+        let files = OutputFormat::ALL
+            .iter()
+            .map(|format| format!("{name}.{}", format.extension()))
+            .filter(|file| output_dir.join(file).is_file())
+            .collect();
+        self.version = RENDER_CACHE_VERSION;
+        self.equations
+            .insert(name.to_string(), CacheEntry { key, files });
+    }
+
+    /// Write the cache to `output_dir`.
+    pub fn write(&self, output_dir: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(output_dir.join(RENDER_CACHE_FILE), json + "\n")
+    }
+}
@@ -0,0 +1,171 @@
+//! A registry of the external programs a run has started, so that they are
+//! stopped with it.
+//!
+//! tectonic, the system LaTeX engines, `pdftocairo` and hooks are run
+//! through [`output`] or [`output_timeout`], which keep each child in the
+//! registry until it exits. [`kill_all`] stops every registered child; it is
+//! called on Ctrl+C and on panics once [`install_handlers`] has run, when a
+//! [`KillOnDrop`] guard goes out of scope, and when the GUI window closes.
+//! Without it, a cancelled run leaves its LaTeX processes using CPU in the
+//! background.
+//!
+//! ```no_run
+//! use equation_processor::children::{self, KillOnDrop};
+//! use std::process::Command;
+//!
+//! children::install_handlers()?;
+//! let _guard = KillOnDrop;
+//! let output = children::output(Command::new("tectonic").arg("--version"))?;
+//! assert!(output.status.success());
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Read};
+use std::panic;
+use std::process::{self, Child, Command, Output, Stdio};
+use std::sync::{Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often a waiting caller checks whether its child has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Exit code after Ctrl+C, as shells report a process ended by SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Running children by process id.
+static CHILDREN: Mutex<BTreeMap<u32, Child>> = Mutex::new(BTreeMap::new());
+
+/// The registry, also after a thread panicked while holding it.
+fn registry() -> MutexGuard<'static, BTreeMap<u32, Child>> {
+    CHILDREN
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Run `command` to completion like [`Command::output`], registered so that
+/// [`kill_all`] can stop it.
+///
+/// Fails with [`io::ErrorKind::Interrupted`] if the child was stopped by
+/// [`kill_all`].
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    output_timeout(command, None)
+}
+
+/// Like [`output`], killing the child after `timeout` and failing with
+/// [`io::ErrorKind::TimedOut`].
+pub fn output_timeout(command: &mut Command, timeout: Option<Duration>) -> io::Result<Output> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Drain both pipes on the side so a chatty tool can't block on a full pipe.
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let id = child.id();
+    registry().insert(id, child);
+
+    let started = Instant::now();
+    let status = loop {
+        let mut children = registry();
+        let Some(child) = children.get_mut(&id) else {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "stopped because the run was cancelled",
+            ));
+        };
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                children.remove(&id);
+                break status;
+            }
+            Ok(None) => {
+                if let Some(timeout) = timeout.filter(|t| started.elapsed() >= *t) {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    children.remove(&id);
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("timed out after {}s", timeout.as_secs_f32()),
+                    ));
+                }
+            }
+            Err(err) => {
+                children.remove(&id);
+                return Err(err);
+            }
+        }
+        drop(children);
+        thread::sleep(POLL_INTERVAL);
+    };
+    let collect = |reader: Option<JoinHandle<Vec<u8>>>| {
+        reader
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default()
+    };
+    Ok(Output {
+        status,
+        stdout: collect(stdout),
+        stderr: collect(stderr),
+    })
+}
+
+/// Read `pipe` to the end on another thread.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> Option<JoinHandle<Vec<u8>>> {
+    pipe.map(|mut pipe| {
+        thread::spawn(move || {
+            let mut data = Vec::new();
+            let _ = pipe.read_to_end(&mut data);
+            data
+        })
+    })
+}
+
+/// How many registered children are running.
+pub fn running() -> usize {
+    registry().len()
+}
+
+/// Kill every registered child and wait for it to exit. Callers waiting
+/// in [`output`] fail with [`io::ErrorKind::Interrupted`]. Returns how many
+/// children were stopped.
+pub fn kill_all() -> usize {
+    let mut children = registry();
+    let count = children.len();
+    for (_, mut child) in std::mem::take(&mut *children) {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    count
+}
+
+/// Stop the registered children on Ctrl+C, exiting with code 130, and
+/// when any thread panics, before the panic message is printed.
+///
+/// Call it once, early in `main`; it fails if a Ctrl+C handler is
+/// already set.
+pub fn install_handlers() -> io::Result<()> {
+    ctrlc::set_handler(|| {
+        kill_all();
+        process::exit(INTERRUPTED_EXIT_CODE);
+    })
+    .map_err(io::Error::other)?;
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        kill_all();
+        previous(info);
+    }));
+    Ok(())
+}
+
+/// Kills the registered children when dropped, e.g. when `main` returns
+/// while render threads are still running.
+#[derive(Debug)]
+pub struct KillOnDrop;
+
+impl Drop for KillOnDrop {
+    fn drop(&mut self) {
+        kill_all();
+    }
+}
@@ -0,0 +1,238 @@
+//! Interactive command-line workflow.
+//!
+//! ```no_run
+//! use equation_processor::cli::run_cli_with;
+//! use equation_processor::parser::ParseOptions;
+//! use equation_processor::render::{RenderOptions, TectonicConfig};
+//! use std::path::{Path, PathBuf};
+//!
+//! run_cli_with(
+//!     PathBuf::from("equations.md"),
+//!     Path::new("./output"),
+//!     &TectonicConfig::from_env(),
+//!     &ParseOptions::default(),
+//!     &RenderOptions::default(),
+//! )?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::model::Filetype;
+use crate::model::{Color, Equation};
+use crate::parser::{
+    ambiguous_tags, detect_file_type, read_file, AmbiguousTagPolicy, ParseOptions,
+};
+use crate::process::{check_batch, notify_outcome, prepare, render_outputs};
+use crate::render::{
+    batch_renderer, BatchSummary, RenderOptions, RetryingRenderer, TectonicConfig,
+};
+use crate::report::{
+    archive_changed, default_stats_file, display_table, record_run, run_summary, skipped_stages,
+    write_index_with, RunStats, GALLERY_FILE, HISTORY_DIR,
+};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Prompt user for yes/no on CLI
+pub fn ask_confirmation(prompt: &str) -> bool {
+    loop {
+        print!("{prompt} (y/n): ");
+        io::stdout().flush().unwrap();
+        let mut buf = String::new();
+        io::stdin().read_line(&mut buf).unwrap();
+        match buf.trim().to_lowercase().as_str() {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => continue,
+        }
+    }
+}
+
+/// Ask how to handle the ambiguous tags (see
+/// [`is_ambiguous_tag`](crate::parser::is_ambiguous_tag)) in the Markdown
+/// file at `input_file`, listing them first. Returns `Ignore` if there are
+/// none.
+pub fn ask_ambiguous_tags(input_file: &PathBuf) -> io::Result<AmbiguousTagPolicy> {
+    if !matches!(detect_file_type(input_file), Filetype::Markdown) {
+        return Ok(AmbiguousTagPolicy::Ignore);
+    }
+    let found = ambiguous_tags(&read_file(input_file)?);
+    if found.is_empty() {
+        return Ok(AmbiguousTagPolicy::Ignore);
+    }
+    println!("These tags look like markers rather than equation names:");
+    for (line, tag) in &found {
+        println!("  line {line}: %%{tag}%%");
+    }
+    loop {
+        print!("Use them as names (n), ignore them (i) or skip their equations (s)? ");
+        io::stdout().flush()?;
+        let mut buf = String::new();
+        io::stdin().read_line(&mut buf)?;
+        match buf.trim().to_lowercase().as_str() {
+            "n" | "name" => return Ok(AmbiguousTagPolicy::Name),
+            "i" | "ignore" => return Ok(AmbiguousTagPolicy::Ignore),
+            "s" | "skip" => return Ok(AmbiguousTagPolicy::Skip),
+            _ => continue,
+        }
+    }
+}
+
+/// Check every per-equation color (tags and mapping) before rendering,
+/// normalizing valid ones to `#RRGGBB`.
+pub fn validate_colors(equations: &mut [Equation]) -> Result<(), String> {
+    for eq in equations {
+        if let Some(color) = &eq.color {
+            let parsed: Color = color
+                .parse()
+                .map_err(|e| format!("equation '{}': {e}", eq.name))?;
+            eq.color = Some(parsed.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// CLI entry: display table, confirm, then render `input_file` into
+/// `output_dir`.
+///
+/// With `options.resume` set, a journal left by an interrupted run in
+/// `output_dir` is honored and already rendered equations are skipped. With
+/// `options.dedup` set, equations with identical bodies are rendered once
+/// and linked. With `options.chemistry` set, every math equation is treated
+/// as an mhchem formula. With `options.notify` set, a desktop notification
+/// reports the outcome. With `options.check_palette` set, a batch whose
+/// colors are hard to tell apart (see [`check_palette`]) is refused.
+/// Options the renderer does not support are reported before anything is
+/// rendered (see [`Capabilities`](crate::render::Capabilities)).
+/// `parse_options` control how the input is read, asking about ambiguous
+/// tags if their policy is [`AmbiguousTagPolicy::Ask`]; `options` apply to
+/// the whole batch, below any per-equation tags.
+pub fn run_cli_with(
+    input_file: PathBuf,
+    output_dir: &Path,
+    tectonic: &TectonicConfig,
+    parse_options: &ParseOptions,
+    options: &RenderOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let color: Color = options.color.parse()?;
+    let options = &RenderOptions {
+        color: color.to_string(),
+        ..options.clone()
+    };
+    fs::create_dir_all(output_dir)?;
+    let mut parse_options = parse_options.clone();
+    if parse_options.ambiguous_tags == AmbiguousTagPolicy::Ask {
+        parse_options.ambiguous_tags = ask_ambiguous_tags(&input_file)?;
+    }
+    let equations = prepare_equations(&input_file, options.chemistry, &parse_options, options)?;
+    if equations.is_empty() {
+        println!("No equations found.");
+        if options.shard.is_some() {
+            // An empty shard still reports in, so merging doesn't miss it.
+            write_index_with(&equations, output_dir, options.shard)?;
+        }
+        return Ok(());
+    }
+    display_table(&equations);
+    let (renderer, fallback) = batch_renderer(tectonic, options);
+    if let Some(warning) = fallback {
+        eprintln!("Warning: {warning}");
+    }
+    let renderer = &RetryingRenderer::new(renderer.as_ref(), options.retry);
+    let space = check_batch(&equations, color, renderer, output_dir, options)?;
+    if space.is_tight() {
+        eprintln!("Warning: the batch will nearly fill the output volume ({space})");
+    } else {
+        println!("Output size: {space}");
+    }
+
+    if !ask_confirmation("Render active equations?") {
+        return Ok(());
+    }
+    if options.keep_history {
+        let archived = archive_changed(&equations, output_dir)?;
+        if !archived.is_empty() {
+            println!(
+                "Archived {} earlier artifact(s) to {:?}",
+                archived.len(),
+                output_dir.join(HISTORY_DIR)
+            );
+        }
+    }
+    let started = Instant::now();
+    let mut warnings = Vec::new();
+    let mut summary = BatchSummary::default();
+    let rendered = render_outputs(
+        &equations,
+        output_dir,
+        renderer,
+        tectonic,
+        options,
+        &mut warnings,
+        &mut summary,
+    );
+    for retry in renderer.retries() {
+        eprintln!("Warning: {retry}");
+    }
+    for warning in warnings {
+        eprintln!("Warning: {warning}");
+    }
+    if let Some(warning) = record_stats(&equations, output_dir, &summary, started.elapsed()) {
+        eprintln!("Warning: {warning}");
+    }
+    if options.notify {
+        let file = input_file.file_name().unwrap_or_default().to_string_lossy();
+        if let Some(warning) = notify_outcome(&file, &equations, &rendered, started.elapsed()) {
+            eprintln!("Warning: {warning}");
+        }
+    }
+    for output in rendered? {
+        let name = output.file_name().unwrap_or_default();
+        if name == GALLERY_FILE {
+            println!("Gallery written to {output:?}");
+        } else if output.extension().is_some_and(|ext| ext == "pdf") {
+            println!("Slides written to {output:?}");
+        }
+    }
+    println!("{}", run_summary(&equations));
+    if let Some(note) = skipped_stages(options) {
+        println!("{note}");
+    }
+    println!("Rendered to {output_dir:?}");
+    Ok(())
+}
+
+/// Read the equations of `input_file` and apply the batch settings: shared
+/// asset paths, chemistry mode, `include_inactive`, the color map, name
+/// affixes and unique names. Warnings, including one per ambiguous tag in
+/// Markdown input, are printed to stderr.
+pub fn prepare_equations(
+    input_file: &Path,
+    chemistry: bool,
+    parse_options: &ParseOptions,
+    options: &RenderOptions,
+) -> Result<Vec<Equation>, Box<dyn std::error::Error>> {
+    let mut warnings = Vec::new();
+    let equations = prepare(input_file, chemistry, parse_options, options, &mut warnings)?;
+    for warning in warnings {
+        eprintln!("Warning: {warning}");
+    }
+    Ok(equations)
+}
+
+/// Add a run of `equations` that ended as `summary` says to the statistics
+/// of `output_dir`. Returns a warning if the statistics could not be
+/// written.
+fn record_stats(
+    equations: &[Equation],
+    output_dir: &Path,
+    summary: &BatchSummary,
+    took: Duration,
+) -> Option<String> {
+    let stats = RunStats::from_batch(equations, summary, took);
+    default_stats_file(output_dir)
+        .and_then(|path| record_run(&path, &stats))
+        .err()
+        .map(|err| format!("could not record run statistics: {err}"))
+}
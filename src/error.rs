@@ -0,0 +1,177 @@
+//! The error type of reading and rendering equations.
+//!
+//! [`EquationError`] says which stage failed, so a message can tell the
+//! user what to fix: the input file, the LaTeX of an equation or the
+//! installed tools. It converts to and from [`io::Error`], so it works with
+//! `?` in functions returning either.
+//!
+//! ```
+//! use equation_processor::error::EquationError;
+//! use equation_processor::parser::{read_input, ParseOptions};
+//!
+//! let path = std::env::temp_dir().join("error_doc_example.md");
+//! std::fs::write(&path, b"$$\nE = mc^2\n$$\n%%energy\xff%%\n")?;
+//! match read_input(&path, &ParseOptions::default()) {
+//!     Err(EquationError::ParseError { line, .. }) => assert_eq!(line, Some(4)),
+//!     other => panic!("expected a parse error, got {other:?}"),
+//! }
+//! # std::fs::remove_file(&path)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::model::LatexEngine;
+use crate::render::ImageFormat;
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// How many lines of a tool's output an error message shows when no error
+/// lines can be picked out of it.
+const LOG_TAIL_LINES: usize = 20;
+
+/// Why an input file could not be read or an equation could not be rendered.
+#[derive(Debug, Error)]
+pub enum EquationError {
+    /// The input file is not valid for its format
+    #[error("{}{}: {reason}", path.display(), line.map(|l| format!(":{l}")).unwrap_or_default())]
+    ParseError {
+        /// The input file
+        path: PathBuf,
+        /// Line of the problem, 1-based, if known
+        line: Option<usize>,
+        /// What is wrong
+        reason: String,
+    },
+    /// The LaTeX engine rejected a document, usually because of a typo in
+    /// an equation's body
+    #[error("{} could not compile {}:\n{}", engine.program(), tex.display(), log_excerpt(log))]
+    LatexCompileFailed {
+        /// Engine that ran
+        engine: LatexEngine,
+        /// The generated document
+        tex: PathBuf,
+        /// What the engine printed
+        log: String,
+    },
+    /// Tectonic failed in offline mode, usually because a package it needs
+    /// is not in its cache
+    #[error(
+        "tectonic failed in offline mode for {}; required resources are probably \
+         not cached (run `prefetch` while online first):\n{}",
+        tex.display(),
+        log_excerpt(log)
+    )]
+    ResourcesNotCached {
+        /// The generated document
+        tex: PathBuf,
+        /// What tectonic printed
+        log: String,
+    },
+    /// `pdftocairo` could not convert a compiled PDF to SVG or PNG
+    #[error("{} conversion of {} failed:\n{}", format.extension().to_uppercase(), pdf.display(), log_excerpt(log))]
+    SvgConversionFailed {
+        /// The PDF to convert
+        pdf: PathBuf,
+        /// The target format
+        format: ImageFormat,
+        /// What `pdftocairo` printed
+        log: String,
+    },
+    /// The built-in Typst fallback could not typeset an equation, usually
+    /// because it uses LaTeX without a Typst translation
+    #[error("the Typst fallback could not typeset '{name}': {reason}")]
+    TypstFailed {
+        /// The equation
+        name: String,
+        /// What went wrong
+        reason: String,
+    },
+    /// An external program is not installed or not on `PATH`
+    #[error("{tool} was not found; install it or add it to PATH")]
+    ToolNotFound {
+        /// Name of the program, e.g. `tectonic`
+        tool: String,
+    },
+    /// Reading or writing a file failed
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// Log lines of network trouble, e.g. while tectonic fetches bundle files,
+/// that make a failure worth retrying. Matched ignoring case.
+const TRANSIENT_LOG_PATTERNS: &[&str] = &[
+    "connection reset",
+    "connection refused",
+    "connection closed",
+    "timed out",
+    "dns error",
+    "failed to lookup address",
+    "temporary failure in name resolution",
+    "network is unreachable",
+    "error sending request",
+    "failed to download",
+];
+
+impl EquationError {
+    /// The log line that marks this failure as transient, such as a
+    /// connection reset while tectonic downloads a package; `None` for
+    /// failures that would happen again, like a typo in the LaTeX.
+    ///
+    /// Only failed compilations and conversions are considered, and only
+    /// when their log mentions network trouble.
+    pub fn transient_reason(&self) -> Option<&str> {
+        let log = match self {
+            EquationError::LatexCompileFailed { log, .. }
+            | EquationError::SvgConversionFailed { log, .. } => log,
+            _ => return None,
+        };
+        log.lines().map(str::trim).find(|line| {
+            let line = line.to_lowercase();
+            TRANSIENT_LOG_PATTERNS
+                .iter()
+                .any(|pattern| line.contains(pattern))
+        })
+    }
+
+    /// [`ToolNotFound`](EquationError::ToolNotFound) for `tool` if `err`
+    /// says the program could not be started because it does not exist,
+    /// otherwise `err` itself.
+    pub(crate) fn spawn(tool: &str, err: io::Error) -> Self {
+        if err.kind() == io::ErrorKind::NotFound {
+            EquationError::ToolNotFound {
+                tool: tool.to_string(),
+            }
+        } else {
+            EquationError::Io(err)
+        }
+    }
+}
+
+impl From<EquationError> for io::Error {
+    fn from(err: EquationError) -> Self {
+        match err {
+            EquationError::Io(err) => err,
+            EquationError::ParseError { .. } => io::Error::new(io::ErrorKind::InvalidData, err),
+            EquationError::ToolNotFound { .. } => io::Error::new(io::ErrorKind::NotFound, err),
+            err => io::Error::other(err),
+        }
+    }
+}
+
+/// The lines of a LaTeX or `pdftocairo` log worth showing: TeX errors
+/// (`! ...` and the `l.<n>` line they occurred on) and `error:` lines if
+/// there are any, otherwise the last few lines.
+fn log_excerpt(log: &str) -> String {
+    let log = log.trim();
+    let errors: Vec<&str> = log
+        .lines()
+        .filter(|line| {
+            line.starts_with('!') || line.starts_with("l.") || line.starts_with("error:")
+        })
+        .collect();
+    if !errors.is_empty() {
+        return errors.join("\n");
+    }
+    let lines: Vec<&str> = log.lines().collect();
+    lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n")
+}
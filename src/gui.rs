@@ -8,14 +8,321 @@
 use eframe::egui;
 use eframe::egui::widgets::Spinner;
 use eframe::egui::Color32;
-use eframe::egui::{ScrollArea, ViewportBuilder};
+use eframe::egui::{RichText, ScrollArea, ViewportBuilder};
 use egui_extras::{Column, TableBuilder};
 use egui_file_dialog::FileDialog;
-use std::path::PathBuf;
-use std::sync::mpsc;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
-use equation_processor::{detect_file_type, parse_markdown, read_csv_file, Equation, Filetype};
+use equation_processor::children;
+use equation_processor::error::EquationError;
+use equation_processor::lint::{lint_file, Diagnostic, Severity};
+use equation_processor::palette::{batch_palette, check_palette, ColorVision};
+use equation_processor::parser::{
+    detect_file_type, read_input, resolve_assets, write_csv_file, ParseOptions,
+};
+use equation_processor::paths::config_file;
+use equation_processor::render::{
+    apply_renames, batch_renderer, disambiguate_names, estimate_disk_space, existing_outputs,
+    missing_tools, plan_renames, CaseTransform, LatexTemplate, RenameRule, RenderOptions,
+    RenderTimer, TectonicConfig, DEFAULT_FONT_PACKAGE, FONT_PACKAGES,
+};
+use equation_processor::report::{
+    default_stats_file, fuzzy_score, notify_desktop, record_run, run_summary, unicode_preview,
+    write_gallery, write_index, write_markdown, write_tex_snippets, RunStats,
+};
+use equation_processor::session::{session_file, Session};
+use equation_processor::settings::Settings;
+use equation_processor::{
+    Background, Color, Equation, EquationKind, Filetype, LatexEngine, StandaloneOptions,
+};
+
+/// Rows per page when the equations table is paged.
+const PAGE_SIZE: usize = 500;
+
+/// Allowed zoom factors for the equations table and previews.
+const ZOOM_RANGE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+
+/// How often the session is autosaved while it changes.
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often a background watch checks the input file for changes.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Window title shown while idle.
+pub(crate) const APP_TITLE: &str = "Equation Processor";
+
+/// Opens the command palette: Ctrl+P, or Cmd+P on macOS.
+const COMMAND_PALETTE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::P);
+
+/// The user's answer to the overwrite confirmation.
+#[derive(Clone, Copy)]
+enum OverwriteChoice {
+    OverwriteAll,
+    SkipExisting,
+    Cancel,
+}
+
+/// A trial render of one equation with the current options, shown next to
+/// its existing output before the user decides to overwrite.
+struct Comparison {
+    /// Equation being compared
+    name: String,
+    /// Temporary directory receiving the trial render
+    dir: PathBuf,
+    /// Receives the outcome of the trial render from the background thread
+    rx: mpsc::Receiver<Result<(), String>>,
+    /// Outcome of the trial render, once received
+    result: Option<Result<(), String>>,
+}
+
+impl Drop for Comparison {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// How an equation fared in the last batch, for the filter chips.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RowStatus {
+    Rendered,
+    Failed,
+    Skipped,
+}
+
+impl RowStatus {
+    const ALL: [RowStatus; 3] = [RowStatus::Failed, RowStatus::Rendered, RowStatus::Skipped];
+
+    fn label(self) -> &'static str {
+        match self {
+            RowStatus::Rendered => "Rendered",
+            RowStatus::Failed => "Failed",
+            RowStatus::Skipped => "Skipped",
+        }
+    }
+}
+
+/// Actions offered by the command palette.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Command {
+    OpenFile,
+    OpenFolder,
+    SetOutput,
+    ToggleAll,
+    Render,
+    Rename,
+    ExportCsv,
+    ExportGallery,
+    ExportMarkdown,
+    ExportSettings,
+    ImportSettings,
+    SaveDefaultSettings,
+}
+
+impl Command {
+    /// In the order the palette lists them before anything is typed.
+    const ALL: [Command; 12] = [
+        Command::OpenFile,
+        Command::OpenFolder,
+        Command::SetOutput,
+        Command::ToggleAll,
+        Command::Render,
+        Command::Rename,
+        Command::ExportCsv,
+        Command::ExportGallery,
+        Command::ExportMarkdown,
+        Command::ExportSettings,
+        Command::ImportSettings,
+        Command::SaveDefaultSettings,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            Command::OpenFile => "Open input file…",
+            Command::OpenFolder => "Open input folder…",
+            Command::SetOutput => "Set output directory…",
+            Command::ToggleAll => "Toggle all rows",
+            Command::Render => "Render selected equations",
+            Command::Rename => "Rename selected equations…",
+            Command::ExportCsv => "Save equations as CSV…",
+            Command::ExportGallery => "Export report: HTML gallery",
+            Command::ExportMarkdown => "Export report: Markdown page",
+            Command::ExportSettings => "Preferences: export settings…",
+            Command::ImportSettings => "Preferences: import settings…",
+            Command::SaveDefaultSettings => "Preferences: save settings as default",
+        }
+    }
+}
+
+/// The command palette's search, while it is open.
+#[derive(Default)]
+struct CommandPalette {
+    /// Fuzzy query typed by the user
+    query: String,
+    /// Highlighted entry among the matching commands
+    selected: usize,
+}
+
+/// Messages sent from the background render thread to the UI.
+enum RenderEvent {
+    /// `done` of `total` active equations have been processed; the last one took `took`.
+    Progress {
+        done: usize,
+        total: usize,
+        took: Duration,
+    },
+    /// The batch finished; `summary` is its [`run_summary`] line,
+    /// `rendered` names the equations that rendered and `failed` lists
+    /// those that did not, with why.
+    Finished {
+        summary: String,
+        rendered: Vec<String>,
+        failed: Vec<(String, EquationError)>,
+    },
+    /// A watched input changed but could not be read, or the batch panicked.
+    Failed(String),
+}
+
+/// Everything a background render needs, taken from the settings when it
+/// starts.
+#[derive(Clone)]
+struct Batch {
+    out: PathBuf,
+    options: RenderOptions,
+    tectonic: TectonicConfig,
+    tex_snippets: bool,
+}
+
+impl Batch {
+    /// Render the active equations of `eqs`, calling `progress` with
+    /// `(done, total, took)` after each, then write the index and the
+    /// requested extras.
+    fn run(
+        &self,
+        eqs: &[Equation],
+        mut progress: impl FnMut(usize, usize, Duration),
+    ) -> RenderEvent {
+        let total = eqs.iter().filter(|e| e.active).count();
+        let (renderer, _) = batch_renderer(&self.tectonic, &self.options);
+        let mut rendered = Vec::new();
+        let mut failed = Vec::new();
+        let batch_started = Instant::now();
+        for (i, eq) in eqs.iter().filter(|e| e.active).enumerate() {
+            let started = Instant::now();
+            match renderer.render(eq, &self.out) {
+                Ok(()) => rendered.push(eq.name.clone()),
+                Err(err) => failed.push((eq.name.clone(), err)),
+            }
+            progress(i + 1, total, started.elapsed());
+        }
+        let stats = RunStats::new(
+            eqs,
+            total - failed.len(),
+            failed.len(),
+            batch_started.elapsed(),
+        );
+        let _ = default_stats_file(&self.out).and_then(|path| record_run(&path, &stats));
+        let _ = write_index(eqs, &self.out);
+        if self.tex_snippets {
+            let _ = write_tex_snippets(eqs, &self.out);
+        }
+        if self.options.html_gallery {
+            let _ = write_gallery(eqs, &self.out);
+        }
+        RenderEvent::Finished {
+            summary: run_summary(eqs),
+            rendered,
+            failed,
+        }
+    }
+
+    /// Like [`Batch::run`], turning a panic of the renderer or the extra
+    /// outputs into [`RenderEvent::Failed`], so the batch always ends.
+    fn run_caught(
+        &self,
+        eqs: &[Equation],
+        progress: impl FnMut(usize, usize, Duration),
+    ) -> RenderEvent {
+        panic::catch_unwind(AssertUnwindSafe(|| self.run(eqs, progress))).unwrap_or_else(
+            |payload| {
+                let reason = payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("unknown cause");
+                RenderEvent::Failed(format!("Rendering stopped unexpectedly: {reason}"))
+            },
+        )
+    }
+}
+
+/// A background watch of the input file, re-rendering it whenever it
+/// changes. Dropping it stops the watch.
+struct Watch {
+    /// Tells the watch thread to stop.
+    stop: Arc<AtomicBool>,
+    /// Outcome of each render the watch triggered.
+    rx: mpsc::Receiver<RenderEvent>,
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Apply per-equation settings of `options` such as chemistry mode to `eq`.
+fn prepare_equation(eq: &mut Equation, options: &RenderOptions) {
+    if options.chemistry && eq.kind == EquationKind::Math {
+        eq.kind = EquationKind::Chemistry;
+    }
+}
+
+/// Parse the equations in `path` by its file type, resolving shared assets.
+fn load_equations(path: &Path) -> Result<Vec<Equation>, String> {
+    if matches!(detect_file_type(path), Filetype::Unknown) {
+        return Err("Unsupported file type selected.".into());
+    }
+    let mut equations = read_input(path, &ParseOptions::default()).map_err(|e| e.to_string())?;
+    if let Some(base_dir) = path.parent() {
+        resolve_assets(&mut equations, base_dir);
+    }
+    Ok(equations)
+}
+
+/// Lines of the input file at `path` for the source pane, with line endings
+/// normalized as the parsers do; `None` for a directory or unreadable file.
+fn load_source(path: &Path) -> Option<Vec<String>> {
+    if path.is_dir() {
+        return None;
+    }
+    let content = std::fs::read_to_string(path).ok()?;
+    let content = content.replace("\r\n", "\n").replace('\r', "\n");
+    Some(content.lines().map(String::from).collect())
+}
+
+/// Whether files can be created in `dir`, or in the directory it would be
+/// created in.
+fn is_writable(dir: &Path) -> bool {
+    match std::fs::metadata(dir) {
+        Ok(meta) => meta.is_dir() && !meta.permissions().readonly(),
+        Err(_) => dir.parent().is_some_and(is_writable),
+    }
+}
+
+/// Message listing the equations of a batch that failed to render, if any,
+/// with the error of the first; the others usually fail the same way.
+fn failure_message(failed: &[(String, EquationError)]) -> Option<String> {
+    let (_, first) = failed.first()?;
+    let names: Vec<&str> = failed.iter().map(|(name, _)| name.as_str()).collect();
+    Some(format!("Failed to render: {}\n{first}", names.join(", ")))
+}
 
 /// Holds the entire state for the GUI application.
 ///
@@ -32,36 +339,257 @@ pub struct EquationProcessorApp {
     font_color: [f32; 3],
     /// Hex color string for text input.
     color_hex_input: String,
+    /// Fill behind equations, normalized to [0.0,1.0]; `None` for transparent.
+    background: Option<[f32; 3]>,
+    /// LaTeX engine for equations without an engine tag.
+    engine: LatexEngine,
     /// Flag to delete intermediate LaTeX/PDF files.
     delete_intermediates: bool,
+    /// Treat all math equations as mhchem chemical formulas.
+    chemistry: bool,
+    /// Also write `\input`-ready `.tex` snippets.
+    tex_snippets: bool,
+    /// Also write an HTML gallery of the rendered equations.
+    html_gallery: bool,
+    /// Standalone border for all equations; empty for the default.
+    border_input: String,
+    /// LaTeX template equations are typeset in; `None` for the built-in one.
+    template: Option<LatexTemplate>,
+    /// Font package; `None` for Computer Modern.
+    font: Option<String>,
     /// Vector of equations parsed from the input file.
     equations: Vec<Equation>,
+    /// Outcome of each equation in the last batch, by name.
+    statuses: HashMap<String, RowStatus>,
+    /// Show only rows with this outcome; `None` shows all.
+    status_filter: Option<RowStatus>,
+    /// Show only rows with this tag; `None` shows all.
+    tag_filter: Option<String>,
     /// Whether a rendering operation is currently in progress.
     processing: bool,
-    /// Receiver channel used to signal completion of the background render.
-    progress_rx: Option<mpsc::Receiver<()>>,
+    /// Receiver channel for progress and completion of the background render.
+    progress_rx: Option<mpsc::Receiver<RenderEvent>>,
+    /// Latest `(done, total)` progress of the running batch.
+    progress: Option<(usize, usize)>,
+    /// Per-equation timings of the running batch, for throughput and ETA.
+    timer: RenderTimer,
     /// File dialog for selecting the input file.
     open_file_dialog: FileDialog,
     /// Directory dialog for selecting the output directory.
     select_dir_dialog: FileDialog,
+    /// Save dialog for writing the equations to a CSV file.
+    export_csv_dialog: FileDialog,
+    /// Save dialog for exporting the settings.
+    export_settings_dialog: FileDialog,
+    /// File dialog for importing settings.
+    import_settings_dialog: FileDialog,
+    /// File dialog for choosing a LaTeX template.
+    template_dialog: FileDialog,
     /// Optional error message to display in red.
     error_message: Option<String>,
     /// Optional success message to display in green.
     success_message: Option<String>,
+    /// Non-fatal notices about the last batch, such as renamed equations.
+    warnings: Vec<String>,
+    /// Problems the parsers and linter found in the input file, by line.
+    diagnostics: Vec<Diagnostic>,
+    /// Lines of the input file, shown in the source pane.
+    source: Option<Vec<String>>,
+    /// 1-based source line selected from the warnings list.
+    highlighted_line: Option<usize>,
+    /// Scroll the source pane to the highlighted line on the next frame.
+    scroll_to_line: bool,
+    /// Row whose generated LaTeX document is shown in the LaTeX window.
+    latex_view: Option<usize>,
+    /// Show the equations table one page of [`PAGE_SIZE`] rows at a time.
+    paged: bool,
+    /// Current page of the equations table when paged.
+    page: usize,
+    /// Scale of the equations table text and previews (Ctrl+scroll or slider).
+    zoom: f32,
+    /// Batch rename being edited in the rename dialog, if open.
+    rename: Option<RenameRule>,
+    /// Command palette, while open.
+    command_palette: Option<CommandPalette>,
+    /// Before/after preview shown in the overwrite dialog.
+    comparison: Option<Comparison>,
+    /// Number of trial renders started, keeping their image URIs distinct.
+    comparisons: usize,
+    /// Existing output files awaiting the user's overwrite decision.
+    pending_overwrite: Option<Vec<PathBuf>>,
+    /// Tectonic cache/bundle configuration taken from the environment.
+    tectonic: TectonicConfig,
+    /// External programs not found on `PATH` at startup (or the last recheck).
+    missing_tools: Vec<&'static str>,
+    /// Background watch of the input file, while enabled.
+    watch: Option<Watch>,
+    /// Session autosaved by an earlier run, offered for restoring until the
+    /// user decides or opens another file.
+    recovered: Option<Session>,
+    /// The last autosaved state, to skip saving when nothing changed.
+    autosaved: String,
+    /// When the session was last autosaved.
+    last_autosave: Option<Instant>,
+    /// Hide the window to the tray instead of closing it.
+    #[cfg(feature = "tray")]
+    minimize_to_tray: bool,
+    /// Tray icon, created the first time "Minimize to tray" is enabled.
+    #[cfg(feature = "tray")]
+    tray: Option<crate::tray::Tray>,
 }
 
 impl EquationProcessorApp {
     /// Constructs the `EquationProcessorApp` and initializes dialogs and defaults.
     ///
     /// This sets up the file and directory dialogs and default values for
-    /// color and flags. Other fields use their `Default` values.
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self {
+    /// color and flags, then applies the default config file if there is
+    /// one. Other fields use their `Default` values.
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        // Needed to display rendered SVG previews from disk.
+        egui_extras::install_image_loaders(&cc.egui_ctx);
+        let mut app = Self {
             open_file_dialog: FileDialog::new(),
             select_dir_dialog: FileDialog::new(),
+            export_csv_dialog: FileDialog::new().default_file_name("equations.csv"),
+            export_settings_dialog: FileDialog::new().default_file_name("equation_processor.toml"),
+            import_settings_dialog: FileDialog::new(),
+            template_dialog: FileDialog::new(),
             font_color: [0.0, 0.0, 0.0],
             color_hex_input: "#000000".to_string(),
+            background: None,
+            font: Some(DEFAULT_FONT_PACKAGE.to_string()),
+            tectonic: TectonicConfig::from_env(),
+            missing_tools: missing_tools(&RenderOptions::default()),
+            zoom: 1.0,
             ..Default::default()
+        };
+        app.load_default_settings();
+        app.recovered = session_file()
+            .and_then(|path| Session::load(&path).ok())
+            .filter(|session| !session.is_empty());
+        app
+    }
+
+    /// Load `path` as the input file, replacing the current equations.
+    ///
+    /// Parse errors are shown in the window rather than returned. A session
+    /// still offered for restoring is dropped.
+    fn open_input(&mut self, path: PathBuf) {
+        self.recovered = None;
+        self.page = 0;
+        self.clear_filters();
+        // A watch follows the file it was started on.
+        self.watch = None;
+        // Validate and parse by file type
+        match load_equations(&path) {
+            Ok(equations) => {
+                self.equations = equations;
+                self.error_message = None;
+            }
+            Err(err) => {
+                self.equations.clear();
+                self.error_message = Some(err);
+                self.success_message = None;
+            }
+        }
+        self.diagnostics = lint_file(&path, &ParseOptions::default()).unwrap_or_default();
+        self.source = load_source(&path);
+        self.highlighted_line = None;
+        self.latex_view = None;
+        self.input_file = Some(path);
+    }
+
+    /// Forget the outcomes of the last batch and show every row.
+    fn clear_filters(&mut self) {
+        self.statuses.clear();
+        self.status_filter = None;
+        self.tag_filter = None;
+    }
+
+    /// Record the outcome of a finished batch for the filter chips;
+    /// equations neither rendered nor failed were skipped.
+    fn record_statuses(&mut self, rendered: &[String], failed: &[(String, EquationError)]) {
+        self.statuses = self
+            .equations
+            .iter()
+            .map(|eq| {
+                let status = if failed.iter().any(|(name, _)| *name == eq.name) {
+                    RowStatus::Failed
+                } else if rendered.contains(&eq.name) {
+                    RowStatus::Rendered
+                } else {
+                    RowStatus::Skipped
+                };
+                (eq.name.clone(), status)
+            })
+            .collect();
+    }
+
+    /// Whether `eq` passes the status and tag filters.
+    fn is_shown(&self, eq: &Equation) -> bool {
+        self.status_filter
+            .is_none_or(|status| self.statuses.get(&eq.name) == Some(&status))
+            && self
+                .tag_filter
+                .as_ref()
+                .is_none_or(|tag| eq.tags.contains(tag))
+    }
+
+    /// Chips above the table choosing which rows it shows: All, one per
+    /// outcome once a batch has finished, and one per tag.
+    fn filter_chips(&mut self, ui: &mut egui::Ui) {
+        let mut tags: Vec<&String> = self.equations.iter().flat_map(|e| &e.tags).collect();
+        tags.sort();
+        tags.dedup();
+        if self.statuses.is_empty() && tags.is_empty() {
+            return;
+        }
+        let mut status_filter = self.status_filter;
+        let mut tag_filter = self.tag_filter.clone();
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Show:");
+            let all = status_filter.is_none() && tag_filter.is_none();
+            if ui
+                .selectable_label(all, format!("All ({})", self.equations.len()))
+                .clicked()
+            {
+                status_filter = None;
+                tag_filter = None;
+            }
+            if !self.statuses.is_empty() {
+                for status in RowStatus::ALL {
+                    let count = self.statuses.values().filter(|s| **s == status).count();
+                    let selected = status_filter == Some(status);
+                    if ui
+                        .selectable_label(selected, format!("{} ({count})", status.label()))
+                        .clicked()
+                    {
+                        status_filter = (!selected).then_some(status);
+                    }
+                }
+            }
+            if !tags.is_empty() {
+                ui.separator();
+                for tag in tags {
+                    let count = self
+                        .equations
+                        .iter()
+                        .filter(|e| e.tags.contains(tag))
+                        .count();
+                    let selected = tag_filter.as_ref() == Some(tag);
+                    if ui
+                        .selectable_label(selected, format!("#{tag} ({count})"))
+                        .clicked()
+                    {
+                        tag_filter = (!selected).then(|| tag.clone());
+                    }
+                }
+            }
+        });
+        if status_filter != self.status_filter || tag_filter != self.tag_filter {
+            self.status_filter = status_filter;
+            self.tag_filter = tag_filter;
+            self.page = 0;
         }
     }
 
@@ -79,24 +607,940 @@ impl EquationProcessorApp {
 
     /// Convert hex string to RGB float array using egui's Color32
     fn hex_to_rgb(hex: &str) -> Option<[f32; 3]> {
-        if let Ok(color32) = Color32::from_hex(hex) {
-            Some([
-                color32.r() as f32 / 255.0,
-                color32.g() as f32 / 255.0,
-                color32.b() as f32 / 255.0,
-            ])
-        } else {
-            None
+        let color: Color = hex.parse().ok()?;
+        Some([
+            color.r as f32 / 255.0,
+            color.g as f32 / 255.0,
+            color.b as f32 / 255.0,
+        ])
+    }
+
+    /// The background chosen with the second color picker.
+    fn background_setting(&self) -> Background {
+        match self.background {
+            Some(rgb) => Background::Color(Self::rgb_to_hex(rgb).parse().unwrap_or_default()),
+            None => Background::Transparent,
+        }
+    }
+
+    /// Tooltip contents for an equation row.
+    ///
+    /// Shows the rendered SVG from the output directory when it exists,
+    /// otherwise a Unicode approximation of the LaTeX body.
+    fn preview_tooltip(ui: &mut egui::Ui, eq: &Equation, output_dir: Option<&PathBuf>, zoom: f32) {
+        let svg = output_dir
+            .map(|dir| dir.join(format!("{}.svg", eq.name)))
+            .filter(|p| p.exists());
+        match svg {
+            Some(path) => {
+                ui.add(
+                    egui::Image::new(format!("file://{}", path.display()))
+                        .max_width(400.0 * zoom)
+                        .max_height(200.0 * zoom),
+                );
+            }
+            None => {
+                ui.label(egui::RichText::new(unicode_preview(&eq.body)).size(20.0 * zoom));
+                ui.weak("(approximation – not rendered yet)");
+            }
         }
     }
 
-    /// Validate hex color format using egui's parser
+    /// Validate hex color format the same way the CLI does
     fn is_valid_hex_color(hex: &str) -> bool {
-        Color32::from_hex(hex).is_ok()
+        hex.parse::<Color>().is_ok()
+    }
+
+    /// Everything that prevents rendering in the current state, listed
+    /// under the Process button; empty when ready.
+    fn validation_errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.input_file.is_none() {
+            errors.push("Select an input file.".to_string());
+        } else if self.equations.is_empty() {
+            errors.push("The input file contains no equations.".to_string());
+        } else if !self.equations.iter().any(|e| e.active) {
+            errors.push("Select at least one equation to render.".to_string());
+        }
+        match &self.output_dir {
+            None => errors.push("Select an output directory.".to_string()),
+            Some(dir) if !is_writable(dir) => errors.push(format!(
+                "The output directory {} is not writable.",
+                dir.display()
+            )),
+            Some(_) => {}
+        }
+        if !Self::is_valid_hex_color(&self.color_hex_input) {
+            errors.push("Enter a valid hex color like #1A2B3C.".to_string());
+        }
+        // Without the LaTeX engine, the Typst fallback renders instead.
+        let fallback =
+            cfg!(feature = "typst-fallback") && self.missing_tools.contains(&self.engine.program());
+        for tool in self.missing_tools.iter().filter(|_| !fallback) {
+            errors.push(format!("{tool} was not found on PATH."));
+        }
+        errors
+    }
+
+    /// Batch options, including the color, from the current settings.
+    fn render_settings(&self) -> RenderOptions {
+        let mut options = RenderOptions {
+            color: format!(
+                "#{:02X}{:02X}{:02X}",
+                (self.font_color[0] * 255.0) as u8,
+                (self.font_color[1] * 255.0) as u8,
+                (self.font_color[2] * 255.0) as u8
+            ),
+            engine: self.engine,
+            delete_intermediates: self.delete_intermediates,
+            chemistry: self.chemistry,
+            html_gallery: self.html_gallery,
+            template: self.template.clone(),
+            font: self.font.clone(),
+            background: self.background_setting(),
+            ..Default::default()
+        };
+        let border = self.border_input.trim();
+        if !border.is_empty() {
+            options.standalone.border = Some(border.to_string());
+        }
+        options
+    }
+
+    /// The current settings, as exported to a file.
+    fn settings(&self) -> Settings {
+        Settings {
+            color: self.color_hex_input.trim().to_string(),
+            engine: self.engine,
+            background: self.background_setting().to_string(),
+            border: self.border_input.trim().to_string(),
+            font: Some(self.font.clone().unwrap_or_default()),
+            delete_intermediates: self.delete_intermediates,
+            chemistry: self.chemistry,
+            tex_snippets: self.tex_snippets,
+            html_gallery: self.html_gallery,
+            output_dir: self.output_dir.clone(),
+            template: self.template.as_ref().map(|t| t.path.clone()),
+            paged: self.paged,
+            zoom: self.zoom,
+            tectonic: (&self.tectonic).into(),
+        }
+    }
+
+    /// Apply the config file from the platform config directory, if there
+    /// is one.
+    fn load_default_settings(&mut self) {
+        let Some(path) = config_file().filter(|p| p.is_file()) else {
+            return;
+        };
+        let loaded = Settings::load(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|settings| self.apply_settings(settings));
+        if let Err(err) = loaded {
+            self.error_message = Some(format!("Could not apply {}: {err}", path.display()));
+        }
+    }
+
+    /// Replace the current settings with imported ones. The output directory
+    /// is kept when the imported one does not exist on this machine.
+    fn apply_settings(&mut self, settings: Settings) -> Result<(), String> {
+        let tectonic = settings.tectonic.to_config()?;
+        let template = settings
+            .template
+            .as_deref()
+            .map(LatexTemplate::read)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+        let font = settings.font_package();
+        let background = match settings.background.parse()? {
+            Background::Transparent => None,
+            Background::Color(color) => Self::hex_to_rgb(&color.to_string()),
+        };
+        let font_color = Self::hex_to_rgb(&settings.color)
+            .ok_or_else(|| format!("invalid color '{}'", settings.color))?;
+        self.tectonic = tectonic.with_env_overrides();
+        self.font_color = font_color;
+        self.color_hex_input = settings.color;
+        self.engine = settings.engine;
+        self.missing_tools = missing_tools(&self.render_settings());
+        self.font = font;
+        self.background = background;
+        self.border_input = settings.border;
+        self.template = template;
+        self.delete_intermediates = settings.delete_intermediates;
+        self.chemistry = settings.chemistry;
+        self.tex_snippets = settings.tex_snippets;
+        self.html_gallery = settings.html_gallery;
+        if let Some(dir) = settings.output_dir.filter(|d| d.is_dir()) {
+            self.output_dir = Some(dir);
+        }
+        self.paged = settings.paged;
+        self.zoom = settings.zoom.clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+        Ok(())
+    }
+
+    /// The current state, as autosaved.
+    fn session(&self) -> Session {
+        Session {
+            saved: String::new(),
+            input_file: self.input_file.clone(),
+            equations: self.equations.clone(),
+            page: self.page,
+            settings: self.settings(),
+        }
+    }
+
+    /// Save the session if it changed and [`AUTOSAVE_INTERVAL`] has passed
+    /// since the last save, or with `now` set regardless of the interval.
+    /// Nothing is saved while an earlier session is offered for restoring.
+    fn autosave(&mut self, ctx: &egui::Context, now: bool) {
+        if self.recovered.is_some() {
+            return;
+        }
+        if !now
+            && self
+                .last_autosave
+                .is_some_and(|t| t.elapsed() < AUTOSAVE_INTERVAL)
+        {
+            ctx.request_repaint_after(AUTOSAVE_INTERVAL);
+            return;
+        }
+        self.last_autosave = Some(Instant::now());
+        let mut session = self.session();
+        let Ok(state) = serde_json::to_string(&session) else {
+            return;
+        };
+        if state == self.autosaved {
+            return;
+        }
+        let Some(path) = session_file() else {
+            return;
+        };
+        match session.save(&path) {
+            Ok(()) => self.autosaved = state,
+            Err(err) => self.error_message = Some(format!("Could not autosave the session: {err}")),
+        }
+    }
+
+    /// Continue an autosaved session: its settings, input file and equations
+    /// with their edits.
+    fn restore_session(&mut self, session: Session) {
+        if let Err(err) = self.apply_settings(session.settings) {
+            self.error_message = Some(format!("Could not restore the settings: {err}"));
+        }
+        if let Some(path) = &session.input_file {
+            self.diagnostics = lint_file(path, &ParseOptions::default()).unwrap_or_default();
+            self.source = load_source(path);
+        }
+        self.input_file = session.input_file;
+        self.equations = session.equations;
+        self.latex_view = None;
+        self.clear_filters();
+        self.page = session.page;
+        self.success_message = Some(format!("Restored the session saved at {}", session.saved));
+    }
+
+    /// Banner offering to restore the session of an earlier run.
+    fn recovery_banner(&mut self, ui: &mut egui::Ui) {
+        let Some(session) = &self.recovered else {
+            return;
+        };
+        let source = session
+            .input_file
+            .as_ref()
+            .map(|p| format!(" from {}", p.display()))
+            .unwrap_or_default();
+        let mut restore = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!(
+                "The session autosaved at {} with {} equation(s){source} can be restored.",
+                session.saved,
+                session.equations.len()
+            ));
+            if ui.button("Restore").clicked() {
+                restore = Some(true);
+            }
+            if ui.button("Discard").clicked() {
+                restore = Some(false);
+            }
+        });
+        ui.add_space(8.0);
+        match restore {
+            Some(true) => {
+                if let Some(session) = self.recovered.take() {
+                    self.restore_session(session);
+                }
+            }
+            Some(false) => self.recovered = None,
+            None => {}
+        }
+    }
+
+    /// Snapshot of the current settings for a render into the output
+    /// directory, if one is selected.
+    fn batch(&self) -> Option<Batch> {
+        Some(Batch {
+            out: self.output_dir.clone()?,
+            options: self.render_settings(),
+            tectonic: self.tectonic.clone(),
+            tex_snippets: self.tex_snippets,
+        })
+    }
+
+    /// Watch the input file in the background with the current settings,
+    /// re-rendering it on every change and notifying the desktop when done.
+    fn start_watch(&mut self, ctx: &egui::Context) {
+        let (Some(input), Some(batch)) = (self.input_file.clone(), self.batch()) else {
+            return;
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let (stopped, repaint) = (stop.clone(), ctx.clone());
+        thread::spawn(move || {
+            let modified = |path: &Path| -> Option<SystemTime> {
+                std::fs::metadata(path).and_then(|m| m.modified()).ok()
+            };
+            let mut last = modified(&input);
+            while !stopped.load(Ordering::SeqCst) {
+                thread::sleep(WATCH_INTERVAL);
+                let now = modified(&input);
+                if now == last {
+                    continue;
+                }
+                last = now;
+                let event = match load_equations(&input) {
+                    Ok(mut eqs) => {
+                        for eq in &mut eqs {
+                            prepare_equation(eq, &batch.options);
+                        }
+                        disambiguate_names(&mut eqs);
+                        batch.run_caught(&eqs, |_, _, _| {})
+                    }
+                    Err(err) => RenderEvent::Failed(err),
+                };
+                let file = input.file_name().unwrap_or_default().to_string_lossy();
+                let _ = match &event {
+                    RenderEvent::Finished {
+                        summary, failed, ..
+                    } => match failure_message(failed) {
+                        Some(message) => {
+                            notify_desktop(&format!("Rendering {file} failed"), &message)
+                        }
+                        None => notify_desktop(&format!("Rendered {file}"), summary),
+                    },
+                    RenderEvent::Failed(err) => {
+                        notify_desktop(&format!("Reading {file} failed"), err)
+                    }
+                    RenderEvent::Progress { .. } => Ok(()),
+                };
+                let _ = tx.send(event);
+                repaint.request_repaint();
+            }
+        });
+        self.watch = Some(Watch { stop, rx });
+    }
+
+    /// Render the equation named `name` with the current settings into a
+    /// temporary directory, to compare it with its existing output.
+    fn start_comparison(&mut self, ctx: &egui::Context, name: &str) {
+        let Some(mut eq) = self.equations.iter().find(|e| e.name == name).cloned() else {
+            return;
+        };
+        prepare_equation(&mut eq, &self.render_settings());
+        self.comparisons += 1;
+        let dir = std::env::temp_dir().join(format!(
+            "eqp_compare_{}_{}",
+            std::process::id(),
+            self.comparisons
+        ));
+        let options = RenderOptions {
+            delete_intermediates: true,
+            ..self.render_settings()
+        };
+        let tectonic = self.tectonic.clone();
+        let (tx, rx) = mpsc::channel();
+        let repaint = ctx.clone();
+        let out = dir.clone();
+        thread::spawn(move || {
+            let result = eq
+                .render_with(&out, &tectonic, &options)
+                .map_err(|e| e.to_string());
+            let _ = tx.send(result);
+            repaint.request_repaint();
+        });
+        self.comparison = Some(Comparison {
+            name: name.to_string(),
+            dir,
+            rx,
+            result: None,
+        });
+    }
+
+    /// Swatches of `palette` as seen with each kind of color vision, and the
+    /// pairs of colors that are hard to tell apart.
+    fn palette_view(ui: &mut egui::Ui, palette: &[Color]) {
+        let conflicts = check_palette(palette);
+        let title = match conflicts.len() {
+            0 => format!("Palette ({} colors)", palette.len()),
+            n => format!("Palette ({} colors, {n} hard to tell apart)", palette.len()),
+        };
+        egui::CollapsingHeader::new(title).show(ui, |ui| {
+            egui::Grid::new("palette").show(ui, |ui| {
+                ui.label("");
+                for color in palette {
+                    ui.monospace(color.to_string());
+                }
+                ui.end_row();
+                for vision in ColorVision::ALL {
+                    ui.label(vision.as_str());
+                    for &color in palette {
+                        let seen = vision.simulate(color);
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(56.0, 20.0), egui::Sense::hover());
+                        ui.painter().rect_filled(
+                            rect,
+                            2.0,
+                            Color32::from_rgb(seen.r, seen.g, seen.b),
+                        );
+                    }
+                    ui.end_row();
+                }
+            });
+            for conflict in &conflicts {
+                ui.colored_label(Color32::from_rgb(200, 120, 0), conflict.to_string());
+            }
+        });
+    }
+
+    /// Existing and trial SVG side by side, or the trial's progress.
+    fn comparison_view(ui: &mut egui::Ui, comparison: &mut Comparison, output_dir: &Path) {
+        if comparison.result.is_none() {
+            comparison.result = comparison.rx.try_recv().ok();
+        }
+        ui.label(format!("Before / after for {}:", comparison.name));
+        let svg = |dir: &Path| {
+            format!(
+                "file://{}",
+                dir.join(format!("{}.svg", comparison.name)).display()
+            )
+        };
+        ui.horizontal(|ui| {
+            ui.vertical(|ui| {
+                ui.weak("Current");
+                ui.add(
+                    egui::Image::new(svg(output_dir))
+                        .max_width(240.0)
+                        .max_height(160.0),
+                );
+            });
+            ui.separator();
+            ui.vertical(|ui| {
+                ui.weak("New settings");
+                match &comparison.result {
+                    None => {
+                        ui.add(Spinner::new());
+                    }
+                    Some(Ok(())) => {
+                        ui.add(
+                            egui::Image::new(svg(&comparison.dir))
+                                .max_width(240.0)
+                                .max_height(160.0),
+                        );
+                    }
+                    Some(Err(e)) => {
+                        ui.colored_label(Color32::RED, e);
+                    }
+                }
+            });
+        });
+    }
+
+    /// The parse warnings list and the source pane. Clicking a warning
+    /// highlights its line in the source and scrolls there.
+    fn diagnostics_view(&mut self, ui: &mut egui::Ui) {
+        if !self.diagnostics.is_empty() {
+            egui::CollapsingHeader::new(format!("Parse warnings ({})", self.diagnostics.len()))
+                .default_open(true)
+                .show(ui, |ui| {
+                    ScrollArea::vertical()
+                        .id_salt("diagnostics")
+                        .max_height(120.0)
+                        .show(ui, |ui| {
+                            for d in &self.diagnostics {
+                                let color = match d.severity {
+                                    Severity::Error => Color32::RED,
+                                    Severity::Warning => Color32::from_rgb(200, 120, 0),
+                                };
+                                let text = RichText::new(format!(
+                                    "Line {}: {} [{}]",
+                                    d.line, d.message, d.code
+                                ))
+                                .color(color);
+                                let selected = self.highlighted_line == Some(d.line);
+                                let response = ui.selectable_label(selected, text);
+                                let response = if d.name.is_empty() {
+                                    response
+                                } else {
+                                    response.on_hover_text(&d.name)
+                                };
+                                if response.clicked() && self.source.is_some() {
+                                    self.highlighted_line = Some(d.line);
+                                    self.scroll_to_line = true;
+                                }
+                            }
+                        });
+                });
+        }
+        let Some(lines) = &self.source else {
+            return;
+        };
+        let scroll = std::mem::take(&mut self.scroll_to_line);
+        egui::CollapsingHeader::new("Source")
+            .open(scroll.then_some(true))
+            .show(ui, |ui| {
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                let mut area = ScrollArea::vertical()
+                    .id_salt("source")
+                    .max_height(200.0)
+                    .auto_shrink([false, true]);
+                if let (true, Some(line)) = (scroll, self.highlighted_line) {
+                    // Keep a few lines of context above the highlighted one.
+                    let row = row_height + ui.spacing().item_spacing.y;
+                    area = area.vertical_scroll_offset(line.saturating_sub(3) as f32 * row);
+                }
+                area.show_rows(ui, row_height, lines.len(), |ui, range| {
+                    for i in range {
+                        let mut text =
+                            RichText::new(format!("{:>4}  {}", i + 1, lines[i])).monospace();
+                        if self.highlighted_line == Some(i + 1) {
+                            text = text.background_color(ui.visuals().selection.bg_fill);
+                        }
+                        ui.add(egui::Label::new(text).truncate());
+                    }
+                });
+            });
+    }
+
+    /// Spawn the background render thread for the loaded equations.
+    ///
+    /// With `skip_existing`, equations that already have output files in the
+    /// output directory are left out of the batch.
+    fn start_render(&mut self, ctx: &egui::Context, skip_existing: bool) {
+        let mut eqs = self.equations.clone();
+        let batch = self.batch().unwrap();
+        let out = &batch.out;
+        if skip_existing {
+            for eq in eqs.iter_mut().filter(|e| e.active) {
+                if !existing_outputs(std::slice::from_ref(eq), out).is_empty() {
+                    eq.active = false;
+                }
+            }
+        }
+        for eq in &mut eqs {
+            prepare_equation(eq, &batch.options);
+        }
+        let (tx, rx) = mpsc::channel();
+        self.progress_rx = Some(rx);
+        self.processing = true;
+        let total = eqs.iter().filter(|e| e.active).count();
+        self.progress = Some((0, total));
+        self.timer = RenderTimer::default();
+        // The thread wakes the UI so progress shows even while unfocused.
+        let repaint = ctx.clone();
+        thread::spawn(move || {
+            let finished = batch.run_caught(&eqs, |done, total, took| {
+                let _ = tx.send(RenderEvent::Progress { done, total, took });
+                repaint.request_repaint();
+            });
+            let _ = tx.send(finished);
+            repaint.request_repaint();
+        });
+    }
+
+    /// Start rendering the active equations, as the Process button does.
+    ///
+    /// Checks the disk space first and asks before overwriting existing
+    /// outputs.
+    fn process(&mut self, ctx: &egui::Context) {
+        self.error_message = None;
+        self.success_message = None;
+        self.warnings = disambiguate_names(&mut self.equations);
+        let out = self.output_dir.as_ref().unwrap();
+        let options = self.render_settings();
+        let (_, fallback) = batch_renderer(&self.tectonic, &options);
+        self.warnings.extend(fallback);
+        let space = estimate_disk_space(&self.equations, out, self.delete_intermediates, &options);
+        let existing = existing_outputs(&self.equations, out);
+        if !space.fits() {
+            self.error_message = Some(format!("Not enough disk space: {space}."));
+            return;
+        }
+        if space.is_tight() {
+            self.warnings
+                .push(format!("The batch will nearly fill the disk ({space})."));
+        }
+        if existing.is_empty() {
+            self.start_render(ctx, false);
+        } else {
+            self.pending_overwrite = Some(existing);
+        }
+    }
+
+    /// Save the settings to the config file at `path`, loaded on startup.
+    fn save_default_settings(&mut self, path: &Path) {
+        let saved = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|()| self.settings().save(path));
+        match saved {
+            Ok(()) => self.success_message = Some(format!("Settings saved to {}", path.display())),
+            Err(err) => self.error_message = Some(format!("Saving failed: {err}")),
+        }
+    }
+
+    /// Show the overwrite confirmation while [`Self::pending_overwrite`] is set.
+    fn overwrite_modal(&mut self, ctx: &egui::Context) {
+        let Some(files) = &self.pending_overwrite else {
+            return;
+        };
+        let mut choice = None;
+        let mut compare = None;
+        let output_dir = self.output_dir.clone().unwrap_or_default();
+        let comparison = &mut self.comparison;
+        let modal = egui::Modal::new(egui::Id::new("overwrite_modal")).show(ctx, |ui| {
+            ui.heading("Overwrite existing files?");
+            ui.label(format!(
+                "{} file(s) in the output directory will be overwritten:",
+                files.len()
+            ));
+            ui.add_space(8.0);
+            ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                for file in files {
+                    ui.horizontal(|ui| {
+                        ui.monospace(file.file_name().unwrap_or_default().to_string_lossy());
+                        if file.extension().is_some_and(|ext| ext == "svg")
+                            && ui.small_button("Compare").clicked()
+                        {
+                            compare = file.file_stem().map(|s| s.to_string_lossy().into_owned());
+                        }
+                    });
+                }
+            });
+            if let Some(comparison) = comparison {
+                ui.add_space(8.0);
+                Self::comparison_view(ui, comparison, &output_dir);
+            }
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button("Overwrite all").clicked() {
+                    choice = Some(OverwriteChoice::OverwriteAll);
+                }
+                if ui.button("Skip existing").clicked() {
+                    choice = Some(OverwriteChoice::SkipExisting);
+                }
+                if ui.button("Cancel").clicked() {
+                    choice = Some(OverwriteChoice::Cancel);
+                }
+            });
+        });
+        if let Some(name) = compare {
+            self.start_comparison(ctx, &name);
+        }
+        if modal.should_close() {
+            choice.get_or_insert(OverwriteChoice::Cancel);
+        }
+        match choice {
+            Some(OverwriteChoice::OverwriteAll) => self.start_render(ctx, false),
+            Some(OverwriteChoice::SkipExisting) => self.start_render(ctx, true),
+            Some(OverwriteChoice::Cancel) | None => {}
+        }
+        if choice.is_some() {
+            self.pending_overwrite = None;
+            self.comparison = None;
+        }
+    }
+}
+
+impl EquationProcessorApp {
+    /// Show the batch rename dialog while [`Self::rename`] is set.
+    ///
+    /// Renames apply to the active (checked) rows; the dialog previews
+    /// old → new names and warns about collisions before applying.
+    fn rename_modal(&mut self, ctx: &egui::Context) {
+        let Some(rule) = &mut self.rename else {
+            return;
+        };
+        let mut apply = false;
+        let mut close = false;
+        let plan = plan_renames(&self.equations, rule);
+        let modal = egui::Modal::new(egui::Id::new("rename_modal")).show(ctx, |ui| {
+            ui.heading("Rename selected equations");
+            egui::Grid::new("rename_rule")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Find:");
+                    ui.add(egui::TextEdit::singleline(&mut rule.find).hint_text("whole name"));
+                    ui.end_row();
+                    ui.label("Replace:");
+                    ui.add(egui::TextEdit::singleline(&mut rule.replace).hint_text("eq_{n}"));
+                    ui.end_row();
+                    ui.label("");
+                    ui.checkbox(&mut rule.regex, "Regular expression");
+                    ui.end_row();
+                    ui.label("Case:");
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut rule.case, CaseTransform::Keep, "Keep");
+                        ui.radio_value(&mut rule.case, CaseTransform::Lower, "lower");
+                        ui.radio_value(&mut rule.case, CaseTransform::Upper, "UPPER");
+                    });
+                    ui.end_row();
+                    ui.label("{n} from:");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut rule.start));
+                        ui.label("digits:");
+                        ui.add(egui::DragValue::new(&mut rule.width).range(0..=9));
+                    });
+                    ui.end_row();
+                });
+            ui.add_space(8.0);
+            match &plan {
+                Ok((renames, warnings)) => {
+                    let changed: Vec<_> = renames.iter().filter(|r| r.old != r.new).collect();
+                    ui.label(format!(
+                        "{} of {} selected name(s) change:",
+                        changed.len(),
+                        renames.len()
+                    ));
+                    ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+                        for rename in &changed {
+                            ui.monospace(format!("{} → {}", rename.old, rename.new));
+                        }
+                    });
+                    for warning in warnings {
+                        ui.colored_label(Color32::from_rgb(200, 120, 0), warning);
+                    }
+                }
+                Err(e) => {
+                    ui.colored_label(Color32::RED, e);
+                }
+            }
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(plan.is_ok(), egui::Button::new("Apply"))
+                    .clicked()
+                {
+                    apply = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    close = true;
+                }
+            });
+        });
+        if apply {
+            if let Ok((renames, _)) = &plan {
+                apply_renames(&mut self.equations, renames);
+            }
+        }
+        if apply || close || modal.should_close() {
+            self.rename = None;
+        }
+    }
+
+    /// Why `command` cannot run right now, or `None` if it can.
+    fn command_unavailable(&self, command: Command) -> Option<String> {
+        match command {
+            Command::ToggleAll if self.equations.is_empty() => Some("No equations loaded".into()),
+            Command::Render | Command::Rename if self.processing => {
+                Some("Rendering in progress".into())
+            }
+            Command::Render => self.validation_errors().into_iter().next(),
+            Command::Rename | Command::ExportCsv if self.equations.is_empty() => {
+                Some("No equations loaded".into())
+            }
+            Command::ExportGallery | Command::ExportMarkdown if self.output_dir.is_none() => {
+                Some("Select an output directory first".into())
+            }
+            Command::SaveDefaultSettings if config_file().is_none() => {
+                Some("No config directory on this system".into())
+            }
+            _ => None,
+        }
+    }
+
+    /// Run `command` as if its button had been clicked.
+    fn run_command(&mut self, ctx: &egui::Context, command: Command) {
+        match command {
+            Command::OpenFile => self.open_file_dialog.pick_file(),
+            Command::OpenFolder => self.open_file_dialog.pick_directory(),
+            Command::SetOutput => self.select_dir_dialog.pick_directory(),
+            Command::ToggleAll => {
+                // Like Select All/None, only the rows shown are affected.
+                let shown: Vec<usize> = (0..self.equations.len())
+                    .filter(|&i| self.is_shown(&self.equations[i]))
+                    .collect();
+                let active = !shown.iter().all(|&i| self.equations[i].active);
+                for i in shown {
+                    self.equations[i].active = active;
+                }
+            }
+            Command::Render => self.process(ctx),
+            Command::Rename => self.rename = Some(RenameRule::default()),
+            Command::ExportCsv => self.export_csv_dialog.save_file(),
+            Command::ExportGallery | Command::ExportMarkdown => {
+                let Some(out) = &self.output_dir else {
+                    return;
+                };
+                let written = if command == Command::ExportGallery {
+                    write_gallery(&self.equations, out)
+                } else {
+                    write_markdown(&self.equations, out, false)
+                };
+                match written {
+                    Ok(path) => {
+                        self.success_message = Some(format!("Report written to {}", path.display()))
+                    }
+                    Err(err) => self.error_message = Some(format!("Export failed: {err}")),
+                }
+            }
+            Command::ExportSettings => self.export_settings_dialog.save_file(),
+            Command::ImportSettings => self.import_settings_dialog.pick_file(),
+            Command::SaveDefaultSettings => {
+                if let Some(path) = config_file() {
+                    self.save_default_settings(&path);
+                }
+            }
+        }
+    }
+
+    /// Show the command palette while [`Self::command_palette`] is set.
+    ///
+    /// Typing filters the commands with [`fuzzy_score`], best match first.
+    /// The arrow keys move the highlight, Enter runs it and Escape closes
+    /// the palette. Commands that cannot run right now are greyed out.
+    fn command_palette_modal(&mut self, ctx: &egui::Context) {
+        let Some(mut palette) = self.command_palette.take() else {
+            return;
+        };
+        let (down, up, enter) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            )
+        });
+        let mut chosen = None;
+        let modal = egui::Modal::new(egui::Id::new("command_palette")).show(ctx, |ui| {
+            ui.set_width(360.0);
+            let search = ui.add(
+                egui::TextEdit::singleline(&mut palette.query)
+                    .hint_text("Type a command")
+                    .desired_width(f32::INFINITY),
+            );
+            search.request_focus();
+            if search.changed() {
+                palette.selected = 0;
+            }
+            let mut matches: Vec<(i32, Command)> = Command::ALL
+                .into_iter()
+                .filter_map(|c| fuzzy_score(&palette.query, c.label()).map(|score| (score, c)))
+                .collect();
+            // The sort is stable, so equal scores keep the listed order.
+            matches.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+            if down {
+                palette.selected += 1;
+            }
+            if up {
+                palette.selected = palette.selected.saturating_sub(1);
+            }
+            palette.selected = palette.selected.min(matches.len().saturating_sub(1));
+            ui.add_space(4.0);
+            if matches.is_empty() {
+                ui.weak("No matching commands");
+            }
+            for (i, &(_, command)) in matches.iter().enumerate() {
+                let unavailable = self.command_unavailable(command);
+                let mut response = ui.add_enabled(
+                    unavailable.is_none(),
+                    egui::SelectableLabel::new(i == palette.selected, command.label()),
+                );
+                if let Some(reason) = &unavailable {
+                    response = response.on_disabled_hover_text(reason);
+                }
+                let picked = response.clicked() || (enter && i == palette.selected);
+                if picked && unavailable.is_none() {
+                    chosen = Some(command);
+                }
+            }
+        });
+        match chosen {
+            Some(command) => self.run_command(ctx, command),
+            None if !modal.should_close() => self.command_palette = Some(palette),
+            None => {}
+        }
+    }
+
+    /// Window showing the LaTeX document compiled for the row whose name was
+    /// clicked, with the current settings, for debugging a failing compile.
+    ///
+    /// Extra preamble lines typed here apply to that equation only and are
+    /// part of the shown document right away.
+    fn latex_window(&mut self, ctx: &egui::Context) {
+        let Some(mut eq) = self.latex_view.and_then(|i| self.equations.get(i)).cloned() else {
+            self.latex_view = None;
+            return;
+        };
+        let options = self.render_settings();
+        prepare_equation(&mut eq, &options);
+        let source = eq.generate_latex_with(&options.color, &options);
+        let mut preamble = eq.preamble.clone().unwrap_or_default();
+        let mut open = true;
+        egui::Window::new(format!("LaTeX source of {}", eq.name))
+            .open(&mut open)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Extra preamble for this equation:");
+                    if ui
+                        .button("Copy")
+                        .on_hover_text("Copy the document")
+                        .clicked()
+                    {
+                        ui.ctx().copy_text(source.clone());
+                    }
+                });
+                ui.add(
+                    egui::TextEdit::multiline(&mut preamble)
+                        .code_editor()
+                        .desired_rows(2)
+                        .desired_width(f32::INFINITY)
+                        .hint_text("\\usepackage{siunitx}"),
+                );
+                ui.separator();
+                ScrollArea::vertical().max_height(360.0).show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut source.as_str())
+                            .code_editor()
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            });
+        if let Some(i) = self.latex_view {
+            self.equations[i].preamble = (!preamble.trim().is_empty()).then_some(preamble);
+        }
+        if !open {
+            self.latex_view = None;
+        }
     }
 }
 
 impl eframe::App for EquationProcessorApp {
+    /// Stop a render still running when the window closes, so its tectonic
+    /// processes don't keep going in the background.
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        children::kill_all();
+    }
+
     /// Called each frame to update application logic and draw the UI.
     ///
     /// This method:
@@ -104,48 +1548,150 @@ impl eframe::App for EquationProcessorApp {
     /// 2. Handles file and directory dialog interactions.
     /// 3. Renders the main UI: selectors, options, process button,
     ///    spinner indicator, messages, and equations table.
+    /// 4. Autosaves the session.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        // 1. Check for background render completion
-        if let Some(rx) = &self.progress_rx {
-            if rx.try_recv().is_ok() {
-                self.processing = false;
-                self.progress_rx = None;
-                self.success_message = Some("Rendering complete!".into());
-                ctx.request_repaint();
+        // 1. Check for background render progress and completion
+        let events: Vec<RenderEvent> = self
+            .progress_rx
+            .as_ref()
+            .map(|rx| rx.try_iter().collect())
+            .unwrap_or_default();
+        for event in events {
+            match event {
+                RenderEvent::Progress { done, total, took } => {
+                    self.progress = Some((done, total));
+                    self.timer.record(took);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Title(format!(
+                        "Rendering {done}/{total} – {APP_TITLE}"
+                    )));
+                }
+                RenderEvent::Finished {
+                    summary,
+                    rendered,
+                    failed,
+                } => {
+                    self.record_statuses(&rendered, &failed);
+                    self.processing = false;
+                    self.progress_rx = None;
+                    self.progress = None;
+                    self.success_message = Some(format!("Rendering complete! {summary}"));
+                    self.error_message = failure_message(&failed);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Title(APP_TITLE.into()));
+                    ctx.request_repaint();
+                }
+                RenderEvent::Failed(err) => {
+                    self.processing = false;
+                    self.progress_rx = None;
+                    self.progress = None;
+                    self.error_message = Some(err);
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Title(APP_TITLE.into()));
+                }
             }
         }
+        let watched: Vec<RenderEvent> = self
+            .watch
+            .as_ref()
+            .map(|w| w.rx.try_iter().collect())
+            .unwrap_or_default();
+        for event in watched {
+            match event {
+                RenderEvent::Finished {
+                    summary,
+                    rendered,
+                    failed,
+                } => {
+                    self.record_statuses(&rendered, &failed);
+                    self.success_message = Some(format!("Input changed, re-rendered. {summary}"));
+                    self.error_message = failure_message(&failed);
+                }
+                RenderEvent::Failed(err) => self.error_message = Some(err),
+                RenderEvent::Progress { .. } => {}
+            }
+        }
+
+        // Closing the window hides it to the tray while that is enabled.
+        #[cfg(feature = "tray")]
+        if self.minimize_to_tray
+            && ctx.input(|i| i.viewport().close_requested())
+            && self.tray.as_ref().is_some_and(|t| !t.quit_requested())
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(false));
+        }
 
         // 2. Update file dialogs and load/validate input
         self.open_file_dialog.update(ctx);
         if let Some(path) = self.open_file_dialog.take_picked() {
-            self.input_file = Some(path.clone());
-            // Validate and parse by file type
-            match detect_file_type(&path) {
-                Filetype::Csv => {
-                    self.equations = read_csv_file(&path).unwrap_or_default();
-                    self.error_message = None;
+            self.open_input(path);
+        }
+        self.select_dir_dialog.update(ctx);
+        if let Some(path) = self.select_dir_dialog.take_picked() {
+            self.output_dir = Some(path);
+        }
+        self.export_csv_dialog.update(ctx);
+        if let Some(path) = self.export_csv_dialog.take_picked() {
+            match write_csv_file(&self.equations, &path) {
+                Ok(()) => {
+                    self.success_message = Some(format!("Equations saved to {}", path.display()))
                 }
-                Filetype::Markdown => {
-                    let txt = std::fs::read_to_string(&path).unwrap_or_default();
-                    self.equations = parse_markdown(&txt);
-                    self.error_message = None;
+                Err(err) => self.error_message = Some(format!("Saving failed: {err}")),
+            }
+        }
+        self.export_settings_dialog.update(ctx);
+        if let Some(path) = self.export_settings_dialog.take_picked() {
+            match self.settings().save(&path) {
+                Ok(()) => {
+                    self.success_message = Some(format!("Settings exported to {}", path.display()))
                 }
-                Filetype::Unknown => {
-                    self.equations.clear();
-                    self.error_message = Some("Unsupported file type selected.".into());
-                    self.success_message = None;
+                Err(err) => self.error_message = Some(format!("Export failed: {err}")),
+            }
+        }
+        self.template_dialog.update(ctx);
+        if let Some(path) = self.template_dialog.take_picked() {
+            match LatexTemplate::read(&path) {
+                Ok(template) => {
+                    self.error_message = None;
+                    self.template = Some(template);
                 }
+                Err(err) => self.error_message = Some(format!("Invalid template: {err}")),
             }
         }
-        self.select_dir_dialog.update(ctx);
-        if let Some(path) = self.select_dir_dialog.take_picked() {
-            self.output_dir = Some(path);
+        self.import_settings_dialog.update(ctx);
+        if let Some(path) = self.import_settings_dialog.take_picked() {
+            let imported = Settings::load(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|settings| self.apply_settings(settings));
+            match imported {
+                Ok(()) => {
+                    self.error_message = None;
+                    self.success_message =
+                        Some(format!("Settings imported from {}", path.display()));
+                }
+                Err(err) => self.error_message = Some(format!("Import failed: {err}")),
+            }
         }
 
         // 3. Render UI components
+        if ctx.input_mut(|i| i.consume_shortcut(&COMMAND_PALETTE_SHORTCUT)) {
+            self.command_palette = match self.command_palette {
+                Some(_) => None,
+                None => Some(CommandPalette::default()),
+            };
+        }
+        self.command_palette_modal(ctx);
+        self.overwrite_modal(ctx);
+        self.rename_modal(ctx);
+        self.latex_window(ctx);
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading("Equation Processor");
+            ui.horizontal(|ui| {
+                ui.heading("Equation Processor");
+                ui.weak(format!(
+                    "{} for commands",
+                    ctx.format_shortcut(&COMMAND_PALETTE_SHORTCUT)
+                ));
+            });
             ui.add_space(12.0);
+            self.recovery_banner(ui);
 
             // Input file selector row
             ui.horizontal(|ui| {
@@ -153,6 +1699,13 @@ impl eframe::App for EquationProcessorApp {
                 if ui.button("Browse…").clicked() {
                     self.open_file_dialog.pick_file();
                 }
+                if ui
+                    .button("Folder…")
+                    .on_hover_text("A folder of .tex files, one equation per file")
+                    .clicked()
+                {
+                    self.open_file_dialog.pick_directory();
+                }
                 if let Some(p) = &self.input_file {
                     ui.label(p.display().to_string());
                 }
@@ -174,78 +1727,267 @@ impl eframe::App for EquationProcessorApp {
             // Rendering options
             ui.horizontal(|ui| {
                 ui.label("Font color:");
-                
-                
+
                 // Color picker
                 let picker_changed = ui.color_edit_button_rgb(&mut self.font_color).changed();
-                
+
                 // Hex text input
                 let text_response = ui.add(
                     egui::TextEdit::singleline(&mut self.color_hex_input)
                         .desired_width(80.0)
-                        .hint_text("#000000")
+                        .hint_text("#000000"),
                 );
-                
+
                 // Synchronize color picker -> hex input
                 if picker_changed {
                     self.color_hex_input = Self::rgb_to_hex(self.font_color);
                 }
-                
+
                 // Synchronize hex input -> color picker
-                if text_response.changed() {
-                    if Self::is_valid_hex_color(&self.color_hex_input) {
-                        if let Some(rgb) = Self::hex_to_rgb(&self.color_hex_input) {
-                            self.font_color = rgb;
-                        }
+                if text_response.changed() && Self::is_valid_hex_color(&self.color_hex_input) {
+                    if let Some(rgb) = Self::hex_to_rgb(&self.color_hex_input) {
+                        self.font_color = rgb;
                     }
                 }
-                
+
                 // Show validation indicator
-                if !Self::is_valid_hex_color(&self.color_hex_input) && !self.color_hex_input.is_empty() {
+                if !Self::is_valid_hex_color(&self.color_hex_input)
+                    && !self.color_hex_input.is_empty()
+                {
                     ui.colored_label(Color32::RED, "Invalid hex color");
                 }
-                
+
+                let mut filled = self.background.is_some();
+                if ui
+                    .checkbox(&mut filled, "Background:")
+                    .on_hover_text("A solid fill behind the equations; transparent when off")
+                    .changed()
+                {
+                    self.background = filled.then_some([1.0, 1.0, 1.0]);
+                }
+                if let Some(rgb) = &mut self.background {
+                    ui.color_edit_button_rgb(rgb);
+                }
+
                 ui.checkbox(&mut self.delete_intermediates, "Delete intermediates");
+                ui.checkbox(&mut self.chemistry, "Chemistry (mhchem)");
+                ui.checkbox(&mut self.tex_snippets, "TeX snippets");
+                ui.checkbox(&mut self.html_gallery, "HTML gallery")
+                    .on_hover_text("gallery.html, following the light/dark system setting");
+                let mut reproducible = self.tectonic.source_date_epoch.is_some();
+                if ui
+                    .checkbox(&mut reproducible, "Reproducible")
+                    .on_hover_text(
+                        "Date every PDF at 1970-01-01 so unchanged equations render to \
+                         byte-identical files",
+                    )
+                    .changed()
+                {
+                    self.tectonic.source_date_epoch = reproducible.then_some(0);
+                }
+
+                ui.label("Border:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.border_input)
+                        .hint_text(StandaloneOptions::DEFAULT_BORDER)
+                        .desired_width(60.0),
+                );
+
+                ui.label("Engine:");
+                let engine = self.engine;
+                egui::ComboBox::from_id_salt("latex_engine")
+                    .selected_text(self.engine.program())
+                    .show_ui(ui, |ui| {
+                        for engine in LatexEngine::ALL {
+                            ui.selectable_value(&mut self.engine, engine, engine.program());
+                        }
+                    });
+                if self.engine != engine {
+                    self.missing_tools = missing_tools(&self.render_settings());
+                }
+
+                ui.label("Font:");
+                egui::ComboBox::from_id_salt("font_package")
+                    .selected_text(self.font.as_deref().unwrap_or("Computer Modern"))
+                    .show_ui(ui, |ui| {
+                        for package in FONT_PACKAGES {
+                            ui.selectable_value(
+                                &mut self.font,
+                                Some(package.to_string()),
+                                *package,
+                            );
+                        }
+                        ui.selectable_value(&mut self.font, None, "Computer Modern")
+                            .on_hover_text("LaTeX's default, no font package");
+                    });
+
+                ui.label("Template:");
+                if ui
+                    .button("Browse…")
+                    .on_hover_text(
+                        "A LaTeX document with {{BODY}}, {{COLOR}} and other placeholders \
+                         to typeset every equation in",
+                    )
+                    .clicked()
+                {
+                    self.template_dialog.pick_file();
+                }
+                match &self.template {
+                    Some(template) => {
+                        let name = template.path.file_name().unwrap_or_default();
+                        ui.label(name.to_string_lossy())
+                            .on_hover_text(template.path.display().to_string());
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text("Use the built-in document")
+                            .clicked()
+                        {
+                            self.template = None;
+                        }
+                    }
+                    None => {
+                        ui.weak("built-in");
+                    }
+                }
+            });
+            ui.add_space(8.0);
+
+            // Sharing the settings between machines
+            ui.horizontal(|ui| {
+                ui.label("Settings:");
+                if ui
+                    .button("Export…")
+                    .on_hover_text("Save the options above and the tectonic setup to a TOML file")
+                    .clicked()
+                {
+                    self.export_settings_dialog.save_file();
+                }
+                if ui
+                    .button("Import…")
+                    .on_hover_text("Load options exported on this or another machine")
+                    .clicked()
+                {
+                    self.import_settings_dialog.pick_file();
+                }
+                if let Some(path) = config_file() {
+                    if ui
+                        .button("Save as default")
+                        .on_hover_text(format!(
+                            "Load these settings on startup from {}",
+                            path.display()
+                        ))
+                        .clicked()
+                    {
+                        self.save_default_settings(&path);
+                    }
+                }
+            });
+            ui.add_space(8.0);
+
+            // Background operation
+            ui.horizontal(|ui| {
+                let mut watching = self.watch.is_some();
+                let can_watch = self.input_file.is_some()
+                    && self.output_dir.is_some()
+                    && Self::is_valid_hex_color(&self.color_hex_input);
+                let toggled = ui
+                    .add_enabled(
+                        can_watch || watching,
+                        egui::Checkbox::new(&mut watching, "Watch input"),
+                    )
+                    .on_hover_text(
+                        "Re-render whenever the input file changes, with the settings \
+                         at the time watching started",
+                    )
+                    .on_disabled_hover_text("Select an input file and output directory first")
+                    .changed();
+                if toggled {
+                    if watching {
+                        self.start_watch(ctx);
+                    } else {
+                        self.watch = None;
+                    }
+                }
+                #[cfg(feature = "tray")]
+                if ui
+                    .checkbox(&mut self.minimize_to_tray, "Minimize to tray")
+                    .on_hover_text("Closing the window hides it; quit from the tray menu")
+                    .changed()
+                    && self.minimize_to_tray
+                    && self.tray.is_none()
+                {
+                    self.tray = Some(crate::tray::Tray::new(ctx));
+                }
             });
             ui.add_space(12.0);
 
+            // The batch's colors as color-blind readers see them
+            if let Ok(batch_color) = self.render_settings().color.parse::<Color>() {
+                let palette = batch_palette(&self.equations, batch_color);
+                if palette.len() > 1 {
+                    Self::palette_view(ui, &palette);
+                    ui.add_space(8.0);
+                }
+            }
+
+            // What a render would run, for debugging the environment
+            if let (Some(batch), Some(eq)) =
+                (self.batch(), self.equations.iter().find(|e| e.active))
+            {
+                egui::CollapsingHeader::new("Show commands").show(ui, |ui| {
+                    ui.weak(format!(
+                        "Run for '{}'; the other equations differ only in their names.",
+                        eq.name
+                    ));
+                    let mut eq = eq.clone();
+                    prepare_equation(&mut eq, &batch.options);
+                    for line in eq.planned_commands(&batch.out, &batch.tectonic, &batch.options) {
+                        ui.monospace(line);
+                    }
+                });
+                ui.add_space(8.0);
+            }
+
             // Process button and optional spinner indicator
+            let problems = self.validation_errors();
             ui.horizontal(|ui| {
-                let btn = ui.add_enabled(!self.processing, egui::Button::new("Process"));
-                if btn.clicked() {
-                    self.error_message = None;
-                    self.success_message = None;
-                    if self.equations.is_empty() {
-                        self.error_message = Some("No equations loaded.".into());
-                    } else if self.output_dir.is_none() {
-                        self.error_message = Some("Select an output directory.".into());
+                let btn = ui
+                    .add_enabled(
+                        !self.processing && problems.is_empty(),
+                        egui::Button::new("Process"),
+                    )
+                    .on_disabled_hover_text(if self.processing {
+                        "Rendering in progress"
                     } else {
-                        // Spawn background render thread
-                        let eqs = std::mem::take(&mut self.equations);
-                        let out = self.output_dir.clone().unwrap();
-                        let del = self.delete_intermediates;
-                        let hex = format!(
-                            "#{:02X}{:02X}{:02X}",
-                            (self.font_color[0] * 255.0) as u8,
-                            (self.font_color[1] * 255.0) as u8,
-                            (self.font_color[2] * 255.0) as u8
-                        );
-                        let (tx, rx) = mpsc::channel();
-                        self.progress_rx = Some(rx);
-                        self.processing = true;
-                        thread::spawn(move || {
-                            for eq in eqs {
-                                let _ = eq.render(&out, &hex, del);
-                            }
-                            let _ = tx.send(());
-                        });
-                    }
+                        "Fix the problems listed below first"
+                    });
+                if btn.clicked() {
+                    self.process(ctx);
                 }
                 if self.processing {
                     ui.add(Spinner::new().size(16.0));
-                    ui.label(" Rendering…");
+                    match self.progress {
+                        Some((done, total)) => {
+                            ui.label(format!(" Rendering {done}/{total}…"));
+                            if let Some(summary) = self.timer.summary(total - done) {
+                                ui.weak(summary);
+                            }
+                        }
+                        None => {
+                            ui.label(" Rendering…");
+                        }
+                    }
                 }
             });
+            if !self.processing && !problems.is_empty() {
+                ui.add_space(4.0);
+                for problem in &problems {
+                    ui.weak(format!("• {problem}"));
+                }
+                if !self.missing_tools.is_empty() && ui.small_button("Check again").clicked() {
+                    self.missing_tools = missing_tools(&self.render_settings());
+                }
+            }
             ui.add_space(12.0);
 
             // Display error or success messages
@@ -257,76 +1999,202 @@ impl eframe::App for EquationProcessorApp {
                 ui.colored_label(Color32::from_rgb(0, 100, 0), msg);
                 ui.add_space(8.0);
             }
+            for warning in &self.warnings {
+                ui.colored_label(Color32::from_rgb(200, 120, 0), warning);
+            }
+            self.diagnostics_view(ui);
 
             ui.separator();
             ui.add_space(8.0);
 
             // Equations table
             if !self.equations.is_empty() {
-                // Select All/None buttons
+                self.filter_chips(ui);
+                let shown: Vec<usize> = (0..self.equations.len())
+                    .filter(|&i| self.is_shown(&self.equations[i]))
+                    .collect();
+                // Select All/None buttons, row count and paging controls
+                let pages = shown.len().div_ceil(PAGE_SIZE).max(1);
+                self.page = self.page.min(pages - 1);
                 ui.horizontal(|ui| {
-                    if ui.button("Select All").clicked() {
-                        for eq in &mut self.equations {
-                            eq.active = true;
+                    if ui
+                        .button("Select All")
+                        .on_hover_text("Activate the rows shown")
+                        .clicked()
+                    {
+                        for &i in &shown {
+                            self.equations[i].active = true;
                         }
                     }
-                    if ui.button("Select None").clicked() {
-                        for eq in &mut self.equations {
-                            eq.active = false;
+                    if ui
+                        .button("Select None")
+                        .on_hover_text("Deactivate the rows shown")
+                        .clicked()
+                    {
+                        for &i in &shown {
+                            self.equations[i].active = false;
+                        }
+                    }
+                    if ui
+                        .add_enabled(!self.processing, egui::Button::new("Rename…"))
+                        .clicked()
+                    {
+                        self.rename = Some(RenameRule::default());
+                    }
+                    if ui
+                        .button("Save as CSV…")
+                        .on_hover_text(
+                            "Write the equations, with the changes made here, to a CSV file",
+                        )
+                        .clicked()
+                    {
+                        self.export_csv_dialog.save_file();
+                    }
+                    ui.separator();
+                    let active = self.equations.iter().filter(|e| e.active).count();
+                    if shown.len() < self.equations.len() {
+                        ui.label(format!(
+                            "{} of {} rows, {active} active",
+                            shown.len(),
+                            self.equations.len()
+                        ));
+                    } else {
+                        ui.label(format!("{} rows, {active} active", self.equations.len()));
+                    }
+                    ui.separator();
+                    ui.add(egui::Slider::new(&mut self.zoom, ZOOM_RANGE).text("Zoom"));
+                    if pages > 1 {
+                        ui.separator();
+                        ui.checkbox(&mut self.paged, "Pages");
+                        if self.paged {
+                            if ui
+                                .add_enabled(self.page > 0, egui::Button::new("◀"))
+                                .clicked()
+                            {
+                                self.page -= 1;
+                            }
+                            ui.label(format!("{}/{pages}", self.page + 1));
+                            if ui
+                                .add_enabled(self.page + 1 < pages, egui::Button::new("▶"))
+                                .clicked()
+                            {
+                                self.page += 1;
+                            }
                         }
                     }
                 });
                 ui.add_space(8.0);
-                ScrollArea::vertical().max_height(350.0).show(ui, |ui| {
-                    TableBuilder::new(ui)
+                let rows = if self.paged {
+                    let first = self.page * PAGE_SIZE;
+                    &shown[first..(first + PAGE_SIZE).min(shown.len())]
+                } else {
+                    &shown[..]
+                };
+                let output_dir = self.output_dir.as_ref();
+                let described = self.equations.iter().any(|e| e.description.is_some());
+                let equations = &mut self.equations;
+                let statuses = &self.statuses;
+                let latex_view = &mut self.latex_view;
+                let zoom = self.zoom;
+                let table = ui.scope(|ui| {
+                    for font in ui.style_mut().text_styles.values_mut() {
+                        font.size *= zoom;
+                    }
+                    // Only the visible rows are laid out each frame.
+                    let mut builder = TableBuilder::new(ui)
                         .striped(true)
+                        .max_scroll_height(350.0)
                         .column(Column::auto())
-                        .column(Column::auto())
+                        .column(Column::auto());
+                    if described {
+                        builder = builder.column(Column::initial(200.0).clip(true));
+                    }
+                    builder
                         .column(Column::remainder().clip(true))
-                        .header(24.0, |mut h| {
+                        .header(24.0 * zoom, |mut h| {
                             h.col(|ui| {
                                 ui.heading("Active");
                             });
                             h.col(|ui| {
                                 ui.heading("Name");
                             });
+                            if described {
+                                h.col(|ui| {
+                                    ui.heading("Description");
+                                });
+                            }
                             h.col(|ui| {
                                 ui.heading("Equation");
                             });
                         })
-                        .body(|mut b| {
-                            for eq in &mut self.equations {
-                                b.row(24.0, |mut r| {
-                                    r.col(|ui| {
-                                        ui.checkbox(&mut eq.active, "");
-                                    });
-                                    r.col(|ui| {
-                                        ui.label(&eq.name);
-                                    });
+                        .body(|b| {
+                            b.rows(24.0 * zoom, rows.len(), |mut r| {
+                                let index = rows[r.index()];
+                                let eq = &mut equations[index];
+                                r.col(|ui| {
+                                    ui.checkbox(&mut eq.active, "");
+                                });
+                                r.col(|ui| {
+                                    let mut name = egui::RichText::new(&eq.name);
+                                    if statuses.get(&eq.name) == Some(&RowStatus::Failed) {
+                                        name = name.color(Color32::RED);
+                                    }
+                                    // Clicking the name shows its LaTeX source.
+                                    let shown = *latex_view == Some(index);
+                                    if ui.selectable_label(shown, name).clicked() {
+                                        *latex_view = (!shown).then_some(index);
+                                    }
+                                });
+                                if described {
                                     r.col(|ui| {
-                                        ui.label(&eq.body);
+                                        ui.label(eq.description.as_deref().unwrap_or_default());
                                     });
+                                }
+                                r.col(|ui| {
+                                    ui.label(&eq.body);
                                 });
-                            }
+                                r.response().on_hover_ui(|ui| {
+                                    Self::preview_tooltip(ui, eq, output_dir, zoom);
+                                });
+                            });
                         });
                 });
+                // Ctrl+scroll (or pinch) over the table zooms it.
+                if table.response.contains_pointer() {
+                    let delta = ui.input(|i| i.zoom_delta());
+                    if delta != 1.0 {
+                        self.zoom =
+                            (self.zoom * delta).clamp(*ZOOM_RANGE.start(), *ZOOM_RANGE.end());
+                    }
+                }
             }
         });
+
+        // 4. Autosave, and save right away when the window is closing
+        let closing = ctx.input(|i| i.viewport().close_requested());
+        self.autosave(ctx, closing);
     }
 }
 
 /// Launch the Equation Processor GUI, reporting failures.
 ///
 /// Attempts to open a native window sized 700×700 px and runs the eframe loop.
-pub fn launch_gui() {
+/// With `input`, that file is loaded as if picked with "Browse…".
+pub fn launch_gui(input: Option<PathBuf>) {
     let options = eframe::NativeOptions {
         viewport: ViewportBuilder::default().with_inner_size([700.0, 700.0]),
         ..Default::default()
     };
     if let Err(err) = eframe::run_native(
-        "Equation Processor",
+        APP_TITLE,
         options,
-        Box::new(|cc| Ok(Box::new(EquationProcessorApp::new(cc)))),
+        Box::new(|cc| {
+            let mut app = EquationProcessorApp::new(cc);
+            if let Some(path) = input {
+                app.open_input(path);
+            }
+            Ok(Box::new(app))
+        }),
     ) {
         eprintln!("Failed to launch GUI: {err}");
     }
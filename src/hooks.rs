@@ -0,0 +1,229 @@
+//! Post-render hooks: external commands run on each artifact after a batch
+//! is rendered, such as `svgo {svg}` or `optipng -o7 {png}`.
+//!
+//! A template is split into words like a shell command line (single and
+//! double quotes group words) and run directly, without a shell. Placeholders:
+//!
+//! - `{svg}`, `{png}`, `{pdf}`: the artifact of that format; the hook runs
+//!   once per equation that has one, also when it names several formats
+//! - `{file}`: every artifact in turn, whatever its format
+//! - `{name}`: the equation name
+//! - `{dir}`: the output directory
+//!
+//! ```
+//! use equation_processor::hooks::PostRenderHook;
+//!
+//! let hook: PostRenderHook = "svgo --multipass {svg}".parse().unwrap();
+//! assert_eq!(hook.extensions(), ["svg"]);
+//! assert!("svgo --multipass".parse::<PostRenderHook>().is_err());
+//! ```
+
+use crate::children;
+use crate::model::Equation;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How long a hook may run on one artifact before it is killed.
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Artifact formats hooks can run on, in the order they run.
+const ARTIFACT_EXTENSIONS: [&str; 3] = ["svg", "png", "pdf"];
+
+/// A command template run on rendered artifacts, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostRenderHook {
+    template: String,
+    words: Vec<String>,
+}
+
+impl FromStr for PostRenderHook {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words = split_words(s)?;
+        if words.is_empty() {
+            return Err("empty hook command".into());
+        }
+        let hook = PostRenderHook {
+            template: s.trim().to_string(),
+            words,
+        };
+        if hook.extensions().is_empty() {
+            return Err(format!(
+                "hook '{}' names no artifact; use {{svg}}, {{png}}, {{pdf}} or {{file}}",
+                hook.template
+            ));
+        }
+        Ok(hook)
+    }
+}
+
+impl fmt::Display for PostRenderHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+impl PostRenderHook {
+    /// Extensions of the artifacts this hook runs on.
+    pub fn extensions(&self) -> Vec<&'static str> {
+        let mentions = |placeholder: &str| self.words.iter().any(|w| w.contains(placeholder));
+        if mentions("{file}") {
+            return ARTIFACT_EXTENSIONS.to_vec();
+        }
+        ARTIFACT_EXTENSIONS
+            .into_iter()
+            .filter(|ext| mentions(&format!("{{{ext}}}")))
+            .collect()
+    }
+
+    /// The command line for the artifact `file` of equation `name`, with
+    /// the placeholders filled in.
+    pub fn command_line(&self, name: &str, file: &Path, output_dir: &Path) -> Vec<String> {
+        let file = file.to_string_lossy();
+        let mut words: Vec<String> = self
+            .words
+            .iter()
+            .map(|w| {
+                w.replace("{file}", &file)
+                    .replace("{name}", name)
+                    .replace("{dir}", &output_dir.to_string_lossy())
+            })
+            .collect();
+        for ext in ARTIFACT_EXTENSIONS {
+            let artifact = output_dir.join(format!("{name}.{ext}"));
+            for word in &mut words {
+                *word = word.replace(&format!("{{{ext}}}"), &artifact.to_string_lossy());
+            }
+        }
+        words
+    }
+}
+
+/// Split a command line into words; quotes group words and are removed.
+fn split_words(s: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    for c in s.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("unbalanced quote in hook '{}'", s.trim()));
+    }
+    if in_word {
+        words.push(word);
+    }
+    Ok(words)
+}
+
+/// A hook that failed on one artifact.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookFailure {
+    /// Equation whose artifact was processed
+    pub name: String,
+    /// The artifact
+    pub file: PathBuf,
+    /// The hook template
+    pub hook: String,
+    /// Why it failed: exit status and last line of stderr, timeout or
+    /// launch error
+    pub reason: String,
+}
+
+impl fmt::Display for HookFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hook '{}' failed on {}: {}",
+            self.hook,
+            self.file.display(),
+            self.reason
+        )
+    }
+}
+
+/// Run every hook on the artifacts of the active equations in `output_dir`,
+/// killing a hook after `timeout`.
+///
+/// Hooks run in the given order, each artifact by one hook at a time.
+/// Failures don't stop the remaining hooks; they are returned for the
+/// caller to report.
+pub fn run_hooks(
+    equations: &[Equation],
+    output_dir: &Path,
+    hooks: &[PostRenderHook],
+    timeout: Duration,
+) -> Vec<HookFailure> {
+    let mut failures = Vec::new();
+    for eq in equations.iter().filter(|e| e.active) {
+        for hook in hooks {
+            let mut ran = Vec::new();
+            for ext in hook.extensions() {
+                let file = output_dir.join(format!("{}.{ext}", eq.name));
+                if !file.exists() {
+                    continue;
+                }
+                let command = hook.command_line(&eq.name, &file, output_dir);
+                // Without `{file}`, every format yields the same command line.
+                if ran.contains(&command) {
+                    continue;
+                }
+                ran.push(command.clone());
+                if let Err(reason) = run_with_timeout(&command, timeout) {
+                    failures.push(HookFailure {
+                        name: eq.name.clone(),
+                        file,
+                        hook: hook.to_string(),
+                        reason,
+                    });
+                }
+            }
+        }
+    }
+    failures
+}
+
+/// Run `command`, failing on a non-zero exit or after `timeout`.
+fn run_with_timeout(command: &[String], timeout: Duration) -> Result<(), String> {
+    let output = children::output_timeout(
+        Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::null()),
+        Some(timeout),
+    )
+    .map_err(|e| match e.kind() {
+        io::ErrorKind::TimedOut => e.to_string(),
+        _ => format!("could not run {}: {e}", command[0]),
+    })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let log = String::from_utf8_lossy(&output.stderr);
+    match log.lines().rev().find(|l| !l.trim().is_empty()) {
+        Some(line) => Err(format!("{}: {}", output.status, line.trim())),
+        None => Err(output.status.to_string()),
+    }
+}
@@ -0,0 +1,81 @@
+//! Compatibility of equation bodies with [KaTeX](https://katex.org), for
+//! sources that are both rendered here and displayed live with KaTeX.
+//!
+//! Commands are checked against a support table bundled with the crate.
+//!
+//! ```
+//! use equation_processor::katex::unsupported_commands;
+//!
+//! assert!(unsupported_commands(r"\frac{a}{b} + \sqrt{x}").is_empty());
+//! assert_eq!(unsupported_commands(r"\intertext{then} x"), [r"\intertext"]);
+//! ```
+
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// The bundled support table: one command per line, environments as `env:name`.
+const SUPPORT_TABLE: &str = include_str!("katex_support.txt");
+
+/// Commands and `env:`-prefixed environments KaTeX supports.
+fn supported() -> &'static HashSet<&'static str> {
+    static SUPPORTED: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    SUPPORTED.get_or_init(|| {
+        SUPPORT_TABLE
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .collect()
+    })
+}
+
+/// Whether KaTeX supports the command `name` (without backslash).
+pub fn is_supported(name: &str) -> bool {
+    supported().contains(name)
+}
+
+/// Whether KaTeX supports the environment `name`, e.g. `pmatrix`.
+pub fn is_supported_environment(name: &str) -> bool {
+    supported().contains(format!("env:{name}").as_str())
+}
+
+/// Commands (`\intertext`) and environments (`\begin{multline}`) in `body`
+/// that KaTeX cannot render, each listed once: commands first, then
+/// environments.
+///
+/// Macros defined in the body with `\newcommand`, `\renewcommand`,
+/// `\providecommand` or `\def` count as supported.
+pub fn unsupported_commands(body: &str) -> Vec<String> {
+    static COMMAND: OnceLock<Regex> = OnceLock::new();
+    static ENVIRONMENT: OnceLock<Regex> = OnceLock::new();
+    static DEFINITION: OnceLock<Regex> = OnceLock::new();
+    let command = COMMAND.get_or_init(|| Regex::new(r"\\([A-Za-z]+\*?)").unwrap());
+    let environment = ENVIRONMENT.get_or_init(|| Regex::new(r"\\begin\s*\{([^}]*)\}").unwrap());
+    let definition = DEFINITION.get_or_init(|| {
+        Regex::new(r"\\(?:(?:re|provide|new)command\*?\s*\{?\s*|def\s*)\\([A-Za-z]+)").unwrap()
+    });
+    let defined: HashSet<&str> = definition
+        .captures_iter(body)
+        .map(|c| c.get(1).unwrap().as_str())
+        .collect();
+    let mut found = Vec::new();
+    for c in command.captures_iter(body) {
+        let name = &c[1];
+        let plain = name.trim_end_matches('*');
+        if is_supported(name) || is_supported(plain) || defined.contains(plain) {
+            continue;
+        }
+        let entry = format!("\\{plain}");
+        if !found.contains(&entry) {
+            found.push(entry);
+        }
+    }
+    for c in environment.captures_iter(body) {
+        let name = c[1].trim();
+        let entry = format!("\\begin{{{name}}}");
+        if !is_supported_environment(name) && !found.contains(&entry) {
+            found.push(entry);
+        }
+    }
+    found
+}
@@ -3,281 +3,152 @@
 //! Provides parsing of equation files (CSV & Markdown), representation of equations,
 //! and rendering to PDF/SVG via external tools (tectonic & pdftocairo),
 //! with optional CLI progress indication.
+//!
+//! The API is organized in modules:
+//!
+//! - [`model`]: [`Equation`], [`EquationKind`], [`LatexEngine`], [`Color`], [`Background`], [`DisplaySize`], [`StandaloneOptions`] and [`Filetype`]
+//! - [`error`]: [`EquationError`], what went wrong reading or rendering equations
+//! - [`parser`]: reading CSV and Markdown input
+//! - [`render`]: compiling equations with tectonic and converting to SVG
+//! - [`backend`]: the LaTeX engines, one [`RenderBackend`](backend::RenderBackend) each
+//! - [`cache`]: skipping equations that have not changed since the last run
+//! - [`palette`]: checking batch colors for color-blind readers
+//! - [`hooks`]: commands run on the artifacts after rendering
+//! - [`children`]: stopping the external programs of a cancelled run
+//! - [`report`]: tables and text previews for humans
+//! - [`remote`]: fetching input files from http(s) URLs
+//! - [`lint`]: checks for common mistakes in input files
+//! - [`katex`]: which LaTeX commands KaTeX can display
+//! - [`typst`]: translating equations to Typst math, and typesetting them
+//!   without LaTeX behind the `typst-fallback` feature
+//! - [`tagging`]: adding and removing tags in input files
+//! - [`scaffold`]: starter files for new projects
+//! - [`settings`]: rendering settings shared as a TOML file
+//! - [`session`]: the GUI's autosaved session, for crash recovery
+//! - [`paths`]: platform locations of the config file and run statistics
+//! - [`process`]: reading and rendering an input file in one call, without prompts
+//! - [`shard`]: splitting a batch across machines and merging their indexes
+//! - [`cli`]: the interactive command-line workflow
+//! - [`rpc`]: JSON-RPC over stdio for editor plugins
+//! - `test_util`: a mock renderer and fixtures, behind the `test-util` feature
+//! - [`prelude`]: the common types and functions, for `use equation_processor::prelude::*`
+//!
+//! The model types are re-exported at the crate root. The free functions that
+//! used to live at the root are kept as deprecated shims for one release.
+
+pub mod backend;
+pub mod cache;
+pub mod children;
+pub mod cli;
+pub mod error;
+pub mod hooks;
+pub mod katex;
+pub mod lint;
+pub mod model;
+pub mod palette;
+pub mod parser;
+pub mod paths;
+pub mod prelude;
+pub mod process;
+pub mod remote;
+pub mod render;
+pub mod report;
+pub mod rpc;
+pub mod scaffold;
+pub mod session;
+pub mod settings;
+pub mod shard;
+pub mod tagging;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod typst;
+
+pub use error::EquationError;
+pub use model::{
+    Background, Color, DisplaySize, Equation, EquationKind, Filetype, LatexEngine,
+    StandaloneOptions,
+};
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Prompt user for yes/no on CLI
+#[deprecated(since = "0.2.0", note = "use `cli::ask_confirmation`")]
+pub fn ask_confirmation(prompt: &str) -> bool {
+    cli::ask_confirmation(prompt)
+}
 
-pub use self::core::*;
-
-mod core {
-    use indicatif::{ProgressBar, ProgressStyle};
-    use prettytable::{row, Table};
-    use regex::Regex;
-    use std::collections::HashMap;
-    use std::fs::{self, File};
-    use std::io::{self, BufRead, BufReader, Read, Write};
-    use std::path::{Path, PathBuf};
-    use std::process::{Command, Stdio};
-
-    /// Supported input file types.
-    #[derive(Debug)]
-    pub enum Filetype {
-        /// CSV with header [active,equation,name]
-        Csv,
-        /// Markdown: $$...$$ blocks, optional %%yes%%/%%no%% and %%name%% tags
-        Markdown,
-        /// Unknown or unsupported extension
-        Unknown,
-    }
-
-    /// A mathematical equation entry.
-    #[derive(Debug, Clone)]
-    pub struct Equation {
-        /// Whether to render this equation
-        pub active: bool,
-        /// Filename-safe name for output files
-        pub name: String,
-        /// LaTeX body of the equation
-        pub body: String,
-    }
-
-    impl Equation {
-        /// Construct new equation, sanitizing name
-        pub fn new(active: bool, name: &str, body: &str) -> Self {
-            let sanitized = Equation::sanitize_filename(name);
-            Equation {
-                active,
-                name: sanitized,
-                body: body.to_string(),
-            }
-        }
-
-        /// Replace invalid characters with underscores
-        fn sanitize_filename(name: &str) -> String {
-            let re = Regex::new(r"[^A-Za-z0-9_.]").unwrap();
-            let mut s = re.replace_all(name, "_").into_owned();
-            if s.is_empty() {
-                s = "default_equation".into();
-            }
-            s
-        }
-
-        /// Render to PDF and SVG, optionally cleaning up _aux files
-        pub fn render(
-            &self,
-            output_dir: &PathBuf,
-            color: &str,
-            delete_intermediates: bool,
-        ) -> io::Result<()> {
-            if !self.active {
-                return Ok(());
-            }
-            fs::create_dir_all(output_dir)?;
-            let tex = self.generate_latex(color);
-            let tex_path = output_dir.join(format!("{}.tex", self.name));
-            fs::write(&tex_path, tex)?;
-
-            let status = Command::new("tectonic")
-                .arg(&tex_path)
-                .arg("--outdir")
-                .arg(output_dir)
-                .stdout(Stdio::null())
-                .stderr(Stdio::null())
-                .status()?;
-
-            if status.success() {
-                self.convert_pdf_to_svg(output_dir)?;
-                if delete_intermediates {
-                    self.cleanup_intermediate_files(output_dir)?;
-                }
-            }
-            Ok(())
-        }
-
-        /// Convert the .pdf to .svg
-        fn convert_pdf_to_svg(&self, output_dir: &Path) -> io::Result<()> {
-            let pdf = output_dir.join(format!("{}.pdf", self.name));
-            let svg = output_dir.join(format!("{}.svg", self.name));
-            let status = Command::new("pdftocairo")
-                .arg("-svg")
-                .arg(&pdf)
-                .arg(&svg)
-                .status()?;
-            if status.success() {
-                Ok(())
-            } else {
-                Err(io::Error::other("SVG conversion failed"))
-            }
-        }
-
-        /// Remove .tex and .pdf intermediates
-        fn cleanup_intermediate_files(&self, output_dir: &Path) -> io::Result<()> {
-            let _ = fs::remove_file(output_dir.join(format!("{}.tex", self.name)));
-            let _ = fs::remove_file(output_dir.join(format!("{}.pdf", self.name)));
-            Ok(())
-        }
-
-        /// Generate LaTeX source including custom font and color
-        fn generate_latex(&self, color: &str) -> String {
-            let code = color.trim_start_matches('#');
-            format!(
-                r#"\documentclass[border=1pt]{{standalone}}
-                \usepackage{{amsmath}}
-                \usepackage{{xfrac}}
-                \usepackage{{gfsneohellenicot}}
-                \usepackage{{xcolor}}
-                \definecolor{{equationcolor}}{{HTML}}{{{}}}
-                \begin{{document}}
-                \setbox0\hbox{{\Large \textcolor{{equationcolor}}{{${}$}}}}
-                \dimen0=12mm
-                \ifdim\ht0<\dimen0 \ht0=\dimen0 \fi
-                \ifdim\dp0<5mm \dp0=5mm \fi
-                \box0
-                \end{{document}}"#,
-                code, self.body
-            )
-        }
-    }
-
-    /// Prompt user for yes/no on CLI
-    pub fn ask_confirmation(prompt: &str) -> bool {
-        loop {
-            print!("{prompt} (y/n): ");
-            io::stdout().flush().unwrap();
-            let mut buf = String::new();
-            io::stdin().read_line(&mut buf).unwrap();
-            match buf.trim().to_lowercase().as_str() {
-                "y" | "yes" => return true,
-                "n" | "no" => return false,
-                _ => continue,
-            }
-        }
-    }
-
-    /// Render all active equations with a CLI progress bar
-    pub fn render_equations(
-        equations: &[Equation],
-        output_dir: &PathBuf,
-        color: &str,
-        delete_intermediates: bool,
-    ) -> io::Result<()> {
-        let active: Vec<&Equation> = equations.iter().filter(|e| e.active).collect();
-        let bar = ProgressBar::new(active.len() as u64).with_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
-        for eq in active {
-            bar.set_message(eq.name.clone());
-            eq.render(output_dir, color, delete_intermediates)?;
-            bar.inc(1);
-        }
-        bar.finish();
-        Ok(())
-    }
-
-    /// Read file to string
-    pub fn read_file(path: &PathBuf) -> io::Result<String> {
-        let mut f = File::open(path)?;
-        let mut s = String::new();
-        f.read_to_string(&mut s)?;
-        Ok(s)
-    }
+/// Render all active equations with a CLI progress bar
+#[deprecated(since = "0.2.0", note = "use `render::render_batch`")]
+pub fn render_equations(
+    equations: &[Equation],
+    output_dir: &Path,
+    color: &str,
+    delete_intermediates: bool,
+) -> io::Result<()> {
+    let options = render::RenderOptions {
+        color: color.to_string(),
+        delete_intermediates,
+        ..Default::default()
+    };
+    render::render_batch(
+        equations,
+        output_dir,
+        &render::TectonicConfig::default(),
+        &options,
+    )
+    .map(|_warnings| ())
+    .map_err(Into::into)
+}
 
-    /// Parse CSV into equations
-    pub fn read_csv_file(path: &PathBuf) -> io::Result<Vec<Equation>> {
-        let f = File::open(path)?;
-        let rdr = BufReader::new(f);
-        let mut eqs = Vec::new();
-        let mut counts = HashMap::new();
-        for line in rdr.lines().skip(1).flatten() {
-            let parts: Vec<&str> = line.split(',').collect();
-            if parts.len() >= 3 {
-                let active = parts[0].trim().eq_ignore_ascii_case("yes");
-                let body = parts[1].trim();
-                let mut name = parts[2].trim().to_string();
-                let c = counts.entry(name.clone()).or_insert(0);
-                if *c > 0 {
-                    name = format!("{name}_{c}");
-                }
-                *c += 1;
-                eqs.push(Equation::new(active, &name, body));
-            }
-        }
-        Ok(eqs)
-    }
+/// Read file to string
+#[deprecated(since = "0.2.0", note = "use `parser::read_file`")]
+pub fn read_file(path: &PathBuf) -> io::Result<String> {
+    parser::read_file(path)
+}
 
-    /// Determine file type by extension
-    pub fn detect_file_type(path: &Path) -> Filetype {
-        match path.extension().and_then(|e| e.to_str()) {
-            Some("csv") => Filetype::Csv,
-            Some("md") | Some("markdown") => Filetype::Markdown,
-            _ => Filetype::Unknown,
-        }
-    }
+/// Parse CSV into equations
+#[deprecated(since = "0.2.0", note = "use `parser::read_csv_file`")]
+pub fn read_csv_file(path: &Path) -> io::Result<Vec<Equation>> {
+    parser::read_csv_file(path).map_err(Into::into)
+}
 
-    /// Parse Markdown into equations
-    pub fn parse_markdown(content: &str) -> Vec<Equation> {
-        let re = Regex::new(r"(?s)(%%(yes|no)?%%)?[\n\r]*\$\$[\n\r]*(.*?)\$\$[\n\r]*(%%(.*?)%%)?")
-            .unwrap();
-        let mut eqs = Vec::new();
-        let mut counts = HashMap::new();
-        for cap in re.captures_iter(content) {
-            let active = cap.get(2).is_none_or(|m| m.as_str() == "yes");
-            let body = cap.get(3).unwrap().as_str().trim();
-            let raw = cap.get(5).map_or("default_equation", |m| m.as_str());
-            let c = counts.entry(raw.to_string()).or_insert(0);
-            let name = if *c > 0 {
-                format!("{raw}_{c}")
-            } else {
-                raw.to_string()
-            };
-            *c += 1;
-            eqs.push(Equation::new(active, &name, body));
-        }
-        eqs
-    }
+/// Determine file type by extension
+#[deprecated(since = "0.2.0", note = "use `parser::detect_file_type`")]
+pub fn detect_file_type(path: &Path) -> Filetype {
+    parser::detect_file_type(path)
+}
 
-    /// CLI entry: display table, confirm, then render.
-    pub fn run_cli(
-        input_file: PathBuf,
-        color: &str,
-        output_dir: &PathBuf,
-        delete_intermediates: bool,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        fs::create_dir_all(output_dir)?;
-        let ft = detect_file_type(&input_file);
-        let content = read_file(&input_file)?;
-        let equations = match ft {
-            Filetype::Csv => read_csv_file(&input_file)?,
-            Filetype::Markdown => parse_markdown(&content),
-            _ => return Err("Unsupported file type".into()),
-        };
-        if equations.is_empty() {
-            println!("No equations found.");
-            return Ok(());
-        }
-        // Show table
-        let mut table = Table::new();
-        table.add_row(row!["Active", "Name", "Equation"]);
-        for eq in &equations {
-            table.add_row(row![if eq.active { "Yes" } else { "No" }, eq.name, eq.body]);
-        }
-        table.printstd();
+/// Parse Markdown into equations
+#[deprecated(since = "0.2.0", note = "use `parser::parse_markdown`")]
+pub fn parse_markdown(content: &str) -> Vec<Equation> {
+    parser::parse_markdown(content)
+}
 
-        if !ask_confirmation("Render active equations?") {
-            return Ok(());
-        }
-        render_equations(&equations, output_dir, color, delete_intermediates)?;
-        println!("Rendered to {output_dir:?}");
-        Ok(())
-    }
+/// CLI entry: display table, confirm, then render.
+#[deprecated(since = "0.2.0", note = "use `cli::run_cli_with`")]
+pub fn run_cli(
+    input_file: PathBuf,
+    color: &str,
+    output_dir: &Path,
+    delete_intermediates: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let options = render::RenderOptions {
+        color: color.to_string(),
+        delete_intermediates,
+        ..Default::default()
+    };
+    cli::run_cli_with(
+        input_file,
+        output_dir,
+        &render::TectonicConfig::default(),
+        &parser::ParseOptions::default(),
+        &options,
+    )
+}
 
-    /// Print a short table summary.
-    pub fn display_table(equations: &[Equation]) {
-        let mut table = Table::new();
-        table.add_row(row!["Active", "Name", "Equation"]);
-        for eq in equations {
-            table.add_row(row![if eq.active { "Yes" } else { "No" }, eq.name, eq.body]);
-        }
-        table.printstd();
-    }
+/// Print a short table summary.
+#[deprecated(since = "0.2.0", note = "use `report::display_table`")]
+pub fn display_table(equations: &[Equation]) {
+    report::display_table(equations)
 }
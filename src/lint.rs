@@ -0,0 +1,507 @@
+//! Checks for common mistakes in equation input files, for editors and
+//! pre-commit hooks.
+//!
+//! ```
+//! use equation_processor::lint::{lint_markdown, Severity};
+//!
+//! let diagnostics = lint_markdown("$$\nx^{2\n$$\n%%square%%\n");
+//! assert_eq!(diagnostics[0].code, "unbalanced-braces");
+//! assert_eq!(diagnostics[0].severity, Severity::Error);
+//! ```
+
+use crate::katex::unsupported_commands;
+use crate::model::{Equation, EquationKind, Filetype};
+use crate::parser::{
+    apply_tag, apply_task_checkboxes, detect_csv_delimiter, detect_file_type, fence_regex,
+    markdown_spans, parse_fence_attributes, read_file, read_input, read_tex_directory,
+    split_csv_line, AmbiguousTagPolicy, CsvColumns, ParseOptions, FLAG_TAGS, TAG_KEYS,
+};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How serious a [`Diagnostic`] is; errors make `lint` exit nonzero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Likely to break rendering or overwrite output
+    Error,
+    /// Probably unintended, but renders
+    Warning,
+}
+
+/// One problem found in an input file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// Error or warning
+    pub severity: Severity,
+    /// Stable identifier such as `duplicate-name`
+    pub code: &'static str,
+    /// 1-based line of the equation in the input
+    pub line: usize,
+    /// Name of the equation concerned
+    pub name: String,
+    /// Human-readable explanation
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}: {severity}[{}] {}: {}",
+            self.line, self.code, self.name, self.message
+        )
+    }
+}
+
+/// Characters that are easy to paste in by accident and break or silently
+/// change LaTeX, with a short description.
+const SUSPICIOUS_CHARS: &[(char, &str)] = &[
+    ('\u{00A0}', "non-breaking space"),
+    ('\u{200B}', "zero-width space"),
+    ('\u{200C}', "zero-width non-joiner"),
+    ('\u{200D}', "zero-width joiner"),
+    ('\u{FEFF}', "byte order mark"),
+    ('\u{2018}', "curly quote"),
+    ('\u{2019}', "curly quote"),
+    ('\u{201C}', "curly quote"),
+    ('\u{201D}', "curly quote"),
+    ('\u{2013}', "en dash"),
+    ('\u{2014}', "em dash"),
+    ('\u{2212}', "Unicode minus sign"),
+];
+
+/// An equation as written in the input, before duplicate names are numbered.
+struct Entry {
+    equation: Equation,
+    line: usize,
+    /// Name as written, `None` if the input gave none
+    declared: Option<String>,
+}
+
+/// Lint the CSV, Markdown, JSON, YAML, TOML, LaTeX or HTML file, Jupyter
+/// notebook or `.tex` fragment directory at `path`.
+pub fn lint_file(path: &PathBuf, options: &ParseOptions) -> io::Result<Vec<Diagnostic>> {
+    match detect_file_type(path) {
+        Filetype::Csv => Ok(lint_csv(&read_file(path)?, options)),
+        Filetype::Markdown => Ok(lint_markdown_with(&read_file(path)?, options)),
+        Filetype::TexDirectory => Ok(sort(check_entries(&tex_entries(path)?))),
+        Filetype::Latex
+        | Filetype::Notebook
+        | Filetype::Html
+        | Filetype::Json
+        | Filetype::Yaml
+        | Filetype::Toml
+        | Filetype::Registered(_) => Ok(sort(check_entries(&registered_entries(path, options)?))),
+        Filetype::Unknown => Err(unsupported_file(path)),
+    }
+}
+
+/// Check the input at `path` (as for [`lint_file`]) for equations KaTeX cannot
+/// display, using the [bundled support table](crate::katex).
+///
+/// Unsupported commands and environments and TikZ drawings are errors
+/// (`katex-unsupported`); chemical formulas get a warning
+/// (`katex-extension`) since they need KaTeX's mhchem extension.
+pub fn check_katex(path: &PathBuf, options: &ParseOptions) -> io::Result<Vec<Diagnostic>> {
+    let entries = match detect_file_type(path) {
+        Filetype::Csv => csv_entries(&read_file(path)?, options).1,
+        Filetype::Markdown => markdown_entries(&read_file(path)?, options).1,
+        Filetype::TexDirectory => tex_entries(path)?,
+        Filetype::Latex
+        | Filetype::Notebook
+        | Filetype::Html
+        | Filetype::Json
+        | Filetype::Yaml
+        | Filetype::Toml
+        | Filetype::Registered(_) => registered_entries(path, options)?,
+        Filetype::Unknown => return Err(unsupported_file(path)),
+    };
+    let mut diagnostics = Vec::new();
+    for entry in &entries {
+        let eq = &entry.equation;
+        match eq.kind {
+            EquationKind::TikZ => {
+                diagnostics.push(error(
+                    "katex-unsupported",
+                    entry.line,
+                    &eq.name,
+                    "TikZ drawings cannot be displayed by KaTeX".into(),
+                ));
+                continue;
+            }
+            EquationKind::Chemistry => diagnostics.push(warning(
+                "katex-extension",
+                entry.line,
+                &eq.name,
+                "chemical formulas need KaTeX's mhchem extension".into(),
+            )),
+            EquationKind::Math => {}
+        }
+        for command in unsupported_commands(&eq.body) {
+            diagnostics.push(error(
+                "katex-unsupported",
+                entry.line,
+                &eq.name,
+                format!("KaTeX does not support {command}"),
+            ));
+        }
+    }
+    Ok(sort(diagnostics))
+}
+
+fn unsupported_file(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!("unsupported file type: {}", path.display()),
+    )
+}
+
+/// Lint Markdown input.
+///
+/// Besides the checks shared with CSV, reports tags after a block that are
+/// neither options nor its name, unknown ```` ```math ```` fence attributes,
+/// and options with invalid values.
+pub fn lint_markdown(content: &str) -> Vec<Diagnostic> {
+    lint_markdown_with(content, &ParseOptions::default())
+}
+
+/// Lint Markdown input read with `options`, e.g. with task checkboxes.
+pub fn lint_markdown_with(content: &str, options: &ParseOptions) -> Vec<Diagnostic> {
+    let (mut diagnostics, entries) = markdown_entries(content, options);
+    diagnostics.extend(check_entries(&entries));
+    sort(diagnostics)
+}
+
+/// Equations of Markdown input, with diagnostics about their tags.
+fn markdown_entries(content: &str, options: &ParseOptions) -> (Vec<Diagnostic>, Vec<Entry>) {
+    let mut spans = markdown_spans(content, options.ambiguous_tags);
+    if options.task_checkboxes {
+        apply_task_checkboxes(content, &mut spans);
+    }
+    let tag = Regex::new(r"%%([^\n\r]*?)%%").unwrap();
+    let fence = fence_regex();
+    let mut diagnostics = Vec::new();
+    let mut entries = Vec::new();
+    for spanned in spans {
+        let line = line_of(content, spanned.span.start);
+        let name = spanned.equation.name.clone();
+        let mut declared = None;
+        let info = fence
+            .captures(&content[spanned.span.clone()])
+            .and_then(|c| c.name("info"));
+        let attributes = info.map_or_else(Vec::new, |m| parse_fence_attributes(m.as_str()));
+        let mut fence_name = None;
+        for (key, value) in &attributes {
+            let valid = match key.as_str() {
+                "name" => {
+                    fence_name = Some(value.trim().to_string());
+                    true
+                }
+                "active" => matches!(
+                    value.trim().to_lowercase().as_str(),
+                    "true" | "false" | "yes" | "no"
+                ),
+                key if TAG_KEYS.contains(&key) || FLAG_TAGS.contains(&key) => {
+                    apply_tag(&mut Equation::new(true, "scratch", ""), key, value)
+                }
+                _ => {
+                    diagnostics.push(warning(
+                        "unknown-tag",
+                        line,
+                        &name,
+                        format!("'{key}' is not a known fence attribute and is ignored"),
+                    ));
+                    continue;
+                }
+            };
+            if !valid {
+                diagnostics.push(warning(
+                    "invalid-tag",
+                    line,
+                    &name,
+                    format!("attribute {key}=\"{value}\" has an invalid value and is ignored"),
+                ));
+            }
+        }
+        for t in tag.captures_iter(&content[spanned.body_span.end..spanned.span.end]) {
+            let text = t[1].trim();
+            match text.split_once(':') {
+                Some((key, value)) if TAG_KEYS.contains(&key.trim()) => {
+                    let mut scratch = Equation::new(true, "scratch", "");
+                    if !apply_tag(&mut scratch, key, value) {
+                        diagnostics.push(warning(
+                            "invalid-tag",
+                            line,
+                            &name,
+                            format!("tag %%{text}%% has an invalid value and is ignored"),
+                        ));
+                    }
+                }
+                None if FLAG_TAGS.contains(&text) => {}
+                _ if declared.is_none() => {
+                    if let Some((key, _)) = text.split_once(':') {
+                        diagnostics.push(warning(
+                            "unknown-tag",
+                            line,
+                            &name,
+                            format!(
+                                "'{key}' is not a known tag key, so %%{text}%% is used as the name"
+                            ),
+                        ));
+                    }
+                    declared = Some(text.to_string());
+                }
+                _ => {}
+            }
+        }
+        if let Some(tag) = &spanned.ambiguous_tag {
+            let policy = options.ambiguous_tags;
+            diagnostics.push(warning(
+                "ambiguous-name",
+                line,
+                &name,
+                format!(
+                    "%%{tag}%% looks like a marker rather than a name, so it is {}",
+                    policy.outcome()
+                ),
+            ));
+            if policy != AmbiguousTagPolicy::Name {
+                declared = None;
+            }
+        }
+        entries.push(Entry {
+            equation: spanned.equation,
+            line,
+            declared: fence_name.filter(|n| !n.is_empty()).or(declared),
+        });
+    }
+    (diagnostics, entries)
+}
+
+/// Lint CSV input (header row first), read with `options` like
+/// [`parse_csv_with`](crate::parser::parse_csv_with).
+pub fn lint_csv(content: &str, options: &ParseOptions) -> Vec<Diagnostic> {
+    let (mut diagnostics, entries) = csv_entries(content, options);
+    diagnostics.extend(check_entries(&entries));
+    sort(diagnostics)
+}
+
+/// Equations of CSV input, with diagnostics about malformed rows.
+fn csv_entries(content: &str, options: &ParseOptions) -> (Vec<Diagnostic>, Vec<Entry>) {
+    let content = content
+        .strip_prefix('\u{feff}')
+        .unwrap_or(content)
+        .replace("\r\n", "\n")
+        .replace('\r', "\n");
+    let mut lines = content.lines().enumerate();
+    let Some((_, header)) = lines.next() else {
+        return (Vec::new(), Vec::new());
+    };
+    let delimiter = options
+        .csv_delimiter
+        .unwrap_or_else(|| detect_csv_delimiter(header));
+    let columns = CsvColumns::resolve(&split_csv_line(header, delimiter), options);
+    let mut diagnostics = Vec::new();
+    let mut entries = Vec::new();
+    for (i, line) in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let parts = split_csv_line(line, delimiter);
+        if parts.len() < columns.min_fields() {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                code: "malformed-row",
+                line: i + 1,
+                name: String::new(),
+                message: format!(
+                    "expected at least {} fields separated by '{}', found {}; the row is ignored",
+                    columns.min_fields(),
+                    delimiter.escape_default(),
+                    parts.len()
+                ),
+            });
+            continue;
+        }
+        let declared = parts[columns.name].trim();
+        entries.push(Entry {
+            equation: Equation::new(
+                CsvColumns::field(&parts, columns.active)
+                    .is_none_or(|flag| flag.eq_ignore_ascii_case("yes")),
+                declared,
+                parts[columns.body].trim(),
+            ),
+            line: i + 1,
+            declared: (!declared.is_empty()).then(|| declared.to_string()),
+        });
+    }
+    (diagnostics, entries)
+}
+
+/// Equations of a `.tex` fragment directory, each on line 1 of its file.
+fn tex_entries(dir: &Path) -> io::Result<Vec<Entry>> {
+    Ok(named_entries(read_tex_directory(dir)?))
+}
+
+/// Entries of a JSON or YAML file or a file in a
+/// [registered format](crate::parser::register_input_format). Their parsers
+/// report no line numbers, so every entry is on line 1.
+fn registered_entries(path: &Path, options: &ParseOptions) -> io::Result<Vec<Entry>> {
+    Ok(named_entries(read_input(path, options)?))
+}
+
+/// Entries for equations that all declare their name, on line 1.
+fn named_entries(equations: Vec<Equation>) -> Vec<Entry> {
+    equations
+        .into_iter()
+        .map(|equation| Entry {
+            declared: Some(equation.name.clone()),
+            equation,
+            line: 1,
+        })
+        .collect()
+}
+
+/// Checks shared by all input formats.
+fn check_entries(entries: &[Entry]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut by_name: HashMap<String, Vec<&Entry>> = HashMap::new();
+    for entry in entries {
+        let eq = &entry.equation;
+        let line = entry.line;
+        match &entry.declared {
+            Some(declared) => {
+                by_name
+                    .entry(Equation::sanitize_filename(declared).to_lowercase())
+                    .or_default()
+                    .push(entry);
+                if !eq.active {
+                    diagnostics.push(warning(
+                        "inactive-named",
+                        line,
+                        &eq.name,
+                        "named but inactive, so it is not rendered".into(),
+                    ));
+                }
+            }
+            None if eq.active => diagnostics.push(warning(
+                "missing-name",
+                line,
+                &eq.name,
+                format!("no name given, so the output is named '{}'", eq.name),
+            )),
+            None => {}
+        }
+        if eq.body.trim().is_empty() {
+            diagnostics.push(error(
+                "empty-body",
+                line,
+                &eq.name,
+                "the body is empty".into(),
+            ));
+            continue;
+        }
+        if let Some(message) = unbalanced_braces(&eq.body) {
+            diagnostics.push(error("unbalanced-braces", line, &eq.name, message));
+        }
+        for (c, description) in SUSPICIOUS_CHARS {
+            if eq.body.contains(*c) {
+                diagnostics.push(warning(
+                    "suspicious-character",
+                    line,
+                    &eq.name,
+                    format!("contains a {description} (U+{:04X})", *c as u32),
+                ));
+            }
+        }
+        if eq
+            .body
+            .chars()
+            .any(|c| c.is_control() && !c.is_whitespace())
+        {
+            diagnostics.push(warning(
+                "suspicious-character",
+                line,
+                &eq.name,
+                "contains a control character".into(),
+            ));
+        }
+    }
+    for group in by_name.values().filter(|g| g.len() > 1) {
+        let active = group.iter().filter(|e| e.equation.active).count();
+        let lines: Vec<String> = group.iter().map(|e| e.line.to_string()).collect();
+        for entry in group {
+            let message = format!(
+                "name '{}' is used on lines {} (names are compared case-insensitively)",
+                entry.declared.as_deref().unwrap_or_default(),
+                lines.join(", ")
+            );
+            let make = if active > 1 { error } else { warning };
+            diagnostics.push(make(
+                "duplicate-name",
+                entry.line,
+                &entry.equation.name,
+                message,
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// Describe unbalanced `{`/`}` in `body`, ignoring escaped `\{` and `\}`.
+fn unbalanced_braces(body: &str) -> Option<String> {
+    let mut depth = 0i32;
+    let mut escaped = false;
+    for c in body.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth < 0 {
+                    return Some("a '}' has no matching '{'".into());
+                }
+            }
+            _ => {}
+        }
+    }
+    (depth > 0).then(|| format!("{depth} '{{' not closed"))
+}
+
+fn error(code: &'static str, line: usize, name: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Error,
+        code,
+        line,
+        name: name.to_string(),
+        message,
+    }
+}
+
+fn warning(code: &'static str, line: usize, name: &str, message: String) -> Diagnostic {
+    Diagnostic {
+        severity: Severity::Warning,
+        ..error(code, line, name, message)
+    }
+}
+
+/// Order diagnostics by line, errors first.
+fn sort(mut diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    diagnostics.sort_by_key(|d| (d.line, d.severity));
+    diagnostics
+}
+
+/// 1-based line number of byte `offset` in `content`.
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
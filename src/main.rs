@@ -6,17 +6,43 @@
 //! Passing an input file path enables CLI mode for unattended batch processing.
 //! ```
 
-use clap::Parser;
-use eframe::egui::{self};
-use egui_file::FileDialog;
-use equation_processor::{
-    ask_confirmation, detect_file_type, parse_markdown, read_csv_file, read_file, render_equations,
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand};
+use equation_processor::children::{self, KillOnDrop};
+use equation_processor::cli::{prepare_equations, run_cli_with};
+use equation_processor::error::EquationError;
+use equation_processor::hooks::{PostRenderHook, DEFAULT_HOOK_TIMEOUT};
+use equation_processor::lint::{check_katex, lint_file, Diagnostic, Severity};
+use equation_processor::model::{parse_env_var, Background, Color, Filetype, LatexEngine};
+use equation_processor::parser::{
+    detect_file_type, read_input, read_toml_defaults, AmbiguousTagPolicy, CsvColumns, ParseOptions,
 };
-use prettytable::{row, Table};
-use std::path::PathBuf;
-use equation_processor::run_cli;
+use equation_processor::paths::config_file;
+use equation_processor::remote::{fetch_input, is_url};
+use equation_processor::render::{
+    convert_directory_with_env, format_duration, parse_font_package, parse_pdf_version, prefetch,
+    read_color_map, ImageFormat, LatexTemplate, LinkStrategy, OutputFormat, PdfOptions,
+    RenderOptions, RetryPolicy, TectonicConfig, DEFAULT_DPI, DEFAULT_FONT_PACKAGE,
+    DEFAULT_RETRY_BACKOFF,
+};
+use equation_processor::report::{
+    default_stats_file, history, read_stats, search_outputs, verify_outputs, SearchQuery,
+    StatsTrend,
+};
+use equation_processor::rpc::RpcServer;
+use equation_processor::scaffold::{init_project, StarterFormat, STARTER_CONFIG};
+use equation_processor::settings::{validate_config, Settings};
+use equation_processor::shard::{merge_manifests, Shard};
+use equation_processor::tagging::{count_tags, edit_tags_file, TagEdit};
+use equation_processor::typst::write_typst_snippets;
+use equation_processor::StandaloneOptions;
+use std::path::{Path, PathBuf};
 use std::process;
+use std::time::Duration;
 mod gui;
+#[cfg(feature = "tray")]
+mod tray;
+mod tui;
 
 /// Command-line arguments for the Equation Processor.
 ///
@@ -24,7 +50,9 @@ mod gui;
 ///   - Reads and parses equations from the specified file.
 ///   - Renders active equations to the output directory with the chosen color.
 ///   - Optionally deletes intermediate files.
-/// - If no `input_file` is provided, launches the GUI application.
+/// - If no `input_file` is provided, launches the GUI application, with a
+///   positional `FILE` preloaded (as "Open with" in file managers passes it).
+/// - Subcommands provide auxiliary tasks such as `prefetch` and `convert`.
 #[derive(Parser)]
 #[command(
     name = "Equation Processor",
@@ -32,18 +60,80 @@ mod gui;
     version = "1.0"
 )]
 struct Args {
-    /// Optional path to the input file containing equations.
+    /// Optional path or http(s) URL of the input file containing equations.
     ///
     /// Supported formats:
     /// - CSV: Expect columns [active, equation, name]
     /// - Markdown: Delimited by `$$...$$` blocks, optional `%%yes%%`/`%%no%%` for active.
+    /// - JSON: An array of `{"active": true, "name": "...", "body": "..."}` objects.
+    /// - YAML (`.yml`/`.yaml`): A list of mappings with the same keys as JSON.
+    /// - TOML: `[[equation]]` tables with the same keys as JSON, after an
+    ///   optional `[defaults]` table with `color`, `scale` and `formats`.
+    /// - LaTeX (`.tex`): The `equation`, `align`, `gather` and `\[...\]`
+    ///   blocks of a document, named by their `\label`.
+    /// - Jupyter (`.ipynb`): The math blocks of the Markdown cells, named by
+    ///   a heading or `cell<N>_eq<M>` unless they have a name tag.
+    /// - HTML (`.html`/`.htm`): MathJax `\(...\)`, `\[...\]` and
+    ///   `<script type="math/tex">` math, named by its `\label`.
+    /// - Directory: `.tex` files, one equation body per file, named after the file.
+    ///
+    /// A URL is downloaded first; its format comes from the extension or the
+    /// `Content-Type`, and GitHub `blob` links are fetched raw.
     #[arg(short, long, value_name = "INPUT_FILE")]
     input_file: Option<std::path::PathBuf>,
 
+    /// Input file to open in the GUI, as file managers pass it for
+    /// "Open with". Use `-i` to render without the GUI.
+    #[arg(value_name = "FILE", conflicts_with = "input_file")]
+    open: Option<std::path::PathBuf>,
+
     /// Hex color code for rendered output (e.g., `#000000` for black).
     #[arg(short, long, default_value = "#000000")]
     color: String,
 
+    /// File mapping equation names to colors, one `name = #RRGGBB` per line.
+    ///
+    /// Takes precedence over `%%color:...%%` tags, which take precedence
+    /// over `--color`.
+    #[arg(long, value_name = "FILE")]
+    color_per_equation: Option<std::path::PathBuf>,
+
+    /// Refuse to render if two of the batch's colors are hard to tell apart
+    /// with typical vision or a color-vision deficiency, suggesting
+    /// color-blind-safe replacements.
+    #[arg(long)]
+    check_palette: bool,
+
+    /// CSV field delimiter, e.g. `;` for European Excel exports or `tab`.
+    ///
+    /// Detected from the header row when omitted.
+    #[arg(long, global = true, value_name = "CHAR", value_parser = parse_delimiter)]
+    delimiter: Option<char>,
+
+    /// Order of the CSV columns, e.g. `name,active,equation`; `_` skips a
+    /// column.
+    ///
+    /// Known names: active, equation, name, assets, tags and description.
+    /// Without it the columns are taken from the header row, in any order,
+    /// and otherwise assumed to be `active,equation,name,assets,tags,description`.
+    #[arg(long, global = true, value_name = "COLUMNS")]
+    columns: Option<CsvColumns>,
+
+    /// Take each Markdown block's active flag from a task item on the line
+    /// before it: `- [x]` renders it, `- [ ]` skips it.
+    ///
+    /// An explicit `%%yes%%`/`%%no%%` tag still wins.
+    #[arg(long, global = true)]
+    task_checkboxes: bool,
+
+    /// What to do with a Markdown tag like `%%TODO%%` where a name is
+    /// expected: `ask`, use it as the `name`, `ignore` it, or `skip` the
+    /// equation.
+    ///
+    /// Outside the interactive CLI, `ask` ignores the tag.
+    #[arg(long, global = true, value_name = "POLICY", default_value = "ask")]
+    ambiguous_tags: AmbiguousTagPolicy,
+
     /// Output directory for rendered files.
     #[arg(short, long, default_value = "./output")]
     output_dir: std::path::PathBuf,
@@ -51,40 +141,1058 @@ struct Args {
     /// Delete intermediate LaTeX/PDF files after rendering.
     #[arg(short, long)]
     delete_intermediates: bool,
+
+    /// Render inactive equations too (`%%no%%` / CSV `no`), for full rebuilds.
+    #[arg(long)]
+    include_inactive: bool,
+
+    /// Also write `\input`-ready `.tex` snippets and a master include file
+    /// into `<OUTPUT_DIR>/snippets`.
+    #[arg(long)]
+    tex_snippets: bool,
+
+    /// Also build `<OUTPUT_DIR>/slides.pdf`, a beamer deck with one
+    /// equation per frame, for review.
+    #[arg(long)]
+    beamer: bool,
+
+    /// Also write `<OUTPUT_DIR>/gallery.html`, a page showing every rendered
+    /// equation that follows the reader's light/dark preference.
+    #[arg(long)]
+    html: bool,
+
+    /// Also write `<OUTPUT_DIR>/equations.md`, referencing the image of
+    /// every rendered equation under its section's heading.
+    #[arg(long)]
+    markdown: bool,
+
+    /// Write the Markdown page's references as `<img>` elements with the
+    /// rendered width and height, so embedding pages don't shift their
+    /// layout while images load.
+    #[arg(long, requires = "markdown")]
+    image_dimensions: bool,
+
+    /// Pop up a desktop notification with the counts when the batch
+    /// completes or fails.
+    #[arg(long)]
+    notify: bool,
+
+    /// Before rendering, move the artifacts of equations whose LaTeX changed
+    /// since the last run to `<OUTPUT_DIR>/history/`, stamped with their
+    /// render time.
+    #[arg(long)]
+    keep_history: bool,
+
+    /// Run a command on each artifact after rendering, e.g. `svgo {svg}` or
+    /// `optipng {png}`; `{file}` stands for every artifact, `{name}` and
+    /// `{dir}` for the equation name and output directory. Repeatable.
+    #[arg(long = "post-render", value_name = "COMMAND")]
+    post_render: Vec<PostRenderHook>,
+
+    /// Seconds a post-render command may run on one artifact before it is
+    /// killed.
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_HOOK_TIMEOUT.as_secs())]
+    hook_timeout: u64,
+
+    /// Number of equations compiled at the same time.
+    ///
+    /// Each job runs its own tectonic and `pdftocairo`; `0` uses one job
+    /// per CPU.
+    #[arg(short, long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
+    /// Times to render an equation again after a transient failure, such
+    /// as a dropped connection while tectonic downloads packages.
+    ///
+    /// Other failures, like LaTeX errors, are never retried.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    retries: u32,
+
+    /// Seconds to wait before the first retry, doubled before each
+    /// further one.
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_RETRY_BACKOFF.as_secs())]
+    retry_backoff: u64,
+
+    /// Render every equation, even those unchanged since they were last
+    /// rendered into the output directory.
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Name artifacts by content hash (`ab34….svg`) and write a name → file
+    /// `manifest.json`, for long-lived CDN caching.
+    #[arg(long)]
+    content_addressed: bool,
+
+    /// Artifacts to produce, comma separated: `pdf`, `svg`, `png`.
+    ///
+    /// `--formats pdf` skips the PDF→image conversion (and `pdftocairo`)
+    /// entirely; a listed `pdf` is kept even with `--delete-intermediates`.
+    #[arg(long, value_delimiter = ',', default_value = "svg")]
+    formats: Vec<OutputFormat>,
+
+    /// PDF stream compression level, 0 (none) to 9 (smallest).
+    #[arg(long, value_name = "LEVEL", value_parser = clap::value_parser!(u8).range(0..=9))]
+    pdf_compression: Option<u8>,
+
+    /// PDF version to write, `1.3` to `1.7`; some journals require `1.4`.
+    #[arg(long, value_name = "VERSION", value_parser = parse_pdf_version)]
+    pdf_version: Option<u8>,
+
+    /// Embed complete fonts in the PDFs instead of subsets of the glyphs
+    /// used. Only LuaLaTeX (`--engine lualatex`) can.
+    #[arg(long)]
+    full_fonts: bool,
+
+    /// Resume an interrupted batch, skipping equations already rendered.
+    #[arg(short, long)]
+    resume: bool,
+
+    /// Render equations with identical bodies once and give the duplicates
+    /// the same artifacts via `copy`, `hardlink` or `symlink`.
+    #[arg(long, value_name = "STRATEGY")]
+    dedup: Option<LinkStrategy>,
+
+    /// Treat all math equations as chemical formulas (mhchem `\ce{...}`).
+    ///
+    /// Individual blocks can opt in with a `%%chem%%` tag instead.
+    #[arg(long)]
+    chemistry: bool,
+
+    /// Border around every equation (standalone's `border`, default `1pt`).
+    ///
+    /// Individual blocks can override it with a `%%border:2pt%%` tag.
+    #[arg(long, value_name = "DIM")]
+    border: Option<String>,
+
+    /// Font size in points (default 14.4, LaTeX's `\Large`).
+    #[arg(long, value_name = "PT")]
+    font_size: Option<f32>,
+
+    /// Fill behind every equation: `transparent` (the default) or a color
+    /// such as `#1E1E1E`.
+    #[arg(long, value_name = "COLOR")]
+    background: Option<Background>,
+
+    /// LaTeX engine for equations without an `%%engine%%` tag: `tectonic`
+    /// or the TeX installation's `pdflatex`, `xelatex` or `lualatex`.
+    #[arg(long, default_value = "tectonic", value_name = "ENGINE")]
+    engine: LatexEngine,
+
+    /// Font package for every equation, e.g. `newtxmath` or `stix2`
+    /// (default `gfsneohellenicot`).
+    #[arg(long, value_name = "PACKAGE", value_parser = parse_font_package)]
+    font: Option<String>,
+
+    /// Load no font package, keeping LaTeX's Computer Modern.
+    #[arg(long, conflicts_with = "font")]
+    no_font_package: bool,
+
+    /// LaTeX document to typeset every equation in instead of the built-in one.
+    ///
+    /// Placeholders such as `{{BODY}}` and `{{COLOR}}` are filled in per
+    /// equation; `init` writes an example.
+    #[arg(long, value_name = "FILE")]
+    template: Option<PathBuf>,
+
+    /// Typeset in a `varwidth` box, optionally limited to a maximum width.
+    #[arg(long, value_name = "WIDTH", num_args = 0..=1, default_missing_value = "true")]
+    varwidth: Option<String>,
+
+    /// Keep the full page instead of cropping to the content.
+    #[arg(long)]
+    no_crop: bool,
+
+    /// Prefix added to every output file name, e.g. `lecture3_`.
+    #[arg(long, value_name = "PREFIX", default_value = "")]
+    name_prefix: String,
+
+    /// Suffix added to every output file name.
+    #[arg(long, value_name = "SUFFIX", default_value = "")]
+    name_suffix: String,
+
+    /// Directory used by tectonic to cache downloaded TeX resources.
+    ///
+    /// Overrides `EQUATION_PROCESSOR_CACHE_DIR`.
+    #[arg(long, global = true, value_name = "DIR")]
+    cache_dir: Option<std::path::PathBuf>,
+
+    /// Tectonic bundle (local file or URL) to fetch TeX resources from.
+    ///
+    /// Overrides `EQUATION_PROCESSOR_BUNDLE`.
+    #[arg(long, global = true, value_name = "PATH_OR_URL")]
+    bundle: Option<String>,
+
+    /// Never access the network; fail clearly if a resource is not cached.
+    ///
+    /// Also enabled by `EQUATION_PROCESSOR_OFFLINE=1`.
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Build reproducible PDFs dated at EPOCH seconds since 1970 (default
+    /// 0), so unchanged equations render to byte-identical files.
+    ///
+    /// Implied by `--content-addressed`. Blocks can set their own date with
+    /// an `%%env:SOURCE_DATE_EPOCH=...%%` tag.
+    #[arg(long, global = true, value_name = "EPOCH", num_args = 0..=1, default_missing_value = "0")]
+    reproducible: Option<u64>,
+
+    /// Set an environment variable for tectonic and `pdftocairo`, e.g.
+    /// `SOURCE_DATE_EPOCH=0` or `TEXINPUTS=./sty//:`; repeatable.
+    ///
+    /// Blocks can add their own with an `%%env:KEY=VALUE%%` tag.
+    #[arg(long = "env", global = true, value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    env: Vec<(String, String)>,
+
+    /// Settings file applied beneath the flags, as exported from the GUI.
+    ///
+    /// Defaults to `EQUATION_PROCESSOR_CONFIG`, else `config.toml` in the
+    /// platform config directory (e.g. `~/.config/equation_processor`),
+    /// used when it exists.
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<std::path::PathBuf>,
+
+    /// Settings read from the config file.
+    #[arg(skip)]
+    settings: Settings,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+/// Auxiliary commands; all but `shard` do not render an input file.
+#[derive(Subcommand)]
+enum Commands {
+    /// Write a starter equations file, with every annotation explained, and a
+    /// commented config file into a directory.
+    Init {
+        /// Directory to write the files into.
+        #[arg(default_value = ".", value_name = "DIR")]
+        dir: PathBuf,
+
+        /// Starter equations file format: markdown or csv.
+        #[arg(long, default_value = "markdown", value_name = "FORMAT")]
+        format: StarterFormat,
+
+        /// Overwrite files that already exist.
+        #[arg(long)]
+        force: bool,
+    },
+    /// Download the TeX resources used by the standard preamble into the cache.
+    Prefetch,
+    /// Work with the config file.
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// Serve JSON-RPC requests on stdin/stdout for editor plugins.
+    Rpc,
+    /// Check an input file for common mistakes without rendering it.
+    ///
+    /// Exits with status 1 if errors were found (or warnings, with
+    /// `--strict`) and 2 if the file cannot be read.
+    Lint {
+        /// CSV or Markdown file to check.
+        #[arg(value_name = "INPUT_FILE")]
+        file: std::path::PathBuf,
+
+        /// Print the diagnostics as JSON instead of text.
+        #[arg(long)]
+        json: bool,
+
+        /// Treat warnings as errors.
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Check that an input file also displays with other renderers.
+    ///
+    /// Exits with status 1 if incompatibilities were found and 2 if the
+    /// file cannot be read.
+    Check {
+        /// CSV or Markdown file to check.
+        #[arg(value_name = "INPUT_FILE")]
+        file: std::path::PathBuf,
+
+        /// Flag LaTeX commands and environments KaTeX does not support.
+        #[arg(long, required = true)]
+        katex: bool,
+
+        /// Print the diagnostics as JSON instead of text.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add, remove or list equation tags in the input file (`-i`).
+    ///
+    /// Tags are edited in CSV input (its `tags` column) and the file is
+    /// replaced atomically; `list` reads any input.
+    Tag {
+        #[command(subcommand)]
+        action: TagAction,
+    },
+    /// List the archived versions of an equation in the output directory
+    /// (see `--keep-history`), oldest first.
+    History {
+        /// Equation name, as used for its output files.
+        name: String,
+    },
+    /// Check the artifacts in the output directory against the checksums in
+    /// `index.json` and `manifest.json`, reporting missing, modified and
+    /// corrupted files. Exits with status 1 if any are found.
+    VerifyOutputs {
+        /// Print the problems as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Search the `index.json` files under the output directory, including
+    /// subdirectories, and print the matching equations with their artifacts.
+    Search {
+        /// Text found in a name, LaTeX body, tag or description; prefix it
+        /// with `name:`, `body:` or `tag:` to search one field.
+        query: SearchQuery,
+
+        /// Print the matches as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the runs recorded in the output directory and the trend of the
+    /// render time per equation, e.g. to spot a slower toolchain.
+    Stats {
+        /// How many recent runs to list and average.
+        #[arg(long, default_value_t = 10, value_name = "N")]
+        last: usize,
+
+        /// Print the runs and trend as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Browse the input file (`-i`) in a keyboard-driven terminal interface:
+    /// toggle and filter equations, preview them and render the selection.
+    Tui,
+    /// Render one part of the input file (`-i`), so several machines or CI
+    /// jobs can share a large batch.
+    ///
+    /// Equations are assigned to shards by a hash of their name, so every
+    /// job given the same input and `--count` renders a disjoint subset.
+    /// Each writes an `index.json` for its part; combine them with
+    /// `merge-manifests`.
+    Shard {
+        /// How many parts the batch is split into.
+        #[arg(long, value_name = "N")]
+        count: usize,
+
+        /// Which part to render, from 0 to N-1.
+        #[arg(long, value_name = "I")]
+        index: usize,
+    },
+    /// Combine the `index.json` files of `shard` runs into one in the output
+    /// directory (`-o`), copying their artifacts there.
+    ///
+    /// Warns about shards that no input covers and fails if an equation is
+    /// listed twice.
+    MergeManifests {
+        /// Output directories of the shard runs, or their `index.json` files.
+        #[arg(required = true, value_name = "DIR")]
+        inputs: Vec<PathBuf>,
+    },
+    /// Translate the equations of the input file (`-i`) to Typst math and
+    /// write one `.typ` snippet per active equation, plus `equations.typ`
+    /// including them all, into `typst/` in the output directory.
+    ///
+    /// The translation is best effort: commands without a Typst equivalent
+    /// are kept as quoted text and listed as warnings.
+    ExportTypst,
+    /// Convert existing equation PDFs to SVG/PNG without recompiling LaTeX.
+    Convert {
+        /// Directory containing the `.pdf` files.
+        #[arg(value_name = "PDF_DIR")]
+        pdf_dir: std::path::PathBuf,
+
+        /// Where to write the converted files (defaults to `PDF_DIR`).
+        #[arg(long, value_name = "DIR")]
+        out: Option<std::path::PathBuf>,
+
+        /// Formats to produce, comma separated: `svg`, `png`.
+        #[arg(long, value_delimiter = ',', default_value = "svg")]
+        format: Vec<ImageFormat>,
+
+        /// Resolution for PNG output.
+        #[arg(long, default_value_t = DEFAULT_DPI)]
+        dpi: u32,
+    },
 }
 
-<<<<<<< HEAD
-#[derive(Default)]
-struct EquationApp {
-    input_file: Option<PathBuf>,
-    open_input_file_dialog: Option<FileDialog>,
-    output_dir: Option<PathBuf>,
-    open_output_file_dialog: Option<FileDialog>,
+/// What `config` does.
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Check a config file for unknown keys and invalid values, printing
+    /// every problem with its line.
+    ///
+    /// Exits with status 1 if problems were found and 2 if the file cannot
+    /// be read.
+    Validate {
+        /// Config file to check; defaults to the one `--config` or the
+        /// environment selects.
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
 }
 
-impl eframe::App for EquationApp {
-    fn update(&mut self, ctx: egui::Context, _frame: &mut eframe::Frame) {}
+/// What `tag` does to the selected equations.
+#[derive(Subcommand)]
+enum TagAction {
+    /// Add a tag to the equations that lack it.
+    Add {
+        tag: String,
+
+        /// Only equations whose name matches this glob, e.g. `ch3_*`.
+        #[arg(long, value_name = "GLOB")]
+        only: Option<String>,
+    },
+    /// Remove a tag from the equations that carry it.
+    Remove {
+        tag: String,
+
+        /// Only equations whose name matches this glob, e.g. `ch3_*`.
+        #[arg(long, value_name = "GLOB")]
+        only: Option<String>,
+    },
+    /// Print each tag with the number of equations carrying it.
+    List {
+        /// Only equations whose name matches this glob, e.g. `ch3_*`.
+        #[arg(long, value_name = "GLOB")]
+        only: Option<String>,
+    },
+}
+
+impl Args {
+    /// Apply the config file to the settings not given as flags.
+    fn load_config(&mut self, matches: &ArgMatches) -> std::io::Result<()> {
+        let path = match &self.config {
+            Some(path) => path.clone(),
+            None => match config_file() {
+                Some(path) if path.is_file() => path,
+                _ => return Ok(()),
+            },
+        };
+        let settings = Settings::load(&path)?;
+        let defaulted = |id: &str| matches.value_source(id) == Some(ValueSource::DefaultValue);
+        if defaulted("color") {
+            self.color = settings.color.clone();
+        }
+        if defaulted("engine") {
+            self.engine = settings.engine;
+        }
+        if let (true, Some(dir)) = (defaulted("output_dir"), &settings.output_dir) {
+            self.output_dir = dir.clone();
+        }
+        if self.border.is_none() && !settings.border.is_empty() {
+            self.border = Some(settings.border.clone());
+        }
+        if self.template.is_none() {
+            self.template = settings.template.clone();
+        }
+        if self.background.is_none() {
+            // Validated when the config file was loaded.
+            self.background = settings.background.parse().ok();
+        }
+        if self.font.is_none() && !self.no_font_package {
+            match settings.font_package() {
+                Some(font) => self.font = Some(font),
+                None => self.no_font_package = true,
+            }
+        }
+        self.delete_intermediates |= settings.delete_intermediates;
+        self.chemistry |= settings.chemistry;
+        self.tex_snippets |= settings.tex_snippets;
+        self.html |= settings.html_gallery;
+        self.settings = settings;
+        Ok(())
+    }
+
+    /// Apply the `[defaults]` of a TOML input file to the settings not given
+    /// as flags; its color and scale are applied to the equations themselves.
+    fn load_input_defaults(
+        &mut self,
+        matches: &ArgMatches,
+        input: &Path,
+    ) -> Result<(), EquationError> {
+        if !matches!(detect_file_type(input), Filetype::Toml) {
+            return Ok(());
+        }
+        let defaults = read_toml_defaults(input)?;
+        let defaulted = matches.value_source("formats") == Some(ValueSource::DefaultValue);
+        if defaulted && !defaults.formats.is_empty() {
+            self.formats = defaults.formats;
+        }
+        Ok(())
+    }
+
+    /// Tectonic configuration from the config file and the environment,
+    /// overridden by flags.
+    fn tectonic_config(&self) -> TectonicConfig {
+        // Validated when the config file was loaded.
+        let mut config = self
+            .settings
+            .tectonic
+            .to_config()
+            .unwrap_or_default()
+            .with_env_overrides();
+        if let Some(dir) = &self.cache_dir {
+            config.cache_dir = Some(dir.clone());
+        }
+        if let Some(bundle) = &self.bundle {
+            config.bundle = Some(bundle.clone());
+        }
+        config.offline |= self.offline;
+        if self.reproducible.is_some() {
+            config.source_date_epoch = self.reproducible;
+        } else if self.content_addressed && config.source_date_epoch.is_none() {
+            config.source_date_epoch = Some(0);
+        }
+        config.env.extend(self.env.iter().cloned());
+        config
+    }
+
+    /// Input parsing options from flags.
+    fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            csv_delimiter: self.delimiter,
+            csv_columns: self.columns,
+            task_checkboxes: self.task_checkboxes,
+            ambiguous_tags: self.ambiguous_tags,
+        }
+    }
+
+    /// Batch-wide render options from flags.
+    fn render_options(&self) -> std::io::Result<RenderOptions> {
+        let colors = match &self.color_per_equation {
+            Some(path) => read_color_map(path)?,
+            None => Default::default(),
+        };
+        Ok(RenderOptions {
+            color: self.color.clone(),
+            engine: self.engine,
+            font_size: self.font_size,
+            template: match &self.template {
+                Some(path) => Some(LatexTemplate::read(path)?),
+                None => None,
+            },
+            font: match (&self.font, self.no_font_package) {
+                (_, true) => None,
+                (Some(font), false) => Some(font.clone()),
+                (None, false) => Some(DEFAULT_FONT_PACKAGE.to_string()),
+            },
+            background: self.background.unwrap_or_default(),
+            delete_intermediates: self.delete_intermediates,
+            chemistry: self.chemistry,
+            resume: self.resume,
+            dedup: self.dedup,
+            standalone: StandaloneOptions {
+                border: self.border.clone(),
+                varwidth: self.varwidth.clone(),
+                crop: self.no_crop.then_some(false),
+            },
+            name_prefix: self.name_prefix.clone(),
+            name_suffix: self.name_suffix.clone(),
+            colors,
+            include_inactive: self.include_inactive,
+            tex_snippets: self.tex_snippets,
+            content_addressed: self.content_addressed,
+            formats: self.formats.clone(),
+            beamer: self.beamer,
+            html_gallery: self.html,
+            markdown: self.markdown,
+            image_dimensions: self.image_dimensions,
+            notify: self.notify,
+            keep_history: self.keep_history,
+            check_palette: self.check_palette,
+            shard: match self.command {
+                Some(Commands::Shard { index, count }) => Some(
+                    Shard::new(index, count)
+                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?,
+                ),
+                _ => None,
+            },
+            hooks: self.post_render.clone(),
+            hook_timeout: Duration::from_secs(self.hook_timeout),
+            jobs: match self.jobs {
+                0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+                jobs => jobs,
+            },
+            retry: RetryPolicy {
+                retries: self.retries,
+                backoff: Duration::from_secs(self.retry_backoff),
+            },
+            cache: !self.no_cache,
+            pdf: PdfOptions {
+                compression: self.pdf_compression,
+                minor_version: self.pdf_version,
+                full_fonts: self.full_fonts,
+            },
+        })
+    }
+}
+
+/// Parse a `--delimiter` value: a single character or `tab`.
+fn parse_delimiter(s: &str) -> Result<char, String> {
+    if s.eq_ignore_ascii_case("tab") || s == "\\t" {
+        return Ok('\t');
+    }
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c != '"' => Ok(c),
+        _ => Err(format!(
+            "invalid delimiter '{s}': expected one character or `tab`"
+        )),
+    }
+}
+
+/// Print the diagnostics for `file` as text or JSON and exit: with 1 if
+/// there are errors (or any diagnostic when `strict`), 2 if the file could
+/// not be read, 0 otherwise.
+fn report_diagnostics(
+    file: &std::path::Path,
+    diagnostics: std::io::Result<Vec<Diagnostic>>,
+    json: bool,
+    strict: bool,
+) -> ! {
+    let diagnostics = match diagnostics {
+        Ok(diagnostics) => diagnostics,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            process::exit(2);
+        }
+    };
+    if json {
+        let report = serde_json::json!({
+            "file": file,
+            "diagnostics": diagnostics,
+        });
+        println!("{report:#}");
+    } else {
+        for diagnostic in &diagnostics {
+            println!("{}:{diagnostic}", file.display());
+        }
+    }
+    let failing = diagnostics
+        .iter()
+        .any(|d| strict || d.severity == Severity::Error);
+    process::exit(if failing { 1 } else { 0 });
+}
+
+/// Print the problems in the config file at `path`, or the default one, and
+/// exit with status 0 if there are none, 1 if there are and 2 if it cannot
+/// be read.
+fn validate_config_file(path: Option<&PathBuf>) -> ! {
+    let Some(path) = path.cloned().or_else(config_file) else {
+        eprintln!("Error: no config directory on this platform; pass a FILE");
+        process::exit(2);
+    };
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("Error: {}: {e}", path.display());
+            process::exit(2);
+        }
+    };
+    let issues = validate_config(&content);
+    for issue in &issues {
+        println!("{}:{issue}", path.display());
+    }
+    if issues.is_empty() {
+        println!("{} is valid.", path.display());
+        process::exit(0);
+    }
+    eprintln!("{} problem(s) in {}", issues.len(), path.display());
+    process::exit(1);
+}
+
+/// `path`, or for an http(s) URL the downloaded copy; exits on failure.
+fn local_input(path: &Path) -> PathBuf {
+    let Some(url) = path.to_str().filter(|p| is_url(p)) else {
+        return path.to_path_buf();
+    };
+    match fetch_input(url) {
+        Ok(local) => local,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            process::exit(1);
+        }
+    }
 }
 
-=======
 /// Entry point.
 ///
 /// Parses arguments and either:
-/// - Calls `run_cli(...)` to process equations in batch (CLI mode), or
-/// - Launches the eframe GUI (`gui::launch_gui()`) if no input file was specified.
->>>>>>> abc17c6641e376c20b93baf09063d375ffec4080
+/// - Calls `run_cli_with(...)` to process equations in batch (CLI mode), or
+/// - Launches the eframe GUI (`gui::launch_gui()`) if no input file was specified,
+///   with the positional `FILE` preloaded if given.
 fn main() {
+    // Stop tectonic and pdftocairo with the run: on Ctrl+C, on a panic and
+    // when main returns with render threads still busy.
+    if let Err(e) = children::install_handlers() {
+        eprintln!("Warning: could not install the Ctrl+C handler: {e}");
+    }
+    let _children = KillOnDrop;
+
     // Parse and validate arguments
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // `config validate` reports every problem instead of stopping at them.
+    let validating = matches!(args.command, Some(Commands::Config { .. }));
+    if !validating {
+        if let Err(e) = args.load_config(&matches) {
+            eprintln!("Error: {e}");
+            process::exit(1);
+        }
+    }
+    let tectonic = args.tectonic_config();
+
+    match &args.command {
+        Some(Commands::Init { dir, format, force }) => {
+            match init_project(dir, *format, *force) {
+                Ok(written) => {
+                    for path in &written {
+                        println!("Created {}", path.display());
+                    }
+                    println!(
+                        "Render them with: equation_processor --config {} -i {}",
+                        dir.join(STARTER_CONFIG).display(),
+                        dir.join(format.file_name()).display()
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Config {
+            action: ConfigAction::Validate { file },
+        }) => validate_config_file(file.as_ref().or(args.config.as_ref())),
+        Some(Commands::Prefetch) => {
+            match prefetch(&tectonic) {
+                Ok(()) => println!("TeX resources cached."),
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Rpc) => {
+            let stdin = std::io::stdin();
+            if let Err(e) = RpcServer::new(tectonic).serve(stdin.lock(), std::io::stdout()) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::Lint { file, json, strict }) => report_diagnostics(
+            file,
+            lint_file(&local_input(file), &args.parse_options()),
+            *json,
+            *strict,
+        ),
+        Some(Commands::Check {
+            file,
+            katex: _,
+            json,
+        }) => report_diagnostics(
+            file,
+            check_katex(&local_input(file), &args.parse_options()),
+            *json,
+            false,
+        ),
+        Some(Commands::Tag { action }) => {
+            let Some(input) = &args.input_file else {
+                eprintln!("Error: tag needs an input file (-i)");
+                process::exit(1);
+            };
+            let options = args.parse_options();
+            let result = match action {
+                TagAction::Add { tag, only } => {
+                    edit_tags_file(input, &TagEdit::Add(tag.clone()), only.as_deref(), &options)
+                        .map(|n| println!("Tagged {n} equation(s) with '{tag}'."))
+                }
+                TagAction::Remove { tag, only } => edit_tags_file(
+                    input,
+                    &TagEdit::Remove(tag.clone()),
+                    only.as_deref(),
+                    &options,
+                )
+                .map(|n| println!("Removed '{tag}' from {n} equation(s).")),
+                TagAction::List { only } => read_input(input, &options)
+                    .map(|equations| {
+                        for (tag, count) in count_tags(&equations, only.as_deref()) {
+                            println!("{tag}\t{count}");
+                        }
+                    })
+                    .map_err(Into::into),
+            };
+            if let Err(e) = result {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::History { name }) => {
+            match history(name, &args.output_dir) {
+                Ok(versions) if versions.is_empty() => {
+                    println!("No earlier versions of '{name}' in {:?}", args.output_dir)
+                }
+                Ok(versions) => {
+                    for version in versions {
+                        let files: Vec<String> = version
+                            .files
+                            .iter()
+                            .map(|f| f.display().to_string())
+                            .collect();
+                        println!("{}  {}", version.timestamp, files.join(", "));
+                    }
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::VerifyOutputs { json }) => {
+            let problems = match verify_outputs(&args.output_dir) {
+                Ok(problems) => problems,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(2);
+                }
+            };
+            if *json {
+                println!("{:#}", serde_json::json!({ "problems": problems }));
+            } else if problems.is_empty() {
+                println!("All artifacts in {:?} are intact.", args.output_dir);
+            } else {
+                for p in &problems {
+                    println!("{:<9} {} ({})", p.problem, p.file, p.name);
+                }
+            }
+            process::exit(if problems.is_empty() { 0 } else { 1 });
+        }
+        Some(Commands::Search { query, json }) => {
+            let hits = match search_outputs(&args.output_dir, query) {
+                Ok(hits) => hits,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(2);
+                }
+            };
+            if *json {
+                println!("{:#}", serde_json::json!({ "matches": hits }));
+            } else {
+                for hit in &hits {
+                    let files: Vec<String> = hit
+                        .artifacts
+                        .iter()
+                        .map(|f| f.display().to_string())
+                        .collect();
+                    println!("{}  {}", hit.name, files.join(", "));
+                    println!("    {}", hit.latex.replace('\n', " "));
+                }
+            }
+            process::exit(if hits.is_empty() { 1 } else { 0 });
+        }
+        Some(Commands::Stats { last, json }) => {
+            let runs = match default_stats_file(&args.output_dir).and_then(|f| read_stats(&f)) {
+                Ok(runs) => runs,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+            };
+            let trend = StatsTrend::new(&runs, *last);
+            let recent = &runs[runs.len() - trend.runs..];
+            if *json {
+                println!(
+                    "{:#}",
+                    serde_json::json!({ "runs": recent, "trend": trend })
+                );
+                return;
+            }
+            if runs.is_empty() {
+                println!("No runs recorded in {:?}", args.output_dir);
+                return;
+            }
+            println!("finished (UTC)       rendered  failed  duration  per equation");
+            for run in recent {
+                let per_equation = run
+                    .seconds_per_equation()
+                    .map_or("-".into(), |s| format!("{s:.2}s"));
+                println!(
+                    "{}  {:>8}  {:>6}  {:>8}  {per_equation:>12}",
+                    run.finished,
+                    run.rendered,
+                    run.failed,
+                    format_duration(Duration::from_secs_f64(run.seconds)),
+                );
+            }
+            if let Some(average) = trend.seconds_per_equation {
+                print!(
+                    "Average over {} run(s): {average:.2}s per equation",
+                    trend.runs
+                );
+                match trend.change() {
+                    Some(change) => {
+                        println!(" ({:+.0}% against the runs before)", change * 100.0)
+                    }
+                    None => println!(),
+                }
+            }
+            if let Some(rate) = trend.failure_rate {
+                println!("Failure rate: {:.1}%", rate * 100.0);
+            }
+            return;
+        }
+        Some(Commands::Tui) => {
+            let Some(input) = &args.input_file else {
+                eprintln!("Error: tui needs an input file (-i)");
+                process::exit(1);
+            };
+            let result = args
+                .render_options()
+                .map_err(Into::into)
+                .and_then(|mut options| {
+                    options.color = args.color.parse::<Color>()?.to_string();
+                    let equations = prepare_equations(
+                        &local_input(input),
+                        options.chemistry,
+                        &args.parse_options(),
+                        &options,
+                    )?;
+                    let settings = tui::RenderSettings {
+                        output_dir: args.output_dir.clone(),
+                        tectonic: tectonic.clone(),
+                        options,
+                    };
+                    Ok::<_, Box<dyn std::error::Error>>(tui::run(equations, settings)?)
+                });
+            if let Err(e) = result {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Commands::ExportTypst) => {
+            let Some(input) = &args.input_file else {
+                eprintln!("Error: export-typst needs an input file (-i)");
+                process::exit(1);
+            };
+            let result = args
+                .render_options()
+                .map_err(Into::into)
+                .and_then(|options| {
+                    let equations = prepare_equations(
+                        &local_input(input),
+                        options.chemistry,
+                        &args.parse_options(),
+                        &options,
+                    )?;
+                    Ok::<_, Box<dyn std::error::Error>>(write_typst_snippets(
+                        &equations,
+                        &args.output_dir,
+                    )?)
+                });
+            match result {
+                Ok(export) => {
+                    for (name, commands) in &export.untranslated {
+                        eprintln!(
+                            "Warning: {name}: no Typst equivalent for {}, kept as text",
+                            commands.join(", ")
+                        );
+                    }
+                    for name in &export.skipped {
+                        eprintln!(
+                            "Warning: {name}: chemical formulas and TikZ drawings are left out"
+                        );
+                    }
+                    println!(
+                        "Wrote {} Typst snippet(s), included by {:?}",
+                        export.written.len(),
+                        export.master
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Convert {
+            pdf_dir,
+            out,
+            format,
+            dpi,
+        }) => {
+            let out = out.as_ref().unwrap_or(pdf_dir);
+            match convert_directory_with_env(pdf_dir, out, format, *dpi, &args.env) {
+                Ok(files) => println!("Converted {} file(s) into {out:?}", files.len()),
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Shard { .. }) if args.input_file.is_none() => {
+            eprintln!("Error: shard needs an input file (-i)");
+            process::exit(1);
+        }
+        Some(Commands::MergeManifests { inputs }) => {
+            match merge_manifests(inputs, &args.output_dir) {
+                Ok(report) => {
+                    if !report.missing_shards.is_empty() {
+                        let missing: Vec<String> =
+                            report.missing_shards.iter().map(usize::to_string).collect();
+                        eprintln!(
+                            "Warning: no input covers shard(s) {}; their equations are missing",
+                            missing.join(", ")
+                        );
+                    }
+                    println!(
+                        "Merged {} equation(s) into {:?}, copying {} artifact(s)",
+                        report.equations, report.index, report.copied
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Commands::Shard { .. }) | None => {}
+    }
 
-    match args.input_file {
+    match args.input_file.clone() {
         Some(path) => {
             // CLI mode: delegate to library and exit on error
-            if let Err(e) = run_cli(
-                path,
-                &args.color,
+            let input = local_input(&path);
+            if let Err(e) = args.load_input_defaults(&matches, &input) {
+                eprintln!("Error: {e}");
+                process::exit(1);
+            }
+            let options = match args.render_options() {
+                Ok(options) => options,
+                Err(e) => {
+                    eprintln!("Error: {e}");
+                    process::exit(1);
+                }
+            };
+            if let Err(e) = run_cli_with(
+                input,
                 &args.output_dir,
-                args.delete_intermediates,
+                &tectonic,
+                &args.parse_options(),
+                &options,
             ) {
                 eprintln!("Error: {e}");
                 process::exit(1);
@@ -92,38 +1200,7 @@ fn main() {
         }
         None => {
             // GUI mode: start the interactive window
-            gui::launch_gui();
-        }
-<<<<<<< HEAD
-    };
-
-    if equations.is_empty() {
-        eprintln!("No equations found to process.");
-    } else {
-        let mut table = Table::new();
-
-        table.add_row(row!["Active", "Name", "Equation"]);
-
-        for eq in &equations {
-            table.add_row(row![if eq.active { "Yes" } else { "No" }, eq.name, eq.body]);
+            gui::launch_gui(args.open.as_deref().map(local_input));
         }
-
-        table.printstd();
-
-        if !ask_confirmation("Are you sure you want to render the active equations?") {
-            return;
-        }
-
-        render_equations(
-            &equations,
-            &args.output_dir,
-            &args.color,
-            args.delete_intermediates,
-        )
-        .unwrap();
-
-        println!("  Equations rendered successfully to {:?}", args.output_dir);
-=======
->>>>>>> abc17c6641e376c20b93baf09063d375ffec4080
     }
 }
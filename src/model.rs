@@ -0,0 +1,479 @@
+//! Data model: equations and input file types.
+//!
+//! ```
+//! use equation_processor::model::Equation;
+//!
+//! let eq = Equation::new(true, "mass energy", "E = mc^2");
+//! assert_eq!(eq.name, "mass_energy");
+//! ```
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Supported input file types.
+#[derive(Debug)]
+pub enum Filetype {
+    /// CSV with header [active,equation,name]
+    Csv,
+    /// Markdown: $$...$$ blocks, optional %%yes%%/%%no%% and %%name%% tags
+    Markdown,
+    /// Directory of `.tex` fragments, one equation body per file
+    TexDirectory,
+    /// LaTeX document: its `equation`, `align` and `gather` environments and
+    /// `\[...\]` blocks, named by their `\label`
+    Latex,
+    /// JSON: an array of `{"active": true, "name": "...", "body": "..."}` objects
+    Json,
+    /// YAML: a list of mappings with the keys of the JSON format
+    Yaml,
+    /// TOML: a `[defaults]` table and `[[equation]]` tables with the keys of
+    /// the JSON format
+    Toml,
+    /// Jupyter notebook: the `$$...$$` blocks of its Markdown cells
+    Notebook,
+    /// HTML page: its MathJax `\(...\)`, `\[...\]` and `math/tex` scripts
+    Html,
+    /// Format added at runtime with
+    /// [`register_input_format`](crate::parser::register_input_format),
+    /// by its lowercase extension
+    Registered(String),
+    /// Unknown or unsupported extension
+    Unknown,
+}
+
+/// What an equation body contains, selecting how it is wrapped for LaTeX.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EquationKind {
+    /// Math-mode formula, wrapped in `$...$`
+    #[default]
+    Math,
+    /// TikZ drawing commands, wrapped in a `tikzpicture` environment
+    TikZ,
+    /// Chemical formula, wrapped in mhchem's `\ce{...}`
+    Chemistry,
+}
+
+impl EquationKind {
+    /// Lowercase identifier, as accepted by `FromStr` and the `kind` tag.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EquationKind::Math => "math",
+            EquationKind::TikZ => "tikz",
+            EquationKind::Chemistry => "chem",
+        }
+    }
+}
+
+impl FromStr for EquationKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "math" => Ok(EquationKind::Math),
+            "tikz" => Ok(EquationKind::TikZ),
+            "chem" | "chemistry" => Ok(EquationKind::Chemistry),
+            other => Err(format!(
+                "unknown equation kind '{other}' (expected math, tikz or chem)"
+            )),
+        }
+    }
+}
+
+/// LaTeX engine an equation is compiled with.
+///
+/// Tectonic is the default; the others are the system TeX installation's
+/// programs, for equations that need features tectonic's engine lacks, such
+/// as `luacode` or certain fonts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LatexEngine {
+    /// Tectonic, with its own bundle and cache
+    #[default]
+    Tectonic,
+    /// The system `pdflatex`
+    Pdflatex,
+    /// The system `xelatex`
+    Xelatex,
+    /// The system `lualatex`
+    Lualatex,
+}
+
+impl LatexEngine {
+    /// Every engine, tectonic first.
+    pub const ALL: [LatexEngine; 4] = [
+        LatexEngine::Tectonic,
+        LatexEngine::Pdflatex,
+        LatexEngine::Xelatex,
+        LatexEngine::Lualatex,
+    ];
+
+    /// Name of the program, as accepted by `FromStr` and the `engine` tag.
+    pub fn program(self) -> &'static str {
+        match self {
+            LatexEngine::Tectonic => "tectonic",
+            LatexEngine::Pdflatex => "pdflatex",
+            LatexEngine::Xelatex => "xelatex",
+            LatexEngine::Lualatex => "lualatex",
+        }
+    }
+}
+
+impl FromStr for LatexEngine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+        LatexEngine::ALL
+            .into_iter()
+            .find(|engine| engine.program() == name)
+            .ok_or_else(|| {
+                format!(
+                    "unknown LaTeX engine '{name}' (expected tectonic, pdflatex, xelatex or lualatex)"
+                )
+            })
+    }
+}
+
+/// Options for the `standalone` document class each equation is compiled with.
+///
+/// Unset fields fall back to a more general setting: per-equation options
+/// override the global ones (see [`StandaloneOptions::or`]), and anything
+/// still unset uses standalone's own default, except the border which
+/// defaults to `1pt`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandaloneOptions {
+    /// Padding around the content, e.g. `2pt` or `1pt 2pt 1pt 2pt` (left bottom right top)
+    pub border: Option<String>,
+    /// Typeset in a `varwidth` box: `true`, `false` or a maximum width like `10cm`
+    pub varwidth: Option<String>,
+    /// Whether to crop the page to the content
+    pub crop: Option<bool>,
+}
+
+impl StandaloneOptions {
+    /// Border used when neither the equation nor the global options set one.
+    pub const DEFAULT_BORDER: &'static str = "1pt";
+
+    /// Fill the unset fields of `self` from `fallback`.
+    pub fn or(&self, fallback: &StandaloneOptions) -> StandaloneOptions {
+        StandaloneOptions {
+            border: self.border.clone().or_else(|| fallback.border.clone()),
+            varwidth: self.varwidth.clone().or_else(|| fallback.varwidth.clone()),
+            crop: self.crop.or(fallback.crop),
+        }
+    }
+
+    /// The option list for `\documentclass[...]{standalone}`.
+    pub fn class_options(&self) -> String {
+        let border = self
+            .border
+            .as_deref()
+            .unwrap_or(Self::DEFAULT_BORDER)
+            .trim();
+        let mut options = if border.contains(char::is_whitespace) {
+            vec![format!("border={{{border}}}")]
+        } else {
+            vec![format!("border={border}")]
+        };
+        match self.varwidth.as_deref().map(str::trim) {
+            None => {}
+            Some("true") => options.push("varwidth".into()),
+            Some(width) => options.push(format!("varwidth={width}")),
+        }
+        if let Some(crop) = self.crop {
+            options.push(format!("crop={crop}"));
+        }
+        options.join(",")
+    }
+}
+
+/// Units accepted by [`parse_display_width`], understood by both LaTeX and CSS.
+pub const WIDTH_UNITS: &[&str] = &["px", "pt", "cm", "mm", "in", "em"];
+
+/// How large an equation is shown where it is used, from `%%width:300px%%`
+/// and `%%scale:0.8%%` tags.
+///
+/// The hints are applied when rendering, so the artifact already has the
+/// size, and carried to `index.json` and the HTML gallery for embedding.
+///
+/// ```
+/// use equation_processor::model::DisplaySize;
+///
+/// let size = DisplaySize { width: None, scale: Some(0.8) };
+/// assert_eq!(size.wrap("$x$"), r"\scalebox{0.8}{$x$}");
+/// let size = DisplaySize { width: Some("300px".into()), scale: None };
+/// assert_eq!(size.wrap("$x$"), r"\resizebox{225bp}{!}{$x$}");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DisplaySize {
+    /// Width with its unit, e.g. `300px` or `8cm`; takes precedence over `scale`
+    pub width: Option<String>,
+    /// Factor the equation is scaled by, e.g. `0.8`
+    pub scale: Option<f64>,
+}
+
+impl DisplaySize {
+    /// Whether neither hint is set.
+    pub fn is_empty(&self) -> bool {
+        self.width.is_none() && self.scale.is_none()
+    }
+
+    /// `content` in a `\resizebox` to the width or a `\scalebox`, or as is
+    /// without hints. Both need the `graphicx` package.
+    pub fn wrap(&self, content: &str) -> String {
+        match (&self.width, self.scale) {
+            (Some(width), _) => format!("\\resizebox{{{}}}{{!}}{{{content}}}", tex_length(width)),
+            (None, Some(scale)) => format!("\\scalebox{{{scale}}}{{{content}}}"),
+            (None, None) => content.to_string(),
+        }
+    }
+}
+
+/// A width of [`parse_display_width`] as a TeX dimension. Pixels are taken
+/// at 96 per inch, as browsers do, so `300px` is `225bp`.
+fn tex_length(width: &str) -> String {
+    match width.strip_suffix("px").and_then(|n| n.parse::<f64>().ok()) {
+        Some(pixels) => format!("{}bp", pixels * 0.75),
+        None => width.to_string(),
+    }
+}
+
+/// Parse a display width such as `300px` or `8.5cm`: a positive number
+/// followed by one of [`WIDTH_UNITS`].
+pub fn parse_display_width(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    let invalid = || {
+        format!(
+            "invalid width '{s}': expected a number and one of {}, e.g. 300px",
+            WIDTH_UNITS.join(", ")
+        )
+    };
+    let unit = WIDTH_UNITS
+        .iter()
+        .find(|unit| s.ends_with(*unit))
+        .ok_or_else(invalid)?;
+    match s[..s.len() - unit.len()].trim().parse::<f64>() {
+        Ok(number) if number > 0.0 && number.is_finite() => Ok(format!("{number}{unit}")),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parse a display scale such as `0.8`: a positive number.
+pub fn parse_display_scale(s: &str) -> Result<f64, String> {
+    match s.trim().parse::<f64>() {
+        Ok(scale) if scale > 0.0 && scale.is_finite() => Ok(scale),
+        _ => Err(format!(
+            "invalid scale '{}': expected a positive number such as 0.8",
+            s.trim()
+        )),
+    }
+}
+
+/// An opaque RGB color, written `#RRGGBB`.
+///
+/// ```
+/// use equation_processor::model::Color;
+///
+/// let red: Color = "#ff0000".parse().unwrap();
+/// assert_eq!(red.to_string(), "#FF0000");
+/// assert!("#GGHHII".parse::<Color>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Color {
+    /// Red component
+    pub r: u8,
+    /// Green component
+    pub g: u8,
+    /// Blue component
+    pub b: u8,
+}
+
+impl Color {
+    /// The six hex digits without `#`, as used by xcolor's `HTML` model.
+    pub fn hex(self) -> String {
+        format!("{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.hex())
+    }
+}
+
+impl FromStr for Color {
+    type Err = String;
+
+    /// Parse `#RRGGBB` (the `#` is optional).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.trim();
+        let digits = digits.strip_prefix('#').unwrap_or(digits);
+        if digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).unwrap();
+            return Ok(Color {
+                r: channel(0),
+                g: channel(2),
+                b: channel(4),
+            });
+        }
+        Err(format!(
+            "invalid color '{}': expected six hex digits like #1A2B3C",
+            s.trim()
+        ))
+    }
+}
+
+/// What is behind an equation: nothing, or a solid color filling the page.
+///
+/// ```
+/// use equation_processor::model::Background;
+///
+/// let dark: Background = "#1E1E1E".parse().unwrap();
+/// assert_eq!(dark.to_string(), "#1E1E1E");
+/// assert_eq!("transparent".parse(), Ok(Background::Transparent));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Background {
+    /// No fill; SVG and PNG output keep a transparent background
+    #[default]
+    Transparent,
+    /// A solid fill, set with xcolor's `\pagecolor`
+    Color(Color),
+}
+
+impl Background {
+    /// The LaTeX setting the page color, empty when transparent.
+    pub fn latex(self) -> String {
+        match self {
+            Background::Transparent => String::new(),
+            Background::Color(color) => format!("\\pagecolor[HTML]{{{}}}", color.hex()),
+        }
+    }
+}
+
+impl fmt::Display for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Background::Transparent => f.write_str("transparent"),
+            Background::Color(color) => color.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Background {
+    type Err = String;
+
+    /// Parse `transparent` (or `none`) or a `#RRGGBB` color.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "transparent" | "none" => Ok(Background::Transparent),
+            _ => s.parse().map(Background::Color).map_err(|_| {
+                format!(
+                    "invalid background '{}': expected transparent or a color like #1E1E1E",
+                    s.trim()
+                )
+            }),
+        }
+    }
+}
+
+/// Parse a `KEY=VALUE` environment variable assignment.
+///
+/// The value may be empty or contain further `=` signs; the key may not.
+pub fn parse_env_var(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!(
+            "invalid environment variable '{}': expected KEY=VALUE",
+            s.trim()
+        )),
+    }
+}
+
+/// A mathematical equation entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Equation {
+    /// Whether to render this equation
+    pub active: bool,
+    /// Filename-safe name for output files
+    pub name: String,
+    /// LaTeX body of the equation
+    pub body: String,
+    /// Extra files (TikZ snippets, graphics) made available to the compile
+    pub assets: Vec<PathBuf>,
+    /// How the body is wrapped for LaTeX
+    pub kind: EquationKind,
+    /// TikZ libraries loaded via `\usetikzlibrary` for [`EquationKind::TikZ`]
+    pub tikz_libraries: Vec<String>,
+    /// Free-form labels for organizing equations
+    pub tags: Vec<String>,
+    /// Heading of the document section the equation appeared in, if any
+    pub section: Option<String>,
+    /// Human context such as "valid for small angles only", carried to
+    /// tables, reports and SVG metadata
+    pub description: Option<String>,
+    /// Per-equation `standalone` class options, overriding the global ones
+    pub standalone: StandaloneOptions,
+    /// Font color (`#RRGGBB`) overriding the batch color
+    pub color: Option<String>,
+    /// Environment variables for this equation's tectonic and converter
+    /// processes, applied after the batch-wide ones
+    pub env: Vec<(String, String)>,
+    /// Engine this equation must be compiled with, overriding tectonic
+    pub engine: Option<LatexEngine>,
+    /// Size hints for where the equation is shown
+    #[serde(default)]
+    pub display: DisplaySize,
+    /// Extra preamble lines for this equation only, such as a
+    /// `\usepackage` it needs, added after the packages of its kind
+    #[serde(default)]
+    pub preamble: Option<String>,
+}
+
+impl Equation {
+    /// Construct new equation, sanitizing name
+    pub fn new(active: bool, name: &str, body: &str) -> Self {
+        let sanitized = Equation::sanitize_filename(name);
+        Equation {
+            active,
+            name: sanitized,
+            body: body.to_string(),
+            assets: Vec::new(),
+            kind: EquationKind::Math,
+            tikz_libraries: Vec::new(),
+            tags: Vec::new(),
+            section: None,
+            description: None,
+            standalone: StandaloneOptions::default(),
+            color: None,
+            env: Vec::new(),
+            engine: None,
+            display: DisplaySize::default(),
+            preamble: None,
+        }
+    }
+
+    /// Add `prefix` and `suffix` around the name, sanitized like the name itself.
+    pub fn add_name_affixes(&mut self, prefix: &str, suffix: &str) {
+        if prefix.is_empty() && suffix.is_empty() {
+            return;
+        }
+        self.name = Equation::sanitize_filename(&format!("{prefix}{}{suffix}", self.name));
+    }
+
+    /// Replace invalid characters with underscores
+    pub fn sanitize_filename(name: &str) -> String {
+        let re = Regex::new(r"[^A-Za-z0-9_.]").unwrap();
+        let mut s = re.replace_all(name, "_").into_owned();
+        if s.is_empty() {
+            s = "default_equation".into();
+        }
+        s
+    }
+}
@@ -0,0 +1,263 @@
+//! Checking that the colors of a batch stay distinguishable for readers
+//! with color-vision deficiencies.
+//!
+//! Each pair of colors is compared as seen with typical vision and with
+//! simulated protanopia, deuteranopia and tritanopia (Machado et al., 2009).
+//! Pairs closer than [`MIN_DELTA_E`] in CIELAB are reported, with a
+//! replacement from the Okabe–Ito palette ([`SAFE_PALETTE`]) where one fits.
+//!
+//! ```
+//! use equation_processor::model::Color;
+//! use equation_processor::palette::{check_palette, ColorVision};
+//!
+//! let red: Color = "#FF0000".parse().unwrap();
+//! let green: Color = "#00AA00".parse().unwrap();
+//! let conflicts = check_palette(&[red, green]);
+//! assert_eq!(conflicts[0].vision, ColorVision::Deuteranopia);
+//! assert!(conflicts[0].suggestion.is_some());
+//! ```
+
+use crate::model::{Color, Equation};
+use std::fmt;
+
+/// Smallest CIELAB distance (ΔE*76) at which two colors of thin strokes such
+/// as equation glyphs are still told apart reliably.
+pub const MIN_DELTA_E: f64 = 15.0;
+
+/// The Okabe–Ito palette, whose colors stay distinct with every common
+/// color-vision deficiency.
+pub const SAFE_PALETTE: [Color; 8] = [
+    rgb(0x00, 0x00, 0x00),
+    rgb(0xE6, 0x9F, 0x00),
+    rgb(0x56, 0xB4, 0xE9),
+    rgb(0x00, 0x9E, 0x73),
+    rgb(0xF0, 0xE4, 0x42),
+    rgb(0x00, 0x72, 0xB2),
+    rgb(0xD5, 0x5E, 0x00),
+    rgb(0xCC, 0x79, 0xA7),
+];
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color { r, g, b }
+}
+
+/// A kind of color vision a palette is checked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorVision {
+    /// Typical trichromatic vision
+    Typical,
+    /// No long-wavelength (red) cones
+    Protanopia,
+    /// No medium-wavelength (green) cones
+    Deuteranopia,
+    /// No short-wavelength (blue) cones
+    Tritanopia,
+}
+
+impl ColorVision {
+    /// Every kind of vision, typical first.
+    pub const ALL: [ColorVision; 4] = [
+        ColorVision::Typical,
+        ColorVision::Protanopia,
+        ColorVision::Deuteranopia,
+        ColorVision::Tritanopia,
+    ];
+
+    /// Lowercase name, as used in messages.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ColorVision::Typical => "typical vision",
+            ColorVision::Protanopia => "protanopia",
+            ColorVision::Deuteranopia => "deuteranopia",
+            ColorVision::Tritanopia => "tritanopia",
+        }
+    }
+
+    /// How `color` looks with this kind of vision.
+    pub fn simulate(self, color: Color) -> Color {
+        let matrix = match self {
+            ColorVision::Typical => return color,
+            ColorVision::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorVision::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorVision::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        };
+        let linear = to_linear(color);
+        let [r, g, b] = matrix.map(|row| {
+            let value: f64 = row.iter().zip(linear).map(|(m, c)| m * c).sum();
+            to_srgb(value)
+        });
+        rgb(r, g, b)
+    }
+}
+
+impl fmt::Display for ColorVision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The channels of `color` in linear RGB, 0 to 1.
+fn to_linear(color: Color) -> [f64; 3] {
+    [color.r, color.g, color.b].map(|channel| {
+        let c = f64::from(channel) / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    })
+}
+
+/// A linear RGB channel as an sRGB byte, clamped to the gamut.
+fn to_srgb(linear: f64) -> u8 {
+    let c = linear.clamp(0.0, 1.0);
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+/// `color` in CIELAB under the D65 white point.
+fn to_lab(color: Color) -> [f64; 3] {
+    let [r, g, b] = to_linear(color);
+    let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+    let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+    let z = (0.0193339 * r + 0.1191920 * g + 0.9503041 * b) / 1.08883;
+    let f = |t: f64| {
+        const DELTA: f64 = 6.0 / 29.0;
+        if t > DELTA.powi(3) {
+            t.cbrt()
+        } else {
+            t / (3.0 * DELTA * DELTA) + 4.0 / 29.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// Perceptual distance between `a` and `b` (CIE76 ΔE); around 2.3 is just
+/// noticeable side by side.
+pub fn delta_e(a: Color, b: Color) -> f64 {
+    let (a, b) = (to_lab(a), to_lab(b));
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// The smallest distance between `a` and `b` over every [`ColorVision`],
+/// and the vision it occurs with.
+fn closest_vision(a: Color, b: Color) -> (ColorVision, f64) {
+    ColorVision::ALL
+        .into_iter()
+        .map(|vision| (vision, delta_e(vision.simulate(a), vision.simulate(b))))
+        .min_by(|x, y| x.1.total_cmp(&y.1))
+        .unwrap()
+}
+
+/// Two colors of a palette that look alike with some kind of vision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteConflict {
+    /// The color used first
+    pub first: Color,
+    /// The color that is hard to tell from `first`
+    pub second: Color,
+    /// The vision with which they are closest
+    pub vision: ColorVision,
+    /// Their distance with that vision
+    pub delta_e: f64,
+    /// A [`SAFE_PALETTE`] color that could replace `second`, if one is
+    /// distinct from all other colors
+    pub suggestion: Option<Color>,
+}
+
+impl fmt::Display for PaletteConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "colors {} and {} are hard to tell apart with {} (ΔE {:.1})",
+            self.first, self.second, self.vision, self.delta_e
+        )?;
+        if let Some(suggestion) = self.suggestion {
+            write!(f, "; try {suggestion} instead of {}", self.second)?;
+        }
+        Ok(())
+    }
+}
+
+/// Pairs of `colors` closer than [`MIN_DELTA_E`] with typical vision or a
+/// color-vision deficiency, in palette order. Duplicate colors are ignored.
+pub fn check_palette(colors: &[Color]) -> Vec<PaletteConflict> {
+    let mut distinct: Vec<Color> = Vec::new();
+    for &color in colors {
+        if !distinct.contains(&color) {
+            distinct.push(color);
+        }
+    }
+    let mut conflicts = Vec::new();
+    for (i, &first) in distinct.iter().enumerate() {
+        for &second in &distinct[i + 1..] {
+            let (vision, delta_e) = closest_vision(first, second);
+            if delta_e < MIN_DELTA_E {
+                let others: Vec<Color> =
+                    distinct.iter().copied().filter(|&c| c != second).collect();
+                conflicts.push(PaletteConflict {
+                    first,
+                    second,
+                    vision,
+                    delta_e,
+                    suggestion: suggest_replacement(second, &others),
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+/// The [`SAFE_PALETTE`] color closest to `color` that is distinct from all
+/// of `others` with every kind of vision.
+pub fn suggest_replacement(color: Color, others: &[Color]) -> Option<Color> {
+    SAFE_PALETTE
+        .into_iter()
+        .filter(|candidate| {
+            others
+                .iter()
+                .all(|&other| closest_vision(*candidate, other).1 >= MIN_DELTA_E)
+        })
+        .min_by(|a, b| delta_e(*a, color).total_cmp(&delta_e(*b, color)))
+}
+
+/// The distinct colors the active `equations` are rendered in, in order of
+/// first use; equations without a color of their own use `batch_color`.
+/// Colors that do not parse are left out.
+pub fn batch_palette(equations: &[Equation], batch_color: Color) -> Vec<Color> {
+    let mut palette = Vec::new();
+    for eq in equations.iter().filter(|e| e.active) {
+        let color = match &eq.color {
+            Some(color) => match color.parse() {
+                Ok(color) => color,
+                Err(_) => continue,
+            },
+            None => batch_color,
+        };
+        if !palette.contains(&color) {
+            palette.push(color);
+        }
+    }
+    palette
+}
@@ -0,0 +1,1673 @@
+//! Parsing equation files (CSV & Markdown) into [`Equation`]s.
+//!
+//! ```
+//! use equation_processor::parser::parse_markdown;
+//!
+//! let equations = parse_markdown("%%yes%%\n$$\nE = mc^2\n$$\n%%energy%%\n");
+//! assert_eq!(equations[0].name, "energy");
+//! assert!(equations[0].active);
+//! ```
+
+use crate::error::EquationError;
+use crate::model::{
+    parse_display_scale, parse_display_width, parse_env_var, Color, Equation, EquationKind,
+    Filetype,
+};
+use crate::render::OutputFormat;
+use crate::tagging::write_atomic;
+use regex::Regex;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Read file to string
+pub fn read_file(path: &PathBuf) -> io::Result<String> {
+    let mut f = File::open(path)?;
+    let mut s = String::new();
+    f.read_to_string(&mut s)?;
+    Ok(s)
+}
+
+/// Read the text file at `path`, reporting invalid UTF-8 as a
+/// [`EquationError::ParseError`] on the line where it occurs.
+fn read_text(path: &Path) -> Result<String, EquationError> {
+    String::from_utf8(fs::read(path)?).map_err(|e| {
+        let valid = &e.as_bytes()[..e.utf8_error().valid_up_to()];
+        EquationError::ParseError {
+            path: path.to_path_buf(),
+            line: Some(valid.iter().filter(|&&b| b == b'\n').count() + 1),
+            reason: "invalid UTF-8; save the file with UTF-8 encoding".into(),
+        }
+    })
+}
+
+/// Options controlling how input files are parsed.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// CSV field delimiter; detected from the header row when `None`
+    pub csv_delimiter: Option<char>,
+    /// Which CSV column holds what; taken from the header row when `None`,
+    /// see [`CsvColumns::resolve`]
+    pub csv_columns: Option<CsvColumns>,
+    /// Take a Markdown block's active flag from a task item (`- [x]` active,
+    /// `- [ ]` inactive) on the line before it; see [`apply_task_checkboxes`]
+    pub task_checkboxes: bool,
+    /// What to do with a Markdown tag that looks like a marker such as
+    /// `%%TODO%%` where a name is expected; see [`is_ambiguous_tag`]
+    pub ambiguous_tags: AmbiguousTagPolicy,
+}
+
+/// What to do with a tag in a name's place that is neither an activation tag
+/// nor a plausible name, such as `%%TODO%%`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AmbiguousTagPolicy {
+    /// Ask which of the other policies to use; the interactive CLI does,
+    /// everything else treats it like `Ignore`
+    Ask,
+    /// Use the tag as the name, like any other tag
+    Name,
+    /// Drop the tag, so the equation is unnamed
+    #[default]
+    Ignore,
+    /// Drop the tag and leave the equation inactive
+    Skip,
+}
+
+impl FromStr for AmbiguousTagPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "ask" => Ok(AmbiguousTagPolicy::Ask),
+            "name" => Ok(AmbiguousTagPolicy::Name),
+            "ignore" => Ok(AmbiguousTagPolicy::Ignore),
+            "skip" => Ok(AmbiguousTagPolicy::Skip),
+            other => Err(format!(
+                "unknown policy '{other}' (expected ask, name, ignore or skip)"
+            )),
+        }
+    }
+}
+
+impl AmbiguousTagPolicy {
+    /// What happens to an ambiguous tag, completing "the tag is …".
+    pub fn outcome(self) -> &'static str {
+        match self {
+            AmbiguousTagPolicy::Name => "used as the name",
+            AmbiguousTagPolicy::Ask | AmbiguousTagPolicy::Ignore => "ignored",
+            AmbiguousTagPolicy::Skip => "ignored and the equation skipped",
+        }
+    }
+}
+
+/// Tags that mark unfinished work rather than name an equation.
+const MARKER_TAGS: &[&str] = &["todo", "fixme", "xxx", "tbd", "tbc", "wip", "tk"];
+
+/// Whether a tag in a name's place is likely not meant as a name: a marker
+/// such as `TODO` or `FIXME`, an activation word that does not introduce a
+/// block (`yes`, `No`, `y`, `true`), or text without letters or digits.
+///
+/// ```
+/// use equation_processor::parser::is_ambiguous_tag;
+///
+/// assert!(is_ambiguous_tag("TODO"));
+/// assert!(is_ambiguous_tag("Yes"));
+/// assert!(is_ambiguous_tag("???"));
+/// assert!(!is_ambiguous_tag("energy"));
+/// ```
+pub fn is_ambiguous_tag(tag: &str) -> bool {
+    let tag = tag.trim();
+    let lower = tag.to_lowercase();
+    MARKER_TAGS.contains(&lower.as_str())
+        || matches!(lower.as_str(), "yes" | "no" | "y" | "n" | "true" | "false")
+        || !tag.chars().any(char::is_alphanumeric)
+}
+
+/// Ambiguous tags (see [`is_ambiguous_tag`]) in a name's place in Markdown
+/// `content`, as 1-based line and tag text, whatever the policy.
+pub fn ambiguous_tags(content: &str) -> Vec<(usize, String)> {
+    parse_markdown_with_spans(content)
+        .into_iter()
+        .filter_map(|s| {
+            let tag = s.ambiguous_tag?;
+            let line = content[..s.span.start].matches('\n').count() + 1;
+            Some((line, tag))
+        })
+        .collect()
+}
+
+/// Parse CSV into equations
+pub fn read_csv_file(path: &Path) -> Result<Vec<Equation>, EquationError> {
+    read_csv_file_with(path, &ParseOptions::default())
+}
+
+/// Read the equations of the input at `path` by its [`Filetype`]: a CSV,
+/// Markdown, JSON, YAML, TOML, LaTeX or HTML file, a Jupyter notebook, a
+/// directory of `.tex` fragments or a file in a
+/// [registered format](register_input_format).
+///
+/// Files that are not valid UTF-8 and errors of registered parsers are
+/// reported as [`EquationError::ParseError`].
+pub fn read_input(path: &Path, options: &ParseOptions) -> Result<Vec<Equation>, EquationError> {
+    match detect_file_type(path) {
+        Filetype::Csv => read_csv_file_with(path, options),
+        Filetype::Markdown => Ok(parse_markdown_with(&read_text(path)?, options)),
+        Filetype::TexDirectory => read_tex_directory(path),
+        Filetype::Latex => read_latex_file(path),
+        Filetype::Notebook => read_notebook_file(path, options),
+        Filetype::Html => read_html_file(path),
+        Filetype::Json => read_json_file(path),
+        Filetype::Yaml => read_yaml_file(path),
+        Filetype::Toml => read_toml_file(path),
+        Filetype::Registered(extension) => {
+            let parse = input_parser(&extension).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("input format '{extension}' is no longer registered"),
+                )
+            })?;
+            parse(&read_text(path)?, options).map_err(|reason| EquationError::ParseError {
+                path: path.to_path_buf(),
+                line: None,
+                reason,
+            })
+        }
+        Filetype::Unknown => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unsupported file type: {}", path.display()),
+        )
+        .into()),
+    }
+}
+
+/// Parse a JSON file into equations: an array of objects with the LaTeX in
+/// `body`, an optional `name` and `active` (default `true`), and any
+/// [`apply_tag`] key as further options, e.g.
+///
+/// ```json
+/// [
+///   { "name": "energy", "body": "E = mc^2", "tags": ["physics"], "scale": 0.8 },
+///   { "active": false, "name": "draft", "body": "x^2", "description": "unfinished" }
+/// ]
+/// ```
+///
+/// Option values may be strings, numbers, booleans (for flags such as
+/// `chem` and for `crop`) or arrays of strings (for lists such as `tags`);
+/// `description` is accepted for `desc`. Syntax errors are reported with
+/// their line; unknown keys and invalid values with the equation's position.
+pub fn read_json_file(path: &Path) -> Result<Vec<Equation>, EquationError> {
+    let content = read_text(path)?;
+    let records = serde_json::from_str(content.trim_start_matches('\u{feff}')).map_err(|e| {
+        let line = (e.line() > 0).then_some(e.line());
+        structured_error(path, line, e.column(), &e.to_string())
+    })?;
+    records_to_equations(path, records)
+}
+
+/// Parse a YAML file into equations: a list of mappings with the same keys
+/// as [`read_json_file`] accepts, e.g.
+///
+/// ```yaml
+/// - name: energy
+///   body: E = mc^2
+///   tags: [physics]
+/// - name: maxwell
+///   active: false
+///   body: |
+///     \nabla \cdot \mathbf{E} = \frac{\rho}{\varepsilon_0}
+/// ```
+///
+/// Block scalars (`|`) keep multi-line LaTeX readable without escaping.
+pub fn read_yaml_file(path: &Path) -> Result<Vec<Equation>, EquationError> {
+    let content = read_text(path)?;
+    let records = serde_yaml::from_str(content.trim_start_matches('\u{feff}')).map_err(|e| {
+        let location = e.location();
+        structured_error(
+            path,
+            location.as_ref().map(|l| l.line()),
+            location.map_or(0, |l| l.column()),
+            &e.to_string(),
+        )
+    })?;
+    records_to_equations(path, records)
+}
+
+/// Parse a TOML file into equations: `[[equation]]` tables with the same
+/// keys as [`read_json_file`] accepts, after an optional `[defaults]` table
+/// describing the whole job, e.g.
+///
+/// ```toml
+/// [defaults]
+/// color = "#1F4E79"
+/// scale = 0.8
+/// formats = ["svg", "pdf"]
+///
+/// [[equation]]
+/// name = "energy"
+/// body = "E = mc^2"
+///
+/// [[equation]]
+/// name = "maxwell"
+/// color = "#000000"
+/// body = '''
+/// \nabla \cdot \mathbf{E} = \frac{\rho}{\varepsilon_0}
+/// '''
+/// ```
+///
+/// The default `color` and `scale` apply to every equation without its own,
+/// as if each had the tag. `formats` is not a property of the equations; see
+/// [`read_toml_defaults`].
+pub fn read_toml_file(path: &Path) -> Result<Vec<Equation>, EquationError> {
+    parse_toml(path).map(|(_, equations)| equations)
+}
+
+/// The `[defaults]` table of the TOML file at `path` (see [`read_toml_file`]),
+/// checked like the rest of the file.
+pub fn read_toml_defaults(path: &Path) -> Result<TomlDefaults, EquationError> {
+    parse_toml(path).map(|(defaults, _)| defaults)
+}
+
+/// The `[defaults]` table of a TOML input file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TomlDefaults {
+    /// Font color (`#RRGGBB`) of the equations without a color of their own
+    pub color: Option<String>,
+    /// Scale of the equations without a scale of their own
+    pub scale: Option<f64>,
+    /// Artifacts the job produces, like `--formats`; empty if not given
+    pub formats: Vec<OutputFormat>,
+}
+
+/// A TOML input file as written.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlInput {
+    #[serde(default)]
+    defaults: TomlDefaultsRecord,
+    #[serde(default)]
+    equation: Vec<EquationRecord>,
+}
+
+/// The `[defaults]` table as written.
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlDefaultsRecord {
+    color: Option<String>,
+    scale: Option<f64>,
+    #[serde(default)]
+    formats: Vec<String>,
+}
+
+/// The defaults and equations of the TOML file at `path`, with the defaults
+/// applied to the equations.
+fn parse_toml(path: &Path) -> Result<(TomlDefaults, Vec<Equation>), EquationError> {
+    let content = read_text(path)?;
+    let content = content.trim_start_matches('\u{feff}');
+    let input: TomlInput = toml::from_str(content).map_err(|e| {
+        let (line, column) = match e.span() {
+            Some(span) => {
+                let before = &content[..span.start];
+                let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+                (
+                    Some(before.matches('\n').count() + 1),
+                    before[line_start..].chars().count() + 1,
+                )
+            }
+            None => (None, 0),
+        };
+        structured_error(path, line, column, e.message().trim_end())
+    })?;
+    let invalid = |reason: String| EquationError::ParseError {
+        path: path.to_path_buf(),
+        line: None,
+        reason: format!("defaults: {reason}"),
+    };
+    let formats = input
+        .defaults
+        .formats
+        .iter()
+        .map(|format| format.parse())
+        .collect::<Result<Vec<OutputFormat>, _>>()
+        .map_err(invalid)?;
+    let color = input
+        .defaults
+        .color
+        .map(|color| color.parse::<Color>().map(|color| color.to_string()))
+        .transpose()
+        .map_err(invalid)?;
+    let scale = input
+        .defaults
+        .scale
+        .map(|scale| parse_display_scale(&scale.to_string()))
+        .transpose()
+        .map_err(invalid)?;
+    let mut equations = records_to_equations(path, input.equation)?;
+    for eq in &mut equations {
+        if eq.color.is_none() {
+            eq.color.clone_from(&color);
+        }
+        if eq.display.scale.is_none() {
+            eq.display.scale = scale;
+        }
+    }
+    let defaults = TomlDefaults {
+        color,
+        scale,
+        formats,
+    };
+    Ok((defaults, equations))
+}
+
+/// A syntax or schema error of a structured input file, with the location
+/// the parser appends to `message` moved into the line and a column note.
+fn structured_error(
+    path: &Path,
+    line: Option<usize>,
+    column: usize,
+    message: &str,
+) -> EquationError {
+    let reason = message.split(" at line ").next().unwrap_or_default();
+    EquationError::ParseError {
+        path: path.to_path_buf(),
+        line,
+        reason: match line {
+            Some(_) => format!("{reason} (column {column})"),
+            None => reason.to_string(),
+        },
+    }
+}
+
+/// The equations of the `records` read from `path`, reporting an invalid
+/// option with the equation's position. Missing and repeated names are
+/// numbered like in [`parse_csv`].
+fn records_to_equations(
+    path: &Path,
+    records: Vec<EquationRecord>,
+) -> Result<Vec<Equation>, EquationError> {
+    let mut equations = records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| {
+            record
+                .into_equation()
+                .map_err(|reason| EquationError::ParseError {
+                    path: path.to_path_buf(),
+                    line: None,
+                    reason: format!("equation {}: {reason}", i + 1),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    number_duplicate_names(&mut equations);
+    Ok(equations)
+}
+
+/// One equation of a structured input file such as JSON or YAML.
+#[derive(Deserialize)]
+struct EquationRecord {
+    #[serde(default = "active_by_default")]
+    active: bool,
+    #[serde(default)]
+    name: String,
+    body: String,
+    /// Further [`apply_tag`] options
+    #[serde(flatten)]
+    options: BTreeMap<String, OptionValue>,
+}
+
+fn active_by_default() -> bool {
+    true
+}
+
+/// The value of an option in a structured input file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OptionValue {
+    Flag(bool),
+    Number(f64),
+    Text(String),
+    List(Vec<String>),
+}
+
+impl EquationRecord {
+    /// The equation with its options applied, or what is wrong with them.
+    fn into_equation(self) -> Result<Equation, String> {
+        let mut eq = Equation::new(self.active, &self.name, self.body.trim());
+        for (key, value) in self.options {
+            let tag = if key == "description" { "desc" } else { &key };
+            if !TAG_KEYS.contains(&tag) && !FLAG_TAGS.contains(&tag) {
+                return Err(format!("unknown key '{key}'"));
+            }
+            let value = match value {
+                OptionValue::Flag(false) if FLAG_TAGS.contains(&tag) && tag != "varwidth" => {
+                    continue
+                }
+                OptionValue::Flag(true) if FLAG_TAGS.contains(&tag) => String::new(),
+                OptionValue::Flag(flag) => flag.to_string(),
+                OptionValue::Number(number) => number.to_string(),
+                OptionValue::Text(text) => text,
+                OptionValue::List(items) => items.join(";"),
+            };
+            if !apply_tag(&mut eq, tag, &value) {
+                return Err(format!("invalid {key} '{value}'"));
+            }
+        }
+        Ok(eq)
+    }
+}
+
+/// Which field of a CSV row holds what, as 0-based positions.
+///
+/// The default is the documented order `active,equation,name,assets,tags,
+/// description`. Other orders are read from the header row or given as a
+/// list of column names, e.g. `--columns name,active,equation`:
+///
+/// ```
+/// use equation_processor::parser::CsvColumns;
+///
+/// let columns: CsvColumns = "name,_,equation".parse().unwrap();
+/// assert_eq!((columns.name, columns.body, columns.active), (0, 2, None));
+/// assert!("name,formula,colour".parse::<CsvColumns>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvColumns {
+    /// `Yes`/`No` flag; every row is active without one
+    pub active: Option<usize>,
+    /// The LaTeX body
+    pub body: usize,
+    /// The equation name
+    pub name: usize,
+    /// Asset files separated by `;`
+    pub assets: Option<usize>,
+    /// Tags separated by `;`
+    pub tags: Option<usize>,
+    /// The description
+    pub description: Option<usize>,
+}
+
+impl Default for CsvColumns {
+    fn default() -> Self {
+        CsvColumns {
+            active: Some(0),
+            body: 1,
+            name: 2,
+            assets: Some(3),
+            tags: Some(4),
+            description: Some(5),
+        }
+    }
+}
+
+impl CsvColumns {
+    /// The columns a header row names, matched case-insensitively with
+    /// common aliases (`body`, `latex` or `formula` for the equation,
+    /// `label` for the name, `desc` for the description). Other header
+    /// fields are ignored, and columns it doesn't name keep their default
+    /// position if that is past its end, so a header of just
+    /// `active,equation,name` means the default order. `None` unless it
+    /// names an equation and a name column.
+    pub fn from_header(fields: &[String]) -> Option<Self> {
+        Self::from_names(fields.iter().map(String::as_str), false).ok()
+    }
+
+    /// The columns of a CSV whose header row is `header`: those given in
+    /// `options`, else those the header names, else the default order.
+    pub fn resolve(header: &[String], options: &ParseOptions) -> Self {
+        options
+            .csv_columns
+            .or_else(|| Self::from_header(header))
+            .unwrap_or_default()
+    }
+
+    /// How many fields a row needs for its flag, equation and name.
+    pub fn min_fields(&self) -> usize {
+        self.active.unwrap_or(0).max(self.body).max(self.name) + 1
+    }
+
+    /// The trimmed field of `row` in `column`, if the row has one.
+    pub fn field(row: &[String], column: Option<usize>) -> Option<&str> {
+        column.and_then(|i| row.get(i)).map(|field| field.trim())
+    }
+
+    /// Columns from a list of column names; `_` or an empty name skips a
+    /// column. Unknown names and duplicates are errors when `strict`, and
+    /// otherwise skipped.
+    fn from_names<'a>(names: impl Iterator<Item = &'a str>, strict: bool) -> Result<Self, String> {
+        let mut slots: [Option<usize>; 6] = [None; 6];
+        let mut count = 0;
+        for (i, name) in names.enumerate() {
+            count = i + 1;
+            let name = name.trim().to_lowercase();
+            let slot = match name.as_str() {
+                "" | "_" => continue,
+                "active" | "enabled" => 0,
+                "equation" | "body" | "latex" | "formula" => 1,
+                "name" | "label" => 2,
+                "assets" => 3,
+                "tags" => 4,
+                "description" | "desc" => 5,
+                _ if strict => {
+                    return Err(format!(
+                        "unknown CSV column '{name}' (expected active, equation, name, \
+                         assets, tags, description or _ to skip one)"
+                    ))
+                }
+                _ => continue,
+            };
+            match slots[slot] {
+                Some(_) if strict => {
+                    return Err(format!("CSV column '{name}' is given twice"));
+                }
+                Some(_) => {}
+                None => slots[slot] = Some(i),
+            }
+        }
+        if !strict {
+            for (default, slot) in slots.iter_mut().enumerate().skip(count) {
+                slot.get_or_insert(default);
+            }
+        }
+        let [active, body, name, assets, tags, description] = slots;
+        match (body, name) {
+            (Some(body), Some(name)) => Ok(CsvColumns {
+                active,
+                body,
+                name,
+                assets,
+                tags,
+                description,
+            }),
+            _ => Err("the CSV columns must include equation and name".into()),
+        }
+    }
+}
+
+impl FromStr for CsvColumns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_names(s.split(','), true)
+    }
+}
+
+/// Parse CSV into equations using `options`
+pub fn read_csv_file_with(
+    path: &Path,
+    options: &ParseOptions,
+) -> Result<Vec<Equation>, EquationError> {
+    Ok(parse_csv_with(&read_text(path)?, options))
+}
+
+/// Parse CSV content (header row first) into equations
+///
+/// An optional fourth column lists asset files separated by `;`, an
+/// optional fifth column tags separated by `;`, and an optional sixth
+/// column holds a description.
+pub fn parse_csv(content: &str) -> Vec<Equation> {
+    parse_csv_with(content, &ParseOptions::default())
+}
+
+/// Parse CSV content (header row first) into equations using `options`
+///
+/// The columns are those of `options.csv_columns`, else those the header
+/// row names, in any order, else the default order (see [`CsvColumns`]).
+/// Tolerates what spreadsheet exports produce: a UTF-8 byte order mark,
+/// CRLF or CR line endings, `;` or tab delimiters (as written by Excel in
+/// locales with decimal commas) and double-quoted fields with `""` escapes.
+pub fn parse_csv_with(content: &str, options: &ParseOptions) -> Vec<Equation> {
+    let content = content
+        .strip_prefix('\u{feff}')
+        .unwrap_or(content)
+        .replace("\r\n", "\n")
+        .replace('\r', "\n");
+    let mut lines = content.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let delimiter = options
+        .csv_delimiter
+        .unwrap_or_else(|| detect_csv_delimiter(header));
+    let columns = CsvColumns::resolve(&split_csv_line(header, delimiter), options);
+    let mut eqs = Vec::new();
+    for line in lines {
+        let parts = split_csv_line(line, delimiter);
+        if parts.len() >= columns.min_fields() {
+            let active = CsvColumns::field(&parts, columns.active)
+                .is_none_or(|flag| flag.eq_ignore_ascii_case("yes"));
+            let body = parts[columns.body].trim();
+            let mut eq = Equation::new(active, parts[columns.name].trim(), body);
+            if let Some(assets) = CsvColumns::field(&parts, columns.assets) {
+                apply_tag(&mut eq, "assets", assets);
+            }
+            if let Some(tags) = CsvColumns::field(&parts, columns.tags) {
+                apply_tag(&mut eq, "tags", tags);
+            }
+            if let Some(description) = CsvColumns::field(&parts, columns.description) {
+                apply_tag(&mut eq, "desc", description);
+            }
+            eqs.push(eq);
+        }
+    }
+    number_duplicate_names(&mut eqs);
+    eqs
+}
+
+/// Guess a CSV delimiter from the header row: the most frequent of `,`, `;`
+/// and tab outside quotes, preferring `,` on ties.
+pub fn detect_csv_delimiter(header: &str) -> char {
+    [',', ';', '\t']
+        .into_iter()
+        .rev()
+        .max_by_key(|&d| split_csv_line(header, d).len())
+        .unwrap_or(',')
+}
+
+/// Split one CSV line at `delimiter`, honoring double quotes.
+pub(crate) fn split_csv_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                quoted = true;
+            }
+            c if c == delimiter && !quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    fields
+}
+
+/// Join CSV fields, quoting those containing the delimiter or quotes.
+pub(crate) fn join_csv_fields(fields: &[String], delimiter: char) -> String {
+    fields
+        .iter()
+        .map(|f| {
+            if f.contains(delimiter) || f.contains('"') {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(&delimiter.to_string())
+}
+
+/// Format equations as CSV in the default column order (see
+/// [`CsvColumns`]), so [`parse_csv`] reads back their flag, body, name,
+/// assets, tags and description.
+///
+/// CSV rows are single lines: multi-line bodies and descriptions are
+/// joined with spaces, dropping LaTeX `%` comments that would otherwise
+/// swallow the lines after them. Annotations CSV has no column for, such
+/// as colors or the engine, are not written.
+///
+/// ```
+/// use equation_processor::parser::{format_csv, parse_csv, parse_markdown};
+///
+/// let equations = parse_markdown("$$\na, b\n$$\n%%pair%%\n%%tags:algebra%%\n");
+/// let csv = format_csv(&equations);
+/// assert_eq!(csv, "active,equation,name,assets,tags,description\nYes,\"a, b\",pair,,algebra,\n");
+/// assert_eq!(parse_csv(&csv)[0].body, equations[0].body);
+/// ```
+pub fn format_csv(equations: &[Equation]) -> String {
+    let mut csv = String::from("active,equation,name,assets,tags,description\n");
+    for eq in equations {
+        let assets: Vec<String> = eq
+            .assets
+            .iter()
+            .map(|asset| asset.display().to_string())
+            .collect();
+        let fields = [
+            if eq.active { "Yes" } else { "No" }.to_string(),
+            single_line(&eq.body),
+            eq.name.clone(),
+            assets.join(";"),
+            eq.tags.join(";"),
+            eq.description
+                .as_deref()
+                .map(single_line)
+                .unwrap_or_default(),
+        ];
+        csv.push_str(&join_csv_fields(&fields, ','));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Write equations, e.g. parsed from Markdown or edited in the GUI, to a
+/// CSV file at `path` that [`read_csv_file`] reads back; see
+/// [`format_csv`]. The file is replaced atomically.
+pub fn write_csv_file(equations: &[Equation], path: &Path) -> io::Result<()> {
+    write_atomic(path, &format_csv(equations))
+}
+
+/// `text` on one line: its lines trimmed, stripped of `%` comments and
+/// joined with spaces.
+fn single_line(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            let comment = line
+                .char_indices()
+                .find(|&(i, c)| c == '%' && !line[..i].ends_with('\\'))
+                .map_or(line.len(), |(i, _)| i);
+            line[..comment].trim()
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Parser for an input format added at runtime: takes the file's content and
+/// returns its equations or a message saying what is wrong with it.
+pub type InputParser =
+    Arc<dyn Fn(&str, &ParseOptions) -> Result<Vec<Equation>, String> + Send + Sync>;
+
+/// Formats added with [`register_input_format`], by lowercase extension.
+static INPUT_FORMATS: RwLock<Vec<(String, InputParser)>> = RwLock::new(Vec::new());
+
+/// Read files with `extension` (without the dot, case-insensitive) using
+/// `parse`, so [`detect_file_type`] and [`read_input`], and with them the CLI,
+/// TUI and GUI, accept a format this crate does not know.
+///
+/// A registered format takes precedence over a built-in one with the same
+/// extension, and registering an extension again replaces its parser.
+///
+/// ```
+/// use equation_processor::parser::{read_input, register_input_format, ParseOptions};
+/// use equation_processor::Equation;
+///
+/// // One `name: body` pair per line.
+/// register_input_format("eqs", |content, _options| {
+///     content
+///         .lines()
+///         .filter(|line| !line.trim().is_empty())
+///         .map(|line| match line.split_once(':') {
+///             Some((name, body)) => Ok(Equation::new(true, name.trim(), body.trim())),
+///             None => Err(format!("expected 'name: body', got '{line}'")),
+///         })
+///         .collect()
+/// });
+///
+/// let path = std::env::temp_dir().join("register_input_format_doc.eqs");
+/// std::fs::write(&path, "energy: E = mc^2\n")?;
+/// let equations = read_input(&path, &ParseOptions::default())?;
+/// assert_eq!(equations[0].name, "energy");
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn register_input_format<F>(extension: &str, parse: F)
+where
+    F: Fn(&str, &ParseOptions) -> Result<Vec<Equation>, String> + Send + Sync + 'static,
+{
+    let extension = extension.trim_start_matches('.').to_lowercase();
+    let mut formats = INPUT_FORMATS.write().unwrap_or_else(|e| e.into_inner());
+    formats.retain(|(registered, _)| *registered != extension);
+    formats.push((extension, Arc::new(parse)));
+}
+
+/// The parser registered for `extension` (lowercase, without the dot).
+pub fn input_parser(extension: &str) -> Option<InputParser> {
+    let formats = INPUT_FORMATS.read().unwrap_or_else(|e| e.into_inner());
+    formats
+        .iter()
+        .find(|(registered, _)| registered == extension)
+        .map(|(_, parse)| Arc::clone(parse))
+}
+
+/// Determine file type by extension; an existing directory is read as
+/// `.tex` fragments. [Registered formats](register_input_format) are
+/// consulted before the built-in ones.
+pub fn detect_file_type(path: &Path) -> Filetype {
+    if path.is_dir() {
+        return Filetype::TexDirectory;
+    }
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        let extension = extension.to_lowercase();
+        if input_parser(&extension).is_some() {
+            return Filetype::Registered(extension);
+        }
+    }
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => Filetype::Csv,
+        Some("md") | Some("markdown") => Filetype::Markdown,
+        Some("json") => Filetype::Json,
+        Some("yml") | Some("yaml") => Filetype::Yaml,
+        Some("toml") => Filetype::Toml,
+        Some("tex") => Filetype::Latex,
+        Some("ipynb") => Filetype::Notebook,
+        Some("html" | "htm") => Filetype::Html,
+        _ => Filetype::Unknown,
+    }
+}
+
+/// Parse the equations out of a LaTeX document: every `equation`, `align`
+/// and `gather` environment (also starred) and every `\[...\]` block, in
+/// order, as active equations.
+///
+/// An equation is named after its first `\label`, without an `eq:` prefix,
+/// or `default_equation` without one; repeated names are numbered like in
+/// Markdown input. The labels are removed from the body, which otherwise
+/// keeps its environment. The closest `\section` or `\subsection` title above it is
+/// recorded as its section. Comments are ignored.
+///
+/// ```
+/// use equation_processor::parser::parse_latex;
+///
+/// let equations = parse_latex(
+///     "\\section{Mechanics}\n\
+///      \\begin{equation}\\label{eq:energy} E = mc^2 \\end{equation}\n\
+///      % \\[ x^2 \\]\n\
+///      \\[ F = ma \\]\n",
+/// );
+/// assert_eq!(equations.len(), 2);
+/// assert_eq!(equations[0].name, "energy");
+/// assert_eq!(equations[0].body, "\\begin{equation} E = mc^2 \\end{equation}");
+/// assert_eq!(equations[1].section.as_deref(), Some("Mechanics"));
+/// ```
+pub fn parse_latex(content: &str) -> Vec<Equation> {
+    let content = strip_latex_comments(content.trim_start_matches('\u{feff}'));
+    let heading = Regex::new(r"\\(?:sub)*section\*?\s*\{([^{}]*)\}").unwrap();
+    let label = Regex::new(r"\\label\s*\{([^{}]*)\}").unwrap();
+    let headings: Vec<(usize, &str)> = heading
+        .captures_iter(&content)
+        .map(|c| (c.get(0).unwrap().start(), c.get(1).unwrap().as_str().trim()))
+        .collect();
+    let mut eqs = Vec::new();
+    let mut pos = 0;
+    while let Some(block) = next_latex_block(&content, pos) {
+        let text = &content[block.clone()];
+        let name = label
+            .captures(text)
+            .map_or("", |c| c.get(1).unwrap().as_str());
+        let name = name.trim().strip_prefix("eq:").unwrap_or(name.trim());
+        let body = label.replace_all(text, "");
+        let mut eq = Equation::new(true, name, body.trim());
+        eq.section = headings
+            .iter()
+            .take_while(|(at, _)| *at < block.start)
+            .last()
+            .map(|(_, title)| title.to_string());
+        eqs.push(eq);
+        pos = block.end;
+    }
+    number_duplicate_names(&mut eqs);
+    eqs
+}
+
+/// `content` without LaTeX comments: everything from an unescaped `%` to the
+/// end of its line.
+fn strip_latex_comments(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        let mut escaped = false;
+        let mut end = line.trim_end_matches(['\r', '\n']).len();
+        for (i, c) in line.char_indices() {
+            match c {
+                '%' if !escaped => {
+                    end = i;
+                    break;
+                }
+                '\\' => escaped = !escaped,
+                _ => escaped = false,
+            }
+        }
+        stripped.push_str(&line[..end]);
+        if line.ends_with('\n') {
+            stripped.push('\n');
+        }
+    }
+    stripped
+}
+
+/// Byte range of the first equation block [`parse_latex`] extracts at or
+/// after `from`, delimiters included; `None` if there is none or it is not
+/// closed.
+fn next_latex_block(content: &str, from: usize) -> Option<Range<usize>> {
+    static BEGIN: OnceLock<Regex> = OnceLock::new();
+    let begin =
+        BEGIN.get_or_init(|| Regex::new(r"\\begin\s*\{((?:equation|align|gather)\*?)\}").unwrap());
+    let rest = &content[from..];
+    // `\\[2pt]` is a line break with extra space, not display math.
+    let display = rest
+        .match_indices("\\[")
+        .find(|(i, _)| !rest[..*i].ends_with('\\'))
+        .map(|(i, _)| i);
+    let environment = begin.captures(rest);
+    let (start, close) = match (display, &environment) {
+        (Some(i), Some(c)) if c.get(0).unwrap().start() < i => {
+            (c.get(0).unwrap().start(), format!("\\end{{{}}}", &c[1]))
+        }
+        (Some(i), _) => (i, String::from("\\]")),
+        (None, Some(c)) => (c.get(0).unwrap().start(), format!("\\end{{{}}}", &c[1])),
+        (None, None) => return None,
+    };
+    let end = start + rest[start..].find(&close)? + close.len();
+    Some(from + start..from + end)
+}
+
+/// Read a LaTeX document into equations (see [`parse_latex`]).
+pub fn read_latex_file(path: &Path) -> Result<Vec<Equation>, EquationError> {
+    Ok(parse_latex(&read_text(path)?))
+}
+
+/// Elements whose content MathJax does not look for math in.
+const HTML_SKIPPED: &[&str] = &["script", "style", "pre", "code", "textarea", "noscript"];
+
+/// Extract the math of an HTML page as MathJax finds it: `\(...\)` and
+/// `\[...\]` in the text and `<script type="math/tex">` elements (with
+/// `; mode=display` for display math), in document order.
+///
+/// Every equation's body is its content in `\(...\)` or `\[...\]`;
+/// entities are decoded in math from the text, not in scripts. Comments and
+/// the content of `pre`, `code`, `textarea` and other scripts are skipped.
+/// Names come from a `\label` and are numbered like in [`parse_latex`], and the closest
+/// `<h1>` to `<h6>` above an equation is recorded as its section.
+///
+/// ```
+/// use equation_processor::parser::parse_html;
+///
+/// let equations = parse_html(
+///     "<h2>Energy</h2><p>Since \\(E = mc^2\\) and <code>\\(x\\)</code></p>\n\
+///      <script type=\"math/tex; mode=display\">a < b \\label{ineq}</script>",
+/// );
+/// assert_eq!(equations.len(), 2);
+/// assert_eq!(equations[0].body, "\\(E = mc^2\\)");
+/// assert_eq!(equations[1].name, "ineq");
+/// assert_eq!(equations[1].body, "\\[a < b\\]");
+/// assert_eq!(equations[1].section.as_deref(), Some("Energy"));
+/// ```
+pub fn parse_html(content: &str) -> Vec<Equation> {
+    let comment = Regex::new(r"(?s)<!--.*?-->").unwrap();
+    let content = comment.replace_all(content.trim_start_matches('\u{feff}'), "");
+    let skipped = Regex::new(&format!(r"(?i)<({})\b([^>]*)>", HTML_SKIPPED.join("|"))).unwrap();
+    let math_script =
+        Regex::new(r#"(?i)type\s*=\s*["']?math/tex(;\s*mode\s*=\s*display)?"#).unwrap();
+    let closing: HashMap<&str, Regex> = HTML_SKIPPED
+        .iter()
+        .map(|&element| (element, Regex::new(&format!("(?i)</{element}")).unwrap()))
+        .collect();
+    let mut blocks = Vec::new();
+    let mut section = None;
+    let mut pos = 0;
+    while pos < content.len() {
+        let open = skipped.captures_at(&content, pos);
+        let text_end = open
+            .as_ref()
+            .map_or(content.len(), |c| c.get(0).unwrap().start());
+        html_text_math(&content[pos..text_end], &mut section, &mut blocks);
+        let Some(open) = open else {
+            break;
+        };
+        let element = open[1].to_ascii_lowercase();
+        let inner_start = open.get(0).unwrap().end();
+        let inner_end = closing[element.as_str()]
+            .find_at(&content, inner_start)
+            .map_or(content.len(), |m| m.start());
+        if element == "script" {
+            if let Some(script) = math_script.captures(&open[2]) {
+                let inner = content[inner_start..inner_end].to_string();
+                blocks.push((script.get(1).is_some(), inner, section.clone()));
+            }
+        }
+        pos = content[inner_end..]
+            .find('>')
+            .map_or(content.len(), |i| inner_end + i + 1);
+    }
+    let label = Regex::new(r"\\label\s*\{([^{}]*)\}").unwrap();
+    let mut eqs: Vec<Equation> = blocks
+        .into_iter()
+        .map(|(display, inner, section)| {
+            let name = label
+                .captures(&inner)
+                .map_or("", |c| c.get(1).unwrap().as_str());
+            let name = name.trim().strip_prefix("eq:").unwrap_or(name.trim());
+            let inner = label.replace_all(&inner, "");
+            let body = if display {
+                format!("\\[{}\\]", inner.trim())
+            } else {
+                format!("\\({}\\)", inner.trim())
+            };
+            let mut eq = Equation::new(true, name, &body);
+            eq.section = section;
+            eq
+        })
+        .collect();
+    number_duplicate_names(&mut eqs);
+    eqs
+}
+
+/// Add the `\(...\)` and `\[...\]` math in a stretch of HTML `text` to
+/// `blocks`, as whether it is display math, its decoded content and the
+/// section it is in, following the headings in `text`.
+fn html_text_math(
+    text: &str,
+    section: &mut Option<String>,
+    blocks: &mut Vec<(bool, String, Option<String>)>,
+) {
+    let heading = Regex::new(r"(?is)<h[1-6]\b[^>]*>(.*?)</h[1-6]\s*>").unwrap();
+    let tag = Regex::new(r"<[^>]*>").unwrap();
+    let headings: Vec<(usize, String)> = heading
+        .captures_iter(text)
+        .map(|c| {
+            let title = decode_html_entities(&tag.replace_all(&c[1], ""));
+            (c.get(0).unwrap().start(), title.trim().to_string())
+        })
+        .collect();
+    let mut next_heading = headings.iter().peekable();
+    let mut pos = 0;
+    while let Some(start) = text[pos..]
+        .match_indices('\\')
+        .map(|(i, _)| pos + i)
+        .find(|&i| {
+            matches!(text[i + 1..].chars().next(), Some('(' | '[')) && !text[..i].ends_with('\\')
+        })
+    {
+        let close = if text[start + 1..].starts_with('(') {
+            "\\)"
+        } else {
+            "\\]"
+        };
+        let Some(length) = text[start + 2..].find(close) else {
+            break;
+        };
+        let end = start + 2 + length + close.len();
+        while let Some((_, title)) = next_heading.next_if(|(at, _)| *at < start) {
+            *section = Some(title.clone());
+        }
+        let inner = decode_html_entities(&text[start + 2..end - close.len()]);
+        blocks.push((close == "\\]", inner, section.clone()));
+        pos = end;
+    }
+    if let Some((_, title)) = next_heading.last() {
+        *section = Some(title.clone());
+    }
+}
+
+/// `text` with the HTML entities math commonly uses decoded: `&lt;`, `&gt;`,
+/// `&amp;`, `&quot;`, `&apos;`, `&nbsp;` and numeric references.
+fn decode_html_entities(text: &str) -> String {
+    let entity = Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap();
+    entity
+        .replace_all(text, |c: &regex::Captures| {
+            let name = &c[1];
+            let decoded = match name {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => name
+                    .strip_prefix("#x")
+                    .or_else(|| name.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| name.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            decoded.map_or_else(|| c[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+/// Read an HTML page into equations (see [`parse_html`]).
+pub fn read_html_file(path: &Path) -> Result<Vec<Equation>, EquationError> {
+    Ok(parse_html(&read_text(path)?))
+}
+
+/// Name of a Markdown block without a name tag, numbered from the second.
+const DEFAULT_NAME: &str = "default_equation";
+
+/// Number repeated names from their second occurrence (`name_1`,
+/// `name_2`, …), so no two equations of an input write the same files.
+pub(crate) fn number_duplicate_names<'a>(equations: impl IntoIterator<Item = &'a mut Equation>) {
+    let mut counts = HashMap::new();
+    for eq in equations {
+        let c = counts.entry(eq.name.clone()).or_insert(0);
+        if *c > 0 {
+            eq.name = format!("{}_{c}", eq.name);
+        }
+        *c += 1;
+    }
+}
+
+/// Read a Jupyter notebook into equations: the `$$...$$` and fenced math
+/// blocks of its Markdown cells, each cell parsed like a Markdown file with
+/// `options`. Code cells are ignored.
+///
+/// A block without a name tag is named after the closest Markdown heading
+/// above it in the notebook, lowercased and made filename-safe, and
+/// otherwise `cell<N>_eq<M>` for the M-th block of the N-th cell (both from
+/// 1). Repeated names, whether tagged or derived, are numbered across the
+/// whole notebook like in Markdown input.
+pub fn read_notebook_file(
+    path: &Path,
+    options: &ParseOptions,
+) -> Result<Vec<Equation>, EquationError> {
+    let content = read_text(path)?;
+    let notebook: Notebook =
+        serde_json::from_str(content.trim_start_matches('\u{feff}')).map_err(|e| {
+            let line = (e.line() > 0).then_some(e.line());
+            structured_error(path, line, e.column(), &e.to_string())
+        })?;
+    let heading = heading_regex();
+    let mut section: Option<String> = None;
+    let mut eqs = Vec::new();
+    for (n, cell) in notebook.cells.iter().enumerate() {
+        if cell.cell_type != "markdown" {
+            continue;
+        }
+        let source = cell.source.text();
+        let mut blocks = markdown_blocks(&source, options.ambiguous_tags);
+        if options.task_checkboxes {
+            apply_task_checkboxes(&source, &mut blocks);
+        }
+        for (m, spanned) in blocks.into_iter().enumerate() {
+            let mut eq = spanned.equation;
+            eq.section = eq.section.or_else(|| section.clone());
+            if eq.name == DEFAULT_NAME {
+                eq.name = match &eq.section {
+                    Some(title) => Equation::sanitize_filename(&title.to_lowercase()),
+                    None => format!("cell{}_eq{}", n + 1, m + 1),
+                };
+            }
+            eqs.push(eq);
+        }
+        if let Some(title) = heading.captures_iter(&source).last() {
+            section = Some(title[1].to_string());
+        }
+    }
+    number_duplicate_names(&mut eqs);
+    Ok(eqs)
+}
+
+/// A Jupyter notebook, as far as [`read_notebook_file`] reads it.
+#[derive(Deserialize)]
+struct Notebook {
+    cells: Vec<NotebookCell>,
+}
+
+#[derive(Deserialize)]
+struct NotebookCell {
+    cell_type: String,
+    #[serde(default)]
+    source: CellSource,
+}
+
+/// The source of a cell: one string, or a list of lines as saved by Jupyter.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CellSource {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl Default for CellSource {
+    fn default() -> Self {
+        CellSource::Text(String::new())
+    }
+}
+
+impl CellSource {
+    fn text(&self) -> String {
+        match self {
+            CellSource::Text(text) => text.clone(),
+            CellSource::Lines(lines) => lines.concat(),
+        }
+    }
+}
+
+/// Read a directory of `.tex` fragment files, one active equation per file
+/// with the file's content as body and its stem as name, in file name order.
+/// Other files and subdirectories are ignored.
+pub fn read_tex_directory(dir: &Path) -> Result<Vec<Equation>, EquationError> {
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file()
+            && path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("tex"))
+        {
+            files.push(path);
+        }
+    }
+    files.sort();
+    let mut equations = files
+        .iter()
+        .map(|path| {
+            let body = read_text(path)?;
+            let body = body.trim_start_matches('\u{feff}').trim();
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            Ok(Equation::new(true, &name, body))
+        })
+        .collect::<Result<Vec<_>, EquationError>>()?;
+    number_duplicate_names(&mut equations);
+    Ok(equations)
+}
+
+/// Apply a `key:value` option tag to an equation.
+///
+/// Returns `false` for unknown keys so the caller can treat the tag otherwise.
+/// Known keys:
+/// - `assets`: files separated by `;` or `,`, made available to the compile
+/// - `kind`: `math`, `tikz` or `chem`
+/// - `tikz`: flag, same as `kind:tikz`
+/// - `chem`: flag, same as `kind:chem`
+/// - `tikzlibs`: TikZ libraries separated by `;` or `,`
+/// - `tags`: labels separated by `;` or `,`
+/// - `border`: standalone border, e.g. `2pt` or `1pt 2pt 1pt 2pt`
+/// - `varwidth`: flag or maximum width such as `10cm`, enabling standalone's `varwidth`
+/// - `crop`: `true`/`false` (or `yes`/`no`), standalone's `crop`
+/// - `color`: font color as `#RRGGBB`, overriding the batch color
+/// - `env`: `KEY=VALUE` pairs separated by `;`, set for tectonic and the converter
+/// - `desc`: a description of the equation, e.g. its range of validity
+/// - `engine`: `tectonic`, `pdflatex`, `xelatex` or `lualatex` to compile with
+/// - `width`: display width such as `300px` or `8cm`, applied with `\resizebox`
+/// - `scale`: display scale such as `0.8`, applied with `\scalebox`
+pub fn apply_tag(eq: &mut Equation, key: &str, value: &str) -> bool {
+    match key.trim() {
+        "assets" => {
+            eq.assets.extend(split_list(value).map(PathBuf::from));
+            true
+        }
+        "kind" => match value.parse() {
+            Ok(kind) => {
+                eq.kind = kind;
+                true
+            }
+            Err(_) => false,
+        },
+        "tikz" => {
+            eq.kind = EquationKind::TikZ;
+            true
+        }
+        "chem" => {
+            eq.kind = EquationKind::Chemistry;
+            true
+        }
+        "tags" => {
+            eq.tags.extend(split_list(value).map(String::from));
+            true
+        }
+        "desc" => {
+            let description = value.trim();
+            if !description.is_empty() {
+                eq.description = Some(description.to_string());
+            }
+            true
+        }
+        "color" if !value.trim().is_empty() => {
+            eq.color = Some(value.trim().to_string());
+            true
+        }
+        "border" if !value.trim().is_empty() => {
+            eq.standalone.border = Some(value.trim().to_string());
+            true
+        }
+        "varwidth" => {
+            let width = value.trim();
+            eq.standalone.varwidth = Some(if width.is_empty() { "true" } else { width }.into());
+            true
+        }
+        "crop" => match value.trim().to_lowercase().as_str() {
+            "true" | "yes" => {
+                eq.standalone.crop = Some(true);
+                true
+            }
+            "false" | "no" => {
+                eq.standalone.crop = Some(false);
+                true
+            }
+            _ => false,
+        },
+        "env" => {
+            let vars: Result<Vec<_>, _> = value
+                .split(';')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(parse_env_var)
+                .collect();
+            match vars {
+                Ok(vars) => {
+                    eq.env.extend(vars);
+                    true
+                }
+                Err(_) => false,
+            }
+        }
+        "engine" => match value.parse() {
+            Ok(engine) => {
+                eq.engine = Some(engine);
+                true
+            }
+            Err(_) => false,
+        },
+        "width" => match parse_display_width(value) {
+            Ok(width) => {
+                eq.display.width = Some(width);
+                true
+            }
+            Err(_) => false,
+        },
+        "scale" => match parse_display_scale(value) {
+            Ok(scale) => {
+                eq.display.scale = Some(scale);
+                true
+            }
+            Err(_) => false,
+        },
+        "tikzlibs" => {
+            eq.tikz_libraries
+                .extend(split_list(value).map(String::from));
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Split a `;`/`,` separated tag value into trimmed, non-empty items.
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+}
+
+/// Keys understood by [`apply_tag`] as `%%key:value%%` tags.
+pub const TAG_KEYS: &[&str] = &[
+    "assets", "kind", "tags", "tikzlibs", "border", "varwidth", "crop", "color", "env", "desc",
+    "engine", "width", "scale",
+];
+
+/// Value-less tags understood by [`apply_tag`], written as `%%flag%%`.
+pub const FLAG_TAGS: &[&str] = &["tikz", "chem", "varwidth"];
+
+/// Make relative asset paths relative to `base_dir` (usually the input file's directory).
+pub fn resolve_assets(equations: &mut [Equation], base_dir: &Path) {
+    for eq in equations {
+        for asset in &mut eq.assets {
+            if asset.is_relative() {
+                *asset = base_dir.join(&*asset);
+            }
+        }
+    }
+}
+
+/// Parse Markdown into equations
+///
+/// Equations are `$$...$$` blocks or fenced ```` ```math ```` blocks, whose
+/// info string may carry options as attributes, e.g.
+/// ```` ```math {name="bernoulli", color="#223344", tags="fluids,ch3"} ````
+/// (see [`parse_fence_attributes`]). `name` and `active` (`true`/`false`)
+/// are understood besides the keys of [`apply_tag`]; unknown attributes are
+/// ignored.
+///
+/// Tags directly after a block are either options (`%%key:value%%` or a
+/// flag such as `%%tikz%%`, see [`apply_tag`]) or, for the first other tag,
+/// the equation name. A tag that
+/// is an activation tag (`%%yes%%`, `%%no%%`, `%%%%`) introducing the next
+/// block is left to that block. Tags may be indented, e.g. inside a list
+/// item. Each equation records the closest preceding Markdown heading as its
+/// section.
+pub fn parse_markdown(content: &str) -> Vec<Equation> {
+    parse_markdown_with(content, &ParseOptions::default())
+}
+
+/// Parse Markdown into equations using `options`
+pub fn parse_markdown_with(content: &str, options: &ParseOptions) -> Vec<Equation> {
+    let mut eqs = markdown_spans(content, options.ambiguous_tags);
+    if options.task_checkboxes {
+        apply_task_checkboxes(content, &mut eqs);
+    }
+    eqs.into_iter().map(|s| s.equation).collect()
+}
+
+/// Set the active flag of blocks preceded by a Markdown task item.
+///
+/// A block is active after `- [x]` (or `* [X]`, `1. [x]`, …) and inactive
+/// after `- [ ]`, where the task item is either earlier on the block's first
+/// line or the closest non-blank line above it. An explicit `%%yes%%` or
+/// `%%no%%` tag wins over the checkbox.
+pub fn apply_task_checkboxes(content: &str, equations: &mut [SpannedEquation]) {
+    let task = Regex::new(r"^\s*(?:[-*+]|\d+[.)])\s+\[([ xX])\]").unwrap();
+    let mut previous_end = 0;
+    for spanned in equations {
+        let start = spanned.span.start;
+        let before = &content[previous_end..start];
+        previous_end = spanned.span.end;
+        if content[start..].starts_with("%%") {
+            continue;
+        }
+        let line = before.rsplit(['\n', '\r']).find(|l| !l.trim().is_empty());
+        if let Some(checkbox) = line.and_then(|l| task.captures(l)) {
+            spanned.equation.active = &checkbox[1] != " ";
+        }
+    }
+}
+
+/// An equation together with the byte ranges it was parsed from.
+#[derive(Debug, Clone)]
+pub struct SpannedEquation {
+    /// The parsed equation
+    pub equation: Equation,
+    /// The whole block: activation tag, `$$...$$` (or the ```` ```math ````
+    /// fence) and the tags after it
+    pub span: Range<usize>,
+    /// The (trimmed) body between the `$$` delimiters
+    pub body_span: Range<usize>,
+    /// A tag in the name's place that looks like a marker such as `%%TODO%%`
+    /// (see [`is_ambiguous_tag`]), whether or not it became the name; `None`
+    /// if a fence attribute names the equation
+    pub ambiguous_tag: Option<String>,
+}
+
+/// Parse Markdown into equations, keeping each one's position in `content`.
+///
+/// Ranges are byte offsets into `content`, so an editor can map diagnostics
+/// back to the source. To re-parse an edited region, pass the slice covering
+/// the affected blocks and add the slice's start to the returned ranges;
+/// duplicate-name numbering then only accounts for blocks in the slice.
+/// Ambiguous tags are handled by the default [`AmbiguousTagPolicy`].
+pub fn parse_markdown_with_spans(content: &str) -> Vec<SpannedEquation> {
+    markdown_spans(content, AmbiguousTagPolicy::default())
+}
+
+/// [`parse_markdown_with_spans`] with a policy for ambiguous tags.
+pub(crate) fn markdown_spans(
+    content: &str,
+    ambiguous_tags: AmbiguousTagPolicy,
+) -> Vec<SpannedEquation> {
+    let mut eqs = markdown_blocks(content, ambiguous_tags);
+    number_duplicate_names(eqs.iter_mut().map(|s| &mut s.equation));
+    eqs
+}
+
+/// The blocks of `content` with their names as written, repeated ones not
+/// yet numbered; blocks without a name are all [`DEFAULT_NAME`].
+fn markdown_blocks(content: &str, ambiguous_tags: AmbiguousTagPolicy) -> Vec<SpannedEquation> {
+    let block = Regex::new(r"(?s)(%%(yes|no)?%%)?[\n\r]*\$\$[\n\r]*(?P<body>.*?)\$\$").unwrap();
+    let fence = fence_regex();
+    let tag = Regex::new(r"^[\n\r \t]*%%([^\n\r]*?)%%").unwrap();
+    let next_prefix = Regex::new(r"^[\n\r \t]*%%(yes|no)?%%[\n\r \t]*(\$\$|```math)").unwrap();
+    let heading = heading_regex();
+    let headings: Vec<(usize, &str)> = heading
+        .captures_iter(content)
+        .map(|c| (c.get(0).unwrap().start(), c.get(1).unwrap().as_str()))
+        .collect();
+    let mut eqs = Vec::new();
+    let mut dollars = block.captures_at(content, 0);
+    let mut fenced = fence.captures_at(content, 0);
+    loop {
+        // Take whichever kind of block comes first.
+        let take_fence = match (&dollars, &fenced) {
+            (Some(d), Some(f)) => f.get(0).unwrap().start() < d.get(0).unwrap().start(),
+            (None, Some(_)) => true,
+            (_, None) => false,
+        };
+        let next = if take_fence {
+            fenced.take()
+        } else {
+            dollars.take()
+        };
+        let Some(cap) = next else {
+            break;
+        };
+        let whole = cap.get(0).unwrap();
+        let leading = whole.as_str().len() - whole.as_str().trim_start_matches(['\n', '\r']).len();
+        let start = whole.start() + leading;
+        let mut pos = whole.end();
+        let mut active = cap.get(2).is_none_or(|m| m.as_str() == "yes");
+        let attributes = cap
+            .name("info")
+            .map(|m| parse_fence_attributes(m.as_str()))
+            .unwrap_or_default();
+        let raw_body = cap.name("body").unwrap();
+        let body = raw_body.as_str().trim();
+        let body_start =
+            raw_body.start() + (raw_body.as_str().len() - raw_body.as_str().trim_start().len());
+        let body_span = body_start..body_start + body.len();
+        let mut raw = None;
+        let mut options = Vec::new();
+        while let Some(t) = tag.captures(&content[pos..]) {
+            if next_prefix.is_match(&content[pos..]) {
+                break;
+            }
+            let text = t.get(1).unwrap().as_str();
+            match text.split_once(':') {
+                Some((key, value)) if TAG_KEYS.contains(&key.trim()) => options.push((key, value)),
+                None if FLAG_TAGS.contains(&text.trim()) => options.push((text, "")),
+                _ if raw.is_none() => raw = Some(text),
+                _ => break,
+            }
+            pos += t.get(0).unwrap().end();
+        }
+        let mut declared = None;
+        for (key, value) in &attributes {
+            match key.as_str() {
+                "name" if !value.trim().is_empty() => declared = Some(value.trim()),
+                "active" => match value.trim().to_lowercase().as_str() {
+                    "true" | "yes" => active = true,
+                    "false" | "no" => active = false,
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+        let ambiguous_tag = raw
+            .filter(|t| declared.is_none() && is_ambiguous_tag(t))
+            .map(str::to_string);
+        if ambiguous_tag.is_some() {
+            match ambiguous_tags {
+                AmbiguousTagPolicy::Name => {}
+                AmbiguousTagPolicy::Ask | AmbiguousTagPolicy::Ignore => raw = None,
+                AmbiguousTagPolicy::Skip => {
+                    raw = None;
+                    active = false;
+                }
+            }
+        }
+        let name = declared.or(raw).unwrap_or(DEFAULT_NAME);
+        let mut eq = Equation::new(active, name, body);
+        for (key, value) in &attributes {
+            apply_tag(&mut eq, key, value);
+        }
+        for (key, value) in options {
+            apply_tag(&mut eq, key, value);
+        }
+        eq.section = headings
+            .iter()
+            .take_while(|(at, _)| *at < start)
+            .last()
+            .map(|(_, title)| title.to_string());
+        eqs.push(SpannedEquation {
+            equation: eq,
+            span: start..pos,
+            body_span,
+            ambiguous_tag,
+        });
+        // Look ahead again for the kind just used and for a match of the
+        // other kind that began inside this block.
+        if !take_fence
+            || dollars
+                .as_ref()
+                .is_some_and(|c| c.get(0).unwrap().start() < pos)
+        {
+            dollars = block.captures_at(content, pos);
+        }
+        if take_fence
+            || fenced
+                .as_ref()
+                .is_some_and(|c| c.get(0).unwrap().start() < pos)
+        {
+            fenced = fence.captures_at(content, pos);
+        }
+    }
+    eqs
+}
+
+/// Regex matching an ATX Markdown heading, with its title in group 1.
+fn heading_regex() -> Regex {
+    Regex::new(r"(?m)^#{1,6}[ \t]+(.+?)[ \t#]*\r?$").unwrap()
+}
+
+/// Regex matching a fenced ```` ```math ```` block, with an optional
+/// activation tag before it, attributes in `info` and the LaTeX in `body`.
+pub(crate) fn fence_regex() -> Regex {
+    Regex::new(
+        r"(?ms)(%%(yes|no)?%%)?[\n\r]*^[ \t]*```math[ \t]*(?:\{(?P<info>[^}\r\n]*)\})?[ \t]*\r?\n(?P<body>.*?)\r?\n[ \t]*```",
+    )
+    .unwrap()
+}
+
+/// Parse the attributes of a fence info string such as
+/// `name="bernoulli", color="#223344" tikz` into `(key, value)` pairs.
+///
+/// Values may be double-quoted or bare; pairs are separated by commas or
+/// spaces, and a key without a value (a flag) gets an empty one.
+pub fn parse_fence_attributes(info: &str) -> Vec<(String, String)> {
+    let attribute =
+        Regex::new(r#"([A-Za-z][\w-]*)(?:[ \t]*=[ \t]*(?:"([^"]*)"|([^\s,"]+)))?"#).unwrap();
+    attribute
+        .captures_iter(info)
+        .map(|c| {
+            let value = c.get(2).or(c.get(3)).map_or("", |m| m.as_str());
+            (c[1].to_string(), value.to_string())
+        })
+        .collect()
+}
+
+/// Fuzzing entry point: run every in-memory parser on arbitrary bytes.
+///
+/// Input is decoded lossily as UTF-8. Panics if a parser breaks one of its
+/// invariants (non-empty, filename-safe names), so it can serve directly as
+/// the body of a `cargo fuzz` target.
+pub fn fuzz_parse(data: &[u8]) -> Vec<Equation> {
+    let text = String::from_utf8_lossy(data);
+    let mut eqs = parse_markdown(&text);
+    eqs.extend(parse_csv(&text));
+    for eq in &eqs {
+        assert!(!eq.name.is_empty(), "parser produced an empty name");
+        assert!(
+            eq.name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.'),
+            "parser produced an unsafe name: {:?}",
+            eq.name
+        );
+    }
+    eqs
+}
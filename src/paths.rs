@@ -0,0 +1,48 @@
+//! Where files outside the output directory are kept: the config file and
+//! the run statistics, in the platform's conventional locations.
+//!
+//! | | Linux | macOS | Windows |
+//! |---|---|---|---|
+//! | config | `~/.config/equation_processor` | `~/Library/Application Support/equation_processor` | `%APPDATA%\equation_processor\config` |
+//! | data | `~/.local/share/equation_processor` | `~/Library/Application Support/equation_processor` | `%APPDATA%\equation_processor\data` |
+//!
+//! `XDG_CONFIG_HOME` and `XDG_DATA_HOME` are honored on Linux, and the
+//! `EQUATION_PROCESSOR_*` variables below override every platform.
+//! Tectonic keeps its own cache in the platform cache directory unless
+//! `--cache-dir` says otherwise.
+
+use directories::ProjectDirs;
+use std::path::PathBuf;
+
+/// Environment variable naming the config file, like `--config`.
+pub const CONFIG_ENV: &str = "EQUATION_PROCESSOR_CONFIG";
+
+/// Environment variable naming the data directory.
+pub const DATA_DIR_ENV: &str = "EQUATION_PROCESSOR_DATA_DIR";
+
+/// File name of the config file in the config directory.
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// The platform directories of this application, if the home directory
+/// is known.
+fn project_dirs() -> Option<ProjectDirs> {
+    ProjectDirs::from("", "", "equation_processor")
+}
+
+/// The config file read at startup: [`CONFIG_ENV`] if set, else
+/// [`CONFIG_FILE`] in the platform config directory. It need not exist.
+pub fn config_file() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    Some(project_dirs()?.config_dir().join(CONFIG_FILE))
+}
+
+/// The directory for data kept across runs, such as run statistics:
+/// [`DATA_DIR_ENV`] if set, else the platform data directory.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(DATA_DIR_ENV) {
+        return Some(PathBuf::from(path));
+    }
+    Some(project_dirs()?.data_dir().to_path_buf())
+}
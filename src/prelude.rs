@@ -0,0 +1,34 @@
+//! The common types and functions in one import.
+//!
+//! ```
+//! use equation_processor::prelude::*;
+//!
+//! let equations = parse_markdown("$$\nE = mc^2\n$$\n%%energy%%\n");
+//! let options = RenderOptions {
+//!     formats: vec![OutputFormat::Pdf],
+//!     ..Default::default()
+//! };
+//! assert_eq!(equations[0].name, "energy");
+//! assert!(options.keeps_pdf());
+//! ```
+//!
+//! Items are only added here once they are stable; nothing re-exported from
+//! the prelude is deprecated.
+
+pub use crate::error::EquationError;
+pub use crate::model::{
+    Background, Color, DisplaySize, Equation, EquationKind, Filetype, LatexEngine,
+    StandaloneOptions,
+};
+pub use crate::parser::{
+    detect_file_type, parse_csv, parse_csv_with, parse_html, parse_latex, parse_markdown,
+    parse_markdown_with, read_csv_file, read_csv_file_with, read_file, read_html_file, read_input,
+    read_json_file, read_latex_file, read_notebook_file, read_toml_file, read_yaml_file,
+    resolve_assets, CsvColumns, ParseOptions,
+};
+pub use crate::process::{process_file, ProcessOptions, ProcessingReport};
+pub use crate::render::{
+    render_equations_with, render_single, ImageFormat, LinkStrategy, OutputFormat, PdfOptions,
+    RenderOptions, RenderedEquation, Renderer, TectonicConfig, TectonicRenderer,
+};
+pub use crate::report::{write_index, ContentManifest};
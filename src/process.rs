@@ -0,0 +1,387 @@
+//! Processing an input file in one call, without prompts or printing.
+//!
+//! [`process_file`] runs what the command line does for `-i`: it detects the
+//! input format, reads the equations, applies the batch settings, checks
+//! them, renders the active ones and writes the extra outputs the options
+//! ask for. Where the command line would print a warning or ask a question,
+//! the warning is collected in the [`ProcessingReport`] and the question is
+//! answered with the non-interactive default.
+//!
+//! ```no_run
+//! use equation_processor::process::{process_file, ProcessOptions};
+//! use std::path::Path;
+//!
+//! let report = process_file(Path::new("equations.md"), &ProcessOptions::default())?;
+//! for warning in &report.warnings {
+//!     eprintln!("{warning}");
+//! }
+//! println!("{} rendered to {:?}", report.rendered.len(), report.output_dir);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::cli::validate_colors;
+use crate::error::EquationError;
+use crate::hooks::run_hooks;
+use crate::model::{Color, Equation, EquationKind, Filetype};
+use crate::palette::{batch_palette, check_palette};
+use crate::parser::{
+    ambiguous_tags, detect_file_type, read_file, read_input, resolve_assets, ParseOptions,
+};
+use crate::render::{
+    apply_color_map, batch_renderer, disambiguate_names, estimate_disk_space, format_duration,
+    render_equations_counted, BatchSummary, RenderOptions, Renderer, Retry, RetryingRenderer,
+    SpaceEstimate, TectonicConfig,
+};
+use crate::report::{
+    archive_changed, content_address, notify_desktop, run_summary, write_beamer, write_gallery,
+    write_index_with, write_markdown, write_tex_snippets,
+};
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Where and how [`process_file`] renders.
+#[derive(Debug, Clone)]
+pub struct ProcessOptions {
+    /// Directory the artifacts are written to, created if needed
+    pub output_dir: PathBuf,
+    /// How tectonic is invoked
+    pub tectonic: TectonicConfig,
+    /// How the input file is read; [`Ask`](crate::parser::AmbiguousTagPolicy::Ask)
+    /// is treated like `Ignore`, since nobody is asked
+    pub parse: ParseOptions,
+    /// Settings for the whole batch; `notify` is ignored
+    pub render: RenderOptions,
+}
+
+impl Default for ProcessOptions {
+    fn default() -> Self {
+        Self {
+            output_dir: PathBuf::from("output"),
+            tectonic: TectonicConfig::default(),
+            parse: ParseOptions::default(),
+            render: RenderOptions::default(),
+        }
+    }
+}
+
+/// What [`process_file`] did.
+#[derive(Debug, Clone)]
+pub struct ProcessingReport {
+    /// The input file
+    pub input: PathBuf,
+    /// The directory the artifacts were written to
+    pub output_dir: PathBuf,
+    /// Every equation read, after the batch settings were applied
+    pub equations: Vec<Equation>,
+    /// Names of the active equations, which are now rendered (or were
+    /// already, with unchanged inputs)
+    pub rendered: Vec<String>,
+    /// Names of the inactive equations, which were left out
+    pub skipped: Vec<String>,
+    /// Files written besides the artifacts of each equation: index,
+    /// snippets, gallery, Markdown page and slides
+    pub outputs: Vec<PathBuf>,
+    /// Earlier artifacts moved to the history, with `keep_history`
+    pub archived: Vec<PathBuf>,
+    /// Warnings the command line would have printed
+    pub warnings: Vec<String>,
+    /// Renders repeated after a transient failure, see
+    /// [`RenderOptions::retry`]
+    pub retries: Vec<Retry>,
+    /// Estimated size of the batch and free space before rendering
+    pub space: SpaceEstimate,
+    /// How long rendering took
+    pub took: Duration,
+}
+
+impl ProcessingReport {
+    /// One-line summary, e.g. `3 rendered, 1 skipped, 2 SVG, 2 PDF`.
+    pub fn summary(&self) -> String {
+        run_summary(&self.equations)
+    }
+}
+
+/// Render the equations of `input` as [`ProcessOptions`] say, without any
+/// terminal interaction, using tectonic and `pdftocairo`, or the Typst
+/// fallback where [`batch_renderer`] picks it, with a warning.
+///
+/// Fails before anything is rendered if the input cannot be read, a color
+/// is invalid, the batch colors are hard to tell apart (with
+/// `check_palette`), the renderer lacks a requested feature or the output
+/// volume is too small. An input without equations is not an error; the
+/// report is simply empty, and only a shard's `index.json` is written.
+pub fn process_file(
+    input: &Path,
+    options: &ProcessOptions,
+) -> Result<ProcessingReport, Box<dyn Error>> {
+    let (renderer, fallback) = batch_renderer(&options.tectonic, &options.render);
+    let mut report = process_file_with(input, options, renderer.as_ref())?;
+    if let Some(warning) = fallback {
+        report.warnings.insert(0, warning);
+    }
+    Ok(report)
+}
+
+/// Like [`process_file`], rendering with `renderer`, e.g. a
+/// [`MockRenderer`](crate::test_util::MockRenderer) in tests.
+pub fn process_file_with(
+    input: &Path,
+    options: &ProcessOptions,
+    renderer: &dyn Renderer,
+) -> Result<ProcessingReport, Box<dyn Error>> {
+    let color: Color = options.render.color.parse()?;
+    let render = &RenderOptions {
+        color: color.to_string(),
+        ..options.render.clone()
+    };
+    let output_dir = options.output_dir.as_path();
+    let mut warnings = Vec::new();
+    let equations = prepare(
+        input,
+        render.chemistry,
+        &options.parse,
+        render,
+        &mut warnings,
+    )?;
+    let mut report = ProcessingReport {
+        input: input.to_path_buf(),
+        output_dir: output_dir.to_path_buf(),
+        rendered: Vec::new(),
+        skipped: Vec::new(),
+        outputs: Vec::new(),
+        archived: Vec::new(),
+        warnings: Vec::new(),
+        retries: Vec::new(),
+        space: SpaceEstimate {
+            required: 0,
+            available: None,
+        },
+        took: Duration::ZERO,
+        equations,
+    };
+    if report.equations.is_empty() {
+        if options.render.shard.is_some() {
+            // An empty shard still reports in, so merging doesn't miss it.
+            fs::create_dir_all(output_dir)?;
+            report.outputs = vec![write_index_with(&[], output_dir, render.shard)?];
+        }
+        report.warnings = warnings;
+        return Ok(report);
+    }
+    fs::create_dir_all(output_dir)?;
+    report.space = check_batch(&report.equations, color, renderer, output_dir, render)?;
+    if report.space.is_tight() {
+        warnings.push(format!(
+            "the batch will nearly fill the output volume ({})",
+            report.space
+        ));
+    }
+    if render.keep_history {
+        report.archived = archive_changed(&report.equations, output_dir)?;
+    }
+    let started = Instant::now();
+    let retrying = RetryingRenderer::new(renderer, render.retry);
+    let rendered = render_outputs(
+        &report.equations,
+        output_dir,
+        &retrying,
+        &options.tectonic,
+        render,
+        &mut warnings,
+        &mut BatchSummary::default(),
+    );
+    report.took = started.elapsed();
+    report.retries = retrying.retries();
+    report.outputs = rendered?;
+    for eq in &report.equations {
+        if eq.active {
+            report.rendered.push(eq.name.clone());
+        } else {
+            report.skipped.push(eq.name.clone());
+        }
+    }
+    report.warnings = warnings;
+    Ok(report)
+}
+
+/// Read the equations of `input_file` and apply the batch settings: shared
+/// asset paths, chemistry mode, `include_inactive`, the color map, name
+/// affixes, unique names and the shard. Warnings, including one per ambiguous tag in
+/// Markdown input, are added to `warnings`.
+pub(crate) fn prepare(
+    input_file: &Path,
+    chemistry: bool,
+    parse_options: &ParseOptions,
+    options: &RenderOptions,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<Equation>, Box<dyn Error>> {
+    let mut equations = read_input(input_file, parse_options)?;
+    if matches!(detect_file_type(input_file), Filetype::Markdown) {
+        for (line, tag) in ambiguous_tags(&read_file(&input_file.to_path_buf())?) {
+            warnings.push(format!(
+                "line {line}: %%{tag}%% looks like a marker rather than a name, so it is {}",
+                parse_options.ambiguous_tags.outcome()
+            ));
+        }
+    }
+    if let Some(base_dir) = input_file.parent() {
+        resolve_assets(&mut equations, base_dir);
+    }
+    apply_batch_settings(&mut equations, chemistry, options, warnings)?;
+    Ok(equations)
+}
+
+/// Apply the batch settings of `options` to equations already read:
+/// chemistry mode, `include_inactive`, the color map, name affixes, unique
+/// names and the shard. Warnings are added to `warnings`.
+pub(crate) fn apply_batch_settings(
+    equations: &mut Vec<Equation>,
+    chemistry: bool,
+    options: &RenderOptions,
+    warnings: &mut Vec<String>,
+) -> Result<(), EquationError> {
+    if chemistry {
+        for eq in equations
+            .iter_mut()
+            .filter(|e| e.kind == EquationKind::Math)
+        {
+            eq.kind = EquationKind::Chemistry;
+        }
+    }
+    if options.include_inactive {
+        for eq in equations.iter_mut() {
+            eq.active = true;
+        }
+    }
+    warnings.extend(apply_color_map(equations, &options.colors));
+    validate_colors(equations).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    for eq in equations.iter_mut() {
+        eq.add_name_affixes(&options.name_prefix, &options.name_suffix);
+    }
+    warnings.extend(disambiguate_names(equations));
+    if let Some(shard) = options.shard {
+        equations.retain(|eq| shard.contains(&eq.name));
+    }
+    Ok(())
+}
+
+/// Check a batch before rendering it: distinguishable colors (with
+/// `options.check_palette`), features `renderer` supports and the space
+/// left in `output_dir`. Returns the space estimate.
+pub(crate) fn check_batch(
+    equations: &[Equation],
+    color: Color,
+    renderer: &dyn Renderer,
+    output_dir: &Path,
+    options: &RenderOptions,
+) -> Result<SpaceEstimate, EquationError> {
+    if options.check_palette {
+        check_batch_palette(equations, color)?;
+    }
+    let unsupported = renderer.capabilities().unsupported(equations, options);
+    if !unsupported.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, unsupported.join("\n")).into());
+    }
+    let space = estimate_disk_space(equations, output_dir, options.delete_intermediates, options);
+    if !space.fits() {
+        return Err(io::Error::new(
+            io::ErrorKind::StorageFull,
+            format!("not enough disk space in {output_dir:?}: {space}"),
+        )
+        .into());
+    }
+    Ok(space)
+}
+
+/// Refuse a batch whose colors, with `color` for equations without their
+/// own, are hard to tell apart; see [`check_palette`].
+fn check_batch_palette(equations: &[Equation], color: Color) -> Result<(), EquationError> {
+    let conflicts = check_palette(&batch_palette(equations, color));
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    let lines: Vec<String> = conflicts.iter().map(ToString::to_string).collect();
+    Err(io::Error::new(io::ErrorKind::InvalidInput, lines.join("\n")).into())
+}
+
+/// Render `equations` and write the outputs `options` ask for besides the
+/// images: post-render hooks, manifest, index, snippets, gallery, Markdown
+/// page and slides.
+/// Hook failures are added to `warnings` and what became of each equation
+/// to `summary`, also when rendering fails. Returns the extra files written.
+pub(crate) fn render_outputs(
+    equations: &[Equation],
+    output_dir: &Path,
+    renderer: &dyn Renderer,
+    tectonic: &TectonicConfig,
+    options: &RenderOptions,
+    warnings: &mut Vec<String>,
+    summary: &mut BatchSummary,
+) -> Result<Vec<PathBuf>, EquationError> {
+    render_equations_counted(
+        equations,
+        output_dir,
+        renderer,
+        options.resume,
+        options.dedup,
+        options.jobs,
+        summary,
+    )?;
+    for failure in run_hooks(equations, output_dir, &options.hooks, options.hook_timeout) {
+        warnings.push(failure.to_string());
+    }
+    if options.content_addressed {
+        content_address(equations, output_dir)?;
+    }
+    let mut outputs = vec![write_index_with(equations, output_dir, options.shard)?];
+    if options.tex_snippets {
+        outputs.push(write_tex_snippets(equations, output_dir)?);
+    }
+    if options.html_gallery {
+        outputs.push(write_gallery(equations, output_dir)?);
+    }
+    if options.markdown {
+        outputs.push(write_markdown(
+            equations,
+            output_dir,
+            options.image_dimensions,
+        )?);
+    }
+    if options.beamer {
+        let deck = write_beamer(equations, output_dir)?;
+        tectonic.compile_document_with(options.engine, &deck, output_dir)?;
+        outputs.push(deck.with_extension("pdf"));
+    }
+    Ok(outputs)
+}
+
+/// Pop up a desktop notification saying how rendering the batch of
+/// `equations` from `source` ended after `took`. Returns a warning if it
+/// could not be shown.
+pub(crate) fn notify_outcome<T>(
+    source: &str,
+    equations: &[Equation],
+    rendered: &Result<T, EquationError>,
+    took: Duration,
+) -> Option<String> {
+    let took = format_duration(took);
+    let shown = match rendered {
+        Ok(_) => notify_desktop(
+            &format!("Rendered {source}"),
+            &format!("{} Took {took}.", run_summary(equations)),
+        ),
+        Err(err) => {
+            let active = equations.iter().filter(|e| e.active).count();
+            notify_desktop(
+                &format!("Rendering {source} failed"),
+                &format!("{err}\n{active} active equation(s), stopped after {took}."),
+            )
+        }
+    };
+    shown
+        .err()
+        .map(|err| format!("could not show a notification: {err}"))
+}
@@ -0,0 +1,126 @@
+//! Input files fetched from http(s) URLs, e.g. an equations CSV kept in a
+//! shared repository.
+//!
+//! The content is downloaded to a temporary file named after the URL, and
+//! its format is taken from the URL's extension or else the response's
+//! `Content-Type`. GitHub `blob` links are fetched from
+//! `raw.githubusercontent.com`.
+//!
+//! ```
+//! use equation_processor::remote::{is_url, raw_url};
+//!
+//! assert!(is_url("https://example.com/equations.csv"));
+//! assert!(!is_url("notes/equations.md"));
+//! assert_eq!(
+//!     raw_url("https://github.com/org/repo/blob/main/data/eqs.csv"),
+//!     "https://raw.githubusercontent.com/org/repo/main/data/eqs.csv"
+//! );
+//! ```
+
+use crate::model::Equation;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long to wait for the server before giving up.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Largest input accepted, to fail early on a wrong link.
+const MAX_INPUT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Whether `input` is an `http://` or `https://` URL rather than a path.
+pub fn is_url(input: &str) -> bool {
+    let lower = input.trim_start().to_ascii_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// The raw-content URL for a GitHub `blob` link; other URLs are returned
+/// unchanged.
+pub fn raw_url(url: &str) -> String {
+    let Some(rest) = url
+        .strip_prefix("https://github.com/")
+        .or_else(|| url.strip_prefix("http://github.com/"))
+    else {
+        return url.to_string();
+    };
+    let parts: Vec<&str> = rest.splitn(4, '/').collect();
+    match parts.as_slice() {
+        [owner, repo, "blob", path] => {
+            format!("https://raw.githubusercontent.com/{owner}/{repo}/{path}")
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// Input file extension for a MIME type, e.g. `text/csv` → `csv`.
+pub fn extension_for_content_type(content_type: &str) -> Option<&'static str> {
+    let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "text/csv" | "application/csv" | "text/tab-separated-values" => Some("csv"),
+        "text/markdown" | "text/x-markdown" => Some("md"),
+        "application/json" | "text/json" => Some("json"),
+        "application/yaml" | "application/x-yaml" | "text/yaml" | "text/x-yaml" => Some("yaml"),
+        "application/toml" | "text/toml" | "text/x-toml" => Some("toml"),
+        "application/x-tex" | "application/x-latex" | "text/x-tex" => Some("tex"),
+        "application/x-ipynb+json" => Some("ipynb"),
+        _ => None,
+    }
+}
+
+/// File name for the content of `url`: its last path segment, with the
+/// extension from `content_type` when the segment has no known one.
+pub fn file_name_for(url: &str, content_type: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let segment = path.rsplit('/').next().unwrap_or_default();
+    let stem = Equation::sanitize_filename(segment);
+    let known = [
+        "csv", "md", "markdown", "json", "yml", "yaml", "toml", "tex", "ipynb", "html", "htm",
+    ];
+    match stem.rsplit_once('.') {
+        Some((_, ext)) if known.contains(&ext.to_ascii_lowercase().as_str()) => Some(stem),
+        _ => Some(format!(
+            "{stem}.{}",
+            extension_for_content_type(content_type)?
+        )),
+    }
+}
+
+/// Download `url` into a temporary file and return its path, which can be
+/// read like any local input.
+///
+/// Fails on HTTP errors, timeouts, inputs over 64 MiB, and content whose
+/// format neither the URL nor the `Content-Type` reveals.
+pub fn fetch_input(url: &str) -> io::Result<PathBuf> {
+    let url = raw_url(url.trim());
+    let response = ureq::get(&url)
+        .timeout(FETCH_TIMEOUT)
+        .call()
+        .map_err(|e| io::Error::other(format!("could not fetch {url}: {e}")))?;
+    let content_type = response.content_type().to_string();
+    let file_name = file_name_for(&url, &content_type).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "cannot tell the format of {url} (Content-Type {content_type}); \
+                 use a URL ending in .csv or .md"
+            ),
+        )
+    })?;
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_INPUT_BYTES + 1)
+        .read_to_end(&mut body)?;
+    if body.len() as u64 > MAX_INPUT_BYTES {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{url} is larger than {} MiB", MAX_INPUT_BYTES >> 20),
+        ));
+    }
+    let dir = std::env::temp_dir().join(format!("eqp_remote_{}", std::process::id()));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    fs::write(&path, body)?;
+    Ok(path)
+}
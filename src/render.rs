@@ -0,0 +1,3052 @@
+//! Rendering equations to PDF/SVG via tectonic and pdftocairo.
+//!
+//! ```no_run
+//! use equation_processor::model::Equation;
+//! use equation_processor::render::{render_batch, RenderOptions, TectonicConfig};
+//! use std::path::PathBuf;
+//!
+//! let equations = vec![Equation::new(true, "energy", "E = mc^2")];
+//! let out = PathBuf::from("./output");
+//! let tectonic = TectonicConfig::from_env();
+//! let options = RenderOptions::builder().delete_intermediates(true).build();
+//! for warning in render_batch(&equations, &out, &tectonic, &options)? {
+//!     eprintln!("Warning: {warning}");
+//! }
+//! # Ok::<(), equation_processor::EquationError>(())
+//! ```
+
+use crate::cache::{cache_key, RenderCache};
+use crate::children;
+use crate::error::EquationError;
+use crate::hooks::{PostRenderHook, DEFAULT_HOOK_TIMEOUT};
+use crate::model::{Background, Color, Equation, EquationKind, LatexEngine, StandaloneOptions};
+use crate::process::{apply_batch_settings, check_batch, notify_outcome, render_outputs};
+use crate::report::{archive_changed, html_escape, svg_dimensions, ContentManifest};
+use crate::shard::Shard;
+use indicatif::{MultiProgress, ProgressBar, ProgressState, ProgressStyle};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Environment variable selecting tectonic's cache directory.
+pub const CACHE_DIR_ENV: &str = "EQUATION_PROCESSOR_CACHE_DIR";
+/// Environment variable selecting the tectonic bundle (path or URL).
+pub const BUNDLE_ENV: &str = "EQUATION_PROCESSOR_BUNDLE";
+/// Environment variable enabling offline mode (`1`/`true`/`yes`).
+pub const OFFLINE_ENV: &str = "EQUATION_PROCESSOR_OFFLINE";
+
+/// How tectonic is invoked: cache location, bundle and network policy.
+#[derive(Debug, Clone, Default)]
+pub struct TectonicConfig {
+    /// Shared cache directory, passed to tectonic as `TECTONIC_CACHE_DIR`
+    pub cache_dir: Option<PathBuf>,
+    /// Bundle file or URL, passed as `--bundle`
+    pub bundle: Option<String>,
+    /// Only use cached resources, never fetch from the network
+    pub offline: bool,
+    /// Extra environment variables for tectonic and `pdftocairo`, e.g.
+    /// `SOURCE_DATE_EPOCH` or `TEXINPUTS`; later entries win
+    pub env: Vec<(String, String)>,
+    /// Build reproducibly, dating every PDF at these seconds since 1970:
+    /// the engines get `SOURCE_DATE_EPOCH` and `FORCE_SOURCE_DATE=1`, and
+    /// tectonic runs in deterministic mode, so the PDF's dates and trailer
+    /// ID depend on the input only. Unchanged equations then render to
+    /// byte-identical artifacts. An equation's `SOURCE_DATE_EPOCH` in
+    /// [`env`](TectonicConfig::env) or its `%%env%%` tag takes precedence.
+    pub source_date_epoch: Option<u64>,
+}
+
+impl TectonicConfig {
+    /// Read the configuration from the `EQUATION_PROCESSOR_*` variables.
+    pub fn from_env() -> Self {
+        TectonicConfig::default().with_env_overrides()
+    }
+
+    /// This configuration with the `EQUATION_PROCESSOR_*` variables that
+    /// are set taking precedence, e.g. over a config file.
+    pub fn with_env_overrides(mut self) -> Self {
+        if let Some(dir) = std::env::var_os(CACHE_DIR_ENV) {
+            self.cache_dir = Some(PathBuf::from(dir));
+        }
+        if let Ok(bundle) = std::env::var(BUNDLE_ENV) {
+            self.bundle = Some(bundle);
+        }
+        self.offline |= std::env::var(OFFLINE_ENV)
+            .is_ok_and(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"));
+        self
+    }
+
+    /// This configuration with `env` appended, so those variables win.
+    pub fn with_env(&self, env: &[(String, String)]) -> TectonicConfig {
+        let mut config = self.clone();
+        config.env.extend_from_slice(env);
+        config
+    }
+
+    /// Build the base tectonic command honoring this configuration.
+    pub(crate) fn command(&self) -> Command {
+        let mut cmd = Command::new("tectonic");
+        if let Some(dir) = &self.cache_dir {
+            cmd.env("TECTONIC_CACHE_DIR", dir);
+        }
+        if let Some(bundle) = &self.bundle {
+            cmd.arg("--bundle").arg(bundle);
+        }
+        if self.offline {
+            cmd.arg("--only-cached");
+        }
+        if self.source_date_epoch.is_some() {
+            cmd.args(["-Z", "deterministic-mode"]);
+        }
+        self.set_env(&mut cmd);
+        cmd
+    }
+
+    /// Give `cmd` the variables of this configuration: the reproducible
+    /// build date, then [`env`](TectonicConfig::env), which wins.
+    pub(crate) fn set_env(&self, cmd: &mut Command) {
+        if let Some(epoch) = self.source_date_epoch {
+            cmd.env("SOURCE_DATE_EPOCH", epoch.to_string())
+                .env("FORCE_SOURCE_DATE", "1");
+        }
+        cmd.envs(self.env.iter().map(|(k, v)| (k, v)));
+    }
+
+    /// Compile a complete LaTeX document, such as a
+    /// [beamer deck](crate::report::write_beamer), into `output_dir`.
+    pub fn compile_document(
+        &self,
+        tex_path: &Path,
+        output_dir: &Path,
+    ) -> Result<(), EquationError> {
+        self.compile_document_with(LatexEngine::Tectonic, tex_path, output_dir)
+    }
+
+    /// Like [`compile_document`](TectonicConfig::compile_document), with
+    /// `engine`.
+    pub fn compile_document_with(
+        &self,
+        engine: LatexEngine,
+        tex_path: &Path,
+        output_dir: &Path,
+    ) -> Result<(), EquationError> {
+        self.compile(engine, tex_path, output_dir, None)
+    }
+
+    /// The `engine` command compiling `tex_path` into `output_dir`, run
+    /// inside `work_dir` if given. Engines other than tectonic only receive
+    /// the environment variables of this configuration.
+    fn compile_command(
+        &self,
+        engine: LatexEngine,
+        tex_path: &Path,
+        output_dir: &Path,
+        work_dir: Option<&Path>,
+    ) -> Command {
+        let mut cmd = engine.backend().command(self, tex_path, output_dir);
+        if let Some(dir) = work_dir {
+            cmd.current_dir(dir);
+        }
+        cmd
+    }
+
+    /// Compile `tex_path` into `output_dir` with `engine`, optionally running
+    /// inside `work_dir`; failures are classified by the engine's
+    /// [`RenderBackend::failure`](crate::backend::RenderBackend::failure).
+    fn compile(
+        &self,
+        engine: LatexEngine,
+        tex_path: &Path,
+        output_dir: &Path,
+        work_dir: Option<&Path>,
+    ) -> Result<(), EquationError> {
+        // Relative output paths must not resolve against the work dir.
+        let output_dir = match work_dir {
+            Some(_) => fs::canonicalize(output_dir)?,
+            None => output_dir.to_path_buf(),
+        };
+        let output = children::output(
+            self.compile_command(engine, tex_path, &output_dir, work_dir)
+                .stdin(Stdio::null()),
+        )
+        .map_err(|e| EquationError::spawn(engine.program(), e))?;
+        let backend = engine.backend();
+        if output.status.success() {
+            for extension in backend.leftovers() {
+                if let Some(name) = tex_path.with_extension(extension).file_name() {
+                    let _ = fs::remove_file(output_dir.join(name));
+                }
+            }
+            return Ok(());
+        }
+        // TeX engines print their errors to stdout, tectonic to stderr.
+        let log = [&output.stdout, &output.stderr]
+            .map(|stream| String::from_utf8_lossy(stream).trim().to_string())
+            .join("\n");
+        Err(backend.failure(self, tex_path.to_path_buf(), log))
+    }
+}
+
+/// Pre-warm the tectonic cache by compiling a sample equation.
+///
+/// This downloads every package used by the standard preamble so later
+/// runs can use offline mode. If compiling fails, its work directory is kept
+/// so the `.tex` file named in the error can be inspected.
+pub fn prefetch(config: &TectonicConfig) -> Result<(), EquationError> {
+    if config.offline {
+        return Err(io::Error::other(
+            "prefetch needs network access and cannot run in offline mode",
+        )
+        .into());
+    }
+    if let Some(dir) = &config.cache_dir {
+        fs::create_dir_all(dir)?;
+    }
+    let work_dir = std::env::temp_dir().join(format!("eqp_prefetch_{}", std::process::id()));
+    fs::create_dir_all(&work_dir)?;
+    let eq = Equation::new(true, "prefetch", r"\frac{a}{b} = \sfrac{1}{2}");
+    let tex_path = work_dir.join("prefetch.tex");
+    fs::write(&tex_path, eq.generate_latex("#000000"))?;
+    let output = children::output(
+        config
+            .command()
+            .arg(&tex_path)
+            .arg("--outdir")
+            .arg(&work_dir)
+            .stdin(Stdio::null()),
+    );
+    let output = output.map_err(|e| {
+        let _ = fs::remove_dir_all(&work_dir);
+        EquationError::spawn("tectonic", e)
+    })?;
+    if output.status.success() {
+        let _ = fs::remove_dir_all(&work_dir);
+        Ok(())
+    } else {
+        Err(EquationError::LatexCompileFailed {
+            engine: LatexEngine::Tectonic,
+            tex: tex_path,
+            log: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+/// Full path of the external `program` on `PATH`, if it is installed.
+pub fn find_tool(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .flat_map(|dir| {
+            [
+                dir.join(program),
+                dir.join(program)
+                    .with_extension(std::env::consts::EXE_EXTENSION),
+            ]
+        })
+        .find(|candidate| candidate.is_file())
+}
+
+/// External programs a batch with `options` needs but [`find_tool`] cannot
+/// find: its [`engine`](RenderOptions::engine), and `pdftocairo` unless only
+/// PDFs are written or the crate converts them in-process (the `poppler`
+/// feature).
+pub fn missing_tools(options: &RenderOptions) -> Vec<&'static str> {
+    let mut tools = vec![options.engine.program()];
+    if options.image_formats().next().is_some() && !cfg!(feature = "poppler") {
+        tools.push("pdftocairo");
+    }
+    tools.retain(|tool| find_tool(tool).is_none());
+    tools
+}
+
+/// Options applying to every equation of a batch.
+///
+/// Build them with [`RenderOptions::builder`] or by struct update from
+/// [`Default`]:
+///
+/// ```
+/// use equation_processor::render::{OutputFormat, RenderOptions};
+///
+/// let options = RenderOptions::builder()
+///     .color("#1F4E79".parse().unwrap())
+///     .formats([OutputFormat::Pdf])
+///     .font_size(12.0)
+///     .padding("4pt")
+///     .delete_intermediates(true)
+///     .build();
+/// assert_eq!(options.color, "#1F4E79");
+/// assert_eq!(options.standalone.border.as_deref(), Some("4pt"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Font color as `#RRGGBB`; an equation's own color takes precedence
+    pub color: String,
+    /// LaTeX engine for equations without an `engine` tag, e.g. pdflatex
+    /// where only TeX Live is installed
+    pub engine: LatexEngine,
+    /// Font size in points; `None` keeps the default of 14.4pt (`\Large`)
+    pub font_size: Option<f32>,
+    /// Document to typeset equations in instead of the built-in one
+    pub template: Option<LatexTemplate>,
+    /// Font package loaded for every equation; `None` keeps LaTeX's
+    /// Computer Modern
+    pub font: Option<String>,
+    /// Fill behind every equation; transparent by default
+    pub background: Background,
+    /// Remove `.tex`/`.pdf` intermediates after each equation
+    pub delete_intermediates: bool,
+    /// Treat all math equations as mhchem chemical formulas
+    pub chemistry: bool,
+    /// Skip equations a previous, interrupted run already rendered, see
+    /// [`RenderJournal`]
+    pub resume: bool,
+    /// Render equations with identical bodies once and link the duplicates
+    pub dedup: Option<LinkStrategy>,
+    /// Global `standalone` class options; an equation's own options take precedence
+    pub standalone: StandaloneOptions,
+    /// Prepended to every output file name (e.g. `lecture3_`)
+    pub name_prefix: String,
+    /// Appended to every output file name
+    pub name_suffix: String,
+    /// Colors by equation name (as parsed, before affixes), overriding
+    /// `%%color%%` tags; see [`apply_color_map`]
+    pub colors: HashMap<String, String>,
+    /// Render inactive equations too, for full rebuilds
+    pub include_inactive: bool,
+    /// Also write `.tex` snippets for LaTeX documents, see
+    /// [`write_tex_snippets`](crate::report::write_tex_snippets)
+    pub tex_snippets: bool,
+    /// Name artifacts by content hash, see
+    /// [`content_address`](crate::report::content_address)
+    pub content_addressed: bool,
+    /// Artifacts to produce; without an image format the conversion stage is
+    /// skipped. Defaults to SVG.
+    pub formats: Vec<OutputFormat>,
+    /// Compression and version of the compiled PDFs
+    pub pdf: PdfOptions,
+    /// Also write and compile a beamer deck with one frame per equation, see
+    /// [`write_beamer`](crate::report::write_beamer)
+    pub beamer: bool,
+    /// Also write an HTML gallery of the artifacts, see
+    /// [`write_gallery`](crate::report::write_gallery)
+    pub html_gallery: bool,
+    /// Also write a Markdown page of the artifacts, see
+    /// [`write_markdown`](crate::report::write_markdown)
+    pub markdown: bool,
+    /// Give the images of the Markdown page their rendered `width` and
+    /// `height`
+    pub image_dimensions: bool,
+    /// Pop up a desktop notification when the batch completes or fails
+    pub notify: bool,
+    /// Move the artifacts of changed equations to `history/` before
+    /// rendering, see [`archive_changed`](crate::report::archive_changed)
+    pub keep_history: bool,
+    /// Commands run on each artifact after rendering, see
+    /// [`run_hooks`](crate::hooks::run_hooks)
+    pub hooks: Vec<PostRenderHook>,
+    /// How long a hook may run on one artifact
+    pub hook_timeout: Duration,
+    /// Equations compiled at the same time, see [`render_equations_parallel`]
+    pub jobs: usize,
+    /// Retries of equations failing transiently, see [`RetryingRenderer`]
+    pub retry: RetryPolicy,
+    /// Skip equations whose inputs are unchanged since the last run, see
+    /// [`cache`](crate::cache)
+    pub cache: bool,
+    /// Refuse batches whose colors are hard to tell apart, see
+    /// [`check_palette`](crate::palette::check_palette)
+    pub check_palette: bool,
+    /// Render only this part of the batch, see [`Shard`]
+    pub shard: Option<Shard>,
+}
+
+/// Builds [`RenderOptions`] one setting at a time; see
+/// [`RenderOptions::builder`]. Settings without a method here are set on
+/// the built options directly.
+#[derive(Debug, Clone, Default)]
+pub struct RenderOptionsBuilder {
+    options: RenderOptions,
+}
+
+impl RenderOptionsBuilder {
+    /// Font color of equations without a color of their own.
+    pub fn color(mut self, color: Color) -> Self {
+        self.options.color = color.to_string();
+        self
+    }
+
+    /// LaTeX engine of equations without an engine of their own.
+    pub fn engine(mut self, engine: LatexEngine) -> Self {
+        self.options.engine = engine;
+        self
+    }
+
+    /// Artifacts to produce.
+    pub fn formats(mut self, formats: impl IntoIterator<Item = OutputFormat>) -> Self {
+        self.options.formats = formats.into_iter().collect();
+        self
+    }
+
+    /// Font size in points.
+    pub fn font_size(mut self, points: f32) -> Self {
+        self.options.font_size = Some(points);
+        self
+    }
+
+    /// Document to typeset equations in, see [`LatexTemplate`].
+    pub fn template(mut self, template: LatexTemplate) -> Self {
+        self.options.template = Some(template);
+        self
+    }
+
+    /// Font package to load, e.g. `newtxmath`; `None` for Computer Modern.
+    pub fn font(mut self, package: Option<&str>) -> Self {
+        self.options.font = package.map(str::to_string);
+        self
+    }
+
+    /// Fill behind every equation.
+    pub fn background(mut self, background: Background) -> Self {
+        self.options.background = background;
+        self
+    }
+
+    /// Padding around equations without a border of their own, e.g. `2pt`
+    /// or `1pt 2pt 1pt 2pt`.
+    pub fn padding(mut self, padding: &str) -> Self {
+        self.options.standalone.border = Some(padding.to_string());
+        self
+    }
+
+    /// Whether to remove the `.tex` and `.pdf` intermediates.
+    pub fn delete_intermediates(mut self, delete: bool) -> Self {
+        self.options.delete_intermediates = delete;
+        self
+    }
+
+    /// Whether to treat math equations as mhchem formulas.
+    pub fn chemistry(mut self, chemistry: bool) -> Self {
+        self.options.chemistry = chemistry;
+        self
+    }
+
+    /// Whether to resume an interrupted batch.
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.options.resume = resume;
+        self
+    }
+
+    /// Render duplicates once and link them with `strategy`.
+    pub fn dedup(mut self, strategy: LinkStrategy) -> Self {
+        self.options.dedup = Some(strategy);
+        self
+    }
+
+    /// Equations compiled at the same time.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.options.jobs = jobs;
+        self
+    }
+
+    /// The options.
+    pub fn build(self) -> RenderOptions {
+        self.options
+    }
+}
+
+/// PDF settings, applied by each engine's
+/// [`RenderBackend::pdf_setup`](crate::backend::RenderBackend::pdf_setup).
+///
+/// Fonts are embedded as subsets of the glyphs used unless `full_fonts`
+/// is set; `index.json` lists them per equation (see
+/// [`PdfProperties`](crate::report::PdfProperties)).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PdfOptions {
+    /// Stream compression level, 0 (none) to 9 (smallest)
+    pub compression: Option<u8>,
+    /// PDF minor version `1.x`; from 1.5 objects may be packed into compressed streams
+    pub minor_version: Option<u8>,
+    /// Embed complete fonts instead of subsets of the glyphs used; only
+    /// LuaLaTeX can, the `xdvipdfmx` and pdfTeX backends always subset
+    pub full_fonts: bool,
+}
+
+impl PdfOptions {
+    /// `\special`s configuring `xdvipdfmx`, placed on the first page:
+    /// compression and version, which tectonic and xelatex apply.
+    pub fn specials(&self) -> String {
+        let mut specials = String::new();
+        if let Some(level) = self.compression {
+            specials.push_str(&format!("\\special{{dvipdfmx:config z {level}}}"));
+        }
+        if let Some(minor) = self.minor_version {
+            specials.push_str(&format!("\\special{{dvipdfmx:config V {minor}}}"));
+        }
+        specials
+    }
+}
+
+/// Parse a PDF version such as `1.4` into its minor version (1.3 to 1.7).
+pub fn parse_pdf_version(s: &str) -> Result<u8, String> {
+    s.trim()
+        .strip_prefix("1.")
+        .and_then(|minor| minor.parse().ok())
+        .filter(|minor| (3..=7).contains(minor))
+        .ok_or_else(|| {
+            format!(
+                "unsupported PDF version '{}' (expected 1.3 to 1.7)",
+                s.trim()
+            )
+        })
+}
+
+/// Font package loaded unless another one, or none, is chosen.
+pub const DEFAULT_FONT_PACKAGE: &str = "gfsneohellenicot";
+
+/// Font packages with math support that are offered for selection; any
+/// other package name works too.
+pub const FONT_PACKAGES: &[&str] = &[
+    DEFAULT_FONT_PACKAGE,
+    "newtxmath",
+    "stix2",
+    "libertinus",
+    "fourier",
+    "mathpazo",
+];
+
+/// Check a font package name such as `newtxmath`, which is put into
+/// `\usepackage{...}` as is.
+pub fn parse_font_package(s: &str) -> Result<String, String> {
+    let package = s.trim();
+    if package.is_empty()
+        || !package
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(format!(
+            "invalid font package '{package}' (expected a package name such as newtxmath)"
+        ));
+    }
+    Ok(package.to_string())
+}
+
+/// Placeholders a [`LatexTemplate`] can contain, each written as
+/// `{{NAME}}`.
+pub const TEMPLATE_PLACEHOLDERS: &[&str] = &[
+    "BODY",
+    "COLOR",
+    "PACKAGES",
+    "FONT",
+    "BACKGROUND",
+    "CLASS_OPTIONS",
+    "SIZE",
+    "PDF_SPECIALS",
+    "NAME",
+];
+
+/// A user-supplied LaTeX document that equations are typeset in instead of
+/// the built-in one, so packages, document class and sizing can be changed.
+///
+/// The template is filled in per equation by replacing these placeholders:
+///
+/// - `{{BODY}}`: the equation, ready to typeset (`$...$` for math, `\ce`
+///   for chemistry, a `tikzpicture` for TikZ); required
+/// - `{{COLOR}}`: the font color as six hex digits, for
+///   `\definecolor{...}{HTML}{{{COLOR}}}`
+/// - `{{PACKAGES}}`: packages the equation's kind needs (mhchem, tikz)
+/// - `{{FONT}}`: `\usepackage` of the [font](RenderOptions::font), or
+///   nothing for Computer Modern
+/// - `{{BACKGROUND}}`: `\pagecolor` of the
+///   [background](RenderOptions::background), or nothing if transparent
+/// - `{{CLASS_OPTIONS}}`: `standalone` class options (border, crop, varwidth)
+/// - `{{SIZE}}`: the font size command, `\Large` or `\fontsize`
+/// - `{{PDF_SPECIALS}}`: PDF compression and version settings
+/// - `{{NAME}}`: the equation's name
+///
+/// ```
+/// use equation_processor::model::Equation;
+/// use equation_processor::render::{LatexTemplate, RenderOptions};
+/// use std::path::Path;
+///
+/// let template = LatexTemplate::parse(
+///     Path::new("mine.tex"),
+///     r"\documentclass{standalone}
+/// \usepackage{xcolor}
+/// \begin{document}\textcolor[HTML]{{{COLOR}}}{{{BODY}}}\end{document}",
+/// )?;
+/// let options = RenderOptions::builder().template(template).build();
+/// let latex = Equation::new(true, "energy", "E = mc^2").generate_latex_with("#1F4E79", &options);
+/// assert!(latex.contains(r"\textcolor[HTML]{1F4E79}{$E = mc^2$}"));
+/// # Ok::<(), equation_processor::EquationError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatexTemplate {
+    /// Where the template was read from
+    pub path: PathBuf,
+    source: String,
+}
+
+impl LatexTemplate {
+    /// Read and check the template at `path`.
+    pub fn read(path: &Path) -> Result<Self, EquationError> {
+        Self::parse(path, &fs::read_to_string(path)?)
+    }
+
+    /// Check `source`, read from `path`: every `{{NAME}}` in it must be one
+    /// of [`TEMPLATE_PLACEHOLDERS`], and `{{BODY}}` must be there.
+    pub fn parse(path: &Path, source: &str) -> Result<Self, EquationError> {
+        let error = |line, reason| EquationError::ParseError {
+            path: path.to_path_buf(),
+            line,
+            reason,
+        };
+        for (number, line) in source.lines().enumerate() {
+            for name in placeholders(line) {
+                if !TEMPLATE_PLACEHOLDERS.contains(&name) {
+                    return Err(error(
+                        Some(number + 1),
+                        format!(
+                            "unknown placeholder {{{{{name}}}}} (expected one of {})",
+                            TEMPLATE_PLACEHOLDERS.join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+        if !source.contains("{{BODY}}") {
+            return Err(error(
+                None,
+                String::from("the template has no {{BODY}} placeholder"),
+            ));
+        }
+        Ok(LatexTemplate {
+            path: path.to_path_buf(),
+            source: source.to_string(),
+        })
+    }
+
+    /// The template as written.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The template with each `{{NAME}}` of `values` replaced.
+    pub fn fill(&self, values: &[(&str, &str)]) -> String {
+        let mut latex = self.source.clone();
+        for (name, value) in values {
+            latex = latex.replace(&format!("{{{{{name}}}}}"), value);
+        }
+        latex
+    }
+}
+
+/// Names of the `{{NAME}}` placeholders in `line`: upper-case letters and
+/// underscores between double braces. Other double braces, which LaTeX uses
+/// for nested groups, are left alone.
+fn placeholders(line: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("{{") {
+        rest = rest[start..].trim_start_matches('{');
+        let Some(end) = rest.find("}}") else { break };
+        let name = &rest[..end];
+        if !name.is_empty() && name.chars().all(|c| c.is_ascii_uppercase() || c == '_') {
+            names.push(name);
+            rest = &rest[end + 2..];
+        }
+    }
+    names
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            color: "#000000".into(),
+            engine: LatexEngine::Tectonic,
+            font_size: None,
+            template: None,
+            font: Some(DEFAULT_FONT_PACKAGE.into()),
+            background: Background::Transparent,
+            delete_intermediates: false,
+            chemistry: false,
+            resume: false,
+            dedup: None,
+            standalone: StandaloneOptions::default(),
+            name_prefix: String::new(),
+            name_suffix: String::new(),
+            colors: HashMap::new(),
+            include_inactive: false,
+            tex_snippets: false,
+            content_addressed: false,
+            formats: vec![OutputFormat::Image(ImageFormat::Svg)],
+            pdf: PdfOptions::default(),
+            beamer: false,
+            html_gallery: false,
+            markdown: false,
+            image_dimensions: false,
+            notify: false,
+            keep_history: false,
+            hooks: Vec::new(),
+            hook_timeout: DEFAULT_HOOK_TIMEOUT,
+            jobs: 1,
+            retry: RetryPolicy::default(),
+            cache: true,
+            check_palette: false,
+            shard: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    /// A [`RenderOptionsBuilder`] starting from the defaults.
+    pub fn builder() -> RenderOptionsBuilder {
+        RenderOptionsBuilder::default()
+    }
+
+    /// Image formats the compiled PDF is converted to, in order.
+    pub fn image_formats(&self) -> impl Iterator<Item = ImageFormat> + '_ {
+        self.formats.iter().filter_map(|f| match f {
+            OutputFormat::Image(format) => Some(*format),
+            OutputFormat::Pdf => None,
+        })
+    }
+
+    /// Whether the PDF is a requested output, kept even when intermediates
+    /// are deleted.
+    pub fn keeps_pdf(&self) -> bool {
+        self.formats.contains(&OutputFormat::Pdf)
+    }
+}
+
+impl Equation {
+    /// Render to PDF and SVG, optionally cleaning up _aux files
+    #[deprecated(since = "0.2.0", note = "use `Equation::render_with`")]
+    pub fn render(
+        &self,
+        output_dir: &Path,
+        color: &str,
+        delete_intermediates: bool,
+    ) -> io::Result<()> {
+        let color: Color = color
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let options = RenderOptions::builder()
+            .color(color)
+            .delete_intermediates(delete_intermediates)
+            .build();
+        self.render_with(output_dir, &TectonicConfig::default(), &options)
+            .map_err(Into::into)
+    }
+
+    /// Render to the formats of `options` in `output_dir`, in the color of
+    /// `options` unless the equation has its own.
+    ///
+    /// The error says which step failed: compiling the LaTeX, converting
+    /// the PDF or finding the tools.
+    pub fn render_with(
+        &self,
+        output_dir: &Path,
+        tectonic: &TectonicConfig,
+        options: &RenderOptions,
+    ) -> Result<(), EquationError> {
+        self.render_in(
+            output_dir,
+            &options.color,
+            options.delete_intermediates,
+            tectonic,
+            options,
+        )
+    }
+
+    /// The engine this equation is compiled with: its `engine` tag, else
+    /// the batch's [`engine`](RenderOptions::engine).
+    pub fn engine_with(&self, options: &RenderOptions) -> LatexEngine {
+        self.engine.unwrap_or(options.engine)
+    }
+
+    /// [`render_with`](Equation::render_with) in `color`, deleting the
+    /// intermediates if `delete_intermediates` is set, whatever `options` say.
+    fn render_in(
+        &self,
+        output_dir: &Path,
+        color: &str,
+        delete_intermediates: bool,
+        tectonic: &TectonicConfig,
+        options: &RenderOptions,
+    ) -> Result<(), EquationError> {
+        if !self.active {
+            return Ok(());
+        }
+        fs::create_dir_all(output_dir)?;
+        let tectonic = &tectonic.with_env(&self.env);
+        let tex = self.generate_latex_with(color, options);
+        let tex_path = output_dir.join(format!("{}.tex", self.name));
+        fs::write(&tex_path, &tex)?;
+
+        let engine = self.engine_with(options);
+        if self.assets.is_empty() {
+            tectonic.compile(engine, &tex_path, output_dir, None)?;
+        } else {
+            let work_dir = self.prepare_asset_dir(&tex)?;
+            let result = tectonic.compile(
+                engine,
+                &work_dir.join(format!("{}.tex", self.name)),
+                output_dir,
+                Some(&work_dir),
+            );
+            let _ = fs::remove_dir_all(&work_dir);
+            result?;
+        }
+        for format in options.image_formats() {
+            self.convert_pdf_to(output_dir, format, &tectonic.env)?;
+        }
+        if let (Some(description), true) = (
+            &self.description,
+            options.image_formats().any(|f| f == ImageFormat::Svg),
+        ) {
+            let svg = output_dir.join(format!("{}.svg", self.name));
+            embed_svg_description(&svg, description)?;
+        }
+        if delete_intermediates {
+            self.cleanup_intermediate_files(output_dir, options.keeps_pdf())?;
+        }
+        Ok(())
+    }
+
+    /// The external commands [`render`](Equation::render) runs for this
+    /// equation, as shell command lines (see [`display_command`]): its
+    /// [engine](Equation::engine_with), then `pdftocairo` once per image
+    /// format.
+    pub fn planned_commands(
+        &self,
+        output_dir: &Path,
+        tectonic: &TectonicConfig,
+        options: &RenderOptions,
+    ) -> Vec<String> {
+        let tectonic = tectonic.with_env(&self.env);
+        let tex_path = output_dir.join(format!("{}.tex", self.name));
+        let engine = self.engine_with(options);
+        let compile = if self.assets.is_empty() {
+            tectonic.compile_command(engine, &tex_path, output_dir, None)
+        } else {
+            let work_dir = self.asset_dir();
+            let output_dir = fs::canonicalize(output_dir).unwrap_or(output_dir.to_path_buf());
+            tectonic.compile_command(
+                engine,
+                &work_dir.join(format!("{}.tex", self.name)),
+                &output_dir,
+                Some(&work_dir),
+            )
+        };
+        let pdf = output_dir.join(format!("{}.pdf", self.name));
+        let mut commands = vec![display_command(&compile)];
+        commands.extend(options.image_formats().map(|format| {
+            display_command(&pdftocairo_command(
+                &pdf,
+                output_dir,
+                format,
+                DEFAULT_DPI,
+                &tectonic.env,
+            ))
+        }));
+        commands
+    }
+
+    /// A fresh temporary compile directory for an equation with assets,
+    /// numbered per call so concurrent renders of the same name don't share it.
+    fn asset_dir(&self) -> PathBuf {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        std::env::temp_dir().join(format!(
+            "eqp_assets_{}_{}_{}",
+            std::process::id(),
+            CALLS.fetch_add(1, Ordering::Relaxed),
+            self.name
+        ))
+    }
+
+    /// Create a temporary compile directory holding the LaTeX source and
+    /// copies of all assets, so `\input`/`\includegraphics` resolve by file name.
+    fn prepare_asset_dir(&self, tex: &str) -> io::Result<PathBuf> {
+        let work_dir = self.asset_dir();
+        fs::create_dir_all(&work_dir)?;
+        fs::write(work_dir.join(format!("{}.tex", self.name)), tex)?;
+        for asset in &self.assets {
+            let file_name = asset.file_name().ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid asset path {}", asset.display()),
+                )
+            })?;
+            fs::copy(asset, work_dir.join(file_name)).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("asset {} for '{}': {e}", asset.display(), self.name),
+                )
+            })?;
+        }
+        Ok(work_dir)
+    }
+
+    /// Convert the .pdf to `format`
+    fn convert_pdf_to(
+        &self,
+        output_dir: &Path,
+        format: ImageFormat,
+        env: &[(String, String)],
+    ) -> Result<(), EquationError> {
+        let pdf = output_dir.join(format!("{}.pdf", self.name));
+        convert_pdf_with_env(&pdf, output_dir, format, DEFAULT_DPI, env).map(|_| ())
+    }
+
+    /// Remove .tex and, unless `keep_pdf`, .pdf intermediates
+    fn cleanup_intermediate_files(&self, output_dir: &Path, keep_pdf: bool) -> io::Result<()> {
+        let _ = fs::remove_file(output_dir.join(format!("{}.tex", self.name)));
+        if !keep_pdf {
+            let _ = fs::remove_file(output_dir.join(format!("{}.pdf", self.name)));
+        }
+        Ok(())
+    }
+
+    /// Generate LaTeX source including custom font and color
+    ///
+    /// The equation's own [`color`](Equation::color) takes precedence over `color`.
+    pub fn generate_latex(&self, color: &str) -> String {
+        self.generate_latex_with(color, &RenderOptions::default())
+    }
+
+    /// Generate LaTeX source, applying the batch-wide `options`; with a
+    /// [`template`](RenderOptions::template) the equation is filled into it
+    pub fn generate_latex_with(&self, color: &str, options: &RenderOptions) -> String {
+        let class_options = self.standalone.or(&options.standalone).class_options();
+        let size = match options.font_size {
+            Some(points) => format!("\\fontsize{{{points}}}{{{:.1}}}\\selectfont", points * 1.2),
+            None => String::from("\\Large"),
+        };
+        let code = self
+            .color
+            .as_deref()
+            .unwrap_or(color)
+            .trim_start_matches('#');
+        let (packages, content) = match self.kind {
+            EquationKind::Math => (String::new(), math_content(&self.body)),
+            EquationKind::Chemistry => (
+                String::from("\\usepackage[version=4]{mhchem}\n"),
+                format!("$\\ce{{{}}}$", self.body),
+            ),
+            EquationKind::TikZ => {
+                let mut packages = String::from("\\usepackage{tikz}\n");
+                if !self.tikz_libraries.is_empty() {
+                    packages.push_str(&format!(
+                        "\\usetikzlibrary{{{}}}\n",
+                        self.tikz_libraries.join(",")
+                    ));
+                }
+                (
+                    packages,
+                    format!(
+                        "\\begin{{tikzpicture}}\n{}\n\\end{{tikzpicture}}",
+                        self.body
+                    ),
+                )
+            }
+        };
+        let (mut packages, content) = if self.display.is_empty() {
+            (packages, content)
+        } else {
+            (
+                format!("\\usepackage{{graphicx}}\n{packages}"),
+                self.display.wrap(&content),
+            )
+        };
+        if let Some(preamble) = self.preamble.as_deref().map(str::trim) {
+            if !preamble.is_empty() {
+                packages.push_str(preamble);
+                packages.push('\n');
+            }
+        }
+        let font = options
+            .font
+            .as_ref()
+            .map(|package| format!("\\usepackage{{{package}}}"))
+            .unwrap_or_default();
+        // Settings the engine cannot apply are reported before the batch,
+        // see `Capabilities::unsupported`.
+        let pdf_setup = self
+            .engine_with(options)
+            .backend()
+            .pdf_setup(&options.pdf)
+            .unwrap_or_default();
+        if let Some(template) = &options.template {
+            return template.fill(&[
+                ("BODY", &content),
+                ("COLOR", code),
+                ("PACKAGES", &packages),
+                ("FONT", &font),
+                ("BACKGROUND", &options.background.latex()),
+                ("CLASS_OPTIONS", &class_options),
+                ("SIZE", &size),
+                ("PDF_SPECIALS", &pdf_setup),
+                ("NAME", &self.name),
+            ]);
+        }
+        format!(
+            r#"\documentclass[{}]{{standalone}}
+            \usepackage{{amsmath}}
+            \usepackage{{xfrac}}
+            {}
+            \usepackage{{xcolor}}
+            {}\definecolor{{equationcolor}}{{HTML}}{{{}}}
+            \begin{{document}}{}{}
+            \setbox0\hbox{{{} \textcolor{{equationcolor}}{{{}}}}}
+            \dimen0=12mm
+            \ifdim\ht0<\dimen0 \ht0=\dimen0 \fi
+            \ifdim\dp0<5mm \dp0=5mm \fi
+            \box0
+            \end{{document}}"#,
+            class_options,
+            font,
+            packages,
+            code,
+            pdf_setup,
+            options.background.latex(),
+            size,
+            content
+        )
+    }
+
+    /// LaTeX source for `\input` into a document: an `equation` environment
+    /// labelled `eq:<name>` (display environments in the body are kept, with
+    /// the label added), or a bare `tikzpicture` for TikZ drawings.
+    pub fn latex_snippet(&self) -> String {
+        let label = format!("\\label{{eq:{}}}", self.name);
+        let body = self.body.trim();
+        let equation =
+            |content: &str| format!("\\begin{{equation}}{label}\n  {content}\n\\end{{equation}}\n");
+        match self.kind {
+            EquationKind::Math => {
+                if let Some((env, _, inner)) = display_environment(body) {
+                    return format!("\\begin{{{env}}}{label}\n  {inner}\n\\end{{{env}}}\n");
+                }
+                equation(strip_math_delimiters(body).map_or(body, |(inner, _)| inner))
+            }
+            EquationKind::Chemistry => equation(&format!("\\ce{{{body}}}")),
+            EquationKind::TikZ => {
+                format!("\\begin{{tikzpicture}}\n{body}\n\\end{{tikzpicture}}\n")
+            }
+        }
+    }
+}
+
+/// Display environments that cannot appear inside the `\hbox` the template
+/// typesets into, with the inner (math-mode) environment used instead.
+/// `None` means the environment's content is used directly.
+const DISPLAY_ENVIRONMENTS: &[(&str, Option<&str>)] = &[
+    ("equation", None),
+    ("equation*", None),
+    ("displaymath", None),
+    ("align", Some("aligned")),
+    ("align*", Some("aligned")),
+    ("gather", Some("gathered")),
+    ("gather*", Some("gathered")),
+    ("multline", Some("gathered")),
+    ("multline*", Some("gathered")),
+];
+
+/// Content of `body` between `open` and `close`, trimmed.
+fn delimited<'a>(body: &'a str, open: &str, close: &str) -> Option<&'a str> {
+    body.strip_prefix(open)
+        .and_then(|b| b.strip_suffix(close))
+        .map(str::trim)
+}
+
+/// Content of a body already in `\[...\]`, `\(...\)` or `$...$`, and whether
+/// it was display math.
+fn strip_math_delimiters(body: &str) -> Option<(&str, bool)> {
+    delimited(body, "\\[", "\\]")
+        .map(|inner| (inner, true))
+        .or_else(|| delimited(body, "\\(", "\\)").map(|inner| (inner, false)))
+        .or_else(|| delimited(body, "$", "$").map(|inner| (inner, false)))
+}
+
+/// A body consisting of one display environment: its entry in
+/// [`DISPLAY_ENVIRONMENTS`] and its content.
+fn display_environment(body: &str) -> Option<(&'static str, Option<&'static str>, &str)> {
+    DISPLAY_ENVIRONMENTS.iter().find_map(|&(env, replacement)| {
+        delimited(
+            body,
+            &format!("\\begin{{{env}}}"),
+            &format!("\\end{{{env}}}"),
+        )
+        .map(|inner| (env, replacement, inner))
+    })
+}
+
+/// Wrap a math body for the template, respecting existing delimiters.
+///
+/// Bodies already in `$...$`, `\(...\)` or `\[...\]`, or consisting of a
+/// single display environment such as `align*`, are unwrapped first so they
+/// are not wrapped twice; display forms keep `\displaystyle` and display
+/// environments become their inner counterparts (`align` → `aligned`).
+/// Math-mode environments like `aligned` or `pmatrix` need no special
+/// treatment and are wrapped like any other body.
+fn math_content(body: &str) -> String {
+    let body = body.trim();
+    if let Some((inner, display)) = strip_math_delimiters(body) {
+        return if display {
+            format!("$\\displaystyle {inner}$")
+        } else {
+            format!("${inner}$")
+        };
+    }
+    match display_environment(body) {
+        Some((_, None, inner)) => format!("$\\displaystyle {inner}$"),
+        Some((_, Some(inner_env), inner)) => {
+            format!("$\\displaystyle \\begin{{{inner_env}}}{inner}\\end{{{inner_env}}}$")
+        }
+        None => format!("${body}$"),
+    }
+}
+
+/// Raster resolution used for PNG conversion unless configured otherwise.
+pub const DEFAULT_DPI: u32 = 300;
+
+/// Image formats produced from a compiled PDF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Scalable vector graphics
+    Svg,
+    /// Raster image at a configurable DPI
+    Png,
+}
+
+impl ImageFormat {
+    /// File extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Svg => "svg",
+            ImageFormat::Png => "png",
+        }
+    }
+}
+
+impl FromStr for ImageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "svg" => Ok(ImageFormat::Svg),
+            "png" => Ok(ImageFormat::Png),
+            other => Err(format!(
+                "unknown image format '{other}' (expected svg or png)"
+            )),
+        }
+    }
+}
+
+/// An artifact a render keeps, selected with `--formats`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The compiled PDF, kept even when intermediates are deleted
+    Pdf,
+    /// An image converted from the PDF
+    Image(ImageFormat),
+}
+
+impl OutputFormat {
+    /// Every format, in the order `--formats` lists them.
+    pub const ALL: [OutputFormat; 3] = [
+        OutputFormat::Pdf,
+        OutputFormat::Image(ImageFormat::Svg),
+        OutputFormat::Image(ImageFormat::Png),
+    ];
+
+    /// File extension of the format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Pdf => "pdf",
+            OutputFormat::Image(format) => format.extension(),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "pdf" => Ok(OutputFormat::Pdf),
+            other => other
+                .parse()
+                .map(OutputFormat::Image)
+                .map_err(|_| format!("unknown output format '{other}' (expected pdf, svg or png)")),
+        }
+    }
+}
+
+/// Add `description` to the SVG at `path` as a `<desc>` element, the
+/// metadata screen readers and image tools show for it.
+pub fn embed_svg_description(path: &Path, description: &str) -> io::Result<()> {
+    let svg = fs::read_to_string(path)?;
+    let Some(root) = svg.find("<svg") else {
+        return Ok(());
+    };
+    let Some(end) = svg[root..].find('>').map(|i| root + i + 1) else {
+        return Ok(());
+    };
+    if svg[..end].ends_with("/>") {
+        return Ok(());
+    }
+    let desc = format!("\n<desc>{}</desc>", html_escape(description));
+    fs::write(path, format!("{}{desc}{}", &svg[..end], &svg[end..]))
+}
+
+/// Convert `pdf` to `format` with `pdftocairo`, writing `<stem>.<ext>` into
+/// `output_dir`. `dpi` only affects raster formats. Returns the written path.
+///
+/// Built with the `poppler` feature, PDFs are converted in-process with the
+/// poppler library when `pdftocairo` is not on `PATH`, e.g. on Windows.
+pub fn convert_pdf(
+    pdf: &Path,
+    output_dir: &Path,
+    format: ImageFormat,
+    dpi: u32,
+) -> Result<PathBuf, EquationError> {
+    convert_pdf_with_env(pdf, output_dir, format, dpi, &[])
+}
+
+/// [`convert_pdf`], running `pdftocairo` with the extra variables `env`.
+pub fn convert_pdf_with_env(
+    pdf: &Path,
+    output_dir: &Path,
+    format: ImageFormat,
+    dpi: u32,
+    env: &[(String, String)],
+) -> Result<PathBuf, EquationError> {
+    let stem = pdf.file_stem().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid PDF path {}", pdf.display()),
+        )
+    })?;
+    let target = output_dir.join(stem).with_extension(format.extension());
+    #[cfg(feature = "poppler")]
+    if find_tool("pdftocairo").is_none() {
+        convert_pdf_in_process(pdf, &target, format, dpi)?;
+        return Ok(target);
+    }
+    let output = children::output(
+        pdftocairo_command(pdf, output_dir, format, dpi, env).stdin(Stdio::null()),
+    )
+    .map_err(|e| EquationError::spawn("pdftocairo", e))?;
+    if output.status.success() {
+        Ok(target)
+    } else {
+        Err(EquationError::SvgConversionFailed {
+            pdf: pdf.to_path_buf(),
+            format,
+            log: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        })
+    }
+}
+
+/// Convert the first page of `pdf` to `format` at `target` with the poppler
+/// and cairo libraries, as `pdftocairo` does: SVG at the page size, PNG at
+/// `dpi` with a transparent background.
+#[cfg(feature = "poppler")]
+fn convert_pdf_in_process(
+    pdf: &Path,
+    target: &Path,
+    format: ImageFormat,
+    dpi: u32,
+) -> Result<(), EquationError> {
+    let failed = |log: String| EquationError::SvgConversionFailed {
+        pdf: pdf.to_path_buf(),
+        format,
+        log,
+    };
+    let uri = cairo::glib::filename_to_uri(std::path::absolute(pdf)?, None)
+        .map_err(|e| failed(e.to_string()))?;
+    let document = poppler::Document::from_file(&uri, None).map_err(|e| failed(e.to_string()))?;
+    let page = document
+        .page(0)
+        .ok_or_else(|| failed("the PDF has no pages".into()))?;
+    let (width, height) = page.size();
+    match format {
+        ImageFormat::Svg => {
+            let surface = cairo::SvgSurface::new(width, height, Some(target))
+                .map_err(|e| failed(e.to_string()))?;
+            let context = cairo::Context::new(&surface).map_err(|e| failed(e.to_string()))?;
+            page.render_for_printing(&context);
+            context.show_page().map_err(|e| failed(e.to_string()))?;
+            surface.finish();
+        }
+        ImageFormat::Png => {
+            let scale = f64::from(dpi) / 72.0;
+            let surface = cairo::ImageSurface::create(
+                cairo::Format::ARgb32,
+                (width * scale).ceil() as i32,
+                (height * scale).ceil() as i32,
+            )
+            .map_err(|e| failed(e.to_string()))?;
+            let context = cairo::Context::new(&surface).map_err(|e| failed(e.to_string()))?;
+            context.scale(scale, scale);
+            page.render(&context);
+            drop(context);
+            surface
+                .write_to_png(&mut File::create(target)?)
+                .map_err(|e| failed(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
+
+/// The `pdftocairo` command converting `pdf` to `format` in `output_dir`.
+fn pdftocairo_command(
+    pdf: &Path,
+    output_dir: &Path,
+    format: ImageFormat,
+    dpi: u32,
+    env: &[(String, String)],
+) -> Command {
+    let stem = pdf.file_stem().unwrap_or_default();
+    let mut cmd = Command::new("pdftocairo");
+    cmd.envs(env.iter().map(|(k, v)| (k, v)));
+    match format {
+        ImageFormat::Svg => cmd
+            .arg("-svg")
+            .arg(pdf)
+            .arg(output_dir.join(stem).with_extension("svg")),
+        // pdftocairo appends the extension itself for raster output.
+        ImageFormat::Png => cmd
+            .args(["-png", "-singlefile", "-transp", "-r"])
+            .arg(dpi.to_string())
+            .arg(pdf)
+            .arg(output_dir.join(stem)),
+    };
+    cmd
+}
+
+/// `cmd` as a shell command line: explicitly set variables, program and
+/// arguments, quoted where needed, after a `cd` into its directory if set.
+///
+/// ```
+/// use equation_processor::render::display_command;
+/// use std::process::Command;
+///
+/// let mut cmd = Command::new("tectonic");
+/// cmd.env("TECTONIC_CACHE_DIR", "/tmp/tex cache").arg("a.tex");
+/// assert_eq!(
+///     display_command(&cmd),
+///     "TECTONIC_CACHE_DIR='/tmp/tex cache' tectonic a.tex"
+/// );
+/// ```
+pub fn display_command(cmd: &Command) -> String {
+    fn quote(word: &str) -> String {
+        let plain = !word.is_empty()
+            && word
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%#".contains(c));
+        if plain {
+            word.to_string()
+        } else {
+            format!("'{}'", word.replace('\'', r"'\''"))
+        }
+    }
+    let mut words = Vec::new();
+    if let Some(dir) = cmd.get_current_dir() {
+        words.push(format!("cd {} &&", quote(&dir.to_string_lossy())));
+    }
+    for (key, value) in cmd.get_envs() {
+        if let Some(value) = value {
+            words.push(format!(
+                "{}={}",
+                key.to_string_lossy(),
+                quote(&value.to_string_lossy())
+            ));
+        }
+    }
+    words.push(quote(&cmd.get_program().to_string_lossy()));
+    words.extend(cmd.get_args().map(|arg| quote(&arg.to_string_lossy())));
+    words.join(" ")
+}
+
+/// Convert every `.pdf` in `input_dir` to each of `formats`, without
+/// compiling any LaTeX.
+///
+/// Outputs go to `output_dir` (created if needed) under the PDF's file
+/// stem; PDFs are processed in file name order with a progress bar.
+/// Returns the written files.
+pub fn convert_directory(
+    input_dir: &Path,
+    output_dir: &Path,
+    formats: &[ImageFormat],
+    dpi: u32,
+) -> Result<Vec<PathBuf>, EquationError> {
+    convert_directory_with_env(input_dir, output_dir, formats, dpi, &[])
+}
+
+/// [`convert_directory`], running `pdftocairo` with the extra variables `env`.
+pub fn convert_directory_with_env(
+    input_dir: &Path,
+    output_dir: &Path,
+    formats: &[ImageFormat],
+    dpi: u32,
+    env: &[(String, String)],
+) -> Result<Vec<PathBuf>, EquationError> {
+    let mut pdfs: Vec<PathBuf> = fs::read_dir(input_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+        })
+        .collect();
+    pdfs.sort();
+    fs::create_dir_all(output_dir)?;
+    let bar = ProgressBar::new((pdfs.len() * formats.len()) as u64).with_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    let mut written = Vec::new();
+    for pdf in &pdfs {
+        for &format in formats {
+            bar.set_message(
+                pdf.file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+            );
+            written.push(convert_pdf_with_env(pdf, output_dir, format, dpi, env)?);
+            bar.inc(1);
+        }
+    }
+    bar.finish();
+    Ok(written)
+}
+
+/// File name of the batch journal kept inside the output directory.
+pub const JOURNAL_FILE: &str = ".equation_journal";
+
+/// Append-only record of batch progress, used to resume interrupted runs.
+///
+/// Each line is either `pending\t<name>` (written when a batch starts) or
+/// `done\t<name>\t<key>` (appended after an equation finished rendering).
+/// The key is the equation's [cache key](Renderer::cache_key), or a hash
+/// of its body for renderers without one, so an equation edited since it
+/// was journaled is rendered again on resume. The file is removed once the
+/// batch completes.
+#[derive(Debug)]
+pub struct RenderJournal {
+    path: PathBuf,
+    completed: HashMap<String, String>,
+}
+
+impl RenderJournal {
+    /// Load the journal in `output_dir`, keeping entries from a previous run.
+    pub fn open(output_dir: &Path) -> io::Result<Self> {
+        let path = output_dir.join(JOURNAL_FILE);
+        let mut completed = HashMap::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                let line = line?;
+                let Some(entry) = line.strip_prefix("done\t") else {
+                    continue;
+                };
+                // Entries without a key, from older versions, never match.
+                let (name, key) = entry.split_once('\t').unwrap_or((entry, ""));
+                completed.insert(name.to_string(), key.to_string());
+            }
+        }
+        Ok(RenderJournal { path, completed })
+    }
+
+    /// Start a fresh journal in `output_dir`, discarding any previous progress.
+    pub fn create(output_dir: &Path) -> io::Result<Self> {
+        let path = output_dir.join(JOURNAL_FILE);
+        File::create(&path)?;
+        Ok(RenderJournal {
+            path,
+            completed: HashMap::new(),
+        })
+    }
+
+    /// Whether `name` was rendered by a previous (interrupted) run from
+    /// inputs with `key`.
+    pub fn is_completed(&self, name: &str, key: &str) -> bool {
+        self.completed
+            .get(name)
+            .is_some_and(|done| !done.is_empty() && done == key)
+    }
+
+    /// Number of equations recorded as completed.
+    pub fn completed_count(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// Record the equations still to be rendered.
+    pub fn record_pending(&mut self, pending: &[&Equation]) -> io::Result<()> {
+        let mut f = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)?;
+        for eq in pending {
+            writeln!(f, "pending\t{}", eq.name)?;
+        }
+        f.sync_data()
+    }
+
+    /// Record that `name` rendered successfully from inputs with `key`,
+    /// syncing to disk immediately.
+    pub fn mark_completed(&mut self, name: &str, key: &str) -> io::Result<()> {
+        let mut f = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)?;
+        writeln!(f, "done\t{name}\t{key}")?;
+        f.sync_data()?;
+        self.completed.insert(name.to_string(), key.to_string());
+        Ok(())
+    }
+
+    /// Remove the journal after the batch finished.
+    pub fn finish(self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How artifacts of duplicate equations (identical bodies) are produced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkStrategy {
+    /// Copy the canonical artifacts
+    Copy,
+    /// Hard-link to the canonical artifacts
+    Hardlink,
+    /// Relative symbolic link to the canonical artifacts
+    Symlink,
+}
+
+impl FromStr for LinkStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "copy" => Ok(LinkStrategy::Copy),
+            "hardlink" => Ok(LinkStrategy::Hardlink),
+            "symlink" => Ok(LinkStrategy::Symlink),
+            other => Err(format!(
+                "unknown link strategy '{other}' (expected copy, hardlink or symlink)"
+            )),
+        }
+    }
+}
+
+impl LinkStrategy {
+    /// Make `link` provide the contents of `target`, replacing any existing file.
+    ///
+    /// Both paths are expected in the same directory, so symlinks are relative.
+    pub fn link(self, target: &Path, link: &Path) -> io::Result<()> {
+        if link.symlink_metadata().is_ok() {
+            fs::remove_file(link)?;
+        }
+        match self {
+            LinkStrategy::Copy => fs::copy(target, link).map(|_| ()),
+            LinkStrategy::Hardlink => fs::hard_link(target, link),
+            LinkStrategy::Symlink => {
+                let relative = target.file_name().map_or(target, Path::new);
+                #[cfg(unix)]
+                return std::os::unix::fs::symlink(relative, link);
+                #[cfg(windows)]
+                return std::os::windows::fs::symlink_file(relative, link);
+                #[cfg(not(any(unix, windows)))]
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "symlinks are not supported on this platform",
+                ));
+            }
+        }
+    }
+}
+
+/// Active equations split by body: one canonical equation per distinct body,
+/// the rest as aliases of it.
+#[derive(Debug)]
+pub struct DuplicatePlan<'a> {
+    /// First equation seen for each distinct body, in input order
+    pub canonical: Vec<&'a Equation>,
+    /// `(alias, canonical)` pairs for every later equation with the same body
+    pub aliases: Vec<(&'a Equation, &'a Equation)>,
+}
+
+impl<'a> DuplicatePlan<'a> {
+    /// Group `equations` by identical (trimmed) body, kind and assets.
+    pub fn new(equations: &[&'a Equation]) -> Self {
+        type Key<'k> = (&'k str, EquationKind, &'k [String], &'k [PathBuf]);
+        let mut first: HashMap<Key, &'a Equation> = HashMap::new();
+        let mut plan = DuplicatePlan {
+            canonical: Vec::new(),
+            aliases: Vec::new(),
+        };
+        for &eq in equations {
+            let key = (
+                eq.body.trim(),
+                eq.kind,
+                eq.tikz_libraries.as_slice(),
+                eq.assets.as_slice(),
+            );
+            match first.get(&key) {
+                Some(&canonical) => plan.aliases.push((eq, canonical)),
+                None => {
+                    first.insert(key, eq);
+                    plan.canonical.push(eq);
+                }
+            }
+        }
+        plan
+    }
+}
+
+/// Read a color mapping file: one `name = #RRGGBB` per line.
+///
+/// Blank lines and lines starting with `//` are ignored.
+pub fn read_color_map(path: &Path) -> io::Result<HashMap<String, String>> {
+    parse_color_map(&fs::read_to_string(path)?).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+/// Parse the contents of a color mapping file, see [`read_color_map`].
+pub fn parse_color_map(content: &str) -> Result<HashMap<String, String>, String> {
+    let mut map = HashMap::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((name, color)) if !name.trim().is_empty() => {
+                let color: Color = color.parse().map_err(|e| format!("line {}: {e}", i + 1))?;
+                map.insert(name.trim().to_string(), color.to_string());
+            }
+            _ => return Err(format!("line {}: expected `name = color`", i + 1)),
+        }
+    }
+    Ok(map)
+}
+
+/// Give equations their color from `colors`, keyed by name.
+///
+/// Colors resolve in this order, first match wins: the mapping, the
+/// equation's `%%color%%` tag, the batch color. Returns a warning for each
+/// mapped name that matches no equation.
+pub fn apply_color_map(
+    equations: &mut [Equation],
+    colors: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut unused: Vec<&String> = colors
+        .keys()
+        .filter(|name| !equations.iter().any(|e| &e.name == *name))
+        .collect();
+    unused.sort();
+    for eq in equations {
+        if let Some(color) = colors.get(&eq.name) {
+            eq.color = Some(color.clone());
+        }
+    }
+    unused
+        .into_iter()
+        .map(|name| format!("color mapping for '{name}' matches no equation"))
+        .collect()
+}
+
+/// Rename active equations whose artifact paths would collide.
+///
+/// Names are compared case-insensitively, since `a.svg` and `A.svg` are the
+/// same file on common filesystems. The first equation keeps its name; each
+/// later one gets a suffix derived from a hash of its body, so the renaming is
+/// stable across runs. Returns one warning per renamed equation.
+pub fn disambiguate_names(equations: &mut [Equation]) -> Vec<String> {
+    let mut taken: HashSet<String> = equations
+        .iter()
+        .filter(|e| e.active)
+        .map(|e| e.name.to_lowercase())
+        .collect();
+    let mut seen = HashSet::new();
+    let mut warnings = Vec::new();
+    for eq in equations.iter_mut().filter(|e| e.active) {
+        if seen.insert(eq.name.to_lowercase()) {
+            continue;
+        }
+        let base = format!("{}_{:08x}", eq.name, body_hash(&eq.body) as u32);
+        let mut name = base.clone();
+        let mut n = 1;
+        while !taken.insert(name.to_lowercase()) {
+            name = format!("{base}_{n}");
+            n += 1;
+        }
+        warnings.push(format!(
+            "equation name '{}' is used more than once; rendering this one as '{name}'",
+            eq.name
+        ));
+        seen.insert(name.to_lowercase());
+        eq.name = name;
+    }
+    warnings
+}
+
+/// Case change applied by a [`RenameRule`] after replacing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseTransform {
+    /// Leave the case alone
+    #[default]
+    Keep,
+    /// `all_lower`
+    Lower,
+    /// `ALL_UPPER`
+    Upper,
+}
+
+/// A batch rename: find/replace (plain or regex), case change and numbering.
+///
+/// In `replace`, `{n}` stands for a running number over the renamed
+/// equations, starting at `start` and zero-padded to `width` digits. An
+/// empty `find` matches the whole name, so `replace: "eq_{n}"` numbers
+/// every equation.
+#[derive(Debug, Clone, Default)]
+pub struct RenameRule {
+    /// Text (or regex) to look for in each name
+    pub find: String,
+    /// Replacement; may use `{n}`, and `$1`-style groups with `regex`
+    pub replace: String,
+    /// Treat `find` as a regular expression
+    pub regex: bool,
+    /// Case change applied after replacing
+    pub case: CaseTransform,
+    /// First value of `{n}`
+    pub start: usize,
+    /// Minimum number of digits for `{n}`
+    pub width: usize,
+}
+
+/// One entry of a [`plan_renames`] preview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    /// Index of the equation in the slice passed to [`plan_renames`]
+    pub index: usize,
+    /// Current name
+    pub old: String,
+    /// Name after the rename, sanitized
+    pub new: String,
+}
+
+/// Preview `rule` applied to the active equations.
+///
+/// Returns the old → new names of every active equation, in order, and one
+/// warning per name that would be shared with another equation (compared
+/// case-insensitively, like [`disambiguate_names`]). Fails for an invalid regex.
+pub fn plan_renames(
+    equations: &[Equation],
+    rule: &RenameRule,
+) -> Result<(Vec<Rename>, Vec<String>), String> {
+    let find = if rule.find.is_empty() {
+        "^.*$".to_string()
+    } else if rule.regex {
+        rule.find.clone()
+    } else {
+        regex::escape(&rule.find)
+    };
+    let re = regex::Regex::new(&find).map_err(|e| format!("invalid pattern: {e}"))?;
+    let mut renames = Vec::new();
+    for (index, eq) in equations.iter().enumerate().filter(|(_, e)| e.active) {
+        let number = format!("{:0width$}", rule.start + renames.len(), width = rule.width);
+        let replacement = rule.replace.replace("{n}", &number);
+        let renamed = if rule.regex {
+            re.replace_all(&eq.name, replacement.as_str())
+        } else {
+            re.replace_all(&eq.name, regex::NoExpand(&replacement))
+        };
+        let renamed = match rule.case {
+            CaseTransform::Keep => renamed.into_owned(),
+            CaseTransform::Lower => renamed.to_lowercase(),
+            CaseTransform::Upper => renamed.to_uppercase(),
+        };
+        renames.push(Rename {
+            index,
+            old: eq.name.clone(),
+            new: Equation::sanitize_filename(&renamed),
+        });
+    }
+
+    let mut new_names: Vec<String> = equations.iter().map(|e| e.name.clone()).collect();
+    for rename in &renames {
+        new_names[rename.index] = rename.new.clone();
+    }
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for name in &new_names {
+        *counts.entry(name.to_lowercase()).or_default() += 1;
+    }
+    let mut warned = HashSet::new();
+    let warnings = renames
+        .iter()
+        .filter(|r| counts[&r.new.to_lowercase()] > 1 && warned.insert(r.new.to_lowercase()))
+        .map(|r| {
+            format!(
+                "'{}' would be used by {} equations",
+                r.new,
+                counts[&r.new.to_lowercase()]
+            )
+        })
+        .collect();
+    Ok((renames, warnings))
+}
+
+/// Apply renames from [`plan_renames`] to the same `equations`.
+pub fn apply_renames(equations: &mut [Equation], renames: &[Rename]) {
+    for rename in renames {
+        equations[rename.index].name = rename.new.clone();
+    }
+}
+
+/// FNV-1a hash of an equation body; stable across runs and platforms.
+fn body_hash(body: &str) -> u64 {
+    body.trim().bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Artifacts of the active `equations` that already exist in `output_dir`.
+///
+/// Checks the files a render (re)writes: `.svg`, `.png`, `.pdf` and `.tex`.
+pub fn existing_outputs(equations: &[Equation], output_dir: &Path) -> Vec<PathBuf> {
+    equations
+        .iter()
+        .filter(|e| e.active)
+        .flat_map(|e| {
+            ALIASED_EXTENSIONS
+                .iter()
+                .map(move |ext| output_dir.join(format!("{}.{ext}", e.name)))
+        })
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Artifact extensions that aliases receive when present for the canonical.
+const ALIASED_EXTENSIONS: &[&str] = &["svg", "png", "pdf", "tex"];
+
+/// Give `alias` the artifacts already produced for `canonical`.
+fn link_artifacts(
+    canonical: &Equation,
+    alias: &Equation,
+    output_dir: &Path,
+    strategy: LinkStrategy,
+) -> io::Result<()> {
+    for ext in ALIASED_EXTENSIONS {
+        let target = output_dir.join(format!("{}.{ext}", canonical.name));
+        if target.exists() {
+            strategy.link(&target, &output_dir.join(format!("{}.{ext}", alias.name)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Number of recent per-equation durations used for rate and ETA estimates.
+const TIMING_WINDOW: usize = 50;
+
+/// Per-equation timing history of a batch, used for throughput and ETA.
+///
+/// Estimates use the mean of the most recent renders, so they adapt when
+/// equation complexity changes over the course of a batch.
+#[derive(Debug, Clone, Default)]
+pub struct RenderTimer {
+    recent: VecDeque<Duration>,
+}
+
+impl RenderTimer {
+    /// Record how long one equation took.
+    pub fn record(&mut self, duration: Duration) {
+        if self.recent.len() == TIMING_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(duration);
+    }
+
+    /// Mean duration of the recent renders, if any were recorded.
+    pub fn mean(&self) -> Option<Duration> {
+        let count = u32::try_from(self.recent.len()).ok().filter(|&n| n > 0)?;
+        Some(self.recent.iter().sum::<Duration>() / count)
+    }
+
+    /// Throughput in equations per minute.
+    pub fn per_minute(&self) -> Option<f64> {
+        self.mean()
+            .filter(|d| !d.is_zero())
+            .map(|d| 60.0 / d.as_secs_f64())
+    }
+
+    /// Estimated time to render `remaining` more equations.
+    pub fn eta(&self, remaining: usize) -> Option<Duration> {
+        let remaining = u32::try_from(remaining).ok()?;
+        Some(self.mean()? * remaining)
+    }
+
+    /// Human-readable status such as `12.5 eq/min, ETA 3m 20s`.
+    pub fn summary(&self, remaining: usize) -> Option<String> {
+        Some(format!(
+            "{:.1} eq/min, ETA {}",
+            self.per_minute()?,
+            format_duration(self.eta(remaining)?)
+        ))
+    }
+}
+
+/// Format a duration as `1h 02m`, `3m 20s` or `45s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{s}s"),
+        (0, m, s) => format!("{m}m {s:02}s"),
+        (h, m, _) => format!("{h}h {m:02}m"),
+    }
+}
+
+/// Typical artifact sizes in bytes, used for extensions without history in
+/// the output directory. PNG is at [`DEFAULT_DPI`].
+const TYPICAL_ARTIFACT_BYTES: &[(&str, u64)] = &[
+    ("tex", 1_000),
+    ("pdf", 15_000),
+    ("svg", 25_000),
+    ("png", 60_000),
+];
+
+/// Disk space needed by a batch compared with the space left on the volume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaceEstimate {
+    /// Estimated bytes the batch will write
+    pub required: u64,
+    /// Free bytes on the output volume, if they could be determined
+    pub available: Option<u64>,
+}
+
+impl SpaceEstimate {
+    /// Whether the batch fits, assuming it does if free space is unknown.
+    pub fn fits(&self) -> bool {
+        self.available.is_none_or(|free| self.required <= free)
+    }
+
+    /// Whether the batch would use more than 90% of the free space.
+    pub fn is_tight(&self) -> bool {
+        self.available
+            .is_some_and(|free| self.required > free / 10 * 9)
+    }
+}
+
+impl fmt::Display for SpaceEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "about {} needed", format_bytes(self.required))?;
+        if let Some(free) = self.available {
+            write!(f, ", {} free", format_bytes(free))?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate the space the active `equations` will take in `output_dir` and
+/// look up the free space of its volume.
+///
+/// Artifact sizes are the averages of files with the same extension already
+/// in `output_dir` (earlier batches), falling back to typical sizes. Only
+/// the artifacts that remain after the batch are counted, plus one
+/// equation's intermediates.
+pub fn estimate_disk_space(
+    equations: &[Equation],
+    output_dir: &Path,
+    delete_intermediates: bool,
+    options: &RenderOptions,
+) -> SpaceEstimate {
+    let history = artifact_size_history(output_dir);
+    let size = |ext: &str| {
+        history.get(ext).copied().unwrap_or_else(|| {
+            TYPICAL_ARTIFACT_BYTES
+                .iter()
+                .find(|(e, _)| *e == ext)
+                .map_or(0, |(_, bytes)| *bytes)
+        })
+    };
+    let mut kept: Vec<&str> = options.image_formats().map(|f| f.extension()).collect();
+    if !delete_intermediates {
+        kept.extend(["tex", "pdf"]);
+    } else if options.keeps_pdf() {
+        kept.push("pdf");
+    }
+    let per_equation: u64 = kept.iter().map(|ext| size(ext)).sum();
+    let count = equations.iter().filter(|e| e.active).count() as u64;
+    let intermediates = if count > 0 {
+        size("tex") + size("pdf")
+    } else {
+        0
+    };
+    // The directory may not exist yet; its closest existing ancestor is on the same volume.
+    let volume = output_dir.ancestors().find(|dir| dir.exists());
+    SpaceEstimate {
+        required: count * per_equation + intermediates,
+        available: volume.and_then(|dir| fs2::available_space(dir).ok()),
+    }
+}
+
+/// Average size per artifact extension of the files in `output_dir`.
+fn artifact_size_history(output_dir: &Path) -> HashMap<String, u64> {
+    let mut totals: HashMap<String, (u64, u64)> = HashMap::new();
+    for entry in fs::read_dir(output_dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+            continue;
+        };
+        if TYPICAL_ARTIFACT_BYTES.iter().any(|(e, _)| *e == ext) {
+            if let Some(meta) = entry.metadata().ok().filter(|m| m.is_file()) {
+                let total = totals.entry(ext.to_string()).or_default();
+                total.0 += meta.len();
+                total.1 += 1;
+            }
+        }
+    }
+    totals
+        .into_iter()
+        .map(|(ext, (bytes, files))| (ext, bytes / files))
+        .collect()
+}
+
+/// Format a byte count as `512 B`, `3.4 KB`, `12.0 MB` or `1.2 GB`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while value >= 1000.0 && unit + 1 < UNITS.len() {
+        value /= 1000.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Produces the artifacts of a single equation in an output directory.
+///
+/// [`render_equations_with`] drives a batch through this trait, so the
+/// journaling, deduplication and progress logic can run against a stand-in
+/// (see `test_util::MockRenderer` behind the `test-util` feature). A
+/// parallel batch shares one renderer between its workers, hence `Sync`.
+pub trait Renderer: Sync {
+    /// Render `eq` into `output_dir`.
+    fn render(&self, eq: &Equation, output_dir: &Path) -> Result<(), EquationError>;
+
+    /// What this renderer can produce; everything unless overridden.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::all("custom")
+    }
+
+    /// Key of everything that shapes the artifacts of `eq` (see
+    /// [`cache_key`]), so unchanged equations are not rendered again;
+    /// `None`, the default, renders every equation.
+    fn cache_key(&self, _eq: &Equation) -> Option<String> {
+        None
+    }
+}
+
+/// What a [`Renderer`] backend can produce, queried before a batch so that
+/// options it does not support fail upfront instead of partway through.
+///
+/// ```
+/// use equation_processor::render::{Capabilities, OutputFormat, RenderOptions};
+///
+/// let mut caps = Capabilities::all("example");
+/// caps.formats = vec![OutputFormat::Pdf];
+/// caps.missing_tools = vec!["pdftocairo"];
+/// let problems = caps.unsupported(&[], &RenderOptions::default());
+/// assert_eq!(
+///     problems,
+///     ["the example backend cannot write svg without pdftocairo; \
+///       install it or choose other --formats"]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Backend name used in messages, e.g. `tectonic`
+    pub backend: &'static str,
+    /// Artifacts the backend can write with the tools that are installed
+    pub formats: Vec<OutputFormat>,
+    /// Equation kinds it can typeset
+    pub kinds: Vec<EquationKind>,
+    /// LaTeX engines an equation's `engine` tag may ask for
+    pub engines: Vec<LatexEngine>,
+    /// Whether images have a transparent background
+    pub transparent_background: bool,
+    /// Whether a font other than the default can be selected
+    pub custom_fonts: bool,
+    /// Whether SVG is written directly rather than converted from a PDF
+    pub direct_svg: bool,
+    /// Whether PDF compression and version can be set
+    pub pdf_options: bool,
+    /// External programs the backend needs but cannot find; the formats
+    /// they produce are missing from `formats`
+    pub missing_tools: Vec<&'static str>,
+}
+
+impl Capabilities {
+    /// A backend called `backend` supporting every format, kind and option,
+    /// converting its SVG from a compiled PDF.
+    pub fn all(backend: &'static str) -> Self {
+        Capabilities {
+            backend,
+            formats: OutputFormat::ALL.to_vec(),
+            kinds: vec![
+                EquationKind::Math,
+                EquationKind::TikZ,
+                EquationKind::Chemistry,
+            ],
+            engines: LatexEngine::ALL.to_vec(),
+            transparent_background: true,
+            custom_fonts: true,
+            direct_svg: false,
+            pdf_options: true,
+            missing_tools: Vec::new(),
+        }
+    }
+
+    /// Why rendering the active `equations` with `options` would fail on
+    /// this backend, one actionable message per problem; empty when the
+    /// batch can run.
+    pub fn unsupported(&self, equations: &[Equation], options: &RenderOptions) -> Vec<String> {
+        let backend = self.backend;
+        let mut problems = Vec::new();
+        let (hint, install) = match self.missing_tools.as_slice() {
+            [] => (String::new(), ""),
+            [tool] => (format!(" without {tool}"), "install it or "),
+            tools => (
+                format!(" without {}", tools.join(" and ")),
+                "install them or ",
+            ),
+        };
+        for format in &options.formats {
+            if !self.formats.contains(format) {
+                problems.push(format!(
+                    "the {backend} backend cannot write {}{hint}; \
+                     {install}choose other --formats",
+                    format.extension()
+                ));
+            }
+        }
+        if options.beamer && !self.formats.contains(&OutputFormat::Pdf) {
+            problems.push(format!(
+                "the {backend} backend cannot compile beamer slides{hint}; {install}drop --beamer"
+            ));
+        }
+        if options.background == Background::Transparent
+            && options.image_formats().next().is_some()
+            && !self.transparent_background
+        {
+            problems.push(format!(
+                "the {backend} backend cannot write transparent images; \
+                 choose a --background color"
+            ));
+        }
+        if options.font.as_deref() != Some(DEFAULT_FONT_PACKAGE) && !self.custom_fonts {
+            problems.push(format!(
+                "the {backend} backend cannot change the font; drop --font and --no-font-package"
+            ));
+        }
+        if options.pdf != PdfOptions::default() && !self.pdf_options {
+            problems.push(format!(
+                "the {backend} backend cannot set PDF compression or version; \
+                 drop --pdf-compression, --pdf-version and --full-fonts"
+            ));
+        }
+        if self.pdf_options && !self.engines.is_empty() {
+            let mut engines = vec![options.engine];
+            for eq in equations.iter().filter(|e| e.active) {
+                if !engines.contains(&eq.engine_with(options)) {
+                    engines.push(eq.engine_with(options));
+                }
+            }
+            for engine in engines {
+                if let Err(reason) = engine.backend().pdf_setup(&options.pdf) {
+                    problems.push(format!(
+                        "{} {reason}; choose another engine or drop the PDF options",
+                        engine.program()
+                    ));
+                }
+            }
+        }
+        for eq in equations.iter().filter(|e| e.active) {
+            if !self.kinds.contains(&eq.kind) {
+                problems.push(format!(
+                    "equation '{}' is {}, which the {backend} backend cannot typeset; \
+                     deactivate it or choose another backend",
+                    eq.name,
+                    eq.kind.as_str()
+                ));
+            }
+            if let Some(engine) = eq.engine.filter(|e| !self.engines.contains(e)) {
+                problems.push(format!(
+                    "equation '{}' asks for {}, which the {backend} backend cannot run; \
+                     install it or remove the equation's engine tag",
+                    eq.name,
+                    engine.program()
+                ));
+            }
+        }
+        problems
+    }
+}
+
+/// The default [`Renderer`]: tectonic followed by `pdftocairo`.
+#[derive(Debug, Clone)]
+pub struct TectonicRenderer<'a> {
+    /// Font color as `#RRGGBB`
+    pub color: &'a str,
+    /// Remove `.aux`/`.log` and similar files after each equation
+    pub delete_intermediates: bool,
+    /// How tectonic is invoked
+    pub tectonic: &'a TectonicConfig,
+    /// Batch-wide options such as the standalone class options
+    pub options: &'a RenderOptions,
+}
+
+impl<'a> TectonicRenderer<'a> {
+    /// A renderer using the color and cleanup policy of `options`.
+    pub fn new(tectonic: &'a TectonicConfig, options: &'a RenderOptions) -> Self {
+        TectonicRenderer {
+            color: &options.color,
+            delete_intermediates: options.delete_intermediates,
+            tectonic,
+            options,
+        }
+    }
+}
+
+impl Renderer for TectonicRenderer<'_> {
+    fn render(&self, eq: &Equation, output_dir: &Path) -> Result<(), EquationError> {
+        eq.render_in(
+            output_dir,
+            self.color,
+            self.delete_intermediates,
+            self.tectonic,
+            self.options,
+        )
+    }
+
+    /// Everything but direct SVG output, limited by which of the batch's
+    /// engine, `pdftocairo` and the other LaTeX engines are installed.
+    fn capabilities(&self) -> Capabilities {
+        let engine = self.options.engine;
+        let mut caps = Capabilities::all(engine.program());
+        caps.engines
+            .retain(|engine| find_tool(engine.program()).is_some());
+        let all_formats = RenderOptions {
+            formats: OutputFormat::ALL.to_vec(),
+            engine,
+            ..RenderOptions::default()
+        };
+        caps.missing_tools = missing_tools(&all_formats);
+        if caps.missing_tools.contains(&engine.program()) {
+            caps.formats.clear();
+        } else if !caps.missing_tools.is_empty() {
+            caps.formats = vec![OutputFormat::Pdf];
+        }
+        caps
+    }
+
+    fn cache_key(&self, eq: &Equation) -> Option<String> {
+        self.options
+            .cache
+            .then(|| cache_key(eq, self.color, self.tectonic, self.options))
+    }
+}
+
+/// Wait before the first retry of a transient failure unless configured
+/// otherwise.
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(2);
+
+/// How often an equation is rendered again after a transient failure (see
+/// [`EquationError::transient_reason`]), and how long to wait in between.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first; 0 never retries
+    pub retries: u32,
+    /// Wait before the first retry, doubled before each further one
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            retries: 0,
+            backoff: DEFAULT_RETRY_BACKOFF,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before retry number `retry`, counting from 1.
+    ///
+    /// ```
+    /// use equation_processor::render::RetryPolicy;
+    /// use std::time::Duration;
+    ///
+    /// let policy = RetryPolicy { retries: 3, backoff: Duration::from_secs(2) };
+    /// assert_eq!(policy.delay(1), Duration::from_secs(2));
+    /// assert_eq!(policy.delay(3), Duration::from_secs(8));
+    /// ```
+    pub fn delay(&self, retry: u32) -> Duration {
+        self.backoff
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+    }
+}
+
+/// A transient failure that [`RetryingRenderer`] retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Retry {
+    /// The equation
+    pub name: String,
+    /// Which retry this was, counting from 1
+    pub retry: u32,
+    /// The log line that made the failure transient
+    pub reason: String,
+    /// How long it waited before retrying
+    pub delay: Duration,
+}
+
+impl fmt::Display for Retry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "retried '{}' after {} (retry {}): {}",
+            self.name,
+            format_duration(self.delay),
+            self.retry,
+            self.reason
+        )
+    }
+}
+
+/// A [`Renderer`] rendering with `inner` and retrying equations that fail
+/// transiently, e.g. when tectonic loses its connection while fetching
+/// bundle files, with exponential backoff as its [`RetryPolicy`] says.
+///
+/// Other failures are returned right away. The retries are kept for the
+/// report; see [`RetryingRenderer::retries`].
+pub struct RetryingRenderer<'a> {
+    inner: &'a dyn Renderer,
+    policy: RetryPolicy,
+    retries: Mutex<Vec<Retry>>,
+}
+
+impl<'a> RetryingRenderer<'a> {
+    /// Retry the transient failures of `inner` as `policy` says.
+    pub fn new(inner: &'a dyn Renderer, policy: RetryPolicy) -> Self {
+        RetryingRenderer {
+            inner,
+            policy,
+            retries: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The retries so far, in the order they happened.
+    pub fn retries(&self) -> Vec<Retry> {
+        self.retries.lock().unwrap().clone()
+    }
+}
+
+impl Renderer for RetryingRenderer<'_> {
+    fn render(&self, eq: &Equation, output_dir: &Path) -> Result<(), EquationError> {
+        let mut retry = 0;
+        loop {
+            let err = match self.inner.render(eq, output_dir) {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+            let Some(reason) = err
+                .transient_reason()
+                .filter(|_| retry < self.policy.retries)
+            else {
+                return Err(err);
+            };
+            retry += 1;
+            let delay = self.policy.delay(retry);
+            self.retries.lock().unwrap().push(Retry {
+                name: eq.name.clone(),
+                retry,
+                reason: reason.to_string(),
+                delay,
+            });
+            thread::sleep(delay);
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn cache_key(&self, eq: &Equation) -> Option<String> {
+        self.inner.cache_key(eq)
+    }
+}
+
+/// The renderer for a batch with `options`: a [`TectonicRenderer`], unless
+/// the batch's LaTeX engine is not installed and the crate was built with
+/// the `typst-fallback` feature. Then equations are typeset in-process by a
+/// [`TypstRenderer`](crate::typst::TypstRenderer), and the second value is
+/// a warning saying so.
+pub fn batch_renderer<'a>(
+    tectonic: &'a TectonicConfig,
+    options: &'a RenderOptions,
+) -> (Box<dyn Renderer + 'a>, Option<String>) {
+    #[cfg(feature = "typst-fallback")]
+    if find_tool(options.engine.program()).is_none() {
+        let warning = format!(
+            "{} was not found; rendering with the built-in Typst fallback, which writes \
+             SVG only and cannot load LaTeX packages, templates, TikZ or chemistry",
+            options.engine.program()
+        );
+        return (
+            Box::new(crate::typst::TypstRenderer::new(options)),
+            Some(warning),
+        );
+    }
+    (Box::new(TectonicRenderer::new(tectonic, options)), None)
+}
+
+/// One equation rendered by [`render_single`], read into memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedEquation {
+    /// The SVG document, if the formats include SVG
+    pub svg: Option<String>,
+    /// The compiled PDF, if the formats include PDF
+    pub pdf: Option<Vec<u8>>,
+    /// The PNG image, if the formats include PNG
+    pub png: Option<Vec<u8>>,
+    /// Width and height in pt, read from the SVG
+    pub dims: Option<(f64, f64)>,
+    /// Distance in pt from the bottom edge of the image up to the
+    /// equation's baseline, for aligning it with surrounding text
+    ///
+    /// The built-in template reserves 12mm above and 5mm below the
+    /// baseline, so equations fitting that space share a baseline. `None`
+    /// for equations reaching beyond it, a custom template, a `varwidth` or
+    /// uncropped page, and renderers that do not compile LaTeX.
+    pub baseline: Option<f64>,
+}
+
+/// Render the LaTeX `body` with `options` and return the artifacts, for
+/// programs that need an image of a formula without an input file, names
+/// or an output directory of their own.
+///
+/// The equation is rendered like one in a batch, with the
+/// [`TectonicConfig::from_env`] (or the Typst fallback, see
+/// [`batch_renderer`]), in a
+/// temporary directory that is removed afterwards. `options.formats`
+/// decides which artifacts are returned; batch settings such as the
+/// cache, hooks or extra outputs do not apply.
+///
+/// ```no_run
+/// use equation_processor::render::{render_single, ImageFormat, OutputFormat, RenderOptions};
+///
+/// let options = RenderOptions::builder()
+///     .formats([OutputFormat::Pdf, OutputFormat::Image(ImageFormat::Svg)])
+///     .build();
+/// let rendered = render_single(r"\int_0^1 x^2 \, dx", &options)?;
+/// let (width, height) = rendered.dims.unwrap();
+/// println!("{width}pt × {height}pt, {} bytes of PDF", rendered.pdf.unwrap().len());
+/// # Ok::<(), equation_processor::EquationError>(())
+/// ```
+pub fn render_single(
+    body: &str,
+    options: &RenderOptions,
+) -> Result<RenderedEquation, EquationError> {
+    let tectonic = TectonicConfig::from_env();
+    let (renderer, _) = batch_renderer(&tectonic, options);
+    render_single_with(body, options, renderer.as_ref())
+}
+
+/// Like [`render_single`], with `renderer`, e.g. a [`TectonicRenderer`]
+/// with a [`TectonicConfig`] of its own.
+pub fn render_single_with(
+    body: &str,
+    options: &RenderOptions,
+    renderer: &dyn Renderer,
+) -> Result<RenderedEquation, EquationError> {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    options
+        .color
+        .parse::<Color>()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut eq = Equation::new(true, "equation", body.trim());
+    if options.chemistry {
+        eq.kind = EquationKind::Chemistry;
+    }
+    let dir = std::env::temp_dir().join(format!(
+        "eqp_single_{}_{}",
+        std::process::id(),
+        CALLS.fetch_add(1, Ordering::Relaxed)
+    ));
+    fs::create_dir_all(&dir)?;
+    let rendered = renderer.render(&eq, &dir).and_then(|()| {
+        let artifact = |format: OutputFormat| -> io::Result<Option<Vec<u8>>> {
+            if !options.formats.contains(&format) {
+                return Ok(None);
+            }
+            fs::read(dir.join(format!("{}.{}", eq.name, format.extension()))).map(Some)
+        };
+        let svg = artifact(OutputFormat::Image(ImageFormat::Svg))?
+            .map(|svg| String::from_utf8_lossy(&svg).into_owned());
+        let dims = svg
+            .is_some()
+            .then(|| svg_dimensions(&dir.join(format!("{}.svg", eq.name))))
+            .flatten();
+        let compiles_latex = !renderer.capabilities().direct_svg;
+        Ok(RenderedEquation {
+            baseline: dims
+                .filter(|_| compiles_latex)
+                .and_then(|(_, height)| template_baseline(&eq, options, height)),
+            svg,
+            pdf: artifact(OutputFormat::Pdf)?,
+            png: artifact(OutputFormat::Image(ImageFormat::Png))?,
+            dims,
+        })
+    });
+    let _ = fs::remove_dir_all(&dir);
+    rendered
+}
+
+/// Height of the box the built-in template pads every equation to.
+const TEMPLATE_HEIGHT_PT: f64 = 12.0 * PT_PER_MM;
+/// Depth the built-in template reserves below the baseline.
+const TEMPLATE_DEPTH_PT: f64 = 5.0 * PT_PER_MM;
+/// TeX points per millimetre.
+const PT_PER_MM: f64 = 72.27 / 25.4;
+
+/// The baseline of `eq`, rendered with the built-in template into an image
+/// `height` pt tall, above the bottom edge: the bottom border plus the
+/// reserved depth, if the image has the padded box's height and so nothing
+/// reaches below the reserved depth.
+fn template_baseline(eq: &Equation, options: &RenderOptions, height: f64) -> Option<f64> {
+    let standalone = eq.standalone.or(&options.standalone);
+    if options.template.is_some()
+        || eq.kind == EquationKind::TikZ
+        || standalone.crop == Some(false)
+        || standalone
+            .varwidth
+            .as_deref()
+            .is_some_and(|v| v.trim() != "false")
+    {
+        return None;
+    }
+    let border = standalone
+        .border
+        .as_deref()
+        .unwrap_or(StandaloneOptions::DEFAULT_BORDER);
+    let border: Vec<f64> = border
+        .split_whitespace()
+        .map(tex_length_pt)
+        .collect::<Option<_>>()?;
+    // One length for every side, horizontal and vertical, or left bottom
+    // right top.
+    let (bottom, top) = match border[..] {
+        [all] => (all, all),
+        [_, vertical] => (vertical, vertical),
+        [_, bottom, _, top] => (bottom, top),
+        _ => return None,
+    };
+    let padded = bottom + TEMPLATE_HEIGHT_PT + TEMPLATE_DEPTH_PT + top;
+    // pdftocairo rounds the page size.
+    ((height - padded).abs() < 0.1).then_some(bottom + TEMPLATE_DEPTH_PT)
+}
+
+/// A TeX length like `2pt` or `1.5mm` in pt; a plain number is in bp, as
+/// in standalone's `border`.
+fn tex_length_pt(length: &str) -> Option<f64> {
+    let length = length.trim();
+    let split = length
+        .find(|c: char| c.is_ascii_alphabetic())
+        .unwrap_or(length.len());
+    let value: f64 = length[..split].parse().ok()?;
+    let per_unit = match &length[split..] {
+        "pt" => 1.0,
+        "" | "bp" => 72.27 / 72.0,
+        "mm" => PT_PER_MM,
+        "cm" => 10.0 * PT_PER_MM,
+        "in" => 72.27,
+        "pc" => 12.0,
+        _ => return None,
+    };
+    Some(value * per_unit)
+}
+
+/// Render all active equations with tectonic and a CLI progress bar, or
+/// the Typst fallback where [`batch_renderer`] picks it, with a warning.
+///
+/// Progress is journaled in `output_dir`; with `options.resume` set,
+/// equations completed by an interrupted previous run are skipped. Equations
+/// that are unchanged since they were last rendered into `output_dir` are
+/// skipped as well (see [`cache`](crate::cache)). With `options.dedup` set,
+/// equations sharing a body are rendered once and the duplicates receive
+/// the canonical artifacts afterward via the given [`LinkStrategy`].
+///
+/// All of `options` applies, as in
+/// [`process_file`](crate::process::process_file) minus reading the input:
+/// the batch settings (`chemistry`, `include_inactive`, `colors`, name
+/// affixes, unique names and `shard`) are applied to a copy of
+/// `equations`, the batch is checked like `process_file` checks it
+/// (`check_palette`, features the renderer lacks, disk space), and after
+/// rendering with `retry` the hooks run and the index and extra outputs are
+/// written. Returns the warnings the command line would have printed.
+pub fn render_batch(
+    equations: &[Equation],
+    output_dir: &Path,
+    tectonic: &TectonicConfig,
+    options: &RenderOptions,
+) -> Result<Vec<String>, EquationError> {
+    let (renderer, fallback) = batch_renderer(tectonic, options);
+    let mut warnings =
+        render_batch_with(equations, output_dir, tectonic, options, renderer.as_ref())?;
+    if let Some(warning) = fallback {
+        warnings.insert(0, warning);
+    }
+    Ok(warnings)
+}
+
+/// Like [`render_batch`], rendering with `renderer`, e.g. a
+/// [`MockRenderer`](crate::test_util::MockRenderer) in tests.
+pub fn render_batch_with(
+    equations: &[Equation],
+    output_dir: &Path,
+    tectonic: &TectonicConfig,
+    options: &RenderOptions,
+    renderer: &dyn Renderer,
+) -> Result<Vec<String>, EquationError> {
+    let color: Color = options
+        .color
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut warnings = Vec::new();
+    let mut equations = equations.to_vec();
+    apply_batch_settings(&mut equations, options.chemistry, options, &mut warnings)?;
+    fs::create_dir_all(output_dir)?;
+    let space = check_batch(&equations, color, renderer, output_dir, options)?;
+    if space.is_tight() {
+        warnings.push(format!(
+            "the batch will nearly fill the output volume ({space})"
+        ));
+    }
+    let renderer = RetryingRenderer::new(renderer, options.retry);
+    if options.keep_history {
+        archive_changed(&equations, output_dir)?;
+    }
+    let started = Instant::now();
+    let rendered = render_outputs(
+        &equations,
+        output_dir,
+        &renderer,
+        tectonic,
+        options,
+        &mut warnings,
+        &mut BatchSummary::default(),
+    );
+    warnings.extend(renderer.retries().iter().map(ToString::to_string));
+    if options.notify {
+        let source = output_dir.display().to_string();
+        warnings.extend(notify_outcome(
+            &source,
+            &equations,
+            &rendered,
+            started.elapsed(),
+        ));
+    }
+    rendered.map(|_| warnings)
+}
+
+/// Render the active equations of `equations` as given, one at a time,
+/// with `renderer`; see [`render_equations_parallel`].
+pub fn render_equations_with(
+    equations: &[Equation],
+    output_dir: &Path,
+    renderer: &dyn Renderer,
+    resume: bool,
+    dedup: Option<LinkStrategy>,
+) -> Result<BatchSummary, EquationError> {
+    render_equations_parallel(equations, output_dir, renderer, resume, dedup, 1)
+}
+
+/// What [`render_equations_parallel`] did with the active equations of a
+/// batch. After a failure, equations that were never started are in none
+/// of the counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    /// Equations compiled and converted
+    pub rendered: usize,
+    /// Equations skipped because the [render cache](crate::cache) knew them
+    /// to be unchanged
+    pub cached: usize,
+    /// Equations skipped because the resumed run had rendered them already
+    pub resumed: usize,
+    /// Duplicates given the artifacts of their canonical equation
+    pub linked: usize,
+    /// Equations that failed to render
+    pub failed: usize,
+}
+
+/// Like [`render_equations_with`], rendering up to `jobs` equations at the
+/// same time, each worker with its own spinner under the progress bar.
+///
+/// Equations the [render cache](crate::cache) knows to be unchanged are
+/// skipped; the cache is written even when the batch fails. After the first
+/// failure no further equations are started; those already running finish
+/// and are journaled, and the first error is returned.
+/// Duplicates are linked once all canonical equations are rendered.
+///
+/// Active equations must have distinct names, compared ignoring case, since
+/// each one's artifacts, journal entry and cache entry are named after it;
+/// otherwise nothing is rendered and an error is returned. See
+/// [`disambiguate_names`].
+pub fn render_equations_parallel(
+    equations: &[Equation],
+    output_dir: &Path,
+    renderer: &dyn Renderer,
+    resume: bool,
+    dedup: Option<LinkStrategy>,
+    jobs: usize,
+) -> Result<BatchSummary, EquationError> {
+    let mut summary = BatchSummary::default();
+    render_equations_counted(
+        equations,
+        output_dir,
+        renderer,
+        resume,
+        dedup,
+        jobs,
+        &mut summary,
+    )?;
+    Ok(summary)
+}
+
+/// [`render_equations_parallel`], counting into `summary` whether or not
+/// the batch fails.
+pub(crate) fn render_equations_counted(
+    equations: &[Equation],
+    output_dir: &Path,
+    renderer: &dyn Renderer,
+    resume: bool,
+    dedup: Option<LinkStrategy>,
+    jobs: usize,
+    summary: &mut BatchSummary,
+) -> Result<(), EquationError> {
+    let active: Vec<&Equation> = equations.iter().filter(|e| e.active).collect();
+    let mut names = HashSet::new();
+    if let Some(eq) = active.iter().find(|e| !names.insert(e.name.to_lowercase())) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "equation name '{}' is used by more than one active equation, so their \
+                 artifacts would overwrite each other; rename them or call \
+                 disambiguate_names first",
+                eq.name
+            ),
+        )
+        .into());
+    }
+    fs::create_dir_all(output_dir)?;
+    let mut journal = if resume {
+        RenderJournal::open(output_dir)?
+    } else {
+        RenderJournal::create(output_dir)?
+    };
+    let (to_render, aliases) = match dedup {
+        Some(_) => {
+            let plan = DuplicatePlan::new(&active);
+            (plan.canonical, plan.aliases)
+        }
+        None => (active.clone(), Vec::new()),
+    };
+    let cache = RenderCache::read(output_dir);
+    let manifest = ContentManifest::read(output_dir);
+    let keyed = |e: &Equation| {
+        let key = renderer.cache_key(e);
+        let done = journal_key(e, key.as_deref());
+        (key, done)
+    };
+    let unfinished: Vec<_> = to_render
+        .into_iter()
+        .map(|e| {
+            let (key, done) = keyed(e);
+            (e, key, done)
+        })
+        .filter(|(e, _, done)| !journal.is_completed(&e.name, done))
+        .collect();
+    let aliases: Vec<(&Equation, &Equation, String)> = aliases
+        .into_iter()
+        .map(|(alias, canonical)| (alias, canonical, keyed(alias).1))
+        .filter(|(alias, _, done)| !journal.is_completed(&alias.name, done))
+        .collect();
+    summary.resumed = active.len() - unfinished.len() - aliases.len();
+    let to_render: Vec<(&Equation, Option<String>, String)> = unfinished
+        .into_iter()
+        .filter(|(e, key, _)| {
+            key.as_ref()
+                .is_none_or(|key| !cache.is_fresh(&e.name, key, output_dir, manifest.as_ref()))
+        })
+        .collect();
+    summary.cached = active.len() - summary.resumed - aliases.len() - to_render.len();
+    let mut pending: Vec<&Equation> = to_render.iter().map(|(e, _, _)| *e).collect();
+    pending.extend(aliases.iter().map(|(alias, _, _)| *alias));
+    journal.record_pending(&pending)?;
+
+    let jobs = jobs.clamp(1, to_render.len().max(1));
+    let progress = BatchProgress::new(active.len(), jobs);
+    progress.set_position(active.len() - pending.len());
+    let queue = Mutex::new(to_render.into_iter());
+    let shared_journal = Mutex::new(journal);
+    let cache = Mutex::new(cache);
+    let failure = Mutex::new(None);
+    let (rendered, failed) = (AtomicUsize::new(0), AtomicUsize::new(0));
+    thread::scope(|scope| {
+        for worker in 0..jobs {
+            let (queue, journal, cache, failure, progress, rendered, failed) = (
+                &queue,
+                &shared_journal,
+                &cache,
+                &failure,
+                &progress,
+                &rendered,
+                &failed,
+            );
+            scope.spawn(move || loop {
+                if failure
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .is_some()
+                {
+                    break;
+                }
+                let Some((eq, key, done)) =
+                    queue.lock().unwrap_or_else(PoisonError::into_inner).next()
+                else {
+                    break;
+                };
+                progress.start(worker, &eq.name);
+                let started = Instant::now();
+                let result = render_caught(renderer, eq, output_dir).and_then(|()| {
+                    progress.finish_one(worker, started.elapsed());
+                    if let Some(key) = key {
+                        cache
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .record(&eq.name, key, output_dir);
+                    }
+                    journal
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .mark_completed(&eq.name, &done)?;
+                    // Counted once journaled, so a failed write counts as a failure only.
+                    rendered.fetch_add(1, Ordering::Relaxed);
+                    Ok(())
+                });
+                if let Err(err) = result {
+                    failed.fetch_add(1, Ordering::Relaxed);
+                    failure
+                        .lock()
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .get_or_insert(err);
+                    break;
+                }
+            });
+        }
+    });
+    summary.rendered = rendered.into_inner();
+    summary.failed = failed.into_inner();
+    let cached = cache
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+        .write(output_dir);
+    if let Some(err) = failure.into_inner().unwrap_or_else(PoisonError::into_inner) {
+        return Err(err);
+    }
+    cached?;
+    let mut journal = shared_journal
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(strategy) = dedup {
+        for (alias, canonical, done) in aliases {
+            progress.start(0, &format!("{} -> {}", alias.name, canonical.name));
+            link_artifacts(canonical, alias, output_dir, strategy)?;
+            progress.finish_one(0, Duration::ZERO);
+            journal.mark_completed(&alias.name, &done)?;
+            summary.linked += 1;
+        }
+    }
+    progress.finish();
+    Ok(journal.finish()?)
+}
+
+/// Key `eq` is journaled under: its `cache_key`, or a hash of its body if
+/// the renderer has none.
+pub fn journal_key(eq: &Equation, cache_key: Option<&str>) -> String {
+    cache_key.map_or_else(|| format!("{:016x}", body_hash(&eq.body)), str::to_string)
+}
+
+/// Render `eq` with `renderer`, turning a panic into an error so that one
+/// broken equation ends the batch like a failed one does.
+fn render_caught(
+    renderer: &dyn Renderer,
+    eq: &Equation,
+    output_dir: &Path,
+) -> Result<(), EquationError> {
+    panic::catch_unwind(AssertUnwindSafe(|| renderer.render(eq, output_dir))).unwrap_or_else(
+        |payload| {
+            let reason = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown cause");
+            Err(io::Error::other(format!("rendering '{}' panicked: {reason}", eq.name)).into())
+        },
+    )
+}
+
+/// Terminal progress for a batch: one overall bar with throughput and ETA,
+/// plus a spinner per worker naming the equation it is rendering.
+///
+/// Built on [`MultiProgress`], so workers on other threads can report
+/// concurrently without garbling the output.
+pub struct BatchProgress {
+    bar: ProgressBar,
+    workers: Vec<ProgressBar>,
+    timer: Arc<Mutex<RenderTimer>>,
+}
+
+impl BatchProgress {
+    /// Progress for `total` equations rendered by `workers` workers.
+    pub fn new(total: usize, workers: usize) -> Self {
+        Self::with_target(total, workers, MultiProgress::new())
+    }
+
+    /// Like [`BatchProgress::new`], drawing to `multi` (e.g. a hidden one in tests).
+    pub fn with_target(total: usize, workers: usize, multi: MultiProgress) -> Self {
+        let timer = Arc::new(Mutex::new(RenderTimer::default()));
+        let rate_timer = Arc::clone(&timer);
+        let eta_timer = Arc::clone(&timer);
+        let bar = multi.add(
+            ProgressBar::new(total as u64).with_style(
+                ProgressStyle::default_bar()
+                    .template("[{bar:40.cyan/blue}] {pos}/{len} {rate} {eta_hist}")
+                    .unwrap()
+                    .with_key("rate", move |_: &ProgressState, w: &mut dyn fmt::Write| {
+                        if let Some(rate) = rate_timer
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .per_minute()
+                        {
+                            let _ = write!(w, "{rate:.1} eq/min");
+                        }
+                    })
+                    .with_key(
+                        "eta_hist",
+                        move |state: &ProgressState, w: &mut dyn fmt::Write| {
+                            let remaining = state.len().unwrap_or(0).saturating_sub(state.pos());
+                            if let Some(eta) = eta_timer
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .eta(remaining as usize)
+                            {
+                                let _ = write!(w, "ETA {}", format_duration(eta));
+                            }
+                        },
+                    )
+                    .progress_chars("#>-"),
+            ),
+        );
+        let spinner = ProgressStyle::default_spinner()
+            .template("{spinner:.green} {prefix} {msg}")
+            .unwrap();
+        let workers = (0..workers.max(1))
+            .map(|i| {
+                let worker = multi.add(ProgressBar::new_spinner().with_style(spinner.clone()));
+                if workers > 1 {
+                    worker.set_prefix(format!("[{}]", i + 1));
+                }
+                worker
+            })
+            .collect();
+        BatchProgress {
+            bar,
+            workers,
+            timer,
+        }
+    }
+
+    /// Mark `done` equations as finished already, e.g. when resuming.
+    pub fn set_position(&self, done: usize) {
+        self.bar.set_position(done as u64);
+    }
+
+    /// Worker `worker` started on `name`.
+    pub fn start(&self, worker: usize, name: &str) {
+        let spinner = &self.workers[worker % self.workers.len()];
+        spinner.set_message(name.to_string());
+        spinner.enable_steady_tick(Duration::from_millis(120));
+    }
+
+    /// Worker `worker` finished an equation after `took`.
+    pub fn finish_one(&self, worker: usize, took: Duration) {
+        if !took.is_zero() {
+            self.timer
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .record(took);
+        }
+        let spinner = &self.workers[worker % self.workers.len()];
+        spinner.disable_steady_tick();
+        spinner.set_message("");
+        self.bar.inc(1);
+    }
+
+    /// Equations finished so far.
+    pub fn position(&self) -> usize {
+        self.bar.position() as usize
+    }
+
+    /// Remove the worker spinners and leave the overall bar on screen.
+    pub fn finish(&self) {
+        for worker in &self.workers {
+            worker.finish_and_clear();
+        }
+        self.bar.finish();
+    }
+}
+
+/// A batch that ended without [`BatchProgress::finish`], e.g. on an error,
+/// still removes its spinners and leaves the bar where it stopped.
+impl Drop for BatchProgress {
+    fn drop(&mut self) {
+        for worker in &self.workers {
+            worker.finish_and_clear();
+        }
+        if !self.bar.is_finished() {
+            self.bar.abandon();
+        }
+    }
+}
@@ -0,0 +1,1410 @@
+//! Reports about equations and render output: tables and text previews for
+//! humans, the `index.json` manifest for tools such as static site generators,
+//! content-addressed artifact names, the history of earlier artifacts, `.tex`
+//! snippets for LaTeX documents, beamer review decks, an HTML gallery,
+//! integrity checks of the artifacts, search across output trees, fuzzy
+//! matching for the GUI's command palette, render statistics and desktop
+//! notifications.
+//!
+//! ```
+//! use equation_processor::report::unicode_preview;
+//!
+//! assert_eq!(unicode_preview(r"\alpha^2"), "α²");
+//! ```
+
+use crate::model::{DisplaySize, Equation, EquationKind};
+use crate::render::{BatchSummary, RenderOptions};
+use crate::shard::Shard;
+use prettytable::{row, Table};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::LazyLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// File name of the manifest written into the output directory.
+pub const INDEX_FILE: &str = "index.json";
+
+/// Version of the `index.json` schema, bumped on incompatible changes.
+pub const INDEX_VERSION: u32 = 2;
+
+/// Version of the [`CONTENT_MANIFEST`] schema, bumped on incompatible changes.
+pub const CONTENT_MANIFEST_VERSION: u32 = 1;
+
+/// Top-level structure of `index.json`.
+#[derive(Debug, Serialize)]
+pub struct Index {
+    /// Schema version, see [`INDEX_VERSION`]
+    pub version: u32,
+    /// One entry per equation, in input order; inactive ones are `skipped`
+    pub equations: Vec<IndexEntry>,
+    /// The part of the batch this run rendered, for
+    /// [`merge_manifests`](crate::shard::merge_manifests); absent for a
+    /// whole batch
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shard: Option<Shard>,
+}
+
+/// One equation in `index.json`.
+///
+/// Paths are relative to the output directory and `null` when the artifact
+/// does not exist. Dimensions are taken from the SVG, in `unit`.
+#[derive(Debug, Serialize)]
+pub struct IndexEntry {
+    /// Equation name, also the artifact file stem
+    pub name: String,
+    /// `rendered`, `missing` (active but no artifact) or `skipped` (inactive)
+    pub status: &'static str,
+    /// PDF file name
+    pub pdf: Option<String>,
+    /// Size, version and fonts of the PDF
+    pub pdf_properties: Option<PdfProperties>,
+    /// SVG file name
+    pub svg: Option<String>,
+    /// PNG file name
+    pub png: Option<String>,
+    /// Rendered width
+    pub width: Option<f64>,
+    /// Rendered height
+    pub height: Option<f64>,
+    /// Unit of `width` and `height`, always `pt`
+    pub unit: &'static str,
+    /// LaTeX body as written in the input
+    pub latex: String,
+    /// Equation kind (`math`, `tikz`, `chem`)
+    pub kind: &'static str,
+    /// Tags from the input
+    pub tags: Vec<String>,
+    /// Enclosing document section, if known
+    pub section: Option<String>,
+    /// Description from the input, if any
+    pub description: Option<String>,
+    /// Width and scale the equation is meant to be shown at, from its
+    /// `%%width%%` and `%%scale%%` tags
+    pub display: DisplaySize,
+    /// SHA-256 of each artifact by extension, for
+    /// [`verify_outputs`]
+    pub sha256: BTreeMap<String, String>,
+}
+
+/// File name of the name → content-addressed file manifest.
+pub const CONTENT_MANIFEST: &str = "manifest.json";
+
+/// Artifact extensions renamed by [`content_address`] and archived by
+/// [`archive_changed`].
+const CONTENT_EXTENSIONS: &[&str] = &["svg", "png", "pdf"];
+
+/// Maps equation names to their content-addressed artifact files.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ContentManifest {
+    /// Schema version, see [`CONTENT_MANIFEST_VERSION`]
+    pub version: u32,
+    /// Equation name → extension → file name, e.g. `energy` → `svg` → `ab34….svg`
+    pub equations: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl ContentManifest {
+    /// Load the manifest from `output_dir`, if one of this version was
+    /// written.
+    pub fn read(output_dir: &Path) -> Option<Self> {
+        let json = fs::read_to_string(output_dir.join(CONTENT_MANIFEST)).ok()?;
+        serde_json::from_str::<Self>(&json)
+            .ok()
+            .filter(|manifest| manifest.version == CONTENT_MANIFEST_VERSION)
+    }
+
+    /// File holding the `ext` artifact of equation `name`.
+    pub fn artifact(&self, name: &str, ext: &str) -> Option<&str> {
+        self.equations.get(name)?.get(ext).map(String::as_str)
+    }
+}
+
+/// Rename the artifacts of the active equations to `<sha256 prefix>.<ext>`
+/// and record the mapping in `manifest.json`.
+///
+/// Equations with identical output share one file. Entries of a previous
+/// manifest are kept, so several batches can share an output directory.
+/// Later reports ([`write_index`], exports) resolve names through the manifest.
+pub fn content_address(equations: &[Equation], output_dir: &Path) -> io::Result<ContentManifest> {
+    let mut manifest = ContentManifest::read(output_dir).unwrap_or_default();
+    manifest.version = CONTENT_MANIFEST_VERSION;
+    for eq in equations.iter().filter(|e| e.active) {
+        for ext in CONTENT_EXTENSIONS {
+            let source = output_dir.join(format!("{}.{ext}", eq.name));
+            if !source.exists() {
+                continue;
+            }
+            let digest = Sha256::digest(fs::read(&source)?);
+            let hash: String = digest[..16].iter().map(|b| format!("{b:02x}")).collect();
+            let file = format!("{hash}.{ext}");
+            let target = output_dir.join(&file);
+            if target.exists() {
+                fs::remove_file(&source)?;
+            } else {
+                fs::rename(&source, &target)?;
+            }
+            manifest
+                .equations
+                .entry(eq.name.clone())
+                .or_default()
+                .insert(ext.to_string(), file);
+        }
+    }
+    let json = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+    fs::write(output_dir.join(CONTENT_MANIFEST), json + "\n")?;
+    Ok(manifest)
+}
+
+/// Read the `width`/`height` attributes (in pt) of an SVG written by pdftocairo.
+pub fn svg_dimensions(path: &Path) -> Option<(f64, f64)> {
+    let svg = fs::read_to_string(path).ok()?;
+    let root = &svg[svg.find("<svg")?..];
+    let root = &root[..root.find('>')?];
+    static WIDTH: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"\bwidth="([0-9.]+)(pt)?""#).unwrap());
+    static HEIGHT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"\bheight="([0-9.]+)(pt)?""#).unwrap());
+    let attr = |re: &Regex| -> Option<f64> { re.captures(root)?[1].parse().ok() };
+    Some((attr(&WIDTH)?, attr(&HEIGHT)?))
+}
+
+/// Properties of a compiled PDF relevant to journal submission.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PdfProperties {
+    /// File size in bytes
+    pub bytes: u64,
+    /// Version from the file header, e.g. `1.5`
+    pub version: Option<String>,
+    /// Embedded fonts, sorted; `None` if they are packed in compressed
+    /// object streams (PDF 1.5+ with compression) and cannot be read
+    pub fonts: Option<Vec<String>>,
+    /// Whether every embedded font is a subset (`ABCDEF+Name`)
+    pub fonts_subset: Option<bool>,
+}
+
+/// Read [`PdfProperties`] from the PDF at `path`.
+pub fn pdf_properties(path: &Path) -> Option<PdfProperties> {
+    let data = fs::read(path).ok()?;
+    let text = String::from_utf8_lossy(&data);
+    static VERSION: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^%PDF-(\d\.\d)").unwrap());
+    static BASE_FONT: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"/BaseFont\s*/([^\s/<>\[\]()]+)").unwrap());
+    static SUBSET: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^[A-Z]{6}\+").unwrap());
+    let version = VERSION.captures(&text).map(|c| c[1].to_string());
+    let mut fonts: Vec<String> = BASE_FONT
+        .captures_iter(&text)
+        .map(|c| c[1].to_string())
+        .collect();
+    fonts.sort();
+    fonts.dedup();
+    let fonts_subset = (!fonts.is_empty()).then(|| fonts.iter().all(|f| SUBSET.is_match(f)));
+    Some(PdfProperties {
+        bytes: data.len() as u64,
+        version,
+        fonts: (!fonts.is_empty()).then_some(fonts),
+        fonts_subset,
+    })
+}
+
+/// Build the index for `equations` from the artifacts in `output_dir`.
+///
+/// Artifacts are looked up through the [`ContentManifest`] when one exists.
+pub fn build_index(equations: &[Equation], output_dir: &Path) -> Index {
+    let manifest = ContentManifest::read(output_dir);
+    let artifact = |name: &str, ext: &str| {
+        let file = manifest
+            .as_ref()
+            .and_then(|m| m.artifact(name, ext))
+            .map_or_else(|| format!("{name}.{ext}"), String::from);
+        output_dir.join(&file).exists().then_some(file)
+    };
+    let equations = equations
+        .iter()
+        .map(|eq| {
+            let svg = artifact(&eq.name, "svg").filter(|_| eq.active);
+            let dims = svg
+                .as_ref()
+                .and_then(|f| svg_dimensions(&output_dir.join(f)));
+            let png = artifact(&eq.name, "png").filter(|_| eq.active);
+            let pdf = artifact(&eq.name, "pdf").filter(|_| eq.active);
+            let sha256 = [("svg", &svg), ("png", &png), ("pdf", &pdf)]
+                .into_iter()
+                .filter_map(|(ext, file)| {
+                    let data = fs::read(output_dir.join(file.as_ref()?)).ok()?;
+                    Some((ext.to_string(), sha256_hex(&data)))
+                })
+                .collect();
+            let status = match (eq.active, svg.is_some() || png.is_some() || pdf.is_some()) {
+                (false, _) => "skipped",
+                (true, true) => "rendered",
+                (true, false) => "missing",
+            };
+            IndexEntry {
+                name: eq.name.clone(),
+                status,
+                pdf_properties: pdf
+                    .as_ref()
+                    .and_then(|f| pdf_properties(&output_dir.join(f))),
+                pdf,
+                svg,
+                png,
+                width: dims.map(|d| d.0),
+                height: dims.map(|d| d.1),
+                unit: "pt",
+                latex: eq.body.clone(),
+                kind: eq.kind.as_str(),
+                tags: eq.tags.clone(),
+                section: eq.section.clone(),
+                description: eq.description.clone(),
+                display: eq.display.clone(),
+                sha256,
+            }
+        })
+        .collect();
+    Index {
+        version: INDEX_VERSION,
+        equations,
+        shard: None,
+    }
+}
+
+/// Write `index.json` describing the rendered equations into `output_dir`.
+pub fn write_index(equations: &[Equation], output_dir: &Path) -> io::Result<PathBuf> {
+    write_index_with(equations, output_dir, None)
+}
+
+/// Like [`write_index`], recording that `equations` are `shard` of a
+/// larger batch.
+pub fn write_index_with(
+    equations: &[Equation],
+    output_dir: &Path,
+    shard: Option<Shard>,
+) -> io::Result<PathBuf> {
+    let mut index = build_index(equations, output_dir);
+    index.shard = shard;
+    let path = output_dir.join(INDEX_FILE);
+    let json = serde_json::to_string_pretty(&index).map_err(io::Error::other)?;
+    fs::write(&path, json + "\n")?;
+    Ok(path)
+}
+
+/// Lowercase hex SHA-256 of `data`.
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// What is wrong with an artifact found by [`verify_outputs`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Integrity {
+    /// Listed in the index or manifest but not on disk
+    Missing,
+    /// Intact file whose content differs from when it was rendered
+    Modified,
+    /// Not a readable SVG/PNG/PDF, e.g. truncated by an interrupted sync
+    Corrupted,
+}
+
+impl fmt::Display for Integrity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Integrity::Missing => "missing",
+            Integrity::Modified => "modified",
+            Integrity::Corrupted => "corrupted",
+        })
+    }
+}
+
+/// An artifact that failed verification.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IntegrityProblem {
+    /// Equation the artifact belongs to
+    pub name: String,
+    /// File name relative to the output directory
+    pub file: String,
+    /// What is wrong with it
+    pub problem: Integrity,
+}
+
+/// The parts of an `index.json` entry needed to verify and search its
+/// artifacts.
+#[derive(Deserialize)]
+struct IndexRecord {
+    name: String,
+    svg: Option<String>,
+    png: Option<String>,
+    pdf: Option<String>,
+    #[serde(default)]
+    sha256: BTreeMap<String, String>,
+    #[serde(default)]
+    latex: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// The equations of the `index.json` at `path`.
+fn read_index_records(path: &Path) -> io::Result<Vec<IndexRecord>> {
+    #[derive(Deserialize)]
+    struct Records {
+        equations: Vec<IndexRecord>,
+    }
+    let records: Records = serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })?;
+    Ok(records.equations)
+}
+
+/// Check the artifacts in `output_dir` against `index.json` and, for
+/// content-addressed output, `manifest.json`. Returns the problems found,
+/// sorted by file; empty when everything is intact.
+///
+/// A file is modified when its SHA-256 differs from the one recorded at
+/// render time (or, for content-addressed files, from its name), and
+/// corrupted when it is no longer a well-formed SVG, PNG or PDF. Indexes
+/// written before checksums were recorded are checked for corruption only.
+/// Fails if neither file exists or one cannot be read.
+pub fn verify_outputs(output_dir: &Path) -> io::Result<Vec<IntegrityProblem>> {
+    // File name → (equation, expected hash or hash prefix)
+    let mut expected: BTreeMap<String, (String, Option<String>)> = BTreeMap::new();
+    let index_path = output_dir.join(INDEX_FILE);
+    let manifest_path = output_dir.join(CONTENT_MANIFEST);
+    if !index_path.exists() && !manifest_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "no {INDEX_FILE} or {CONTENT_MANIFEST} in {}",
+                output_dir.display()
+            ),
+        ));
+    }
+    if index_path.exists() {
+        for record in read_index_records(&index_path)? {
+            for (ext, file) in [
+                ("svg", record.svg),
+                ("png", record.png),
+                ("pdf", record.pdf),
+            ] {
+                if let Some(file) = file {
+                    let hash = record.sha256.get(ext).cloned();
+                    expected.insert(file, (record.name.clone(), hash));
+                }
+            }
+        }
+    }
+    if manifest_path.exists() {
+        let manifest: ContentManifest = serde_json::from_str(&fs::read_to_string(&manifest_path)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        for (name, files) in manifest.equations {
+            for file in files.into_values() {
+                let prefix = file.split('.').next().map(String::from);
+                expected.entry(file).or_insert((name.clone(), prefix));
+            }
+        }
+    }
+    let mut problems = Vec::new();
+    for (file, (name, hash)) in expected {
+        let problem = match fs::read(output_dir.join(&file)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Some(Integrity::Missing),
+            Err(e) => return Err(e),
+            Ok(data) => match hash.map(|h| sha256_hex(&data).starts_with(&h)) {
+                Some(true) => None,
+                _ if !is_well_formed(&file, &data) => Some(Integrity::Corrupted),
+                Some(false) => Some(Integrity::Modified),
+                None => None,
+            },
+        };
+        if let Some(problem) = problem {
+            problems.push(IntegrityProblem {
+                name,
+                file,
+                problem,
+            });
+        }
+    }
+    Ok(problems)
+}
+
+/// Whether `data` looks like a complete artifact of the type `file` names.
+fn is_well_formed(file: &str, data: &[u8]) -> bool {
+    let tail = &data[data.len().saturating_sub(1024)..];
+    let contains =
+        |haystack: &[u8], needle: &[u8]| haystack.windows(needle.len()).any(|w| w == needle);
+    match file.rsplit('.').next() {
+        Some("svg") => {
+            let text = String::from_utf8_lossy(data);
+            let end = text.trim_end();
+            text.contains("<svg") && (end.ends_with("</svg>") || end.ends_with("/>"))
+        }
+        Some("png") => data.starts_with(b"\x89PNG\r\n\x1a\n") && contains(tail, b"IEND"),
+        Some("pdf") => data.starts_with(b"%PDF-") && contains(tail, b"%%EOF"),
+        _ => true,
+    }
+}
+
+/// What a [`SearchQuery`] compares against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    /// Name, LaTeX, tags or description contain the text
+    Any,
+    /// The name contains the text
+    Name,
+    /// The LaTeX body contains the text
+    Body,
+    /// One of the tags equals the text
+    Tag,
+}
+
+/// A case-insensitive search over `index.json` entries: plain text, or
+/// text after a `name:`, `body:` or `tag:` prefix to search one field.
+///
+/// ```
+/// use equation_processor::report::{SearchField, SearchQuery};
+///
+/// let query: SearchQuery = "tag:Mechanics".parse().unwrap();
+/// assert_eq!(query.field, SearchField::Tag);
+/// assert_eq!(query.text, "mechanics");
+/// assert_eq!("frac".parse::<SearchQuery>().unwrap().field, SearchField::Any);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    /// Field searched
+    pub field: SearchField,
+    /// Lowercased text to look for
+    pub text: String,
+}
+
+impl FromStr for SearchQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, text) = match s.split_once(':') {
+            Some(("name", text)) => (SearchField::Name, text),
+            Some(("body", text)) => (SearchField::Body, text),
+            Some(("tag", text)) => (SearchField::Tag, text),
+            _ => (SearchField::Any, s),
+        };
+        let text = text.trim().to_lowercase();
+        if text.is_empty() {
+            return Err("empty search query".into());
+        }
+        Ok(SearchQuery { field, text })
+    }
+}
+
+impl SearchQuery {
+    /// Whether the index entry `record` matches.
+    fn matches(&self, record: &IndexRecord) -> bool {
+        let contains = |field: &str| field.to_lowercase().contains(&self.text);
+        let tagged = || record.tags.iter().any(|t| t.to_lowercase() == self.text);
+        match self.field {
+            SearchField::Name => contains(&record.name),
+            SearchField::Body => contains(&record.latex),
+            SearchField::Tag => tagged(),
+            SearchField::Any => {
+                contains(&record.name)
+                    || contains(&record.latex)
+                    || record.tags.iter().any(|t| contains(t))
+                    || record.description.as_deref().is_some_and(contains)
+            }
+        }
+    }
+}
+
+/// How well `text` matches the fuzzy `query`, higher being better, or `None`
+/// if it does not match.
+///
+/// Every character of the query must appear in `text` in order, ignoring
+/// case, but not necessarily next to each other, so `rsel` matches "Render
+/// selected". Runs of adjacent characters and matches at the start of a word
+/// score higher; an empty query matches everything with 0.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut chars = text.chars().flat_map(char::to_lowercase).enumerate();
+    let mut previous: Option<(usize, char)> = None;
+    let mut last_match = None;
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        if wanted.is_whitespace() {
+            continue;
+        }
+        loop {
+            let (i, c) = chars.next()?;
+            let word_start = previous.is_none_or(|(_, p)| !p.is_alphanumeric());
+            previous = Some((i, c));
+            if c != wanted {
+                continue;
+            }
+            score += 1;
+            if word_start {
+                score += 3;
+            }
+            if last_match.is_some_and(|last| last + 1 == i) {
+                score += 5;
+            }
+            last_match = Some(i);
+            break;
+        }
+    }
+    Some(score)
+}
+
+/// An equation found by [`search_outputs`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SearchHit {
+    /// Equation name
+    pub name: String,
+    /// LaTeX body
+    pub latex: String,
+    /// Tags from the input
+    pub tags: Vec<String>,
+    /// Description from the input, if any
+    pub description: Option<String>,
+    /// Artifacts listed in the index, joined to the directory holding it
+    pub artifacts: Vec<PathBuf>,
+}
+
+/// Search every `index.json` under `root` for equations matching `query`.
+///
+/// Subdirectories are searched recursively, except the `history` folders
+/// of archived artifacts. Hits are ordered by directory, then as in the
+/// index.
+pub fn search_outputs(root: &Path, query: &SearchQuery) -> io::Result<Vec<SearchHit>> {
+    let mut hits = Vec::new();
+    let mut dirs = vec![root.to_path_buf()];
+    let mut indexes = Vec::new();
+    while let Some(dir) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() && !path.ends_with(HISTORY_DIR) {
+                dirs.push(path);
+            } else if path.ends_with(INDEX_FILE) {
+                indexes.push(path);
+            }
+        }
+    }
+    indexes.sort();
+    for index in indexes {
+        let dir = index.parent().unwrap_or(root);
+        for record in read_index_records(&index)? {
+            if !query.matches(&record) {
+                continue;
+            }
+            let artifacts = [&record.svg, &record.png, &record.pdf]
+                .into_iter()
+                .flatten()
+                .map(|file| dir.join(file))
+                .collect();
+            hits.push(SearchHit {
+                name: record.name,
+                latex: record.latex,
+                tags: record.tags,
+                description: record.description,
+                artifacts,
+            });
+        }
+    }
+    Ok(hits)
+}
+
+/// Directory inside the output directory that keeps earlier artifacts.
+pub const HISTORY_DIR: &str = "history";
+
+/// One archived version of an equation's artifacts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    /// When the version was rendered, `YYYY-MM-DDTHH-MM-SS` in UTC
+    pub timestamp: String,
+    /// Its files, e.g. `history/energy.2024-05-01T10-00-00.svg`
+    pub files: Vec<PathBuf>,
+}
+
+/// Move the artifacts of active equations whose LaTeX changed since the
+/// last `index.json` into `output_dir/history`, as
+/// `<name>.<timestamp>.<ext>` stamped with the time they were rendered.
+/// Returns the archived files.
+///
+/// New and unchanged equations are left alone, as is everything when the
+/// output directory has no index yet.
+pub fn archive_changed(equations: &[Equation], output_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let previous = match fs::read_to_string(output_dir.join(INDEX_FILE)) {
+        Ok(json) => json,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let previous: serde_json::Value = serde_json::from_str(&previous).map_err(io::Error::other)?;
+    let latex: HashMap<&str, &str> = previous["equations"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|e| Some((e["name"].as_str()?, e["latex"].as_str()?)))
+        .collect();
+    let dir = output_dir.join(HISTORY_DIR);
+    let mut archived = Vec::new();
+    for eq in equations.iter().filter(|e| e.active) {
+        match latex.get(eq.name.as_str()) {
+            Some(old) if *old != eq.body => {}
+            _ => continue,
+        }
+        let files: Vec<PathBuf> = CONTENT_EXTENSIONS
+            .iter()
+            .map(|ext| output_dir.join(format!("{}.{ext}", eq.name)))
+            .filter(|p| p.is_file())
+            .collect();
+        let Some(first) = files.first() else {
+            continue;
+        };
+        let timestamp = file_timestamp(fs::metadata(first)?.modified()?);
+        fs::create_dir_all(&dir)?;
+        for file in files {
+            let ext = file.extension().unwrap_or_default().to_string_lossy();
+            let target = dir.join(format!("{}.{timestamp}.{ext}", eq.name));
+            fs::rename(&file, &target)?;
+            archived.push(target);
+        }
+    }
+    Ok(archived)
+}
+
+/// Archived versions of the equation `name` in `output_dir/history`,
+/// oldest first.
+pub fn history(name: &str, output_dir: &Path) -> io::Result<Vec<Version>> {
+    let entries = match fs::read_dir(output_dir.join(HISTORY_DIR)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    // Timestamps sort chronologically as text.
+    let mut versions: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for entry in entries {
+        let path = entry?.path();
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if let Some((stem_name, timestamp)) = stem.rsplit_once('.') {
+            if stem_name == name {
+                versions
+                    .entry(timestamp.to_string())
+                    .or_default()
+                    .push(path.clone());
+            }
+        }
+    }
+    Ok(versions
+        .into_iter()
+        .map(|(timestamp, mut files)| {
+            files.sort();
+            Version { timestamp, files }
+        })
+        .collect())
+}
+
+/// Format `time` as `YYYY-MM-DDTHH-MM-SS` in UTC, which is safe in file names.
+pub(crate) fn file_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+    // Civil date from days since 1970-01-01, after Howard Hinnant's
+    // `civil_from_days`.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}-{:02}-{:02}",
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
+/// File in the output directory where earlier versions collected
+/// [`RunStats`]; [`stats_file`] moves it to the data directory.
+pub const STATS_FILE: &str = ".equation_stats.jsonl";
+
+/// Outcome of one render run, appended to the [`stats_file`] by
+/// [`record_run`], one JSON object per line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunStats {
+    /// When the run finished, `YYYY-MM-DDTHH-MM-SS` in UTC
+    pub finished: String,
+    /// Equations rendered
+    pub rendered: usize,
+    /// Equations that failed to render
+    pub failed: usize,
+    /// Active equations left as they were: unchanged since the last run,
+    /// rendered by the run being resumed, or linked to a duplicate
+    #[serde(default)]
+    pub cached: usize,
+    /// Inactive equations
+    pub skipped: usize,
+    /// Wall-clock duration of the run
+    pub seconds: f64,
+}
+
+impl RunStats {
+    /// Stats of a run over `equations` that just finished after `took`.
+    pub fn new(equations: &[Equation], rendered: usize, failed: usize, took: Duration) -> Self {
+        RunStats {
+            finished: file_timestamp(SystemTime::now()),
+            rendered,
+            failed,
+            cached: 0,
+            skipped: equations.iter().filter(|e| !e.active).count(),
+            seconds: took.as_secs_f64(),
+        }
+    }
+
+    /// Stats of a run over `equations` that ended as `summary` says after
+    /// `took`.
+    pub fn from_batch(equations: &[Equation], summary: &BatchSummary, took: Duration) -> Self {
+        RunStats {
+            cached: summary.cached + summary.resumed + summary.linked,
+            ..RunStats::new(equations, summary.rendered, summary.failed, took)
+        }
+    }
+
+    /// Average seconds per rendered equation, if any were rendered.
+    pub fn seconds_per_equation(&self) -> Option<f64> {
+        (self.rendered > 0).then(|| self.seconds / self.rendered as f64)
+    }
+}
+
+/// The statistics file of runs into `output_dir`, kept under
+/// `data_dir/stats` and named after the directory and a hash of its
+/// absolute path. A [`STATS_FILE`] left in the output directory by earlier
+/// versions is moved there first, ahead of any newer runs.
+pub fn stats_file(data_dir: &Path, output_dir: &Path) -> io::Result<PathBuf> {
+    let absolute = fs::canonicalize(output_dir).or_else(|_| std::path::absolute(output_dir))?;
+    let hash = sha256_hex(absolute.to_string_lossy().as_bytes());
+    let dir_name = absolute.file_name().map_or("root".into(), |n| {
+        Equation::sanitize_filename(&n.to_string_lossy())
+    });
+    let path = data_dir
+        .join("stats")
+        .join(format!("{dir_name}-{}.jsonl", &hash[..12]));
+    let legacy = output_dir.join(STATS_FILE);
+    if legacy.is_file() {
+        let mut runs = fs::read_to_string(&legacy)?;
+        match fs::read_to_string(&path) {
+            Ok(newer) => runs.push_str(&newer),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        fs::create_dir_all(data_dir.join("stats"))?;
+        fs::write(&path, runs)?;
+        fs::remove_file(&legacy)?;
+    }
+    Ok(path)
+}
+
+/// [`stats_file`] in the platform [data directory](crate::paths::data_dir),
+/// or the [`STATS_FILE`] in `output_dir` when there is none.
+pub fn default_stats_file(output_dir: &Path) -> io::Result<PathBuf> {
+    match crate::paths::data_dir() {
+        Some(data_dir) => stats_file(&data_dir, output_dir),
+        None => Ok(output_dir.join(STATS_FILE)),
+    }
+}
+
+/// Append `stats` to the statistics file at `path`.
+pub fn record_run(path: &Path, stats: &RunStats) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let line = serde_json::to_string(stats).map_err(io::Error::other)?;
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)?;
+    writeln!(file, "{line}")
+}
+
+/// The runs recorded in the statistics file at `path`, oldest first;
+/// empty when there is none. Lines that do not parse, e.g. from an
+/// interrupted write, are skipped.
+pub fn read_stats(path: &Path) -> io::Result<Vec<RunStats>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Averages over the most recent runs, compared with the runs before them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsTrend {
+    /// Runs averaged, at most the `last` asked for
+    pub runs: usize,
+    /// Render time per equation over those runs
+    pub seconds_per_equation: Option<f64>,
+    /// Render time per equation over as many runs before them
+    pub previous_seconds_per_equation: Option<f64>,
+    /// Share of attempted equations that failed over those runs
+    pub failure_rate: Option<f64>,
+}
+
+impl StatsTrend {
+    /// Trend over the `last` runs of `runs` (oldest first).
+    ///
+    /// ```
+    /// use equation_processor::report::{RunStats, StatsTrend};
+    ///
+    /// let run = |rendered, seconds| RunStats {
+    ///     finished: String::new(),
+    ///     rendered,
+    ///     failed: 0,
+    ///     cached: 0,
+    ///     skipped: 0,
+    ///     seconds,
+    /// };
+    /// let trend = StatsTrend::new(&[run(10, 10.0), run(10, 15.0)], 1);
+    /// assert_eq!(trend.seconds_per_equation, Some(1.5));
+    /// assert_eq!(trend.change(), Some(0.5));
+    /// ```
+    pub fn new(runs: &[RunStats], last: usize) -> Self {
+        let split = runs.len().saturating_sub(last);
+        let recent = &runs[split..];
+        let previous = &runs[split.saturating_sub(last)..split];
+        let per_equation = |runs: &[RunStats]| {
+            let rendered: usize = runs.iter().map(|r| r.rendered).sum();
+            let seconds: f64 = runs
+                .iter()
+                .filter(|r| r.rendered > 0)
+                .map(|r| r.seconds)
+                .sum();
+            (rendered > 0).then(|| seconds / rendered as f64)
+        };
+        let failed: usize = recent.iter().map(|r| r.failed).sum();
+        let attempted: usize = recent.iter().map(|r| r.rendered + r.failed).sum();
+        StatsTrend {
+            runs: recent.len(),
+            seconds_per_equation: per_equation(recent),
+            previous_seconds_per_equation: per_equation(previous),
+            failure_rate: (attempted > 0).then(|| failed as f64 / attempted as f64),
+        }
+    }
+
+    /// Relative change of the render time per equation against the
+    /// previous runs, e.g. `0.25` when 25% slower.
+    pub fn change(&self) -> Option<f64> {
+        let (now, before) = (
+            self.seconds_per_equation?,
+            self.previous_seconds_per_equation?,
+        );
+        (before > 0.0).then(|| now / before - 1.0)
+    }
+}
+
+/// Directory inside the output directory that receives the `.tex` snippets.
+pub const SNIPPET_DIR: &str = "snippets";
+
+/// File name of the master include file in [`SNIPPET_DIR`].
+pub const SNIPPET_MASTER: &str = "equations.tex";
+
+/// Write one `\input`-ready snippet per active equation (see
+/// [`Equation::latex_snippet`]) into `output_dir/snippets`, plus a master file
+/// inputting all of them in order. Returns the master file's path.
+///
+/// The master file reads snippets from `\equationsnippets`, which defaults to
+/// the snippet directory as given and can be defined before `\input` when the
+/// document is compiled from elsewhere.
+pub fn write_tex_snippets(equations: &[Equation], output_dir: &Path) -> io::Result<PathBuf> {
+    let dir = output_dir.join(SNIPPET_DIR);
+    fs::create_dir_all(&dir)?;
+    let active: Vec<&Equation> = equations.iter().filter(|e| e.active).collect();
+    let packages = required_packages(&active);
+    let mut master = format!(
+        "% Generated by equation_processor; requires: {}\n\\providecommand{{\\equationsnippets}}{{{}}}\n",
+        packages.join(", "),
+        dir.display().to_string().replace('\\', "/")
+    );
+    for eq in active {
+        fs::write(dir.join(format!("{}.tex", eq.name)), eq.latex_snippet())?;
+        master.push_str(&format!("\\input{{\\equationsnippets/{}}}\n", eq.name));
+    }
+    let path = dir.join(SNIPPET_MASTER);
+    fs::write(&path, master)?;
+    Ok(path)
+}
+
+/// LaTeX packages needed by the snippets of `equations`.
+fn required_packages(equations: &[&Equation]) -> Vec<&'static str> {
+    let mut packages = vec!["amsmath"];
+    if equations.iter().any(|e| e.kind == EquationKind::Chemistry) {
+        packages.push("mhchem");
+    }
+    if equations.iter().any(|e| e.kind == EquationKind::TikZ) {
+        packages.push("tikz");
+    }
+    packages
+}
+
+/// File name of the beamer deck written by [`write_beamer`].
+pub const BEAMER_FILE: &str = "slides.tex";
+
+/// Write a beamer presentation with one frame per active equation into
+/// `output_dir`, titled with the equation's name and subtitled with its
+/// section. Returns the path of the `.tex` file; compile it with
+/// [`TectonicConfig::compile_document`](crate::render::TectonicConfig::compile_document).
+pub fn write_beamer(equations: &[Equation], output_dir: &Path) -> io::Result<PathBuf> {
+    let active: Vec<&Equation> = equations.iter().filter(|e| e.active).collect();
+    let mut deck = String::from("% Generated by equation_processor\n\\documentclass{beamer}\n");
+    for package in required_packages(&active) {
+        match package {
+            "mhchem" => deck.push_str("\\usepackage[version=4]{mhchem}\n"),
+            package => deck.push_str(&format!("\\usepackage{{{package}}}\n")),
+        }
+    }
+    let mut libraries: Vec<&str> = active
+        .iter()
+        .flat_map(|e| e.tikz_libraries.iter().map(String::as_str))
+        .collect();
+    libraries.sort_unstable();
+    libraries.dedup();
+    if !libraries.is_empty() {
+        deck.push_str(&format!("\\usetikzlibrary{{{}}}\n", libraries.join(",")));
+    }
+    deck.push_str("\\begin{document}\n");
+    for eq in active {
+        deck.push_str(&format!("\\begin{{frame}}{{{}}}\n", latex_escape(&eq.name)));
+        if let Some(section) = &eq.section {
+            deck.push_str(&format!("\\framesubtitle{{{}}}\n", latex_escape(section)));
+        }
+        deck.push_str(&format!(
+            "\\vfill\n\\begin{{center}}\\Large\n{}\\end{{center}}\n\\vfill\n\\end{{frame}}\n",
+            eq.latex_snippet()
+        ));
+    }
+    deck.push_str("\\end{document}\n");
+    let path = output_dir.join(BEAMER_FILE);
+    fs::write(&path, deck)?;
+    Ok(path)
+}
+
+/// File name of the HTML gallery written by [`write_gallery`].
+pub const GALLERY_FILE: &str = "gallery.html";
+
+/// Page style of the gallery. Artifacts are rendered with dark ink on a
+/// transparent background, so in dark mode they are inverted; the hue
+/// rotation keeps colored equations close to their original hue.
+const GALLERY_STYLE: &str = "\
+:root {
+  color-scheme: light dark;
+  --background: #ffffff;
+  --text: #1f2328;
+  --muted: #656d76;
+  --border: #d0d7de;
+  --equation-filter: none;
+}
+@media (prefers-color-scheme: dark) {
+  :root {
+    --background: #0d1117;
+    --text: #e6edf3;
+    --muted: #8d96a0;
+    --border: #30363d;
+    --equation-filter: invert(1) hue-rotate(180deg);
+  }
+}
+body { margin: 2rem; background: var(--background); color: var(--text); font-family: system-ui, sans-serif; }
+h2 { margin-top: 2rem; border-bottom: 1px solid var(--border); }
+figure { margin: 1rem 0; padding: 1rem; border: 1px solid var(--border); border-radius: 6px; }
+figure img { max-width: 100%; filter: var(--equation-filter); }
+figcaption { margin-top: 0.5rem; color: var(--muted); }
+.description { font-style: italic; }
+code { white-space: pre-wrap; }
+";
+
+/// Write `gallery.html` into `output_dir`: a page showing the image of every
+/// rendered equation with its name, description and LaTeX source, grouped
+/// by section.
+///
+/// The page follows the reader's light or dark preference
+/// (`prefers-color-scheme`) through CSS variables, so formula sheets stay
+/// readable in dark mode. SVGs are preferred over PNGs; equations without
+/// an image are left out. Returns the path of the page.
+pub fn write_gallery(equations: &[Equation], output_dir: &Path) -> io::Result<PathBuf> {
+    let index = build_index(equations, output_dir);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"color-scheme\" content=\"light dark\">\n\
+         <title>Equations</title>\n<style>\n{GALLERY_STYLE}</style>\n</head>\n<body>\n\
+         <h1>Equations</h1>\n"
+    );
+    let mut section = None;
+    for entry in &index.equations {
+        let Some(image) = entry.svg.as_ref().or(entry.png.as_ref()) else {
+            continue;
+        };
+        if entry.section.is_some() && entry.section != section {
+            section = entry.section.clone();
+            html.push_str(&format!(
+                "<h2>{}</h2>\n",
+                html_escape(section.as_deref().unwrap_or_default())
+            ));
+        }
+        html.push_str(&format!(
+            "<figure id=\"{name}\">\n<img src=\"{src}\" alt=\"{alt}\"{style}>\n\
+             <figcaption><strong>{name}</strong>{description} <code>{alt}</code></figcaption>\n\
+             </figure>\n",
+            name = html_escape(&entry.name),
+            src = html_escape(image),
+            alt = html_escape(&entry.latex),
+            style = entry
+                .display
+                .width
+                .as_deref()
+                .map(|width| format!(" style=\"width: {}\"", html_escape(width)))
+                .unwrap_or_default(),
+            description = entry
+                .description
+                .as_deref()
+                .map(|d| format!(" <span class=\"description\">{}</span>", html_escape(d)))
+                .unwrap_or_default(),
+        ));
+    }
+    html.push_str("</body>\n</html>\n");
+    let path = output_dir.join(GALLERY_FILE);
+    fs::write(&path, html)?;
+    Ok(path)
+}
+
+/// File name of the Markdown page written by [`write_markdown`].
+pub const MARKDOWN_FILE: &str = "equations.md";
+
+/// CSS pixels per point, for `width`/`height` attributes of `<img>`.
+const PIXELS_PER_POINT: f64 = 96.0 / 72.0;
+
+/// Write `equations.md` into `output_dir`: an image reference to every
+/// rendered equation, with its description below it, under a heading per
+/// section. SVGs are preferred over PNGs; equations without an image are
+/// left out. Returns the path of the page.
+///
+/// With `dimensions`, references are `<img>` elements with the `width` and
+/// `height` of the SVG in CSS pixels, so a page embedding them doesn't
+/// shift its layout while the images load. Otherwise they are plain
+/// `![...](...)` images. The LaTeX source is the alternative text.
+pub fn write_markdown(
+    equations: &[Equation],
+    output_dir: &Path,
+    dimensions: bool,
+) -> io::Result<PathBuf> {
+    let index = build_index(equations, output_dir);
+    let mut markdown = String::from("# Equations\n");
+    let mut section = None;
+    for entry in &index.equations {
+        let Some(image) = entry.svg.as_ref().or(entry.png.as_ref()) else {
+            continue;
+        };
+        if entry.section.is_some() && entry.section != section {
+            section = entry.section.clone();
+            markdown.push_str(&format!(
+                "\n## {}\n",
+                section.as_deref().unwrap_or_default()
+            ));
+        }
+        let size = entry
+            .width
+            .zip(entry.height)
+            .filter(|_| dimensions && entry.svg.is_some());
+        let reference = match size {
+            Some((width, height)) => format!(
+                "<img src=\"{}\" alt=\"{}\" width=\"{}\" height=\"{}\">",
+                html_escape(image),
+                html_escape(&entry.latex),
+                css_pixels(width),
+                css_pixels(height)
+            ),
+            None => format!(
+                "![{}](<{}>)",
+                markdown_escape(&entry.latex),
+                image.replace('>', "%3E")
+            ),
+        };
+        markdown.push_str(&format!("\n{reference}\n"));
+        if let Some(description) = &entry.description {
+            markdown.push_str(&format!("\n*{}*\n", markdown_escape(description)));
+        }
+    }
+    let path = output_dir.join(MARKDOWN_FILE);
+    fs::write(&path, markdown)?;
+    Ok(path)
+}
+
+/// `points` in whole CSS pixels, at least 1.
+fn css_pixels(points: f64) -> u64 {
+    ((points * PIXELS_PER_POINT).round() as u64).max(1)
+}
+
+/// Escape text for Markdown image alternative text and emphasis: the
+/// characters that would end or format it are backslash-escaped.
+fn markdown_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+    {
+        if matches!(c, '\\' | '[' | ']' | '*' | '_' | '`' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape text for HTML element content and double-quoted attributes (also
+/// valid in XML such as SVG).
+pub(crate) fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape LaTeX special characters in plain text such as names and headings.
+fn latex_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            '#' | '$' | '%' | '&' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// One-line end-of-run summary: how many equations were rendered and
+/// which were skipped as inactive.
+pub fn run_summary(equations: &[Equation]) -> String {
+    let rendered = equations.iter().filter(|e| e.active).count();
+    let skipped: Vec<&str> = equations
+        .iter()
+        .filter(|e| !e.active)
+        .map(|e| e.name.as_str())
+        .collect();
+    if skipped.is_empty() {
+        format!("Rendered {rendered} equation(s).")
+    } else {
+        format!(
+            "Rendered {rendered} equation(s), skipped {} inactive: {}",
+            skipped.len(),
+            skipped.join(", ")
+        )
+    }
+}
+
+/// Pop up a desktop notification, e.g. when a batch finishes while the
+/// user is looking elsewhere.
+pub fn notify_desktop(summary: &str, body: &str) -> Result<(), String> {
+    notify_rust::Notification::new()
+        .appname("Equation Processor")
+        .summary(summary)
+        .body(body)
+        .show()
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Note on pipeline stages the batch `options` skipped, if any.
+pub fn skipped_stages(options: &RenderOptions) -> Option<String> {
+    (options.image_formats().next().is_none())
+        .then(|| "Skipped PDF→image conversion; only PDFs were written.".to_string())
+}
+
+/// Print a short table summary.
+pub fn display_table(equations: &[Equation]) {
+    let mut table = Table::new();
+    if equations.iter().any(|e| e.description.is_some()) {
+        table.add_row(row!["Active", "Name", "Equation", "Description"]);
+        for eq in equations {
+            let description = eq.description.as_deref().unwrap_or_default();
+            table.add_row(row![
+                if eq.active { "Yes" } else { "No" },
+                eq.name,
+                eq.body,
+                description
+            ]);
+        }
+    } else {
+        table.add_row(row!["Active", "Name", "Equation"]);
+        for eq in equations {
+            table.add_row(row![if eq.active { "Yes" } else { "No" }, eq.name, eq.body]);
+        }
+    }
+    table.printstd();
+}
+
+/// Approximate a LaTeX body as plain Unicode text.
+///
+/// Greek letters, common operators and simple super-/subscripts are mapped
+/// to their Unicode counterparts and `\frac{a}{b}` becomes `(a)/(b)`.
+/// Other commands are dropped in favor of their argument, or kept verbatim
+/// when they have none. Intended for quick previews only.
+pub fn unicode_preview(body: &str) -> String {
+    const SYMBOLS: &[(&str, &str)] = &[
+        ("alpha", "α"),
+        ("beta", "β"),
+        ("gamma", "γ"),
+        ("delta", "δ"),
+        ("epsilon", "ε"),
+        ("varepsilon", "ε"),
+        ("zeta", "ζ"),
+        ("eta", "η"),
+        ("theta", "θ"),
+        ("iota", "ι"),
+        ("kappa", "κ"),
+        ("lambda", "λ"),
+        ("mu", "μ"),
+        ("nu", "ν"),
+        ("xi", "ξ"),
+        ("pi", "π"),
+        ("rho", "ρ"),
+        ("sigma", "σ"),
+        ("tau", "τ"),
+        ("phi", "φ"),
+        ("varphi", "φ"),
+        ("chi", "χ"),
+        ("psi", "ψ"),
+        ("omega", "ω"),
+        ("Gamma", "Γ"),
+        ("Delta", "Δ"),
+        ("Theta", "Θ"),
+        ("Lambda", "Λ"),
+        ("Pi", "Π"),
+        ("Sigma", "Σ"),
+        ("Phi", "Φ"),
+        ("Psi", "Ψ"),
+        ("Omega", "Ω"),
+        ("int", "∫"),
+        ("iint", "∬"),
+        ("oint", "∮"),
+        ("sum", "∑"),
+        ("prod", "∏"),
+        ("partial", "∂"),
+        ("nabla", "∇"),
+        ("infty", "∞"),
+        ("cdot", "·"),
+        ("times", "×"),
+        ("pm", "±"),
+        ("mp", "∓"),
+        ("leq", "≤"),
+        ("le", "≤"),
+        ("geq", "≥"),
+        ("ge", "≥"),
+        ("neq", "≠"),
+        ("approx", "≈"),
+        ("equiv", "≡"),
+        ("propto", "∝"),
+        ("sim", "∼"),
+        ("to", "→"),
+        ("rightarrow", "→"),
+        ("leftarrow", "←"),
+        ("Rightarrow", "⇒"),
+        ("Leftrightarrow", "⇔"),
+        ("in", "∈"),
+        ("forall", "∀"),
+        ("exists", "∃"),
+        ("hbar", "ℏ"),
+        ("ell", "ℓ"),
+        ("sqrt", "√"),
+        ("quad", " "),
+        ("qquad", "  "),
+        ("left", ""),
+        ("right", ""),
+    ];
+    const SUPERSCRIPTS: &[(char, char)] = &[
+        ('0', '⁰'),
+        ('1', '¹'),
+        ('2', '²'),
+        ('3', '³'),
+        ('4', '⁴'),
+        ('5', '⁵'),
+        ('6', '⁶'),
+        ('7', '⁷'),
+        ('8', '⁸'),
+        ('9', '⁹'),
+        ('+', '⁺'),
+        ('-', '⁻'),
+        ('n', 'ⁿ'),
+        ('i', 'ⁱ'),
+    ];
+    const SUBSCRIPTS: &[(char, char)] = &[
+        ('0', '₀'),
+        ('1', '₁'),
+        ('2', '₂'),
+        ('3', '₃'),
+        ('4', '₄'),
+        ('5', '₅'),
+        ('6', '₆'),
+        ('7', '₇'),
+        ('8', '₈'),
+        ('9', '₉'),
+        ('+', '₊'),
+        ('-', '₋'),
+    ];
+
+    let frac = Regex::new(r"\\[dt]?frac\{([^{}]*)\}\{([^{}]*)\}").unwrap();
+    let mut text = body.to_string();
+    while frac.is_match(&text) {
+        text = frac.replace_all(&text, "($1)/($2)").into_owned();
+    }
+    // Unknown commands taking an argument (`\mathrm{d}`) keep only the argument.
+    let command = Regex::new(r"\\([A-Za-z]+)(\{)?").unwrap();
+    let text = command.replace_all(&text, |c: &regex::Captures| {
+        let brace = c.get(2).map_or("", |m| m.as_str());
+        match SYMBOLS.iter().find(|(cmd, _)| *cmd == &c[1]) {
+            Some((_, sym)) => format!("{sym}{brace}"),
+            None if brace.is_empty() => c[0].to_string(),
+            None => brace.to_string(),
+        }
+    });
+    let script = Regex::new(r"([\^_])(\{([^{}]*)\}|(.))").unwrap();
+    let text = script.replace_all(&text, |c: &regex::Captures| {
+        let table = if &c[1] == "^" {
+            SUPERSCRIPTS
+        } else {
+            SUBSCRIPTS
+        };
+        let inner = c.get(3).or(c.get(4)).map_or("", |m| m.as_str());
+        let mapped: Option<String> = inner
+            .chars()
+            .map(|ch| table.iter().find(|(k, _)| *k == ch).map(|(_, v)| *v))
+            .collect();
+        mapped.unwrap_or_else(|| format!("{}({inner})", &c[1]))
+    });
+    text.replace(['{', '}'], "")
+}
@@ -0,0 +1,367 @@
+//! JSON-RPC 2.0 over stdio, for editor plugins (Obsidian, VS Code, …) that
+//! keep one long-lived `equation_processor rpc` child process.
+//!
+//! Each message is a single line of JSON. Requests:
+//!
+//! - `parse` `{content, format?}`: equations of a Markdown (default) or CSV
+//!   document, with byte spans for Markdown
+//! - `render` `{body, outputDir, name?, kind?, color?}`: render one equation,
+//!   returns the artifact paths
+//! - `renderBatch` `{content, outputDir, format?, color?}`: render every active
+//!   equation of a document, sending `$/progress` notifications
+//!   `{id, done, total, name}`; returns `{rendered, failed, cancelled}`
+//! - `status`: server version and the ids of running renders
+//! - `shutdown`: stop reading requests once running renders finish
+//!
+//! Renders run in the background, so other requests (and the
+//! `$/cancelRequest` `{id}` notification, which stops a batch before its
+//! next equation) are served meanwhile.
+//!
+//! ```
+//! use equation_processor::render::TectonicConfig;
+//! use equation_processor::rpc::RpcServer;
+//!
+//! let input = r#"{"jsonrpc":"2.0","id":1,"method":"parse","params":{"content":"$$\nx\n$$\n%%x%%"}}"#;
+//! let output = RpcServer::new(TectonicConfig::default()).serve(input.as_bytes(), Vec::new())?;
+//! assert!(String::from_utf8(output).unwrap().contains(r#""name":"x""#));
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::error::EquationError;
+use crate::model::{Equation, EquationKind};
+use crate::parser::{parse_csv, parse_markdown_with_spans};
+use crate::render::{
+    disambiguate_names, RenderOptions, Renderer, TectonicConfig, TectonicRenderer,
+};
+use crate::report::write_index;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Invalid JSON was received.
+pub const PARSE_ERROR: i64 = -32700;
+/// The message is not a valid request object.
+pub const INVALID_REQUEST: i64 = -32600;
+/// The method does not exist.
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// The parameters do not match the method.
+pub const INVALID_PARAMS: i64 = -32602;
+/// The request failed, e.g. tectonic reported an error.
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// A JSON-RPC server rendering with tectonic, or with a custom [`Renderer`].
+pub struct RpcServer {
+    tectonic: TectonicConfig,
+    renderer: Option<Arc<dyn Renderer + Send + Sync>>,
+}
+
+/// State shared between the request loop and background renders.
+struct Session<W> {
+    output: Mutex<W>,
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl<W: Write> Session<W> {
+    fn send(&self, message: &Value) {
+        let mut out = self.output.lock().unwrap();
+        let _ = writeln!(out, "{message}");
+        let _ = out.flush();
+    }
+
+    fn respond(&self, id: &Value, result: Result<Value, (i64, String)>) {
+        let message = match result {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message},
+            }),
+        };
+        self.send(&message);
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ParseParams {
+    content: String,
+    #[serde(default)]
+    format: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RenderParams {
+    body: String,
+    output_dir: PathBuf,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    kind: Option<String>,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BatchParams {
+    content: String,
+    output_dir: PathBuf,
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    color: Option<String>,
+}
+
+impl RpcServer {
+    /// A server rendering with tectonic configured by `tectonic`.
+    pub fn new(tectonic: TectonicConfig) -> Self {
+        RpcServer {
+            tectonic,
+            renderer: None,
+        }
+    }
+
+    /// Render with `renderer` instead of tectonic; the `color` parameter is
+    /// then up to the renderer.
+    pub fn with_renderer(mut self, renderer: Arc<dyn Renderer + Send + Sync>) -> Self {
+        self.renderer = Some(renderer);
+        self
+    }
+
+    /// Serve requests from `input` until end of input or `shutdown`, writing
+    /// responses and notifications to `output`. Returns `output` once all
+    /// background renders have finished.
+    pub fn serve<R: BufRead, W: Write + Send + 'static>(
+        &self,
+        input: R,
+        output: W,
+    ) -> io::Result<W> {
+        let session = Arc::new(Session {
+            output: Mutex::new(output),
+            running: Mutex::new(HashMap::new()),
+        });
+        let mut workers = Vec::new();
+        for line in input.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message: Value = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(e) => {
+                    session.respond(&Value::Null, Err((PARSE_ERROR, e.to_string())));
+                    continue;
+                }
+            };
+            let id = message.get("id").cloned();
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+            let Some(method) = message.get("method").and_then(Value::as_str) else {
+                session.respond(
+                    &id.unwrap_or(Value::Null),
+                    Err((INVALID_REQUEST, "missing method".into())),
+                );
+                continue;
+            };
+            match (method, id) {
+                ("$/cancelRequest", _) => {
+                    if let Some(id) = params.get("id") {
+                        if let Some(flag) = session.running.lock().unwrap().get(&id.to_string()) {
+                            flag.store(true, Ordering::Relaxed);
+                        }
+                    }
+                }
+                (_, None) => {} // other notifications are ignored
+                ("parse", Some(id)) => session.respond(&id, parse(params)),
+                ("status", Some(id)) => {
+                    let mut running: Vec<Value> = session
+                        .running
+                        .lock()
+                        .unwrap()
+                        .keys()
+                        .filter_map(|k| serde_json::from_str(k).ok())
+                        .collect();
+                    running.sort_by_key(|v| v.to_string());
+                    session.respond(
+                        &id,
+                        Ok(json!({
+                            "version": env!("CARGO_PKG_VERSION"),
+                            "busy": !running.is_empty(),
+                            "running": running,
+                        })),
+                    );
+                }
+                ("shutdown", Some(id)) => {
+                    session.respond(&id, Ok(Value::Null));
+                    break;
+                }
+                ("render" | "renderBatch", Some(id)) => {
+                    let cancelled = Arc::new(AtomicBool::new(false));
+                    session
+                        .running
+                        .lock()
+                        .unwrap()
+                        .insert(id.to_string(), Arc::clone(&cancelled));
+                    let session = Arc::clone(&session);
+                    let tectonic = self.tectonic.clone();
+                    let custom = self.renderer.clone();
+                    let batch = method == "renderBatch";
+                    workers.push(thread::spawn(move || {
+                        let result = if batch {
+                            render_batch(params, &tectonic, custom, &session, &id, &cancelled)
+                        } else {
+                            render_one(params, &tectonic, custom)
+                        };
+                        session.running.lock().unwrap().remove(&id.to_string());
+                        session.respond(&id, result);
+                    }));
+                }
+                (other, Some(id)) => session.respond(
+                    &id,
+                    Err((METHOD_NOT_FOUND, format!("unknown method '{other}'"))),
+                ),
+            }
+        }
+        for worker in workers {
+            let _ = worker.join();
+        }
+        match Arc::try_unwrap(session) {
+            Ok(session) => Ok(session.output.into_inner().unwrap()),
+            Err(_) => Err(io::Error::other("render worker still running")),
+        }
+    }
+}
+
+/// Deserialize request parameters, mapping failures to `INVALID_PARAMS`.
+fn params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, (i64, String)> {
+    serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))
+}
+
+/// JSON shape of an equation in `parse` results.
+fn equation_json(eq: &Equation) -> Value {
+    json!({
+        "name": eq.name,
+        "active": eq.active,
+        "body": eq.body,
+        "kind": eq.kind.as_str(),
+        "tags": eq.tags,
+        "section": eq.section,
+        "description": eq.description,
+    })
+}
+
+/// Parse `content` as `format` (`markdown` or `csv`).
+fn parse_document(content: &str, format: Option<&str>) -> Result<Vec<Equation>, (i64, String)> {
+    match format.unwrap_or("markdown") {
+        "markdown" | "md" => Ok(parse_markdown_with_spans(content)
+            .into_iter()
+            .map(|s| s.equation)
+            .collect()),
+        "csv" => Ok(parse_csv(content)),
+        other => Err((INVALID_PARAMS, format!("unknown format '{other}'"))),
+    }
+}
+
+fn parse(params_value: Value) -> Result<Value, (i64, String)> {
+    let p: ParseParams = params(params_value)?;
+    let equations: Vec<Value> = match p.format.as_deref().unwrap_or("markdown") {
+        "markdown" | "md" => parse_markdown_with_spans(&p.content)
+            .iter()
+            .map(|s| {
+                let mut value = equation_json(&s.equation);
+                value["span"] = json!({"start": s.span.start, "end": s.span.end});
+                value["bodySpan"] = json!({"start": s.body_span.start, "end": s.body_span.end});
+                value
+            })
+            .collect(),
+        format => parse_document(&p.content, Some(format))?
+            .iter()
+            .map(equation_json)
+            .collect(),
+    };
+    Ok(Value::Array(equations))
+}
+
+/// Render `eq` with the custom renderer, or with tectonic in `color`.
+fn render_with(
+    eq: &Equation,
+    output_dir: &Path,
+    color: &str,
+    tectonic: &TectonicConfig,
+    custom: Option<&(dyn Renderer + Send + Sync)>,
+) -> Result<(), EquationError> {
+    match custom {
+        Some(renderer) => renderer.render(eq, output_dir),
+        None => TectonicRenderer {
+            color,
+            delete_intermediates: false,
+            tectonic,
+            options: &RenderOptions::default(),
+        }
+        .render(eq, output_dir),
+    }
+}
+
+fn render_one(
+    params_value: Value,
+    tectonic: &TectonicConfig,
+    custom: Option<Arc<dyn Renderer + Send + Sync>>,
+) -> Result<Value, (i64, String)> {
+    let p: RenderParams = params(params_value)?;
+    let mut eq = Equation::new(true, p.name.as_deref().unwrap_or("equation"), p.body.trim());
+    if let Some(kind) = p.kind {
+        eq.kind = kind
+            .parse::<EquationKind>()
+            .map_err(|e| (INVALID_PARAMS, e))?;
+    }
+    std::fs::create_dir_all(&p.output_dir).map_err(|e| (INTERNAL_ERROR, e.to_string()))?;
+    let color = p.color.as_deref().unwrap_or("#000000");
+    render_with(&eq, &p.output_dir, color, tectonic, custom.as_deref())
+        .map_err(|e| (INTERNAL_ERROR, e.to_string()))?;
+    let artifact = |ext: &str| {
+        let path = p.output_dir.join(format!("{}.{ext}", eq.name));
+        path.exists().then(|| path.display().to_string())
+    };
+    Ok(json!({"name": eq.name, "svg": artifact("svg"), "pdf": artifact("pdf")}))
+}
+
+fn render_batch<W: Write>(
+    params_value: Value,
+    tectonic: &TectonicConfig,
+    custom: Option<Arc<dyn Renderer + Send + Sync>>,
+    session: &Session<W>,
+    id: &Value,
+    cancelled: &AtomicBool,
+) -> Result<Value, (i64, String)> {
+    let p: BatchParams = params(params_value)?;
+    let mut equations = parse_document(&p.content, p.format.as_deref())?;
+    disambiguate_names(&mut equations);
+    std::fs::create_dir_all(&p.output_dir).map_err(|e| (INTERNAL_ERROR, e.to_string()))?;
+    let color = p.color.as_deref().unwrap_or("#000000");
+    let active: Vec<&Equation> = equations.iter().filter(|e| e.active).collect();
+    let mut rendered = Vec::new();
+    let mut failed = Vec::new();
+    let mut was_cancelled = false;
+    for (done, eq) in active.iter().enumerate() {
+        if cancelled.load(Ordering::Relaxed) {
+            was_cancelled = true;
+            break;
+        }
+        match render_with(eq, &p.output_dir, color, tectonic, custom.as_deref()) {
+            Ok(()) => rendered.push(eq.name.clone()),
+            Err(e) => failed.push(json!({"name": eq.name, "error": e.to_string()})),
+        }
+        session.send(&json!({
+            "jsonrpc": "2.0",
+            "method": "$/progress",
+            "params": {"id": id, "done": done + 1, "total": active.len(), "name": eq.name},
+        }));
+    }
+    write_index(&equations, &p.output_dir).map_err(|e| (INTERNAL_ERROR, e.to_string()))?;
+    Ok(json!({"rendered": rendered, "failed": failed, "cancelled": was_cancelled}))
+}
@@ -0,0 +1,232 @@
+//! Starter files for a new project, written by the `init` command.
+//!
+//! ```
+//! use equation_processor::scaffold::{init_project, StarterFormat};
+//!
+//! let dir = std::env::temp_dir().join("scaffold_doc_example");
+//! # let _ = std::fs::remove_dir_all(&dir);
+//! let written = init_project(&dir, StarterFormat::Markdown, false)?;
+//! assert!(written.iter().any(|p| p.ends_with("equations.md")));
+//! // A second run would overwrite them, so it is refused without `force`.
+//! assert!(init_project(&dir, StarterFormat::Markdown, false).is_err());
+//! # std::fs::remove_dir_all(&dir)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File name of the starter config, passed with `--config`.
+pub const STARTER_CONFIG: &str = "equation_processor.toml";
+
+/// File name of the starter LaTeX template, passed with `--template`.
+pub const STARTER_TEMPLATE: &str = "template.tex";
+
+/// Input format of the starter equations file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StarterFormat {
+    /// `equations.md`, with every kind of `%%tag%%` explained in the text
+    #[default]
+    Markdown,
+    /// `equations.csv`, with the optional columns filled in
+    Csv,
+}
+
+impl StarterFormat {
+    /// File name of the starter equations file.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            StarterFormat::Markdown => "equations.md",
+            StarterFormat::Csv => "equations.csv",
+        }
+    }
+
+    /// Content of the starter equations file.
+    pub fn content(self) -> &'static str {
+        match self {
+            StarterFormat::Markdown => STARTER_MARKDOWN,
+            StarterFormat::Csv => STARTER_CSV,
+        }
+    }
+}
+
+impl FromStr for StarterFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "md" | "markdown" => Ok(StarterFormat::Markdown),
+            "csv" => Ok(StarterFormat::Csv),
+            other => Err(format!(
+                "unknown starter format '{other}' (expected markdown or csv)"
+            )),
+        }
+    }
+}
+
+const STARTER_MARKDOWN: &str = r#"# Equations
+
+Each block between double dollar signs is rendered to its own SVG. Text
+outside the blocks, like this paragraph, is ignored, and the closest heading
+above a block is recorded as its section.
+
+## Basics
+
+A tag after a block names its output file. `%%no%%` on the line before a
+block skips it, and `%%yes%%` renders it (the default).
+
+$$
+E = mc^2
+$$
+%%mass_energy%%
+
+%%no%%
+$$
+\int_0^1 x^2 \, dx = \frac{1}{3}
+$$
+%%integral_draft%%
+
+## Options
+
+Further tags after the name set options for that block only: tags for
+organizing (`tags:`), a description for tables and the SVG (`desc:`), a color
+overriding `--color` (`color:`) and the padding around it (`border:`).
+
+$$
+\sin\theta \approx \theta
+$$
+%%small_angle%%
+%%tags:mechanics,approximations%%
+%%desc:valid for small angles only%%
+%%color:#1F4E79%%
+%%border:4pt%%
+
+`%%chem%%` renders a chemical formula with mhchem.
+
+$$
+2H2 + O2 -> 2H2O
+$$
+%%water%%
+%%chem%%
+
+## Fenced Blocks
+
+The same options can be given as attributes of a fenced math block.
+
+```math {name="bernoulli", tags="fluids", desc="along a streamline"}
+p + \frac{1}{2}\rho v^2 + \rho g h = \text{const}
+```
+"#;
+
+const STARTER_CSV: &str = "active,equation,name,assets,tags,description
+Yes,E = mc^2,mass_energy,,physics,Set active to No to skip a row
+No,\\int_0^1 x^2 \\, dx = \\frac{1}{3},integral_draft,,calculus,Skipped until active is Yes
+Yes,\\sin\\theta \\approx \\theta,small_angle,,mechanics;approximations,Tags are separated by semicolons
+Yes,\"p + \\frac{1}{2}\\rho v^2 = \\text{const}\",bernoulli,,fluids,\"Quote fields that contain commas, like this one\"
+";
+
+const STARTER_CONFIG_CONTENT: &str = r##"# Defaults for equation_processor, used with
+#   equation_processor --config equation_processor.toml -i equations.md
+# Flags given on the command line take precedence. Put this file in the
+# platform config directory as config.toml to use it everywhere; the GUI
+# imports it with "Import…".
+
+# Font color as #RRGGBB
+color = "#000000"
+# LaTeX engine: "tectonic", or "pdflatex", "xelatex" or "lualatex" from a
+# TeX installation such as TeX Live
+engine = "tectonic"
+# Fill behind every equation: "transparent" or #RRGGBB
+background = "transparent"
+# Padding around every equation, e.g. "2pt"; empty for the default 1pt
+border = ""
+# Font package, e.g. "newtxmath"; empty for LaTeX's Computer Modern
+font = "gfsneohellenicot"
+# Remove the .tex and .pdf files after converting
+delete_intermediates = false
+# Treat every math block as an mhchem formula
+chemistry = false
+# Also write \input-ready .tex snippets and an HTML gallery
+tex_snippets = false
+html_gallery = false
+# Where the artifacts go, relative to where the tool runs
+output_dir = "output"
+# Typeset equations in your own LaTeX document instead of the built-in one
+# template = "template.tex"
+
+[tectonic]
+# Only use TeX resources that are already cached (run `prefetch` first)
+offline = false
+# Environment variables for tectonic and pdftocairo, as KEY=VALUE
+env = []
+# Date every PDF at these seconds since 1970, for byte-identical re-renders
+# source_date_epoch = 0
+"##;
+
+const STARTER_TEMPLATE_CONTENT: &str = r"% A LaTeX template for equation_processor, used with --template or the
+% template key of the config file. Every equation is typeset in a copy of
+% this document, with these names in double braces filled in:
+%   BODY           the equation: $...$, \ce{...} or a tikzpicture (required)
+%   COLOR          the font color as six hex digits
+%   PACKAGES       packages the equation needs, such as mhchem or tikz
+%   FONT           the font package, unless --no-font-package is given
+%   CLASS_OPTIONS  border, crop and varwidth for the standalone class
+%   SIZE           the font size, \Large unless --font-size is given
+%   PDF_SPECIALS   PDF compression and version settings
+%   BACKGROUND     the page color of --background, nothing if transparent
+%   NAME           the equation's name
+% This copy reproduces the built-in document; change the packages and
+% sizing as you like.
+\documentclass[{{CLASS_OPTIONS}}]{standalone}
+\usepackage{amsmath}
+\usepackage{xfrac}
+{{FONT}}
+\usepackage{xcolor}
+{{PACKAGES}}\definecolor{equationcolor}{HTML}{{{COLOR}}}
+\begin{document}{{PDF_SPECIALS}}{{BACKGROUND}}
+\setbox0\hbox{{{SIZE}} \textcolor{equationcolor}{{{BODY}}}}
+\dimen0=12mm
+\ifdim\ht0<\dimen0 \ht0=\dimen0 \fi
+\ifdim\dp0<5mm \dp0=5mm \fi
+\box0
+\end{document}
+";
+
+/// Write a starter equations file in `format`, a commented config file
+/// ([`STARTER_CONFIG`]) and a LaTeX template ([`STARTER_TEMPLATE`]) into
+/// `dir`, creating it if needed. Returns the written files.
+///
+/// Existing files are only overwritten with `force`; otherwise nothing is
+/// written and the error names them.
+pub fn init_project(dir: &Path, format: StarterFormat, force: bool) -> io::Result<Vec<PathBuf>> {
+    let files = [
+        (dir.join(format.file_name()), format.content()),
+        (dir.join(STARTER_CONFIG), STARTER_CONFIG_CONTENT),
+        (dir.join(STARTER_TEMPLATE), STARTER_TEMPLATE_CONTENT),
+    ];
+    if !force {
+        let existing: Vec<String> = files
+            .iter()
+            .filter(|(path, _)| path.exists())
+            .map(|(path, _)| path.display().to_string())
+            .collect();
+        if !existing.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!(
+                    "{} already exist(s); pass --force to overwrite",
+                    existing.join(", ")
+                ),
+            ));
+        }
+    }
+    fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for (path, content) in files {
+        fs::write(&path, content)?;
+        written.push(path);
+    }
+    Ok(written)
+}
@@ -0,0 +1,83 @@
+//! GUI sessions autosaved to the data directory, so curated equations
+//! survive a crash or an accidental close.
+//!
+//! The GUI saves a [`Session`] every few seconds while something changed and
+//! offers to restore it on the next start.
+//!
+//! ```
+//! use equation_processor::model::Equation;
+//! use equation_processor::session::Session;
+//!
+//! let path = std::env::temp_dir().join("session_doc_example.json");
+//! let mut session = Session::default();
+//! session.equations.push(Equation::new(false, "energy", "E = mc^2"));
+//! session.save(&path)?;
+//! let restored = Session::load(&path)?;
+//! assert_eq!(restored.equations[0].name, "energy");
+//! assert!(!restored.equations[0].active);
+//! # std::fs::remove_file(&path)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::model::Equation;
+use crate::paths::data_dir;
+use crate::report::file_timestamp;
+use crate::settings::Settings;
+use crate::tagging::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// File name of the autosaved session in the data directory.
+pub const SESSION_FILE: &str = "gui_session.json";
+
+/// Where the GUI autosaves its session, if the data directory is known.
+pub fn session_file() -> Option<PathBuf> {
+    Some(data_dir()?.join(SESSION_FILE))
+}
+
+/// Everything needed to continue where the GUI left off.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// When the session was saved, `YYYY-MM-DDTHH-MM-SS` in UTC
+    pub saved: String,
+    /// Input file the equations were loaded from
+    pub input_file: Option<PathBuf>,
+    /// The equations with their edits: active flags, renames
+    pub equations: Vec<Equation>,
+    /// Current page of the equations table
+    pub page: usize,
+    /// Rendering options
+    pub settings: Settings,
+}
+
+impl Session {
+    /// Whether there is anything worth restoring.
+    pub fn is_empty(&self) -> bool {
+        self.input_file.is_none() && self.equations.is_empty()
+    }
+
+    /// Read a session saved at `path`.
+    pub fn load(path: &Path) -> io::Result<Session> {
+        serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {e}", path.display()),
+            )
+        })
+    }
+
+    /// Write the session to `path` with the current time, replacing the file
+    /// atomically so a crash while saving keeps the previous session.
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        self.saved = file_timestamp(SystemTime::now());
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        write_atomic(path, &json)
+    }
+}
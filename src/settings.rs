@@ -0,0 +1,458 @@
+//! Rendering settings saved to a TOML file, so a team can share one setup.
+//!
+//! The GUI exports its settings with "Export settings…" and applies a file
+//! with "Import settings…". Keys missing from a file keep their defaults;
+//! unknown keys and values of the wrong type are rejected by
+//! [`validate_config`], which [`Settings::load`] runs first.
+//!
+//! ```
+//! use equation_processor::settings::Settings;
+//!
+//! let settings: Settings = toml::from_str(
+//!     r##"
+//!     color = "#1A2B3C"
+//!     html_gallery = true
+//!
+//!     [tectonic]
+//!     offline = true
+//!     env = ["SOURCE_DATE_EPOCH=0"]
+//!     "##,
+//! )?;
+//! assert_eq!(settings.color, "#1A2B3C");
+//! assert!(settings.tectonic.offline);
+//! assert!(!settings.chemistry);
+//! # Ok::<(), toml::de::Error>(())
+//! ```
+
+use crate::model::{parse_env_var, Background, Color, LatexEngine};
+use crate::render::{parse_font_package, TectonicConfig, DEFAULT_FONT_PACKAGE};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+/// Everything the GUI lets a user configure besides the input file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Font color as `#RRGGBB`
+    pub color: String,
+    /// LaTeX engine for equations without an `engine` tag
+    pub engine: LatexEngine,
+    /// Fill behind equations: `transparent` or `#RRGGBB`
+    pub background: String,
+    /// Standalone border for all equations, e.g. `2pt`; empty for the default
+    pub border: String,
+    /// Font package, e.g. `newtxmath`; empty for Computer Modern and `None`
+    /// for the default, see [`Settings::font_package`]
+    pub font: Option<String>,
+    /// Remove `.tex`/`.pdf` intermediates after each equation
+    pub delete_intermediates: bool,
+    /// Treat all math equations as mhchem chemical formulas
+    pub chemistry: bool,
+    /// Also write `\input`-ready `.tex` snippets
+    pub tex_snippets: bool,
+    /// Also write an HTML gallery
+    pub html_gallery: bool,
+    /// Where artifacts are written; may not exist on another machine
+    pub output_dir: Option<PathBuf>,
+    /// LaTeX template equations are typeset in, see
+    /// [`LatexTemplate`](crate::render::LatexTemplate); `None` for the built-in one
+    pub template: Option<PathBuf>,
+    /// Show the equations table one page at a time
+    pub paged: bool,
+    /// Scale of the equations table
+    pub zoom: f32,
+    /// Where tectonic finds its resources
+    pub tectonic: ToolSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            color: "#000000".into(),
+            engine: LatexEngine::Tectonic,
+            background: Background::Transparent.to_string(),
+            border: String::new(),
+            font: None,
+            delete_intermediates: false,
+            chemistry: false,
+            tex_snippets: false,
+            html_gallery: false,
+            output_dir: None,
+            template: None,
+            paged: false,
+            zoom: 1.0,
+            tectonic: ToolSettings::default(),
+        }
+    }
+}
+
+/// The file form of [`TectonicConfig`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToolSettings {
+    /// Shared tectonic cache directory
+    pub cache_dir: Option<PathBuf>,
+    /// Bundle file or URL
+    pub bundle: Option<String>,
+    /// Only use cached resources
+    pub offline: bool,
+    /// Environment variables for tectonic and `pdftocairo`, as `KEY=VALUE`
+    pub env: Vec<String>,
+    /// Date of reproducible builds in seconds since 1970, see
+    /// [`TectonicConfig::source_date_epoch`]
+    pub source_date_epoch: Option<u64>,
+}
+
+impl From<&TectonicConfig> for ToolSettings {
+    fn from(config: &TectonicConfig) -> Self {
+        ToolSettings {
+            cache_dir: config.cache_dir.clone(),
+            bundle: config.bundle.clone(),
+            offline: config.offline,
+            env: config.env.iter().map(|(k, v)| format!("{k}={v}")).collect(),
+            source_date_epoch: config.source_date_epoch,
+        }
+    }
+}
+
+impl ToolSettings {
+    /// The tectonic configuration these settings describe.
+    pub fn to_config(&self) -> Result<TectonicConfig, String> {
+        Ok(TectonicConfig {
+            cache_dir: self.cache_dir.clone(),
+            bundle: self.bundle.clone(),
+            offline: self.offline,
+            env: self
+                .env
+                .iter()
+                .map(|var| parse_env_var(var))
+                .collect::<Result<_, _>>()?,
+            source_date_epoch: self.source_date_epoch,
+        })
+    }
+}
+
+impl Settings {
+    /// Read settings from the TOML file at `path`, rejecting everything
+    /// [`validate_config`] reports. The error lists every problem found.
+    pub fn load(path: &Path) -> io::Result<Settings> {
+        let invalid = |e: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {e}", path.display()),
+            )
+        };
+        let content = fs::read_to_string(path)?;
+        let issues = validate_config(&content);
+        if !issues.is_empty() {
+            let lines: Vec<String> = issues.iter().map(ToString::to_string).collect();
+            return Err(invalid(lines.join("\n")));
+        }
+        toml::from_str(&content).map_err(|e| invalid(e.to_string()))
+    }
+
+    /// The font package for [`RenderOptions::font`](crate::render::RenderOptions::font):
+    /// the default without a `font` key, none if it is empty.
+    pub fn font_package(&self) -> Option<String> {
+        match self.font.as_deref().map(str::trim) {
+            None => Some(DEFAULT_FONT_PACKAGE.to_string()),
+            Some("") => None,
+            Some(font) => Some(font.to_string()),
+        }
+    }
+
+    /// Write the settings to `path` as TOML.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let content = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+}
+
+/// What a config key holds.
+#[derive(Debug, Clone, Copy)]
+enum KeyType {
+    String,
+    Boolean,
+    Number,
+    Integer,
+    Strings,
+    Table(&'static [(&'static str, KeyType)]),
+}
+
+impl KeyType {
+    /// Whether `value` has this type.
+    fn accepts(self, value: &Value) -> bool {
+        match (self, value) {
+            (KeyType::String, Value::String(_))
+            | (KeyType::Boolean, Value::Boolean(_))
+            | (KeyType::Number, Value::Float(_) | Value::Integer(_))
+            | (KeyType::Table(_), Value::Table(_)) => true,
+            (KeyType::Integer, Value::Integer(n)) => *n >= 0,
+            (KeyType::Strings, Value::Array(items)) => items.iter().all(Value::is_str),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for KeyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeyType::String => "a string",
+            KeyType::Boolean => "a boolean (true or false)",
+            KeyType::Number => "a number",
+            KeyType::Integer => "a non-negative integer",
+            KeyType::Strings => "an array of strings",
+            KeyType::Table(_) => "a table",
+        })
+    }
+}
+
+/// The keys of [`ToolSettings`] in the `[tectonic]` table.
+const TECTONIC_KEYS: &[(&str, KeyType)] = &[
+    ("cache_dir", KeyType::String),
+    ("bundle", KeyType::String),
+    ("offline", KeyType::Boolean),
+    ("env", KeyType::Strings),
+    ("source_date_epoch", KeyType::Integer),
+];
+
+/// The keys of [`Settings`].
+const SETTINGS_KEYS: &[(&str, KeyType)] = &[
+    ("color", KeyType::String),
+    ("engine", KeyType::String),
+    ("background", KeyType::String),
+    ("border", KeyType::String),
+    ("font", KeyType::String),
+    ("delete_intermediates", KeyType::Boolean),
+    ("chemistry", KeyType::Boolean),
+    ("tex_snippets", KeyType::Boolean),
+    ("html_gallery", KeyType::Boolean),
+    ("output_dir", KeyType::String),
+    ("template", KeyType::String),
+    ("paged", KeyType::Boolean),
+    ("zoom", KeyType::Number),
+    ("tectonic", KeyType::Table(TECTONIC_KEYS)),
+];
+
+/// A problem [`validate_config`] found in a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// Dotted path of the key, e.g. `tectonic.offline`; `None` if the file
+    /// is not valid TOML
+    pub key: Option<String>,
+    /// 1-based line of the key, if it could be found
+    pub line: Option<usize>,
+    /// What is wrong, with a suggestion where there is one
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        match &self.key {
+            Some(key) => write!(f, "{key}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Check the content of a config file against the keys [`Settings`] knows.
+///
+/// Reports invalid TOML, unknown keys (suggesting the closest known one),
+/// values of the wrong type and values of the right type that do not parse,
+/// such as
+/// a color that is not `#RRGGBB`. An empty result means
+/// [`Settings::load`] accepts the file.
+///
+/// ```
+/// use equation_processor::settings::validate_config;
+///
+/// let issues = validate_config("colour = \"#FF0000\"\n[tectonic]\noffline = \"yes\"\n");
+/// assert_eq!(
+///     issues[0].to_string(),
+///     "line 1: colour: unknown key (did you mean 'color'?)"
+/// );
+/// assert_eq!(
+///     issues[1].to_string(),
+///     "line 3: tectonic.offline: expected a boolean (true or false), found a string"
+/// );
+/// ```
+pub fn validate_config(content: &str) -> Vec<ConfigIssue> {
+    let table: toml::Table = match toml::from_str(content) {
+        Ok(table) => table,
+        Err(e) => {
+            return vec![ConfigIssue {
+                key: None,
+                line: e.span().map(|span| line_at(content, span.start)),
+                message: e.message().to_string(),
+            }]
+        }
+    };
+    let mut issues = Vec::new();
+    check_table(&table, SETTINGS_KEYS, &mut Vec::new(), &mut issues);
+    if issues.is_empty() {
+        check_values(&table, &mut issues);
+    }
+    for issue in &mut issues {
+        issue.line = issue.key.as_deref().and_then(|key| key_line(content, key));
+    }
+    issues.sort_by_key(|issue| issue.line);
+    issues
+}
+
+/// Check the keys of `table` and the types of their values against `keys`.
+fn check_table(
+    table: &toml::Table,
+    keys: &[(&str, KeyType)],
+    path: &mut Vec<String>,
+    issues: &mut Vec<ConfigIssue>,
+) {
+    for (key, value) in table {
+        path.push(key.clone());
+        let dotted = path.join(".");
+        match keys.iter().find(|(known, _)| known == key) {
+            None => issues.push(ConfigIssue {
+                key: Some(dotted),
+                line: None,
+                message: format!("unknown key{}", suggestion(key, keys)),
+            }),
+            Some((_, KeyType::Table(inner))) if value.is_table() => {
+                if let Value::Table(inner_table) = value {
+                    check_table(inner_table, inner, path, issues);
+                }
+            }
+            Some((_, expected)) if !expected.accepts(value) => issues.push(ConfigIssue {
+                key: Some(dotted),
+                line: None,
+                message: format!("expected {expected}, found {}", describe(value)),
+            }),
+            Some(_) => {}
+        }
+        path.pop();
+    }
+}
+
+/// Check the values whose type is right but whose content may not be.
+fn check_values(table: &toml::Table, issues: &mut Vec<ConfigIssue>) {
+    let mut check = |key: &str, result: Result<(), String>| {
+        if let Err(message) = result {
+            issues.push(ConfigIssue {
+                key: Some(key.to_string()),
+                line: None,
+                message,
+            });
+        }
+    };
+    let text = |value: Option<&Value>| value.and_then(Value::as_str).map(str::to_string);
+    if let Some(color) = text(table.get("color")) {
+        check("color", color.parse::<Color>().map(drop));
+    }
+    if let Some(engine) = text(table.get("engine")) {
+        check("engine", engine.parse::<LatexEngine>().map(drop));
+    }
+    if let Some(background) = text(table.get("background")) {
+        check("background", background.parse::<Background>().map(drop));
+    }
+    if let Some(font) = text(table.get("font")).filter(|f| !f.trim().is_empty()) {
+        check("font", parse_font_package(&font).map(drop));
+    }
+    let env = table
+        .get("tectonic")
+        .and_then(|t| t.get("env"))
+        .and_then(Value::as_array);
+    for (i, var) in env.into_iter().flatten().enumerate() {
+        if let Some(var) = var.as_str() {
+            check(&format!("tectonic.env[{i}]"), parse_env_var(var).map(drop));
+        }
+    }
+}
+
+/// ` (did you mean '…'?)` naming the known key closest to `key`, or for a
+/// key of a nested table the table to put it in; empty if none is close.
+fn suggestion(key: &str, keys: &[(&str, KeyType)]) -> String {
+    for (table, kind) in keys {
+        if let KeyType::Table(inner) = kind {
+            if inner.iter().any(|(known, _)| *known == key) {
+                return format!(" (did you mean '{key}' in the [{table}] table?)");
+            }
+        }
+    }
+    keys.iter()
+        .map(|(known, _)| (edit_distance(key, known), *known))
+        .filter(|(distance, _)| *distance <= 2.max(key.len() / 4))
+        .min()
+        .map(|(_, known)| format!(" (did you mean '{known}'?)"))
+        .unwrap_or_default()
+}
+
+/// Levenshtein distance between `a` and `b`, in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substituted = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substituted.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// `value`'s type with an article, e.g. `an integer`.
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Integer(n) if *n < 0 => "a negative integer".into(),
+        Value::Array(items) if !items.iter().all(Value::is_str) => {
+            "an array with other values than strings".into()
+        }
+        Value::Integer(_) | Value::Array(_) => format!("an {}", value.type_str()),
+        _ => format!("a {}", value.type_str()),
+    }
+}
+
+/// 1-based line of byte `offset` in `content`.
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+/// 1-based line where the dotted `key` is assigned, following `[table]`
+/// headers and dotted keys; `None` if it isn't found, e.g. in an inline table.
+fn key_line(content: &str, key: &str) -> Option<usize> {
+    let key = key.split('[').next().unwrap_or(key);
+    let normalize = |s: &str| {
+        s.split('.')
+            .map(|part| part.trim().trim_matches(|c| c == '"' || c == '\''))
+            .collect::<Vec<_>>()
+            .join(".")
+    };
+    let mut table = String::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[').and_then(|h| h.split(']').next()) {
+            table = normalize(header);
+            if table == key {
+                return Some(i + 1);
+            }
+        } else if let Some((name, _)) = line.split_once('=') {
+            let name = normalize(name);
+            let full = if table.is_empty() {
+                name
+            } else {
+                format!("{table}.{name}")
+            };
+            if full == key {
+                return Some(i + 1);
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,211 @@
+//! Splitting a large batch across machines and combining the results.
+//!
+//! A [`Shard`] selects a deterministic subset of the equations by a hash of
+//! their names, so several machines or CI jobs given the same input and
+//! shard count render disjoint subsets that together cover the batch. Each
+//! writes a partial `index.json` recording its shard; [`merge_manifests`]
+//! combines them and gathers the artifacts into one output directory.
+//!
+//! ```
+//! use equation_processor::shard::Shard;
+//!
+//! let shards: Vec<Shard> = (0..3).map(|i| Shard::new(i, 3).unwrap()).collect();
+//! let owners = shards.iter().filter(|s| s.contains("energy")).count();
+//! assert_eq!(owners, 1);
+//! assert!(Shard::new(3, 3).is_err());
+//! ```
+
+use crate::report::{
+    ContentManifest, CONTENT_MANIFEST, CONTENT_MANIFEST_VERSION, INDEX_FILE, INDEX_VERSION,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+/// Artifact fields of an `index.json` entry, copied by [`merge_manifests`].
+const ARTIFACT_FIELDS: &[&str] = &["svg", "png", "pdf"];
+
+/// One of `count` disjoint parts of a batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shard {
+    /// Which part, from 0
+    pub index: usize,
+    /// How many parts the batch is split into
+    pub count: usize,
+}
+
+impl Shard {
+    /// Shard `index` of `count`; fails unless `index < count`.
+    pub fn new(index: usize, count: usize) -> Result<Self, String> {
+        if count == 0 {
+            return Err("the shard count must be at least 1".into());
+        }
+        if index >= count {
+            return Err(format!(
+                "shard index {index} is out of range for {count} shard(s) (expected 0 to {})",
+                count - 1
+            ));
+        }
+        Ok(Shard { index, count })
+    }
+
+    /// Whether the equation called `name` belongs to this shard.
+    pub fn contains(&self, name: &str) -> bool {
+        shard_of(name, self.count) == self.index
+    }
+}
+
+impl fmt::Display for Shard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// The shard of `count` the equation called `name` belongs to: its SHA-256
+/// modulo `count`, the same on every machine and independent of the order
+/// and number of other equations.
+pub fn shard_of(name: &str, count: usize) -> usize {
+    let digest = Sha256::digest(name.as_bytes());
+    let mut prefix = [0; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    (u64::from_be_bytes(prefix) % count.max(1) as u64) as usize
+}
+
+/// `index.json` combined from shard runs, keeping their entries as written.
+#[derive(Serialize)]
+struct MergedIndex {
+    version: u32,
+    equations: Vec<Value>,
+}
+
+/// What [`merge_manifests`] did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    /// The merged `index.json`
+    pub index: PathBuf,
+    /// Equations listed in it
+    pub equations: usize,
+    /// Artifacts copied into the output directory
+    pub copied: usize,
+    /// Shard indices of the recorded count that no input covered, e.g. a
+    /// CI job that failed
+    pub missing_shards: Vec<usize>,
+}
+
+/// Combine the `index.json` files of shard runs into one in `output_dir`.
+///
+/// Each input is an `index.json` or a directory holding one. Artifacts
+/// listed in an input outside `output_dir` are copied there, and the
+/// `manifest.json` files of content-addressed runs are merged as well.
+/// Fails if an equation appears in more than one input, if an artifact is
+/// not a plain file name, or if the inputs were split into different shard
+/// counts.
+pub fn merge_manifests(inputs: &[PathBuf], output_dir: &Path) -> io::Result<MergeReport> {
+    let invalid = |path: &Path, reason: String| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {reason}", path.display()),
+        )
+    };
+    fs::create_dir_all(output_dir)?;
+    let target = fs::canonicalize(output_dir)?;
+    let mut equations = Vec::new();
+    let mut names = HashSet::new();
+    let mut covered = HashSet::new();
+    let mut count = None;
+    let mut copied = 0;
+    let mut manifest = ContentManifest::read(output_dir).unwrap_or_default();
+    for input in inputs {
+        let (dir, path) = if input.is_dir() {
+            (input.clone(), input.join(INDEX_FILE))
+        } else {
+            let dir = input.parent().unwrap_or(Path::new(".")).to_path_buf();
+            (dir, input.clone())
+        };
+        let index: Value = serde_json::from_str(&fs::read_to_string(&path)?)
+            .map_err(|e| invalid(&path, e.to_string()))?;
+        if let Some(shard) = index.get("shard").filter(|s| !s.is_null()) {
+            let shard: Shard = serde_json::from_value(shard.clone())
+                .map_err(|e| invalid(&path, format!("invalid shard: {e}")))?;
+            if count.is_some_and(|c| c != shard.count) {
+                return Err(invalid(
+                    &path,
+                    format!(
+                        "shard {shard} does not match the {} shards of the other inputs",
+                        count.unwrap_or_default()
+                    ),
+                ));
+            }
+            count = Some(shard.count);
+            covered.insert(shard.index);
+        }
+        let entries = index
+            .get("equations")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid(&path, "no equations array".into()))?;
+        let same_dir = fs::canonicalize(&dir).is_ok_and(|d| d == target);
+        for entry in entries {
+            let name = entry
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| invalid(&path, "an equation has no name".into()))?;
+            if !names.insert(name.to_string()) {
+                return Err(invalid(
+                    &path,
+                    format!("equation '{name}' is already listed by another input"),
+                ));
+            }
+            for field in ARTIFACT_FIELDS {
+                let Some(file) = entry.get(*field).and_then(Value::as_str) else {
+                    continue;
+                };
+                // Only a plain file name stays inside the input and output directories.
+                let mut parts = Path::new(file).components();
+                if !matches!(
+                    (parts.next(), parts.next()),
+                    (Some(Component::Normal(_)), None)
+                ) {
+                    return Err(invalid(
+                        &path,
+                        format!(
+                            "equation '{name}' lists {field} file '{file}' outside its directory"
+                        ),
+                    ));
+                }
+                if !same_dir {
+                    fs::copy(dir.join(file), output_dir.join(file))?;
+                    copied += 1;
+                }
+            }
+            equations.push(entry.clone());
+        }
+        if let (false, Some(partial)) = (same_dir, ContentManifest::read(&dir)) {
+            manifest.version = CONTENT_MANIFEST_VERSION;
+            manifest.equations.extend(partial.equations);
+        }
+    }
+    if !manifest.equations.is_empty() {
+        let json = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+        fs::write(output_dir.join(CONTENT_MANIFEST), json + "\n")?;
+    }
+    let merged = MergedIndex {
+        version: INDEX_VERSION,
+        equations,
+    };
+    let index = output_dir.join(INDEX_FILE);
+    let json = serde_json::to_string_pretty(&merged).map_err(io::Error::other)?;
+    fs::write(&index, json + "\n")?;
+    Ok(MergeReport {
+        index,
+        equations: names.len(),
+        copied,
+        missing_shards: (0..count.unwrap_or_default())
+            .filter(|i| !covered.contains(i))
+            .collect(),
+    })
+}
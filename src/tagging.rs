@@ -0,0 +1,199 @@
+//! Adding, removing and listing equation tags from the command line.
+//!
+//! Edits are made to CSV input, in its `tags` column (tags separated by `;`),
+//! by default the optional fifth one. Only the changed rows are rewritten and the file is
+//! replaced atomically.
+//!
+//! ```
+//! use equation_processor::parser::ParseOptions;
+//! use equation_processor::tagging::{edit_csv_tags, TagEdit};
+//!
+//! let csv = "active,equation,name\nyes,x^2,ch3_square\nyes,y,ch4_y\n";
+//! let edit = TagEdit::Add("exam".into());
+//! let (edited, changed) = edit_csv_tags(csv, &edit, Some("ch3_*"), &ParseOptions::default());
+//! assert_eq!(changed, 1);
+//! assert_eq!(edited, "active,equation,name,assets,tags\nyes,x^2,ch3_square,,exam\nyes,y,ch4_y\n");
+//! ```
+
+use crate::model::{Equation, Filetype};
+use crate::parser::{
+    detect_csv_delimiter, detect_file_type, join_csv_fields, read_file, split_csv_line, CsvColumns,
+    ParseOptions,
+};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// CSV header up to and including the `tags` column, in the default order.
+const COLUMNS: [&str; 5] = ["active", "equation", "name", "assets", "tags"];
+
+/// A change to the tags of the selected equations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TagEdit {
+    /// Add the tag where it is missing
+    Add(String),
+    /// Remove the tag where it is present
+    Remove(String),
+}
+
+/// Whether `name` matches the glob `pattern`, where `*` matches any run of
+/// characters and `?` exactly one.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // Position after the last `*` and the name position it was tried at.
+    let (mut p, mut n, mut star) = (0, 0, None);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((after, tried)) => {
+                    p = after;
+                    n = tried + 1;
+                    star = Some((after, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Apply `edit` to the rows of CSV `content` whose equation name matches the
+/// glob `only` (all rows when `None`). Returns the new content and the number
+/// of rows changed; unchanged rows are kept byte for byte.
+///
+/// When a tag is added, the header is extended with the `assets` and `tags`
+/// columns if it lacks them.
+pub fn edit_csv_tags(
+    content: &str,
+    edit: &TagEdit,
+    only: Option<&str>,
+    options: &ParseOptions,
+) -> (String, usize) {
+    let bom = if content.starts_with('\u{feff}') {
+        "\u{feff}"
+    } else {
+        ""
+    };
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let body = &content[bom.len()..];
+    let mut lines: Vec<String> = body.lines().map(String::from).collect();
+    let Some(header) = lines.first() else {
+        return (content.to_string(), 0);
+    };
+    let delimiter = options
+        .csv_delimiter
+        .unwrap_or_else(|| detect_csv_delimiter(header));
+    let header_fields = split_csv_line(header, delimiter);
+    let columns = CsvColumns::resolve(&header_fields, options);
+    // Without a `tags` column, one is added after the last.
+    let tags_column = columns.tags.unwrap_or(header_fields.len());
+    let mut changed = 0;
+    for line in lines.iter_mut().skip(1) {
+        let mut fields = split_csv_line(line, delimiter);
+        if fields.len() < columns.min_fields() {
+            continue;
+        }
+        let name = Equation::sanitize_filename(fields[columns.name].trim());
+        if only.is_some_and(|pattern| !glob_match(pattern, &name)) {
+            continue;
+        }
+        let mut tags: Vec<String> = fields
+            .get(tags_column)
+            .map(|t| {
+                t.split([';', ','])
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        match edit {
+            TagEdit::Add(tag) if !tags.contains(tag) => tags.push(tag.clone()),
+            TagEdit::Remove(tag) if tags.contains(tag) => tags.retain(|t| t != tag),
+            _ => continue,
+        }
+        fields.resize(fields.len().max(tags_column + 1), String::new());
+        fields[tags_column] = tags.join(";");
+        *line = join_csv_fields(&fields, delimiter);
+        changed += 1;
+    }
+    if changed > 0 && matches!(edit, TagEdit::Add(_)) {
+        let mut fields = header_fields;
+        if fields.len() <= tags_column {
+            let default_order = columns == CsvColumns::default();
+            for i in fields.len()..=tags_column {
+                let name = COLUMNS.get(i).filter(|_| default_order);
+                fields.push(name.unwrap_or(&"").to_string());
+            }
+            fields[tags_column] = "tags".into();
+            lines[0] = join_csv_fields(&fields, delimiter);
+        }
+    }
+    let mut edited = format!("{bom}{}", lines.join(newline));
+    if body.ends_with('\n') {
+        edited.push_str(newline);
+    }
+    (edited, changed)
+}
+
+/// Apply `edit` to the CSV file at `path` (see [`edit_csv_tags`]), replacing
+/// it atomically. Returns the number of equations changed.
+pub fn edit_tags_file(
+    path: &PathBuf,
+    edit: &TagEdit,
+    only: Option<&str>,
+    options: &ParseOptions,
+) -> io::Result<usize> {
+    if !matches!(detect_file_type(path), Filetype::Csv) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("tags can only be edited in CSV input: {}", path.display()),
+        ));
+    }
+    let (edited, changed) = edit_csv_tags(&read_file(path)?, edit, only, options);
+    if changed > 0 {
+        write_atomic(path, &edited)?;
+    }
+    Ok(changed)
+}
+
+/// Write `content` to a temporary file next to `path` and rename it over
+/// `path`, so readers never see a partial file.
+pub(crate) fn write_atomic(path: &Path, content: &str) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(".tmp{}", std::process::id()));
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, content)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Number of equations carrying each tag among those whose name matches the
+/// glob `only` (all when `None`), sorted by tag.
+pub fn count_tags(equations: &[Equation], only: Option<&str>) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for eq in equations
+        .iter()
+        .filter(|e| only.is_none_or(|pattern| glob_match(pattern, &e.name)))
+    {
+        for tag in &eq.tags {
+            *counts.entry(tag.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
@@ -0,0 +1,211 @@
+//! Helpers for testing code built on this crate without a LaTeX toolchain.
+//!
+//! Enabled by the `test-util` feature.
+//!
+//! ```
+//! use equation_processor::render::render_equations_with;
+//! use equation_processor::test_util::{EquationBuilder, MockRenderer, TempOutput};
+//!
+//! let out = TempOutput::new("doc_example");
+//! let equations = vec![
+//!     EquationBuilder::new("energy").body("E = mc^2").build(),
+//!     EquationBuilder::new("skipped").inactive().build(),
+//! ];
+//! let renderer = MockRenderer::new();
+//! render_equations_with(&equations, out.path(), &renderer, false, None)?;
+//! assert_eq!(renderer.calls(), ["energy"]);
+//! assert!(out.path().join("energy.svg").exists());
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use crate::cache::cache_key;
+use crate::error::EquationError;
+use crate::model::{Equation, EquationKind};
+use crate::render::{Capabilities, RenderOptions, Renderer, TectonicConfig};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// SVG written by [`MockRenderer`]; 10pt × 5pt so dimension readers have data.
+pub const MOCK_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10pt" height="5pt" viewBox="0 0 10 5"></svg>"#;
+
+/// A [`Renderer`] that writes placeholder artifacts and records its calls.
+///
+/// For every equation it writes `<name>.tex` (the real LaTeX source),
+/// `<name>.pdf` (a placeholder) and `<name>.svg` ([`MOCK_SVG`]). Like the
+/// real renderer it provides cache keys, so unchanged equations are skipped
+/// when a batch is rendered into the same directory again.
+#[derive(Debug, Default)]
+pub struct MockRenderer {
+    calls: Mutex<Vec<String>>,
+    failing: HashSet<String>,
+    flaky: Mutex<HashMap<String, u32>>,
+    capabilities: Option<Capabilities>,
+}
+
+impl MockRenderer {
+    /// A renderer that succeeds for every equation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail with an error when asked to render the equation named `name`.
+    pub fn fail_on(mut self, name: &str) -> Self {
+        self.failing.insert(name.to_string());
+        self
+    }
+
+    /// Fail the first `times` renders of the equation named `name` with a
+    /// network error, as tectonic does when a bundle download breaks off.
+    pub fn fail_transiently(self, name: &str, times: u32) -> Self {
+        self.flaky.lock().unwrap().insert(name.to_string(), times);
+        self
+    }
+
+    /// Report `capabilities` instead of supporting everything, to test
+    /// checks against a limited backend.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// Names of the equations rendered so far, in call order.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+impl Renderer for MockRenderer {
+    fn render(&self, eq: &Equation, output_dir: &Path) -> Result<(), EquationError> {
+        self.calls.lock().unwrap().push(eq.name.clone());
+        if let Some(times) = self.flaky.lock().unwrap().get_mut(&eq.name) {
+            if *times > 0 {
+                *times -= 1;
+                return Err(EquationError::LatexCompileFailed {
+                    engine: eq.engine.unwrap_or_default(),
+                    tex: output_dir.join(format!("{}.tex", eq.name)),
+                    log: "error: failed to download texmf.bundle: connection reset by peer".into(),
+                });
+            }
+        }
+        if self.failing.contains(&eq.name) {
+            return Err(EquationError::LatexCompileFailed {
+                engine: eq.engine.unwrap_or_default(),
+                tex: output_dir.join(format!("{}.tex", eq.name)),
+                log: format!("! mock failure for {}", eq.name),
+            });
+        }
+        fs::write(
+            output_dir.join(format!("{}.tex", eq.name)),
+            eq.generate_latex("#000000"),
+        )?;
+        fs::write(output_dir.join(format!("{}.pdf", eq.name)), b"%PDF-mock")?;
+        fs::write(output_dir.join(format!("{}.svg", eq.name)), MOCK_SVG)?;
+        Ok(())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities.clone().unwrap_or_else(|| Capabilities {
+            direct_svg: true,
+            ..Capabilities::all("mock")
+        })
+    }
+
+    fn cache_key(&self, eq: &Equation) -> Option<String> {
+        Some(cache_key(
+            eq,
+            "#000000",
+            &TectonicConfig::default(),
+            &RenderOptions::default(),
+        ))
+    }
+}
+
+/// Builder for [`Equation`]s in tests; active, math and `x` by default.
+#[derive(Debug, Clone)]
+pub struct EquationBuilder {
+    eq: Equation,
+}
+
+impl EquationBuilder {
+    /// Start an active equation called `name` (sanitized like parsed names).
+    pub fn new(name: &str) -> Self {
+        Self {
+            eq: Equation::new(true, name, "x"),
+        }
+    }
+
+    /// Set the LaTeX body.
+    pub fn body(mut self, body: &str) -> Self {
+        self.eq.body = body.to_string();
+        self
+    }
+
+    /// Mark the equation as inactive.
+    pub fn inactive(mut self) -> Self {
+        self.eq.active = false;
+        self
+    }
+
+    /// Set the equation kind.
+    pub fn kind(mut self, kind: EquationKind) -> Self {
+        self.eq.kind = kind;
+        self
+    }
+
+    /// Add a tag.
+    pub fn tag(mut self, tag: &str) -> Self {
+        self.eq.tags.push(tag.to_string());
+        self
+    }
+
+    /// Add an asset file.
+    pub fn asset(mut self, path: impl Into<PathBuf>) -> Self {
+        self.eq.assets.push(path.into());
+        self
+    }
+
+    /// Set the Markdown section.
+    pub fn section(mut self, section: &str) -> Self {
+        self.eq.section = Some(section.to_string());
+        self
+    }
+
+    /// Finish building.
+    pub fn build(self) -> Equation {
+        self.eq
+    }
+}
+
+/// A fresh directory under the system temp dir, removed on drop.
+#[derive(Debug)]
+pub struct TempOutput {
+    path: PathBuf,
+}
+
+impl TempOutput {
+    /// Create an empty directory unique to `name` and this process.
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("eqp_test_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).expect("failed to create temp output directory");
+        Self { path }
+    }
+
+    /// The directory path.
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    /// Path of `file` inside the directory.
+    pub fn join(&self, file: impl AsRef<Path>) -> PathBuf {
+        self.path.join(file)
+    }
+}
+
+impl Drop for TempOutput {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
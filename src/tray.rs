@@ -0,0 +1,99 @@
+//! System tray icon for running the GUI in the background.
+//!
+//! Only built with the `tray` feature. On Linux the icon lives on its own
+//! thread running a gtk main loop; elsewhere it is created on the UI thread,
+//! whose event loop already serves it.
+
+use eframe::egui;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
+
+use crate::gui::APP_TITLE;
+
+/// Width and height of the generated icon in pixels.
+const ICON_SIZE: u32 = 32;
+
+/// The tray icon with its "Show" and "Quit" menu.
+pub struct Tray {
+    /// Set once "Quit" was chosen, so closing the window exits for real.
+    quit: Arc<AtomicBool>,
+    /// The icon, when owned by the UI thread.
+    _icon: Option<TrayIcon>,
+}
+
+impl Tray {
+    /// Add the icon to the system tray. Menu choices are sent to `ctx` as
+    /// viewport commands, so they work while the window is hidden.
+    pub fn new(ctx: &egui::Context) -> Self {
+        let quit = Arc::new(AtomicBool::new(false));
+        #[cfg(target_os = "linux")]
+        let icon = {
+            let (ctx, flag) = (ctx.clone(), quit.clone());
+            std::thread::spawn(move || {
+                if let Err(err) = gtk::init() {
+                    eprintln!("Failed to start the tray icon: {err}");
+                    return;
+                }
+                match build(&ctx, flag) {
+                    // The icon must outlive the gtk main loop on this thread.
+                    Ok(_icon) => gtk::main(),
+                    Err(err) => eprintln!("Failed to start the tray icon: {err}"),
+                }
+            });
+            None
+        };
+        #[cfg(not(target_os = "linux"))]
+        let icon = build(ctx, quit.clone())
+            .map_err(|err| eprintln!("Failed to start the tray icon: {err}"))
+            .ok();
+        Self { quit, _icon: icon }
+    }
+
+    /// Whether "Quit" was chosen from the tray menu.
+    pub fn quit_requested(&self) -> bool {
+        self.quit.load(Ordering::SeqCst)
+    }
+}
+
+/// Create the icon and route its menu events to `ctx`.
+fn build(ctx: &egui::Context, quit: Arc<AtomicBool>) -> Result<TrayIcon, String> {
+    let show = MenuItem::new("Show", true, None);
+    let exit = MenuItem::new("Quit", true, None);
+    let menu = Menu::new();
+    menu.append_items(&[&show, &exit])
+        .map_err(|e| e.to_string())?;
+    let (show_id, quit_id) = (show.id().clone(), exit.id().clone());
+    let ctx = ctx.clone();
+    MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+        if event.id == quit_id {
+            quit.store(true, Ordering::SeqCst);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        } else if event.id == show_id {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Visible(true));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        ctx.request_repaint();
+    }));
+    TrayIconBuilder::new()
+        .with_menu(Box::new(menu))
+        .with_tooltip(APP_TITLE)
+        .with_icon(icon()?)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// A dark disc, drawn rather than loaded so no image file has to ship.
+fn icon() -> Result<Icon, String> {
+    let center = ICON_SIZE as f32 / 2.0;
+    let mut rgba = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let (dx, dy) = (x as f32 + 0.5 - center, y as f32 + 0.5 - center);
+            let alpha = if dx.hypot(dy) <= center - 1.0 { 255 } else { 0 };
+            rgba.extend_from_slice(&[40, 40, 40, alpha]);
+        }
+    }
+    Icon::from_rgba(rgba, ICON_SIZE, ICON_SIZE).map_err(|e| e.to_string())
+}
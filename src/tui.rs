@@ -0,0 +1,422 @@
+//! Keyboard-driven terminal interface for the Equation Processor.
+//!
+//! A middle ground between the CLI prompts and the GUI, e.g. over SSH: the
+//! equation list with checkboxes, a filter, a preview pane and a render
+//! action. Rendered PNGs are shown inline on terminals speaking the kitty
+//! graphics protocol (kitty, WezTerm, Ghostty, Konsole); elsewhere the
+//! preview is a Unicode approximation.
+
+use base64::Engine;
+use equation_processor::render::{RenderOptions, Renderer, TectonicConfig, TectonicRenderer};
+use equation_processor::report::{
+    default_stats_file, record_run, run_summary, unicode_preview, write_index, RunStats,
+};
+use equation_processor::Equation;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a key before checking on a running render.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Key help shown in the status line while idle.
+const HELP: &str = "↑↓ move  space toggle  a all  n none  / filter  r render  q quit";
+
+/// Settings for the render action, fixed when the TUI starts.
+#[derive(Clone)]
+pub struct RenderSettings {
+    /// Where artifacts are written
+    pub output_dir: PathBuf,
+    /// How tectonic is invoked
+    pub tectonic: TectonicConfig,
+    /// Batch-wide options, including the color
+    pub options: RenderOptions,
+}
+
+impl RenderSettings {
+    /// The renderer these settings describe.
+    fn renderer(&self) -> TectonicRenderer<'_> {
+        TectonicRenderer::new(&self.tectonic, &self.options)
+    }
+}
+
+/// Messages from the background render thread.
+enum Progress {
+    /// `done` of `total` equations are rendered.
+    Rendered { done: usize, total: usize },
+    /// The batch is complete; these equations failed.
+    Finished { failed: Vec<String> },
+}
+
+/// State of the terminal interface.
+struct Tui {
+    equations: Vec<Equation>,
+    settings: RenderSettings,
+    /// Case-insensitive text matched against names, bodies, tags and
+    /// descriptions.
+    filter: String,
+    /// Keys edit the filter instead of acting on the list.
+    editing_filter: bool,
+    /// Selection within the filtered rows.
+    list: ListState,
+    /// Outcome of the last action, shown in the status line.
+    status: Option<String>,
+    /// Progress of a running render.
+    progress_rx: Option<mpsc::Receiver<Progress>>,
+    /// `(done, total)` of the running render.
+    progress: Option<(usize, usize)>,
+    /// The terminal understands the kitty graphics protocol.
+    graphics: bool,
+    /// Where the preview image goes, as laid out in the last frame.
+    image_area: Rect,
+    /// The image currently drawn and where, to redraw only on change.
+    shown_image: Option<(PathBuf, Rect)>,
+}
+
+/// Run the terminal interface on `equations` until the user quits.
+pub fn run(equations: Vec<Equation>, settings: RenderSettings) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let mut tui = Tui {
+        equations,
+        settings,
+        filter: String::new(),
+        editing_filter: false,
+        list: ListState::default().with_selected(Some(0)),
+        status: None,
+        progress_rx: None,
+        progress: None,
+        graphics: supports_kitty_graphics(),
+        image_area: Rect::default(),
+        shown_image: None,
+    };
+    let result = tui.run(&mut terminal);
+    if tui.shown_image.is_some() {
+        let _ = delete_images(terminal.backend_mut());
+    }
+    ratatui::restore();
+    result
+}
+
+/// Whether the terminal is known to speak the kitty graphics protocol.
+fn supports_kitty_graphics() -> bool {
+    let var = |name: &str| std::env::var(name).unwrap_or_default();
+    std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || std::env::var_os("KONSOLE_VERSION").is_some()
+        || var("TERM").contains("kitty")
+        || matches!(var("TERM_PROGRAM").as_str(), "WezTerm" | "ghostty")
+}
+
+impl Tui {
+    /// The event loop: draw, then handle one key or progress tick.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            self.poll_progress();
+            terminal.draw(|frame| self.draw(frame))?;
+            self.draw_image(terminal.backend_mut())?;
+            if !event::poll(TICK)? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && self.handle_key(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Indices of the equations matching the filter.
+    fn visible(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+        self.equations
+            .iter()
+            .enumerate()
+            .filter(|(_, eq)| {
+                filter.is_empty()
+                    || eq.name.to_lowercase().contains(&filter)
+                    || eq.body.to_lowercase().contains(&filter)
+                    || eq.tags.iter().any(|t| t.to_lowercase().contains(&filter))
+                    || eq
+                        .description
+                        .as_ref()
+                        .is_some_and(|d| d.to_lowercase().contains(&filter))
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Index of the selected equation, if any row is visible.
+    fn selected(&self) -> Option<usize> {
+        self.visible().get(self.list.selected()?).copied()
+    }
+
+    /// Handle a key press; returns whether to quit.
+    fn handle_key(&mut self, code: KeyCode) -> bool {
+        if self.editing_filter {
+            match code {
+                KeyCode::Char(c) => self.filter.push(c),
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Esc => {
+                    self.filter.clear();
+                    self.editing_filter = false;
+                }
+                KeyCode::Enter => self.editing_filter = false,
+                _ => {}
+            }
+            self.list.select(Some(0));
+            return false;
+        }
+        let rows = self.visible().len();
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => self.list.select_first(),
+            KeyCode::End | KeyCode::Char('G') => self.list.select(rows.checked_sub(1)),
+            KeyCode::Char(' ') => {
+                if let Some(i) = self.selected() {
+                    self.equations[i].active = !self.equations[i].active;
+                }
+            }
+            KeyCode::Char('a') => self.set_visible_active(true),
+            KeyCode::Char('n') => self.set_visible_active(false),
+            KeyCode::Char('/') => self.editing_filter = true,
+            KeyCode::Char('r') if self.progress_rx.is_none() => self.start_render(),
+            _ => {}
+        }
+        // select_next/select_last may run past the end; the list clamps
+        // when drawn, but the preview needs a valid row now.
+        if let Some(i) = self.list.selected() {
+            self.list.select(Some(i.min(rows.saturating_sub(1))));
+        }
+        false
+    }
+
+    /// Mark every equation passing the filter active or inactive.
+    fn set_visible_active(&mut self, active: bool) {
+        for i in self.visible() {
+            self.equations[i].active = active;
+        }
+    }
+
+    /// Render the active equations on a background thread.
+    fn start_render(&mut self) {
+        let total = self.equations.iter().filter(|e| e.active).count();
+        if total == 0 {
+            self.status = Some("No active equations to render.".into());
+            return;
+        }
+        let unsupported = self
+            .settings
+            .renderer()
+            .capabilities()
+            .unsupported(&self.equations, &self.settings.options);
+        if !unsupported.is_empty() {
+            self.status = Some(unsupported.join("; "));
+            return;
+        }
+        let equations = self.equations.clone();
+        let settings = self.settings.clone();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let renderer = settings.renderer();
+            let output_dir = &settings.output_dir;
+            let mut failed = Vec::new();
+            let started = Instant::now();
+            for (i, eq) in equations.iter().filter(|e| e.active).enumerate() {
+                if renderer.render(eq, output_dir).is_err() {
+                    failed.push(eq.name.clone());
+                }
+                let _ = tx.send(Progress::Rendered { done: i + 1, total });
+            }
+            let stats = RunStats::new(
+                &equations,
+                total - failed.len(),
+                failed.len(),
+                started.elapsed(),
+            );
+            let _ = default_stats_file(output_dir).and_then(|path| record_run(&path, &stats));
+            let _ = write_index(&equations, output_dir);
+            let _ = tx.send(Progress::Finished { failed });
+        });
+        self.progress = Some((0, total));
+        self.progress_rx = Some(rx);
+        // Artifacts are about to change; draw the preview afresh.
+        self.shown_image = None;
+    }
+
+    /// Apply messages from a running render.
+    fn poll_progress(&mut self) {
+        let messages: Vec<Progress> = self
+            .progress_rx
+            .as_ref()
+            .map(|rx| rx.try_iter().collect())
+            .unwrap_or_default();
+        for message in messages {
+            match message {
+                Progress::Rendered { done, total } => self.progress = Some((done, total)),
+                Progress::Finished { failed } => {
+                    self.progress_rx = None;
+                    self.progress = None;
+                    self.shown_image = None;
+                    self.status = Some(if failed.is_empty() {
+                        run_summary(&self.equations)
+                    } else {
+                        format!("Failed to render: {}", failed.join(", "))
+                    });
+                }
+            }
+        }
+    }
+
+    /// Lay out the list, the preview and the status line.
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(main);
+
+        let visible = self.visible();
+        let items: Vec<ListItem> = visible
+            .iter()
+            .map(|&i| {
+                let eq = &self.equations[i];
+                let check = if eq.active { "[x]" } else { "[ ]" };
+                ListItem::new(format!("{check} {}", eq.name))
+            })
+            .collect();
+        let active = self.equations.iter().filter(|e| e.active).count();
+        let title = match self.filter.is_empty() {
+            true => format!(" Equations ({active}/{} active) ", self.equations.len()),
+            false => format!(
+                " Equations matching \"{}\" ({}) ",
+                self.filter,
+                visible.len()
+            ),
+        };
+        let list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list);
+
+        let block = Block::bordered().title(" Preview ");
+        let inner = block.inner(preview_area);
+        frame.render_widget(block, preview_area);
+        self.image_area = Rect::default();
+        if let Some(eq) = self.selected().map(|i| &self.equations[i]) {
+            let mut lines = vec![Line::from(eq.name.clone()).bold()];
+            if let Some(description) = &eq.description {
+                lines.push(Line::from(description.clone()).italic());
+            }
+            lines.push(Line::default());
+            lines.extend(eq.body.lines().map(|l| Line::from(l.to_string())));
+            lines.push(Line::default());
+            let png = self.png(eq);
+            match (&png, self.graphics) {
+                (Some(_), true) => {}
+                (None, true) => {
+                    lines.push(Line::from(unicode_preview(&eq.body)));
+                    lines.push(Line::from("(render with --formats png for an image)").dim());
+                }
+                (_, false) => lines.push(Line::from(unicode_preview(&eq.body))),
+            }
+            let height = (lines.len() as u16).min(inner.height);
+            let [text, image] =
+                Layout::vertical([Constraint::Length(height), Constraint::Min(0)]).areas(inner);
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), text);
+            if png.is_some() && self.graphics {
+                self.image_area = image;
+            }
+        }
+
+        let line = if self.editing_filter {
+            Line::from(format!("/{}", self.filter))
+        } else if let Some((done, total)) = self.progress {
+            Line::from(format!("Rendering {done}/{total}…"))
+        } else if let Some(status) = &self.status {
+            Line::from(status.clone())
+        } else {
+            Line::from(HELP).dim()
+        };
+        frame.render_widget(Paragraph::new(line), status);
+    }
+
+    /// The rendered PNG of `eq`, if there is one.
+    fn png(&self, eq: &Equation) -> Option<PathBuf> {
+        let path = self.settings.output_dir.join(format!("{}.png", eq.name));
+        path.exists().then_some(path)
+    }
+
+    /// Draw the selected equation's PNG over the preview's image area with
+    /// the kitty graphics protocol, if it changed since the last frame.
+    fn draw_image(&mut self, out: &mut impl Write) -> io::Result<()> {
+        let wanted = self
+            .selected()
+            .and_then(|i| self.png(&self.equations[i]))
+            .filter(|_| self.image_area.area() > 0)
+            .map(|png| (png, self.image_area));
+        if wanted == self.shown_image {
+            return Ok(());
+        }
+        delete_images(out)?;
+        if let Some((png, area)) = &wanted {
+            if let Ok(data) = fs::read(png) {
+                let (columns, rows) = fit_cells(&data, *area);
+                write!(out, "\x1b[{};{}H", area.y + 1, area.x + 1)?;
+                let encoded = base64::engine::general_purpose::STANDARD.encode(&data);
+                let chunks: Vec<&[u8]> = encoded.as_bytes().chunks(4096).collect();
+                for (i, chunk) in chunks.iter().enumerate() {
+                    let more = u8::from(i + 1 < chunks.len());
+                    if i == 0 {
+                        write!(out, "\x1b_Ga=T,f=100,c={columns},r={rows},q=2,m={more};")?;
+                    } else {
+                        write!(out, "\x1b_Gm={more};")?;
+                    }
+                    out.write_all(chunk)?;
+                    write!(out, "\x1b\\")?;
+                }
+            }
+        }
+        out.flush()?;
+        self.shown_image = wanted;
+        Ok(())
+    }
+}
+
+/// Remove every image drawn with the kitty graphics protocol.
+fn delete_images(out: &mut impl Write) -> io::Result<()> {
+    write!(out, "\x1b_Ga=d,q=2\x1b\\")?;
+    out.flush()
+}
+
+/// Cells the PNG `data` covers when fitted into `area`, keeping its aspect
+/// ratio with terminal cells about twice as tall as wide.
+fn fit_cells(data: &[u8], area: Rect) -> (u16, u16) {
+    let dimension = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as f64)
+            .filter(|&d| d > 0.0)
+    };
+    // Width and height follow the signature in the IHDR chunk.
+    let (Some(width), Some(height)) = (dimension(16), dimension(20)) else {
+        return (area.width, area.height);
+    };
+    let rows = (f64::from(area.width) * height / width / 2.0).ceil();
+    if rows <= f64::from(area.height) {
+        (area.width, rows.max(1.0) as u16)
+    } else {
+        let columns = f64::from(area.height) * 2.0 * width / height;
+        (columns.max(1.0) as u16, area.height)
+    }
+}
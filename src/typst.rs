@@ -0,0 +1,807 @@
+//! Translating equations to [Typst](https://typst.app) math, for projects
+//! moving their notes from LaTeX to Typst.
+//!
+//! The translation is best effort. Symbols are looked up in a table bundled
+//! with the crate; fractions, roots, fonts, accents, matrices and `cases`
+//! are rewritten to their Typst functions. Commands without a Typst
+//! equivalent are kept as quoted text and reported, so they can be fixed by
+//! hand.
+//!
+//! ```
+//! use equation_processor::typst::latex_to_typst;
+//!
+//! let math = latex_to_typst(r"\frac{\alpha}{2} \leq \sqrt{x_{0}}");
+//! assert_eq!(math.source, "frac(alpha, 2) <= sqrt(x_0)");
+//! assert!(math.untranslated.is_empty());
+//!
+//! let math = latex_to_typst(r"\intertext{so} x");
+//! assert_eq!(math.untranslated, [r"\intertext"]);
+//! ```
+
+use crate::model::{Equation, EquationKind};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+#[cfg(feature = "typst-fallback")]
+use {
+    crate::error::EquationError,
+    crate::model::{Background, StandaloneOptions},
+    crate::render::{
+        embed_svg_description, Capabilities, ImageFormat, OutputFormat, RenderOptions, Renderer,
+    },
+    ::typst::diag::{FileError, FileResult},
+    ::typst::foundations::{Bytes, Datetime},
+    ::typst::syntax::{FileId, Source},
+    ::typst::text::{Font, FontBook},
+    ::typst::Library,
+    comemo::Prehashed,
+};
+
+/// The bundled symbol table: a command without backslash and its Typst
+/// code per line.
+const SYMBOL_TABLE: &str = include_str!("typst_symbols.txt");
+
+/// Subdirectory of the output directory [`write_typst_snippets`] writes to.
+pub const TYPST_DIR: &str = "typst";
+
+/// File name of the file in [`TYPST_DIR`] including every snippet.
+pub const TYPST_MASTER: &str = "equations.typ";
+
+/// Typst code of the commands in the symbol table, by command name.
+fn symbols() -> &'static HashMap<&'static str, &'static str> {
+    static SYMBOLS: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    SYMBOLS.get_or_init(|| {
+        SYMBOL_TABLE
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .filter_map(|l| l.split_once(' '))
+            .collect()
+    })
+}
+
+/// Typst math translated from a LaTeX body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypstMath {
+    /// Typst math code, without the surrounding `$`
+    pub source: String,
+    /// Commands (`\intertext`) and environments (`\begin{multline}`) without
+    /// a Typst equivalent, each listed once; they are kept in `source` as
+    /// quoted text
+    pub untranslated: Vec<String>,
+}
+
+/// Translate the LaTeX math `latex` to Typst math.
+///
+/// Math delimiters such as `\[...\]` and display environments such as
+/// `align` are removed, keeping their `&` alignment points and `\\` line
+/// breaks. Labels, `\displaystyle` and sizing commands like `\left` are
+/// dropped, since Typst sizes delimiters itself.
+pub fn latex_to_typst(latex: &str) -> TypstMath {
+    let mut translator = Translator {
+        tokens: tokenize(latex),
+        pos: 0,
+        mode: Mode::Math,
+        untranslated: Vec::new(),
+    };
+    let mut out = Output::default();
+    loop {
+        translator.sequence(&mut out);
+        // Left over by unbalanced input: a `}` or `\end` without a start.
+        match translator.next() {
+            Some(Token::Command("end")) => {
+                translator.raw_argument();
+            }
+            Some(_) => {}
+            None => break,
+        }
+    }
+    TypstMath {
+        source: out.finish(),
+        untranslated: translator.untranslated,
+    }
+}
+
+/// What [`write_typst_snippets`] wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypstExport {
+    /// The file including every snippet
+    pub master: PathBuf,
+    /// Names of the equations written, in order
+    pub written: Vec<String>,
+    /// Names of the equations with untranslated commands, and the commands
+    pub untranslated: Vec<(String, Vec<String>)>,
+    /// Names of the chemical formulas and TikZ drawings, which Typst math
+    /// cannot express and are left out
+    pub skipped: Vec<String>,
+}
+
+/// Write one `.typ` snippet per active math equation into
+/// `output_dir/typst`, with its math as a block equation labelled
+/// `<eq:name>`, plus a master file ([`TYPST_MASTER`]) including all of them
+/// in order.
+pub fn write_typst_snippets(equations: &[Equation], output_dir: &Path) -> io::Result<TypstExport> {
+    let dir = output_dir.join(TYPST_DIR);
+    fs::create_dir_all(&dir)?;
+    let mut master = String::from("// Generated by equation_processor\n");
+    let mut export = TypstExport {
+        master: dir.join(TYPST_MASTER),
+        written: Vec::new(),
+        untranslated: Vec::new(),
+        skipped: Vec::new(),
+    };
+    for eq in equations.iter().filter(|e| e.active) {
+        if eq.kind != EquationKind::Math {
+            export.skipped.push(eq.name.clone());
+            continue;
+        }
+        let math = latex_to_typst(&eq.body);
+        let snippet = format!("$ {} $ <eq:{}>\n", math.source, eq.name);
+        fs::write(dir.join(format!("{}.typ", eq.name)), snippet)?;
+        master.push_str(&format!("#include \"{}.typ\"\n", eq.name));
+        export.written.push(eq.name.clone());
+        if !math.untranslated.is_empty() {
+            export
+                .untranslated
+                .push((eq.name.clone(), math.untranslated));
+        }
+    }
+    fs::write(&export.master, master)?;
+    Ok(export)
+}
+
+/// Renders math equations in-process with the Typst compiler, for machines
+/// without a TeX distribution; behind the `typst-fallback` feature.
+///
+/// Bodies are translated with [`latex_to_typst`], so only what that
+/// translation covers can be typeset: no LaTeX packages, templates,
+/// preambles, TikZ or chemistry, and SVG is the only output. Equations with
+/// commands it cannot translate fail rather than render as quoted text.
+/// The color, background, font size and border of the batch are applied.
+#[cfg(feature = "typst-fallback")]
+#[derive(Debug, Clone)]
+pub struct TypstRenderer<'a> {
+    /// Batch-wide options such as the color and font size
+    pub options: &'a RenderOptions,
+}
+
+#[cfg(feature = "typst-fallback")]
+impl<'a> TypstRenderer<'a> {
+    /// A renderer using the color, background and font size of `options`.
+    pub fn new(options: &'a RenderOptions) -> Self {
+        TypstRenderer { options }
+    }
+
+    /// The Typst document typesetting `math` as `eq` on a page of its own
+    /// size.
+    fn document(&self, eq: &Equation, math: &str) -> String {
+        let options = self.options;
+        let border = eq
+            .standalone
+            .or(&options.standalone)
+            .border
+            .unwrap_or_else(|| StandaloneOptions::DEFAULT_BORDER.to_string());
+        let fill = match options.background {
+            Background::Transparent => "none".to_string(),
+            Background::Color(color) => format!("rgb(\"{color}\")"),
+        };
+        let color = eq.color.as_deref().unwrap_or(&options.color);
+        // LaTeX's \Large, as in the LaTeX documents.
+        let size = options.font_size.unwrap_or(14.4);
+        format!(
+            "#set page(width: auto, height: auto, margin: {}, fill: {fill})\n\
+             #set text(size: {size}pt, fill: rgb(\"{color}\"))\n\
+             $ {math} $\n",
+            typst_margin(&border)
+        )
+    }
+}
+
+#[cfg(feature = "typst-fallback")]
+impl Renderer for TypstRenderer<'_> {
+    fn render(&self, eq: &Equation, output_dir: &Path) -> Result<(), EquationError> {
+        if !eq.active {
+            return Ok(());
+        }
+        let failed = |reason: String| EquationError::TypstFailed {
+            name: eq.name.clone(),
+            reason,
+        };
+        let math = latex_to_typst(&eq.body);
+        if !math.untranslated.is_empty() {
+            return Err(failed(format!(
+                "no Typst translation for {}",
+                math.untranslated.join(", ")
+            )));
+        }
+        let world = FallbackWorld::new(self.document(eq, &math.source));
+        let document =
+            ::typst::compile(&world, &mut ::typst::eval::Tracer::new()).map_err(|errors| {
+                let messages: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+                failed(messages.join("; "))
+            })?;
+        let page = document
+            .pages
+            .first()
+            .ok_or_else(|| failed("the document has no page".into()))?;
+        fs::create_dir_all(output_dir)?;
+        let svg = output_dir.join(format!("{}.svg", eq.name));
+        fs::write(&svg, typst_svg::svg(&page.frame))?;
+        if let Some(description) = &eq.description {
+            embed_svg_description(&svg, description)?;
+        }
+        Ok(())
+    }
+
+    /// SVG of math equations, without LaTeX engines or font packages.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            backend: "typst",
+            formats: vec![OutputFormat::Image(ImageFormat::Svg)],
+            kinds: vec![EquationKind::Math],
+            engines: Vec::new(),
+            transparent_background: true,
+            custom_fonts: false,
+            direct_svg: true,
+            pdf_options: false,
+            missing_tools: Vec::new(),
+        }
+    }
+}
+
+/// A standalone `border` such as `2pt` or `1pt 2pt 3pt 4pt` (left, bottom,
+/// right, top) as a Typst page margin; lengths in units Typst lacks fall
+/// back to [`StandaloneOptions::DEFAULT_BORDER`].
+#[cfg(feature = "typst-fallback")]
+fn typst_margin(border: &str) -> String {
+    let lengths: Vec<&str> = border.split_whitespace().collect();
+    let typst_length = |length: &str| {
+        ["pt", "mm", "cm", "in", "em"].iter().any(|unit| {
+            length
+                .strip_suffix(unit)
+                .is_some_and(|number| number.parse::<f64>().is_ok())
+        })
+    };
+    match lengths.as_slice() {
+        [length] if typst_length(length) => length.to_string(),
+        [left, bottom, right, top] if lengths.iter().all(|l| typst_length(l)) => {
+            format!("(left: {left}, bottom: {bottom}, right: {right}, top: {top})")
+        }
+        _ => StandaloneOptions::DEFAULT_BORDER.to_string(),
+    }
+}
+
+/// The standard library and the fonts bundled with Typst, loaded once.
+#[cfg(feature = "typst-fallback")]
+struct TypstResources {
+    library: Prehashed<Library>,
+    book: Prehashed<FontBook>,
+    fonts: Vec<Font>,
+}
+
+#[cfg(feature = "typst-fallback")]
+fn typst_resources() -> &'static TypstResources {
+    static RESOURCES: OnceLock<TypstResources> = OnceLock::new();
+    RESOURCES.get_or_init(|| {
+        let fonts: Vec<Font> = typst_assets::fonts()
+            .flat_map(|data| Font::iter(Bytes::from_static(data)))
+            .collect();
+        TypstResources {
+            library: Prehashed::new(Library::default()),
+            book: Prehashed::new(FontBook::from_fonts(&fonts)),
+            fonts,
+        }
+    })
+}
+
+/// A Typst world holding one document and no other files.
+#[cfg(feature = "typst-fallback")]
+struct FallbackWorld {
+    resources: &'static TypstResources,
+    source: Source,
+}
+
+#[cfg(feature = "typst-fallback")]
+impl FallbackWorld {
+    fn new(document: String) -> Self {
+        FallbackWorld {
+            resources: typst_resources(),
+            source: Source::detached(document),
+        }
+    }
+}
+
+#[cfg(feature = "typst-fallback")]
+impl ::typst::World for FallbackWorld {
+    fn library(&self) -> &Prehashed<Library> {
+        &self.resources.library
+    }
+
+    fn book(&self) -> &Prehashed<FontBook> {
+        &self.resources.book
+    }
+
+    fn main(&self) -> Source {
+        self.source.clone()
+    }
+
+    fn source(&self, id: FileId) -> FileResult<Source> {
+        if id == self.source.id() {
+            Ok(self.source.clone())
+        } else {
+            Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+        }
+    }
+
+    fn file(&self, id: FileId) -> FileResult<Bytes> {
+        Err(FileError::NotFound(id.vpath().as_rootless_path().into()))
+    }
+
+    fn font(&self, index: usize) -> Option<Font> {
+        self.resources.fonts.get(index).cloned()
+    }
+
+    fn today(&self, _offset: Option<i64>) -> Option<Datetime> {
+        None
+    }
+}
+
+/// A piece of LaTeX source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    /// `\name`, or a backslash and one other character such as `\,`
+    Command(&'a str),
+    Open,
+    Close,
+    Char(char),
+}
+
+/// The tokens of `latex`, without comments.
+fn tokenize(latex: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut chars = latex.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                let start = i + 1;
+                let mut end = start;
+                while let Some((j, _)) = chars.next_if(|(_, c)| c.is_ascii_alphabetic()) {
+                    end = j + 1;
+                }
+                if end == start {
+                    if let Some((j, c)) = chars.next() {
+                        end = j + c.len_utf8();
+                    }
+                }
+                tokens.push(Token::Command(&latex[start..end]));
+            }
+            '{' => tokens.push(Token::Open),
+            '}' => tokens.push(Token::Close),
+            '%' => while chars.next_if(|(_, c)| *c != '\n').is_some() {},
+            c => tokens.push(Token::Char(c)),
+        }
+    }
+    tokens
+}
+
+/// What `&` and `\\` stand for where the translator is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Mode {
+    /// Alignment points and line breaks, as in `align`
+    Math,
+    /// Cells and rows of a matrix
+    Matrix,
+    /// Alignment points and branches of `cases`
+    Cases,
+}
+
+/// Typst code being written, with spaces where adjacent pieces would
+/// otherwise run together into one identifier.
+#[derive(Default)]
+struct Output {
+    code: String,
+    /// Set after a sub- or superscript, which must not absorb what follows.
+    attached: bool,
+}
+
+impl Output {
+    fn push(&mut self, piece: &str) {
+        let Some(first) = piece.chars().next() else {
+            return;
+        };
+        if matches!(first, ',' | ';' | ')') {
+            self.code.truncate(self.code.trim_end_matches(' ').len());
+        }
+        if let Some(last) = self.code.chars().last() {
+            let word = |c: char| c.is_alphanumeric();
+            let digits = last.is_ascii_digit() && first.is_ascii_digit();
+            let after = (word(last) && !digits) || matches!(last, ')' | '"') || self.attached;
+            // `f(a)(b)` would be a call of the result of `f(a)`.
+            if (word(first) && after) || (first == '(' && last == ')') {
+                self.code.push(' ');
+            }
+        }
+        self.attached = false;
+        self.code.push_str(piece);
+    }
+
+    /// Append a sub- or superscript to the `^` or `_` just pushed.
+    fn attach(&mut self, script: &str) {
+        let simple = !script.is_empty() && script.chars().all(|c| c.is_alphanumeric() || c == '.');
+        if simple {
+            self.code.push_str(script);
+        } else {
+            self.code.push_str(&format!("({script})"));
+        }
+        self.attached = true;
+    }
+
+    fn space(&mut self) {
+        if !self.code.is_empty() && !self.code.ends_with([' ', '(']) {
+            self.code.push(' ');
+        }
+        self.attached = false;
+    }
+
+    fn finish(self) -> String {
+        self.code.trim().to_string()
+    }
+}
+
+/// `text` as a Typst string literal.
+fn quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Typst function for a LaTeX font command taking one argument.
+fn font_function(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "mathbf" | "boldsymbol" | "bm" => "bold",
+        "mathrm" => "upright",
+        "mathit" => "italic",
+        "mathcal" => "cal",
+        "mathbb" => "bb",
+        "mathsf" => "sans",
+        "mathtt" => "mono",
+        "mathfrak" => "frak",
+        _ => return None,
+    })
+}
+
+/// Typst function for a LaTeX accent or decoration taking one argument.
+fn accent_function(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "hat" | "widehat" => "hat",
+        "bar" => "macron",
+        "overline" => "overline",
+        "underline" => "underline",
+        "vec" | "overrightarrow" => "arrow",
+        "dot" => "dot",
+        "ddot" => "dot.double",
+        "tilde" | "widetilde" => "tilde",
+        "check" => "caron",
+        "breve" => "breve",
+        "acute" => "acute",
+        "grave" => "grave",
+        "overbrace" => "overbrace",
+        "underbrace" => "underbrace",
+        _ => return None,
+    })
+}
+
+/// Opening of the Typst call for a LaTeX matrix environment.
+fn matrix_call(env: &str) -> Option<&'static str> {
+    Some(match env {
+        "matrix" | "smallmatrix" | "array" => "mat(delim: #none, ",
+        "pmatrix" => "mat(delim: \"(\", ",
+        "bmatrix" => "mat(delim: \"[\", ",
+        "Bmatrix" => "mat(delim: \"{\", ",
+        "vmatrix" => "mat(delim: \"|\", ",
+        "Vmatrix" => "mat(delim: \"||\", ",
+        _ => return None,
+    })
+}
+
+/// Display environments whose content Typst math takes as it is.
+const TRANSPARENT_ENVIRONMENTS: &[&str] = &[
+    "equation",
+    "equation*",
+    "displaymath",
+    "align",
+    "align*",
+    "aligned",
+    "alignat",
+    "alignat*",
+    "alignedat",
+    "gather",
+    "gather*",
+    "gathered",
+    "multline",
+    "multline*",
+    "split",
+];
+
+struct Translator<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+    mode: Mode,
+    untranslated: Vec<String>,
+}
+
+impl<'a> Translator<'a> {
+    fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(Token::Char(c)) if c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn flag(&mut self, command: String) {
+        if !self.untranslated.contains(&command) {
+            self.untranslated.push(command);
+        }
+    }
+
+    /// Translate up to the end of the current group or environment, which
+    /// is left for the caller.
+    fn sequence(&mut self, out: &mut Output) {
+        while let Some(token) = self.peek() {
+            if matches!(token, Token::Close | Token::Command("end")) {
+                return;
+            }
+            self.pos += 1;
+            self.token(token, out);
+        }
+    }
+
+    /// The rest of a group whose `{` was just read, translated.
+    fn group_rest(&mut self) -> String {
+        let mut out = Output::default();
+        self.sequence(&mut out);
+        if self.peek() == Some(Token::Close) {
+            self.pos += 1;
+        }
+        out.finish()
+    }
+
+    /// The next argument of a command, translated.
+    fn argument(&mut self) -> String {
+        self.skip_whitespace();
+        match self.next() {
+            Some(Token::Open) => self.group_rest(),
+            Some(token) => {
+                let mut out = Output::default();
+                self.token(token, &mut out);
+                out.finish()
+            }
+            None => String::new(),
+        }
+    }
+
+    /// The next argument of a command as written, with escaped characters
+    /// such as `\%` unescaped.
+    fn raw_argument(&mut self) -> String {
+        self.skip_whitespace();
+        let mut text = String::new();
+        let mut depth = 0;
+        while let Some(token) = self.next() {
+            match token {
+                Token::Open if depth == 0 => {}
+                Token::Open => text.push('{'),
+                Token::Close if depth == 1 => break,
+                Token::Close => text.push('}'),
+                Token::Char(c) => text.push(c),
+                Token::Command(name) if name.chars().all(|c| c.is_ascii_alphabetic()) => {
+                    text.push('\\');
+                    text.push_str(name);
+                }
+                Token::Command(name) => text.push_str(name),
+            }
+            match token {
+                Token::Open => depth += 1,
+                Token::Close => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+        text
+    }
+
+    /// The tokens up to the character `end` (consumed), translated.
+    fn until_char(&mut self, end: char) -> String {
+        let mut out = Output::default();
+        while let Some(token) = self.next() {
+            if token == Token::Char(end) {
+                break;
+            }
+            self.token(token, &mut out);
+        }
+        out.finish()
+    }
+
+    fn token(&mut self, token: Token<'a>, out: &mut Output) {
+        match token {
+            Token::Open => {
+                let group = self.group_rest();
+                out.push(&group);
+            }
+            Token::Close => {}
+            Token::Command(name) => self.command(name, out),
+            Token::Char(c) => match c {
+                '^' | '_' => {
+                    let script = self.argument();
+                    out.push(&c.to_string());
+                    out.attach(&script);
+                }
+                '&' if self.mode == Mode::Matrix => {
+                    out.push(",");
+                    out.space();
+                }
+                '/' => out.push("slash"),
+                '"' | '#' => out.push(&format!("\\{c}")),
+                '$' => {}
+                '~' => out.space(),
+                c if c.is_whitespace() => out.space(),
+                c => out.push(&c.to_string()),
+            },
+        }
+    }
+
+    fn command(&mut self, name: &'a str, out: &mut Output) {
+        match name {
+            "frac" | "dfrac" | "tfrac" | "cfrac" => {
+                let (numerator, denominator) = (self.argument(), self.argument());
+                out.push(&format!("frac({numerator}, {denominator})"));
+            }
+            "binom" | "dbinom" | "tbinom" => {
+                let (n, k) = (self.argument(), self.argument());
+                out.push(&format!("binom({n}, {k})"));
+            }
+            "sqrt" => {
+                self.skip_whitespace();
+                if self.peek() == Some(Token::Char('[')) {
+                    self.pos += 1;
+                    let index = self.until_char(']');
+                    let radicand = self.argument();
+                    out.push(&format!("root({index}, {radicand})"));
+                } else {
+                    let radicand = self.argument();
+                    out.push(&format!("sqrt({radicand})"));
+                }
+            }
+            "text" | "textrm" | "textnormal" | "textup" | "mbox" => {
+                let text = self.raw_argument();
+                out.push(&quote(&text));
+            }
+            "textbf" => {
+                let text = self.raw_argument();
+                out.push(&format!("bold({})", quote(&text)));
+            }
+            "textit" | "emph" => {
+                let text = self.raw_argument();
+                out.push(&format!("italic({})", quote(&text)));
+            }
+            "operatorname" => {
+                let limits = self.peek() == Some(Token::Char('*'));
+                if limits {
+                    self.pos += 1;
+                }
+                let text = self.raw_argument();
+                if limits {
+                    out.push(&format!("op({}, limits: #true)", quote(&text)));
+                } else {
+                    out.push(&format!("op({})", quote(&text)));
+                }
+            }
+            "begin" => self.environment(out),
+            "end" | "label" | "tag" => {
+                self.raw_argument();
+            }
+            "left" | "right" | "middle" | "big" | "Big" | "bigg" | "Bigg" | "bigl" | "bigr"
+            | "Bigl" | "Bigr" | "biggl" | "biggr" | "Biggl" | "Biggr" => {
+                self.skip_whitespace();
+                if self.peek() == Some(Token::Char('.')) {
+                    self.pos += 1;
+                }
+            }
+            "displaystyle" | "textstyle" | "scriptstyle" | "limits" | "nolimits" | "nonumber"
+            | "notag" | "[" | "]" | "(" | ")" | "!" | "" => {}
+            "\\" => match self.mode {
+                Mode::Matrix => out.push(";"),
+                Mode::Cases => out.push(","),
+                Mode::Math => {
+                    out.push("\\");
+                    out.space();
+                }
+            },
+            "," => out.push("thin"),
+            ";" | ":" | ">" => out.push("med"),
+            " " => out.push("space"),
+            "|" => out.push("||"),
+            "{" | "}" | "%" | "&" | "#" | "$" | "_" => out.push(&format!("\\{name}")),
+            _ => {
+                if let Some(function) = font_function(name) {
+                    let start = self.pos;
+                    let text = self.raw_argument();
+                    // Several letters are a word such as an operator name.
+                    if text.chars().count() > 1 && text.chars().all(char::is_alphabetic) {
+                        out.push(&format!("{function}({})", quote(&text)));
+                    } else {
+                        self.pos = start;
+                        let argument = self.argument();
+                        out.push(&format!("{function}({argument})"));
+                    }
+                } else if let Some(function) = accent_function(name) {
+                    let argument = self.argument();
+                    out.push(&format!("{function}({argument})"));
+                } else if let Some(code) = symbols().get(name) {
+                    out.push(code);
+                } else {
+                    let command = format!("\\{name}");
+                    out.push(&quote(&command));
+                    self.flag(command);
+                }
+            }
+        }
+    }
+
+    /// Translate an environment whose `\begin` was just read, up to and
+    /// including its `\end`.
+    fn environment(&mut self, out: &mut Output) {
+        let env = self.raw_argument();
+        let env = env.trim();
+        let (mode, call) = if let Some(call) = matrix_call(env) {
+            if env == "array" {
+                // The column specification has no equivalent.
+                self.raw_argument();
+            }
+            (Mode::Matrix, Some(call))
+        } else if env == "cases" {
+            (Mode::Cases, Some("cases("))
+        } else {
+            if !TRANSPARENT_ENVIRONMENTS.contains(&env) {
+                self.flag(format!("\\begin{{{env}}}"));
+            }
+            (Mode::Math, None)
+        };
+        if env.starts_with("alignat") || env == "alignedat" {
+            // The number of columns has no equivalent.
+            self.raw_argument();
+        }
+        let outer = std::mem::replace(&mut self.mode, mode);
+        let mut inner = Output::default();
+        self.sequence(&mut inner);
+        self.mode = outer;
+        if self.peek() == Some(Token::Command("end")) {
+            self.pos += 1;
+            self.raw_argument();
+        }
+        let inner = inner.finish();
+        match call {
+            Some(call) => {
+                let inner = inner.trim_end_matches([';', ',', ' ']);
+                out.push(&format!("{call}{inner})"));
+            }
+            None => {
+                out.space();
+                out.push(&inner);
+                out.space();
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use equation_processor::lint::lint_markdown_with;
+use equation_processor::parser::{
+    ambiguous_tags, parse_markdown, parse_markdown_with, AmbiguousTagPolicy, ParseOptions,
+};
+
+const NOTES: &str = "$$\nE = mc^2\n$$\n%%energy%%\n\n$$\nF = ma\n$$\n%%TODO%%\n\n```math {name=\"force\"}\nF = ma\n```\n%%FIXME%%\n";
+
+fn with_policy(policy: AmbiguousTagPolicy) -> ParseOptions {
+    ParseOptions {
+        ambiguous_tags: policy,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_marker_tags_are_not_names_by_default() {
+    let eqs = parse_markdown(NOTES);
+    let names: Vec<&str> = eqs.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["energy", "default_equation", "force"]);
+    assert!(eqs.iter().all(|e| e.active));
+}
+
+#[test]
+fn test_policies() {
+    let named = parse_markdown_with(NOTES, &with_policy(AmbiguousTagPolicy::Name));
+    assert_eq!(named[1].name, "TODO");
+
+    let skipped = parse_markdown_with(NOTES, &with_policy(AmbiguousTagPolicy::Skip));
+    assert_eq!(skipped[1].name, "default_equation");
+    assert!(!skipped[1].active);
+    // A fence attribute names the block, so the marker is not in question.
+    assert!(skipped[2].active);
+
+    let asked = parse_markdown_with(NOTES, &with_policy(AmbiguousTagPolicy::Ask));
+    assert_eq!(asked[1].name, "default_equation");
+}
+
+#[test]
+fn test_ambiguous_tags_are_listed_with_lines() {
+    assert_eq!(ambiguous_tags(NOTES), [(6, "TODO".to_string())]);
+    assert_eq!(ambiguous_tags("$$x$$\n%%Yes%%\n$$y$$\n%%???%%\n").len(), 2);
+    assert!(ambiguous_tags("%%yes%%\n$$x$$\n%%energy%%\n").is_empty());
+}
+
+#[test]
+fn test_lint_reports_ambiguous_tags() {
+    let diagnostics = lint_markdown_with(NOTES, &ParseOptions::default());
+    let ambiguous: Vec<_> = diagnostics
+        .iter()
+        .filter(|d| d.code == "ambiguous-name")
+        .collect();
+    assert_eq!(ambiguous.len(), 1);
+    assert_eq!(ambiguous[0].line, 6);
+    assert!(ambiguous[0].message.contains("ignored"));
+    assert!(diagnostics.iter().any(|d| d.code == "missing-name"));
+
+    let named = lint_markdown_with(NOTES, &with_policy(AmbiguousTagPolicy::Name));
+    assert!(named.iter().any(|d| d.message.contains("used as the name")));
+    assert!(!named.iter().any(|d| d.code == "missing-name"));
+}
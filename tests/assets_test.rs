@@ -0,0 +1,25 @@
+use equation_processor::parser::{parse_csv, parse_markdown, resolve_assets};
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_markdown_assets_tag_is_not_a_name() {
+    let md = "$$\n\\input{fig.tikz}\n$$\n%%figure%%\n%%assets:fig.tikz; img/logo.png%%\n";
+    let equations = parse_markdown(md);
+    assert_eq!(equations.len(), 1);
+    assert_eq!(equations[0].name, "figure");
+    assert_eq!(
+        equations[0].assets,
+        [PathBuf::from("fig.tikz"), PathBuf::from("img/logo.png")]
+    );
+}
+
+#[test]
+fn test_csv_assets_column_and_resolution() {
+    let csv = "active,equation,name,assets\nyes,\\includegraphics{a.png},pic,a.png;/abs/b.png\n";
+    let mut equations = parse_csv(csv);
+    resolve_assets(&mut equations, Path::new("notes"));
+    assert_eq!(
+        equations[0].assets,
+        [PathBuf::from("notes/a.png"), PathBuf::from("/abs/b.png")]
+    );
+}
@@ -0,0 +1,44 @@
+use equation_processor::report::{write_beamer, BEAMER_FILE};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use equation_processor::EquationKind;
+use std::fs;
+
+#[test]
+fn test_one_frame_per_active_equation() {
+    let out = TempOutput::new("beamer_frames");
+    let equations = vec![
+        EquationBuilder::new("energy_eq")
+            .body("E = mc^2")
+            .section("Relativity & Mass")
+            .build(),
+        EquationBuilder::new("hidden").inactive().build(),
+        EquationBuilder::new("water")
+            .body("H2O")
+            .kind(EquationKind::Chemistry)
+            .build(),
+    ];
+    let path = write_beamer(&equations, out.path()).unwrap();
+    assert_eq!(path, out.join(BEAMER_FILE));
+    let deck = fs::read_to_string(path).unwrap();
+    assert!(deck.contains("\\documentclass{beamer}"));
+    assert!(deck.contains("\\usepackage[version=4]{mhchem}"));
+    assert_eq!(deck.matches("\\begin{frame}").count(), 2);
+    assert!(deck.contains("\\begin{frame}{energy\\_eq}"));
+    assert!(deck.contains("\\framesubtitle{Relativity \\& Mass}"));
+    assert!(deck.contains("E = mc^2"));
+    assert!(!deck.contains("hidden"));
+    assert!(deck.trim_end().ends_with("\\end{document}"));
+}
+
+#[test]
+fn test_tikz_libraries_are_collected() {
+    let out = TempOutput::new("beamer_tikz");
+    let mut drawing = EquationBuilder::new("arrow")
+        .body("\\draw[->] (0,0) -- (1,0);")
+        .kind(EquationKind::TikZ)
+        .build();
+    drawing.tikz_libraries = vec!["calc".into(), "arrows.meta".into()];
+    let deck = fs::read_to_string(write_beamer(&[drawing], out.path()).unwrap()).unwrap();
+    assert!(deck.contains("\\usepackage{tikz}"));
+    assert!(deck.contains("\\usetikzlibrary{arrows.meta,calc}"));
+}
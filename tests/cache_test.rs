@@ -0,0 +1,101 @@
+use equation_processor::cache::{cache_key, RenderCache, RENDER_CACHE_FILE, RENDER_CACHE_VERSION};
+use equation_processor::render::{render_equations_with, RenderOptions, TectonicConfig};
+use equation_processor::report::content_address;
+use equation_processor::test_util::{EquationBuilder, MockRenderer, TempOutput};
+use equation_processor::Equation;
+use std::fs;
+
+#[test]
+fn test_only_changed_equations_are_rendered_again() {
+    let out = TempOutput::new("cache_changed");
+    let mut equations = vec![
+        EquationBuilder::new("energy").body("E = mc^2").build(),
+        EquationBuilder::new("force").body("F = ma").build(),
+    ];
+    let first = MockRenderer::new();
+    render_equations_with(&equations, out.path(), &first, false, None).unwrap();
+    assert_eq!(first.calls(), ["energy", "force"]);
+    assert!(out.join(RENDER_CACHE_FILE).exists());
+
+    let unchanged = MockRenderer::new();
+    render_equations_with(&equations, out.path(), &unchanged, false, None).unwrap();
+    assert!(unchanged.calls().is_empty());
+
+    equations[1].body = "F = m a".into();
+    equations[0].color = Some("#FF0000".into());
+    let changed = MockRenderer::new();
+    render_equations_with(&equations, out.path(), &changed, false, None).unwrap();
+    assert_eq!(changed.calls(), ["energy", "force"]);
+}
+
+#[test]
+fn test_missing_artifact_is_rendered_again() {
+    let out = TempOutput::new("cache_missing");
+    let equations = vec![EquationBuilder::new("energy").build()];
+    render_equations_with(&equations, out.path(), &MockRenderer::new(), false, None).unwrap();
+    fs::remove_file(out.join("energy.svg")).unwrap();
+
+    let renderer = MockRenderer::new();
+    render_equations_with(&equations, out.path(), &renderer, false, None).unwrap();
+    assert_eq!(renderer.calls(), ["energy"]);
+}
+
+#[test]
+fn test_content_addressed_artifacts_are_served_from_the_cache() {
+    let out = TempOutput::new("cache_content_addressed");
+    let equations = vec![EquationBuilder::new("energy").build()];
+    render_equations_with(&equations, out.path(), &MockRenderer::new(), false, None).unwrap();
+    let first = content_address(&equations, out.path()).unwrap();
+    assert!(!out.join("energy.svg").exists());
+
+    let renderer = MockRenderer::new();
+    render_equations_with(&equations, out.path(), &renderer, false, None).unwrap();
+    assert!(renderer.calls().is_empty());
+    let second = content_address(&equations, out.path()).unwrap();
+    assert_eq!(second.equations, first.equations);
+}
+
+#[test]
+fn test_failed_batch_keeps_cache_of_finished_equations() {
+    let out = TempOutput::new("cache_failure");
+    let equations = vec![
+        EquationBuilder::new("ok").build(),
+        EquationBuilder::new("broken").body("y").build(),
+    ];
+    let failing = MockRenderer::new().fail_on("broken");
+    assert!(render_equations_with(&equations, out.path(), &failing, false, None).is_err());
+
+    let cache = RenderCache::read(out.path());
+    assert_eq!(cache.version, RENDER_CACHE_VERSION);
+    assert_eq!(cache.equations.keys().collect::<Vec<_>>(), ["ok"]);
+    assert_eq!(cache.equations["ok"].files, ["ok.pdf", "ok.svg"]);
+
+    let newer = fs::read_to_string(out.join(RENDER_CACHE_FILE))
+        .unwrap()
+        .replace(r#""version": 1"#, r#""version": 99"#);
+    fs::write(out.join(RENDER_CACHE_FILE), newer).unwrap();
+    assert!(RenderCache::read(out.path()).equations.is_empty());
+}
+
+#[test]
+fn test_cache_key_depends_on_the_build_date() {
+    let eq = Equation::new(true, "energy", "E = mc^2");
+    let options = RenderOptions::default();
+    let dated = |epoch| TectonicConfig {
+        source_date_epoch: epoch,
+        ..TectonicConfig::default()
+    };
+    let key = |epoch| cache_key(&eq, "#000000", &dated(epoch), &options);
+    assert_eq!(key(Some(0)), key(Some(0)));
+    assert_ne!(key(Some(0)), key(None));
+    assert_ne!(key(Some(0)), key(Some(1)));
+}
+
+#[test]
+fn test_cache_key_depends_on_the_description() {
+    let mut eq = Equation::new(true, "energy", "E = mc^2");
+    let (tectonic, options) = (TectonicConfig::default(), RenderOptions::default());
+    let plain = cache_key(&eq, "#000000", &tectonic, &options);
+    eq.description = Some("Mass-energy equivalence".into());
+    assert_ne!(plain, cache_key(&eq, "#000000", &tectonic, &options));
+}
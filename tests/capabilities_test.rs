@@ -0,0 +1,113 @@
+use equation_processor::model::EquationKind;
+use equation_processor::render::{
+    Capabilities, ImageFormat, OutputFormat, PdfOptions, RenderOptions, Renderer,
+};
+use equation_processor::test_util::{EquationBuilder, MockRenderer};
+
+#[test]
+fn test_full_backend_accepts_everything() {
+    let equations = vec![EquationBuilder::new("diagram")
+        .kind(EquationKind::TikZ)
+        .build()];
+    let options = RenderOptions {
+        formats: OutputFormat::ALL.to_vec(),
+        beamer: true,
+        pdf: PdfOptions {
+            compression: Some(9),
+            minor_version: Some(5),
+            full_fonts: false,
+        },
+        ..RenderOptions::default()
+    };
+    assert!(Capabilities::all("full")
+        .unsupported(&equations, &options)
+        .is_empty());
+}
+
+#[test]
+fn test_missing_converter_rejects_image_formats_only() {
+    let mut caps = Capabilities::all("tectonic");
+    caps.formats = vec![OutputFormat::Pdf];
+    caps.missing_tools = vec!["pdftocairo"];
+    let options = RenderOptions {
+        formats: vec![OutputFormat::Pdf, OutputFormat::Image(ImageFormat::Png)],
+        beamer: true,
+        ..RenderOptions::default()
+    };
+    assert_eq!(
+        caps.unsupported(&[], &options),
+        ["the tectonic backend cannot write png without pdftocairo; \
+          install it or choose other --formats"]
+    );
+}
+
+#[test]
+fn test_unsupported_kinds_name_active_equations() {
+    let mut caps = Capabilities::all("limited");
+    caps.kinds = vec![EquationKind::Math];
+    caps.pdf_options = false;
+    let equations = vec![
+        EquationBuilder::new("water")
+            .kind(EquationKind::Chemistry)
+            .build(),
+        EquationBuilder::new("skipped")
+            .kind(EquationKind::TikZ)
+            .inactive()
+            .build(),
+        EquationBuilder::new("energy").build(),
+    ];
+    let options = RenderOptions {
+        pdf: PdfOptions {
+            compression: Some(0),
+            minor_version: None,
+            full_fonts: false,
+        },
+        ..RenderOptions::default()
+    };
+    let problems = caps.unsupported(&equations, &options);
+    assert_eq!(problems.len(), 2);
+    assert!(problems[0].contains("PDF compression"));
+    assert!(problems[1].starts_with("equation 'water' is chem"));
+}
+
+#[test]
+fn test_mock_renderer_reports_configured_capabilities() {
+    assert_eq!(MockRenderer::new().capabilities().backend, "mock");
+    let mut caps = Capabilities::all("pdf-only");
+    caps.formats = vec![OutputFormat::Pdf];
+    let renderer = MockRenderer::new().with_capabilities(caps.clone());
+    assert_eq!(renderer.capabilities(), caps);
+    let problems = renderer
+        .capabilities()
+        .unsupported(&[], &RenderOptions::default());
+    assert_eq!(
+        problems,
+        ["the pdf-only backend cannot write svg; choose other --formats"]
+    );
+}
+
+#[test]
+fn test_font_change_needs_custom_fonts() {
+    let mut caps = Capabilities::all("fixed");
+    caps.custom_fonts = false;
+    assert!(caps.unsupported(&[], &RenderOptions::default()).is_empty());
+    let options = RenderOptions::builder().font(None).build();
+    assert_eq!(
+        caps.unsupported(&[], &options),
+        ["the fixed backend cannot change the font; drop --font and --no-font-package"]
+    );
+}
+
+#[test]
+fn test_transparent_images_need_transparent_background() {
+    let mut caps = Capabilities::all("opaque");
+    caps.transparent_background = false;
+    assert_eq!(
+        caps.unsupported(&[], &RenderOptions::default()),
+        ["the opaque backend cannot write transparent images; choose a --background color"]
+    );
+    let options = RenderOptions::builder()
+        .background("#FFFFFF".parse().unwrap())
+        .build();
+    assert!(caps.unsupported(&[], &options).is_empty());
+}
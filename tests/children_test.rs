@@ -0,0 +1,56 @@
+//! The tests run `sh` and `sleep`.
+#![cfg(unix)]
+
+use equation_processor::children::{self, KillOnDrop};
+use std::io::ErrorKind;
+use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The registry is shared by the tests of this file, so they take turns.
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// Wait until `count` children are registered, for at most a few seconds.
+fn wait_for_running(count: usize) {
+    let started = Instant::now();
+    while children::running() < count {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "child never started"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_output_collects_streams_and_unregisters() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let output =
+        children::output(Command::new("sh").args(["-c", "echo out; echo err >&2"])).unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"out\n");
+    assert_eq!(output.stderr, b"err\n");
+
+    let err = children::output_timeout(
+        Command::new("sleep").arg("5"),
+        Some(Duration::from_millis(50)),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert_eq!(children::running(), 0);
+}
+
+#[test]
+fn test_kill_on_drop_stops_running_children() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let started = Instant::now();
+    let waiter = thread::spawn(|| children::output(Command::new("sleep").arg("30")));
+    wait_for_running(1);
+    drop(KillOnDrop);
+
+    let err = waiter.join().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Interrupted);
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(children::running(), 0);
+}
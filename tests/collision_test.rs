@@ -0,0 +1,97 @@
+use equation_processor::render::{disambiguate_names, existing_outputs};
+use equation_processor::Equation;
+use std::collections::HashSet;
+
+#[test]
+fn test_colliding_names_get_hash_suffix() {
+    // "a b" and "a_b" sanitize to the same file name.
+    let mut eqs = vec![
+        Equation::new(true, "a b", "x"),
+        Equation::new(true, "a_b", "y"),
+        Equation::new(true, "A_B", "z"),
+        Equation::new(false, "a_b", "w"),
+    ];
+    let warnings = disambiguate_names(&mut eqs);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(eqs[0].name, "a_b");
+    assert!(eqs[1].name.starts_with("a_b_"));
+    assert!(eqs[2].name.starts_with("A_B_"));
+    assert_eq!(eqs[3].name, "a_b");
+
+    let names: HashSet<String> = eqs[..3].iter().map(|e| e.name.to_lowercase()).collect();
+    assert_eq!(names.len(), 3);
+}
+
+#[test]
+fn test_disambiguation_is_stable_and_handles_identical_bodies() {
+    let make = || {
+        vec![
+            Equation::new(true, "eq", "x"),
+            Equation::new(true, "eq", "x"),
+            Equation::new(true, "eq", "x"),
+        ]
+    };
+    let mut first = make();
+    let mut second = make();
+    disambiguate_names(&mut first);
+    disambiguate_names(&mut second);
+    let names: Vec<_> = first.iter().map(|e| e.name.clone()).collect();
+    assert_eq!(
+        names,
+        second.iter().map(|e| e.name.clone()).collect::<Vec<_>>()
+    );
+    assert_eq!(names.iter().collect::<HashSet<_>>().len(), 3);
+}
+
+#[test]
+fn test_unique_names_are_untouched() {
+    let mut eqs = vec![Equation::new(true, "a", "x"), Equation::new(true, "b", "x")];
+    assert!(disambiguate_names(&mut eqs).is_empty());
+    assert_eq!(eqs[0].name, "a");
+    assert_eq!(eqs[1].name, "b");
+}
+
+#[test]
+fn test_name_affixes_are_sanitized() {
+    let mut eq = Equation::new(true, "energy", "E = mc^2");
+    eq.add_name_affixes("lecture 3-", "_v2");
+    assert_eq!(eq.name, "lecture_3_energy_v2");
+    eq.add_name_affixes("", "");
+    assert_eq!(eq.name, "lecture_3_energy_v2");
+}
+
+#[test]
+fn test_existing_outputs_lists_active_artifacts() {
+    let dir = std::env::temp_dir().join(format!("eqp_existing_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("kept.svg"), "").unwrap();
+    std::fs::write(dir.join("off.svg"), "").unwrap();
+    let eqs = vec![
+        Equation::new(true, "kept", "x"),
+        Equation::new(true, "new", "y"),
+        Equation::new(false, "off", "z"),
+    ];
+    assert_eq!(existing_outputs(&eqs, &dir), vec![dir.join("kept.svg")]);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_parallel_render_refuses_colliding_names() {
+    use equation_processor::render::render_equations_parallel;
+    use equation_processor::test_util::{MockRenderer, TempOutput};
+
+    let out = TempOutput::new("parallel_collision");
+    let mut eqs = vec![
+        Equation::new(true, "a b", "x"),
+        Equation::new(true, "A_B", "y"),
+    ];
+    let renderer = MockRenderer::new();
+    let err = render_equations_parallel(&eqs, out.path(), &renderer, false, None, 2).unwrap_err();
+    assert!(err.to_string().contains("'A_B'"), "{err}");
+    assert!(renderer.calls().is_empty());
+
+    disambiguate_names(&mut eqs);
+    render_equations_parallel(&eqs, out.path(), &renderer, false, None, 2).unwrap();
+    assert_eq!(renderer.calls().len(), 2);
+}
@@ -0,0 +1,33 @@
+use equation_processor::parser::parse_markdown;
+use equation_processor::render::{apply_color_map, parse_color_map};
+
+#[test]
+fn test_parse_color_map() {
+    let map = parse_color_map("// key results\nenergy = #FF0000\n\n mass=#00FF00 \n").unwrap();
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["energy"], "#FF0000");
+    assert_eq!(map["mass"], "#00FF00");
+    assert!(parse_color_map("energy #FF0000")
+        .unwrap_err()
+        .contains("line 1"));
+}
+
+#[test]
+fn test_color_precedence_map_then_tag_then_default() {
+    let md = "$$\na\n$$\n%%energy%%\n%%color:#0000FF%%\n\n$$\nb\n$$\n%%mass%%\n%%color:#0000FF%%\n\n$$\nc\n$$\n%%plain%%\n";
+    let mut equations = parse_markdown(md);
+    let map = parse_color_map("energy = #FF0000\nmissing = #00FF00\n").unwrap();
+    let warnings = apply_color_map(&mut equations, &map);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("missing"));
+
+    assert!(equations[0]
+        .generate_latex("#000000")
+        .contains("{HTML}{FF0000}"));
+    assert!(equations[1]
+        .generate_latex("#000000")
+        .contains("{HTML}{0000FF}"));
+    assert!(equations[2]
+        .generate_latex("#000000")
+        .contains("{HTML}{000000}"));
+}
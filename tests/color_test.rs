@@ -0,0 +1,44 @@
+use equation_processor::cli::validate_colors;
+use equation_processor::parser::parse_markdown;
+use equation_processor::render::parse_color_map;
+use equation_processor::Color;
+
+#[test]
+fn test_color_parses_with_or_without_hash() {
+    let color: Color = "#1a2B3c".parse().unwrap();
+    assert_eq!(
+        color,
+        Color {
+            r: 0x1A,
+            g: 0x2B,
+            b: 0x3C
+        }
+    );
+    assert_eq!(color.to_string(), "#1A2B3C");
+    assert_eq!("1A2B3C".parse::<Color>().unwrap(), color);
+}
+
+#[test]
+fn test_invalid_colors_are_rejected() {
+    for bad in ["#GGHHII", "#FFF", "#1A2B3C4D", "", "#+F+F+F", "red"] {
+        let err = bad.parse::<Color>().unwrap_err();
+        assert!(err.contains("six hex digits"), "{bad}: {err}");
+    }
+}
+
+#[test]
+fn test_color_map_rejects_bad_values() {
+    let err = parse_color_map("energy = #FF0000\nmass = #GGHHII\n").unwrap_err();
+    assert!(err.contains("line 2"), "{err}");
+}
+
+#[test]
+fn test_validate_colors_names_equation_and_normalizes() {
+    let mut equations = parse_markdown("$$\na\n$$\n%%ok%%\n%%color:ff0000%%\n");
+    validate_colors(&mut equations).unwrap();
+    assert_eq!(equations[0].color.as_deref(), Some("#FF0000"));
+
+    let mut equations = parse_markdown("$$\na\n$$\n%%broken%%\n%%color:#GGHHII%%\n");
+    let err = validate_colors(&mut equations).unwrap_err();
+    assert!(err.contains("broken"), "{err}");
+}
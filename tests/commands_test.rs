@@ -0,0 +1,93 @@
+use equation_processor::render::{ImageFormat, OutputFormat, RenderOptions, TectonicConfig};
+use equation_processor::test_util::EquationBuilder;
+use std::path::Path;
+
+#[test]
+fn test_planned_commands_follow_settings() {
+    let eq = EquationBuilder::new("energy").build();
+    let tectonic = TectonicConfig {
+        cache_dir: Some("/var/cache/tex".into()),
+        bundle: None,
+        offline: true,
+        env: vec![("SOURCE_DATE_EPOCH".into(), "0".into())],
+        source_date_epoch: None,
+    };
+    let options = RenderOptions {
+        formats: vec![
+            OutputFormat::Image(ImageFormat::Svg),
+            OutputFormat::Image(ImageFormat::Png),
+        ],
+        ..RenderOptions::default()
+    };
+    let commands = eq.planned_commands(Path::new("out"), &tectonic, &options);
+    assert_eq!(commands.len(), 3);
+    assert!(commands[0].contains("TECTONIC_CACHE_DIR=/var/cache/tex"));
+    assert!(commands[0].contains("SOURCE_DATE_EPOCH=0"));
+    assert!(commands[0].ends_with("tectonic --only-cached out/energy.tex --outdir out"));
+    assert!(commands[1].ends_with("pdftocairo -svg out/energy.pdf out/energy.svg"));
+    assert!(commands[2]
+        .ends_with("pdftocairo -png -singlefile -transp -r 300 out/energy.pdf out/energy"));
+}
+
+#[test]
+fn test_planned_commands_pdf_only_and_quoting() {
+    let eq = EquationBuilder::new("energy").build();
+    let options = RenderOptions {
+        formats: vec![OutputFormat::Pdf],
+        ..RenderOptions::default()
+    };
+    let commands =
+        eq.planned_commands(Path::new("my output"), &TectonicConfig::default(), &options);
+    assert_eq!(
+        commands,
+        ["tectonic 'my output/energy.tex' --outdir 'my output'"]
+    );
+}
+
+#[test]
+fn test_planned_commands_with_assets_run_in_work_dir() {
+    let eq = EquationBuilder::new("figure").asset("plot.tikz").build();
+    let commands = eq.planned_commands(
+        Path::new("out"),
+        &TectonicConfig::default(),
+        &RenderOptions::default(),
+    );
+    assert!(commands[0].starts_with("cd "));
+    assert!(commands[0].contains("eqp_assets_"));
+
+    let again = eq.planned_commands(
+        Path::new("out"),
+        &TectonicConfig::default(),
+        &RenderOptions::default(),
+    );
+    assert_ne!(again[0], commands[0], "each render gets its own work dir");
+}
+
+#[test]
+fn test_reproducible_builds_pin_the_date() {
+    let tectonic = TectonicConfig {
+        source_date_epoch: Some(1_700_000_000),
+        ..TectonicConfig::default()
+    };
+    let options = RenderOptions {
+        formats: vec![OutputFormat::Pdf],
+        ..RenderOptions::default()
+    };
+    let eq = EquationBuilder::new("energy").build();
+    let commands = eq.planned_commands(Path::new("out"), &tectonic, &options);
+    assert_eq!(
+        commands,
+        ["FORCE_SOURCE_DATE=1 SOURCE_DATE_EPOCH=1700000000 \
+          tectonic -Z deterministic-mode out/energy.tex --outdir out"]
+    );
+
+    // The equation's own date is set after the batch's, so it wins.
+    let mut dated = EquationBuilder::new("dated").build();
+    dated.env = vec![("SOURCE_DATE_EPOCH".into(), "86400".into())];
+    let commands = dated.planned_commands(Path::new("out"), &tectonic, &options);
+    assert!(
+        commands[0].contains("SOURCE_DATE_EPOCH=86400"),
+        "{}",
+        commands[0]
+    );
+}
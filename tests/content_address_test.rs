@@ -0,0 +1,43 @@
+use equation_processor::report::{
+    content_address, write_index, ContentManifest, CONTENT_MANIFEST, CONTENT_MANIFEST_VERSION,
+};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+#[test]
+fn test_artifacts_are_renamed_by_hash_and_deduplicated() {
+    let out = TempOutput::new("content_address");
+    fs::write(out.join("a.svg"), "<svg>same</svg>").unwrap();
+    fs::write(out.join("b.svg"), "<svg>same</svg>").unwrap();
+    fs::write(out.join("c.svg"), "<svg>other</svg>").unwrap();
+    let equations = vec![
+        EquationBuilder::new("a").build(),
+        EquationBuilder::new("b").build(),
+        EquationBuilder::new("c").build(),
+    ];
+
+    let manifest = content_address(&equations, out.path()).unwrap();
+    let a = manifest.artifact("a", "svg").unwrap();
+    assert_eq!(a.len(), 32 + ".svg".len());
+    assert_eq!(manifest.artifact("b", "svg"), Some(a));
+    assert_ne!(manifest.artifact("c", "svg"), Some(a));
+    assert!(!out.join("a.svg").exists());
+    assert!(out.join(a).exists());
+
+    let reread = ContentManifest::read(out.path()).unwrap();
+    assert_eq!(reread.artifact("c", "svg"), manifest.artifact("c", "svg"));
+    assert_eq!(reread.version, CONTENT_MANIFEST_VERSION);
+
+    let index: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(write_index(&equations, out.path()).unwrap()).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(index["equations"][1]["svg"], a);
+    assert_eq!(index["equations"][1]["status"], "rendered");
+
+    let newer = fs::read_to_string(out.join(CONTENT_MANIFEST))
+        .unwrap()
+        .replace(r#""version": 1"#, r#""version": 99"#);
+    fs::write(out.join(CONTENT_MANIFEST), newer).unwrap();
+    assert!(ContentManifest::read(out.path()).is_none());
+}
@@ -0,0 +1,22 @@
+use equation_processor::render::{convert_directory, ImageFormat};
+use equation_processor::test_util::TempOutput;
+use std::fs;
+
+#[test]
+fn test_image_format_parsing() {
+    assert_eq!("SVG".parse::<ImageFormat>(), Ok(ImageFormat::Svg));
+    assert_eq!("png".parse::<ImageFormat>(), Ok(ImageFormat::Png));
+    assert!("gif".parse::<ImageFormat>().is_err());
+    assert_eq!(ImageFormat::Png.extension(), "png");
+}
+
+#[test]
+fn test_convert_directory_ignores_non_pdfs() {
+    let dir = TempOutput::new("convert_empty");
+    fs::write(dir.join("notes.txt"), "not a pdf").unwrap();
+    let out = dir.join("out");
+    let written =
+        convert_directory(dir.path(), &out, &[ImageFormat::Svg, ImageFormat::Png], 300).unwrap();
+    assert!(written.is_empty());
+    assert!(out.is_dir());
+}
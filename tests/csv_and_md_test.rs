@@ -1,4 +1,4 @@
-use equation_processor::*;
+use equation_processor::parser::*;
 use std::fs;
 use std::path::PathBuf;
 use std::io::Write;
@@ -0,0 +1,56 @@
+use equation_processor::parser::{parse_csv, parse_csv_with, CsvColumns, ParseOptions};
+use equation_processor::tagging::{edit_csv_tags, TagEdit};
+
+#[test]
+fn test_csv_columns_are_taken_from_the_header() {
+    let csv = "Label,Description,LaTeX,Enabled,Notes\n\
+               energy,mass-energy,E = mc^2,yes,checked\n\
+               draft,,x,no,\n";
+    let equations = parse_csv(csv);
+    assert_eq!(equations.len(), 2);
+    assert_eq!(equations[0].name, "energy");
+    assert_eq!(equations[0].body, "E = mc^2");
+    assert_eq!(equations[0].description.as_deref(), Some("mass-energy"));
+    assert!(equations[0].active);
+    assert!(!equations[1].active);
+
+    // A header without equation and name columns keeps the default order.
+    let equations = parse_csv("a,b,c\nyes,x^2,square\n");
+    assert_eq!(equations[0].name, "square");
+}
+
+#[test]
+fn test_csv_columns_option_overrides_the_header() {
+    let options = ParseOptions {
+        csv_columns: Some("name,_,equation".parse().unwrap()),
+        ..Default::default()
+    };
+    let equations = parse_csv_with("x,y,z\nsquare,ignored,x^2\nshort,x\n", &options);
+    assert_eq!(equations.len(), 1);
+    assert_eq!(
+        (equations[0].name.as_str(), equations[0].body.as_str()),
+        ("square", "x^2")
+    );
+    assert!(equations[0].active);
+
+    for invalid in [
+        "name,colour,equation",
+        "name,equation,label",
+        "active,equation",
+    ] {
+        assert!(invalid.parse::<CsvColumns>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn test_tag_edits_follow_the_header_columns() {
+    let csv = "name,equation,tags\nenergy,E = mc^2,physics\n";
+    let (edited, changed) = edit_csv_tags(
+        csv,
+        &TagEdit::Add("exam".into()),
+        None,
+        &ParseOptions::default(),
+    );
+    assert_eq!(changed, 1);
+    assert_eq!(edited, "name,equation,tags\nenergy,E = mc^2,physics;exam\n");
+}
@@ -0,0 +1,29 @@
+use equation_processor::parser::{parse_markdown, read_csv_file, write_csv_file};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+#[test]
+fn test_markdown_equations_round_trip_through_csv() {
+    let out = TempOutput::new("csv_export");
+    let mut equations = parse_markdown(
+        "$$\nf(x) = \"a\", b\n$$\n%%quoted%%\n%%tags:exam,ch3%%\n%%desc:A; tricky one%%\n\n\
+         $$\n\\begin{aligned}\nx &= 1 \\\\ % first\ny &= 50\\%\n\\end{aligned}\n$$\n%%system%%\n",
+    );
+    equations.push(EquationBuilder::new("draft").inactive().build());
+    let path = out.join("equations.csv");
+    write_csv_file(&equations, &path).unwrap();
+
+    let read = read_csv_file(&path).unwrap();
+    assert_eq!(read.len(), 3);
+    assert_eq!(read[0].name, "quoted");
+    assert_eq!(read[0].body, "f(x) = \"a\", b");
+    assert_eq!(read[0].tags, ["exam", "ch3"]);
+    assert_eq!(read[0].description.as_deref(), Some("A; tricky one"));
+    assert_eq!(
+        read[1].body,
+        "\\begin{aligned} x &= 1 \\\\ y &= 50\\% \\end{aligned}"
+    );
+    assert!(read[1].active);
+    assert!(!read[2].active);
+    assert!(!fs::read_to_string(&path).unwrap().contains("first"));
+}
@@ -0,0 +1,37 @@
+use equation_processor::parser::{detect_csv_delimiter, parse_csv, parse_csv_with, ParseOptions};
+
+#[test]
+fn test_semicolon_export_with_bom_and_crlf() {
+    let csv = "\u{feff}active;equation;name\r\nYes;x = 3,14;pi_approx\r\nNo;\"a;b\";pair\r\n";
+    let equations = parse_csv(csv);
+    assert_eq!(equations.len(), 2);
+    assert!(equations[0].active);
+    assert_eq!(equations[0].body, "x = 3,14");
+    assert_eq!(equations[0].name, "pi_approx");
+    assert_eq!(equations[1].body, "a;b");
+    assert!(!equations[1].active);
+}
+
+#[test]
+fn test_cr_only_line_endings_and_quotes() {
+    let csv = "active,equation,name\rYes,\"\\frac{a}{b}, \"\"q\"\"\",frac\r";
+    let equations = parse_csv(csv);
+    assert_eq!(equations.len(), 1);
+    assert_eq!(equations[0].body, "\\frac{a}{b}, \"q\"");
+}
+
+#[test]
+fn test_delimiter_detection_and_override() {
+    assert_eq!(detect_csv_delimiter("active,equation,name"), ',');
+    assert_eq!(detect_csv_delimiter("active;equation;name"), ';');
+    assert_eq!(detect_csv_delimiter("active\tequation\tname"), '\t');
+    assert_eq!(detect_csv_delimiter("active"), ',');
+
+    let csv = "active|equation|name\nyes|x|piped\n";
+    assert!(parse_csv(csv).is_empty());
+    let options = ParseOptions {
+        csv_delimiter: Some('|'),
+        ..Default::default()
+    };
+    assert_eq!(parse_csv_with(csv, &options)[0].name, "piped");
+}
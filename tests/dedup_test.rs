@@ -0,0 +1,54 @@
+use equation_processor::render::{DuplicatePlan, LinkStrategy};
+use equation_processor::test_util::TempOutput;
+use equation_processor::Equation;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_duplicate_plan_keeps_first_as_canonical() {
+    let a = Equation::new(true, "a", "x^2");
+    let b = Equation::new(true, "b", "y^2");
+    let c = Equation::new(true, "c", " x^2 ");
+    let plan = DuplicatePlan::new(&[&a, &b, &c]);
+    let canonical: Vec<&str> = plan.canonical.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(canonical, ["a", "b"]);
+    assert_eq!(plan.aliases.len(), 1);
+    assert_eq!(plan.aliases[0].0.name, "c");
+    assert_eq!(plan.aliases[0].1.name, "a");
+}
+
+#[test]
+fn test_link_strategies_provide_canonical_contents() {
+    let dir = TempOutput::new("link_strategies");
+    let target = dir.join("canonical.svg");
+    fs::write(&target, "<svg/>").unwrap();
+    for (strategy, name) in [
+        (LinkStrategy::Copy, "copy.svg"),
+        (LinkStrategy::Hardlink, "hard.svg"),
+    ] {
+        let link = dir.join(name);
+        fs::write(&link, "stale").unwrap();
+        strategy.link(&target, &link).unwrap();
+        assert_eq!(fs::read_to_string(&link).unwrap(), "<svg/>");
+    }
+    assert_eq!(
+        "Hardlink".parse::<LinkStrategy>(),
+        Ok(LinkStrategy::Hardlink)
+    );
+    assert!("move".parse::<LinkStrategy>().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_strategy_is_relative() {
+    let dir = TempOutput::new("link_symlink");
+    let target = dir.join("canonical.svg");
+    fs::write(&target, "<svg/>").unwrap();
+    let link = dir.join("alias.svg");
+    LinkStrategy::Symlink.link(&target, &link).unwrap();
+    assert_eq!(
+        fs::read_link(&link).unwrap(),
+        PathBuf::from("canonical.svg")
+    );
+    assert_eq!(fs::read_to_string(&link).unwrap(), "<svg/>");
+}
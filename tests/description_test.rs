@@ -0,0 +1,46 @@
+use equation_processor::parser::{parse_csv, parse_markdown};
+use equation_processor::render::embed_svg_description;
+use equation_processor::report::build_index;
+use equation_processor::test_util::TempOutput;
+use std::fs;
+
+#[test]
+fn test_description_from_tag_and_csv_column() {
+    let eqs = parse_markdown(
+        "$$\n\\sin x \\approx x\n$$\n%%small_angle%%\n%%desc:valid for small angles only%%\n",
+    );
+    assert_eq!(eqs[0].name, "small_angle");
+    assert_eq!(
+        eqs[0].description.as_deref(),
+        Some("valid for small angles only")
+    );
+
+    let eqs = parse_csv(
+        "active,equation,name,assets,tags,description\nyes,x,a,,,\"in SI units, per mole\"\nyes,y,b\n",
+    );
+    assert_eq!(eqs[0].description.as_deref(), Some("in SI units, per mole"));
+    assert_eq!(eqs[1].description, None);
+
+    let out = TempOutput::new("description_index");
+    let index = build_index(&eqs, out.path());
+    assert_eq!(
+        index.equations[0].description.as_deref(),
+        Some("in SI units, per mole")
+    );
+}
+
+#[test]
+fn test_description_embedded_in_svg() {
+    let out = TempOutput::new("description_svg");
+    let path = out.join("eq.svg");
+    fs::write(
+        &path,
+        "<?xml version=\"1.0\"?>\n<svg width=\"10pt\"><g/></svg>\n",
+    )
+    .unwrap();
+    embed_svg_description(&path, "x < 1 & y").unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "<?xml version=\"1.0\"?>\n<svg width=\"10pt\">\n<desc>x &lt; 1 &amp; y</desc><g/></svg>\n"
+    );
+}
@@ -0,0 +1,62 @@
+use equation_processor::render::{
+    estimate_disk_space, format_bytes, ImageFormat, OutputFormat, RenderOptions, SpaceEstimate,
+};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+#[test]
+fn test_estimate_uses_history_of_output_dir() {
+    let out = TempOutput::new("space_history");
+    fs::write(out.join("old_a.svg"), vec![b'x'; 1000]).unwrap();
+    fs::write(out.join("old_b.svg"), vec![b'x'; 3000]).unwrap();
+    fs::write(out.join("old_a.pdf"), vec![b'x'; 500]).unwrap();
+    fs::write(out.join("old_a.tex"), vec![b'x'; 100]).unwrap();
+    let equations = vec![
+        EquationBuilder::new("a").build(),
+        EquationBuilder::new("b").build(),
+        EquationBuilder::new("off").inactive().build(),
+    ];
+    // Two SVGs at the 2000 byte average plus one equation's .tex and .pdf.
+    let space = estimate_disk_space(&equations, out.path(), true, &RenderOptions::default());
+    assert_eq!(space.required, 2 * 2000 + 600);
+    assert!(space.available.is_some());
+
+    // Keeping intermediates keeps every .tex and .pdf.
+    let space = estimate_disk_space(&equations, out.path(), false, &RenderOptions::default());
+    assert_eq!(space.required, 2 * 2600 + 600);
+}
+
+#[test]
+fn test_estimate_falls_back_to_typical_sizes() {
+    let out = TempOutput::new("space_typical");
+    let options = RenderOptions {
+        formats: vec![OutputFormat::Image(ImageFormat::Png)],
+        ..Default::default()
+    };
+    let equations = vec![EquationBuilder::new("a").build()];
+    let png_only = estimate_disk_space(&equations, out.path(), true, &options);
+    let svg_only = estimate_disk_space(&equations, out.path(), true, &RenderOptions::default());
+    assert!(png_only.required > svg_only.required);
+    assert_eq!(
+        estimate_disk_space(&[], &out.join("missing/dir"), true, &options).required,
+        0
+    );
+}
+
+#[test]
+fn test_fits_and_tight() {
+    let estimate = |required, available| SpaceEstimate {
+        required,
+        available,
+    };
+    assert!(estimate(10, Some(100)).fits());
+    assert!(!estimate(10, Some(100)).is_tight());
+    assert!(estimate(95, Some(100)).is_tight());
+    assert!(!estimate(101, Some(100)).fits());
+    assert!(estimate(u64::MAX, None).fits());
+    assert_eq!(
+        estimate(1_500_000, Some(2_000_000_000)).to_string(),
+        "about 1.5 MB needed, 2.0 GB free"
+    );
+    assert_eq!(format_bytes(999), "999 B");
+}
@@ -0,0 +1,60 @@
+use equation_processor::model::{parse_display_scale, parse_display_width};
+use equation_processor::parser::parse_markdown;
+use equation_processor::render::RenderOptions;
+use equation_processor::report::{write_gallery, write_index};
+use equation_processor::test_util::TempOutput;
+use std::fs;
+
+#[test]
+fn test_width_and_scale_tags_resize_the_rendered_equation() {
+    let equations = parse_markdown(
+        "$$E = mc^2$$\n%%energy%%\n%%width:300px%%\n\n$$F = ma$$\n%%force%%\n%%scale:0.8%%\n\n$$a$$\n%%plain%%\n",
+    );
+    assert_eq!(equations[0].display.width.as_deref(), Some("300px"));
+    assert_eq!(equations[1].display.scale, Some(0.8));
+
+    let options = RenderOptions::default();
+    let energy = equations[0].generate_latex_with("#000000", &options);
+    assert!(energy.contains(r"\usepackage{graphicx}"));
+    assert!(energy.contains(r"\resizebox{225bp}{!}{$E = mc^2$}"));
+    let force = equations[1].generate_latex_with("#000000", &options);
+    assert!(force.contains(r"\scalebox{0.8}{$F = ma$}"));
+    let plain = equations[2].generate_latex_with("#000000", &options);
+    assert!(!plain.contains("graphicx"));
+}
+
+#[test]
+fn test_display_hints_reach_index_and_gallery() {
+    let out = TempOutput::new("display_size");
+    let equations = parse_markdown(
+        "$$E = mc^2$$\n%%energy%%\n%%width:8cm%%\n%%scale:0.5%%\n\n$$a$$\n%%plain%%\n",
+    );
+    fs::write(out.join("energy.svg"), "<svg/>").unwrap();
+    fs::write(out.join("plain.svg"), "<svg/>").unwrap();
+
+    let index: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(write_index(&equations, out.path()).unwrap()).unwrap(),
+    )
+    .unwrap();
+    let entries = index["equations"].as_array().unwrap();
+    assert_eq!(entries[0]["display"]["width"], "8cm");
+    assert_eq!(entries[0]["display"]["scale"], 0.5);
+    assert!(entries[1]["display"]["width"].is_null());
+
+    let html = fs::read_to_string(write_gallery(&equations, out.path()).unwrap()).unwrap();
+    assert!(html.contains(r#"<img src="energy.svg" alt="E = mc^2" style="width: 8cm">"#));
+    assert!(html.contains(r#"<img src="plain.svg" alt="a">"#));
+}
+
+#[test]
+fn test_invalid_display_hints_are_rejected() {
+    assert_eq!(parse_display_width(" 12.50 mm "), Ok("12.5mm".into()));
+    assert!(parse_display_width("300").is_err());
+    assert!(parse_display_width("-3cm").is_err());
+    assert!(parse_display_width("3furlong").is_err());
+    assert!(parse_display_scale("0").is_err());
+    assert!(parse_display_scale("big").is_err());
+
+    let equations = parse_markdown("$$a$$\n%%plain%%\n%%scale:huge%%\n");
+    assert_eq!(equations[0].display.scale, None);
+}
@@ -0,0 +1,110 @@
+use equation_processor::parser::parse_markdown;
+use equation_processor::render::{Capabilities, PdfOptions, RenderOptions, TectonicConfig};
+use equation_processor::test_util::EquationBuilder;
+use equation_processor::LatexEngine;
+use std::path::Path;
+
+#[test]
+fn test_engine_tag_is_parsed() {
+    let eqs =
+        parse_markdown("$$\nx\n$$\n%%luacode%%\n%%engine:LuaLaTeX%%\n\n$$\ny\n$$\n%%plain%%\n");
+    assert_eq!(eqs[0].engine, Some(LatexEngine::Lualatex));
+    assert_eq!(eqs[1].engine, None);
+    assert!("context".parse::<LatexEngine>().is_err());
+}
+
+#[test]
+fn test_planned_commands_use_the_equation_engine() {
+    let mut eq = EquationBuilder::new("luacode").build();
+    eq.engine = Some(LatexEngine::Lualatex);
+    let tectonic = TectonicConfig {
+        offline: true,
+        env: vec![("TEXINPUTS".into(), "./sty//:".into())],
+        ..TectonicConfig::default()
+    };
+    let commands = eq.planned_commands(Path::new("out"), &tectonic, &RenderOptions::default());
+    assert!(commands[0].contains("TEXINPUTS=./sty//:"));
+    assert!(commands[0].ends_with(
+        "lualatex -interaction=nonstopmode -halt-on-error -output-directory=out out/luacode.tex"
+    ));
+    assert!(!commands[0].contains("--only-cached"));
+}
+
+#[test]
+fn test_missing_engine_is_reported_before_rendering() {
+    let mut caps = Capabilities::all("example");
+    caps.engines = vec![LatexEngine::Tectonic];
+    let mut eq = EquationBuilder::new("luacode").build();
+    eq.engine = Some(LatexEngine::Lualatex);
+    let inactive = EquationBuilder::new("later").inactive().build();
+    let problems = caps.unsupported(&[eq, inactive], &RenderOptions::default());
+    assert_eq!(
+        problems,
+        [
+            "equation 'luacode' asks for lualatex, which the example backend cannot run; \
+          install it or remove the equation's engine tag"
+        ]
+    );
+}
+
+#[test]
+fn test_batch_engine_applies_to_untagged_equations() {
+    let options = RenderOptions::builder()
+        .engine(LatexEngine::Pdflatex)
+        .build();
+    let plain = EquationBuilder::new("plain").build();
+    let mut tagged = EquationBuilder::new("luacode").build();
+    tagged.engine = Some(LatexEngine::Lualatex);
+    assert_eq!(plain.engine_with(&options), LatexEngine::Pdflatex);
+    assert_eq!(tagged.engine_with(&options), LatexEngine::Lualatex);
+
+    let commands = plain.planned_commands(Path::new("out"), &TectonicConfig::default(), &options);
+    assert!(commands[0].starts_with("pdflatex -interaction=nonstopmode"));
+}
+
+#[test]
+fn test_backends_reject_pdf_options_they_cannot_apply() {
+    let options = RenderOptions::builder()
+        .engine(LatexEngine::Lualatex)
+        .build();
+    let options = RenderOptions {
+        pdf: PdfOptions {
+            full_fonts: true,
+            ..PdfOptions::default()
+        },
+        ..options
+    };
+    let mut tagged = EquationBuilder::new("plain").build();
+    tagged.engine = Some(LatexEngine::Pdflatex);
+    let caps = Capabilities::all("example");
+    assert!(caps.unsupported(&[], &options).is_empty());
+    let problems = caps.unsupported(&[tagged], &options);
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].starts_with("pdflatex cannot embed complete fonts"));
+}
+
+#[test]
+fn test_pdf_options_are_applied_per_engine() {
+    let pdf = PdfOptions {
+        compression: Some(9),
+        minor_version: Some(4),
+        full_fonts: false,
+    };
+    assert_eq!(
+        LatexEngine::Tectonic.backend().pdf_setup(&pdf).unwrap(),
+        "\\special{dvipdfmx:config z 9}\\special{dvipdfmx:config V 4}"
+    );
+    assert_eq!(
+        LatexEngine::Pdflatex.backend().pdf_setup(&pdf).unwrap(),
+        "\\pdfcompresslevel=9\\relax\\pdfobjcompresslevel=0\\relax\\pdfminorversion=4\\relax"
+    );
+    let mut eq = EquationBuilder::new("luacode").build();
+    eq.engine = Some(LatexEngine::Lualatex);
+    let options = RenderOptions {
+        pdf,
+        ..RenderOptions::default()
+    };
+    let tex = eq.generate_latex_with("#000000", &options);
+    assert!(tex.contains("\\pdfvariable compresslevel=9\\relax"));
+    assert!(!tex.contains("dvipdfmx"));
+}
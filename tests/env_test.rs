@@ -0,0 +1,41 @@
+use equation_processor::model::parse_env_var;
+use equation_processor::parser::parse_markdown;
+use equation_processor::render::TectonicConfig;
+
+#[test]
+fn test_parse_env_var() {
+    assert_eq!(
+        parse_env_var("SOURCE_DATE_EPOCH=0").unwrap(),
+        ("SOURCE_DATE_EPOCH".to_string(), "0".to_string())
+    );
+    assert_eq!(parse_env_var("OPTS=a=b").unwrap().1, "a=b");
+    assert_eq!(parse_env_var("EMPTY=").unwrap().1, "");
+    assert!(parse_env_var("NOVALUE").is_err());
+    assert!(parse_env_var("=x").is_err());
+}
+
+#[test]
+fn test_env_tag_sets_equation_env() {
+    let eqs =
+        parse_markdown("$$\nx\n$$\n%%local%%\n%%env:TEXINPUTS=./sty//:;SOURCE_DATE_EPOCH=1%%\n");
+    assert_eq!(eqs.len(), 1);
+    assert_eq!(eqs[0].name, "local");
+    assert_eq!(
+        eqs[0].env,
+        [
+            ("TEXINPUTS".to_string(), "./sty//:".to_string()),
+            ("SOURCE_DATE_EPOCH".to_string(), "1".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_equation_env_comes_after_batch_env() {
+    let config = TectonicConfig {
+        env: vec![("SOURCE_DATE_EPOCH".into(), "0".into())],
+        ..Default::default()
+    };
+    let merged = config.with_env(&[("SOURCE_DATE_EPOCH".into(), "1".into())]);
+    assert_eq!(merged.env.last().unwrap().1, "1");
+    assert_eq!(config.env.len(), 1);
+}
@@ -0,0 +1,59 @@
+use equation_processor::parser::{read_input, ParseOptions};
+use equation_processor::render::render_equations_with;
+use equation_processor::test_util::{EquationBuilder, MockRenderer, TempOutput};
+use equation_processor::{EquationError, LatexEngine};
+use std::fs;
+use std::io;
+
+#[test]
+fn test_invalid_utf8_is_a_parse_error_with_line() {
+    let out = TempOutput::new("error_utf8");
+    let path = out.join("broken.csv");
+    fs::write(&path, b"active,equation,name\nYes,x,a\nYes,\xe9,b\n").unwrap();
+
+    let err = read_input(&path, &ParseOptions::default()).unwrap_err();
+    assert!(matches!(
+        err,
+        EquationError::ParseError { line: Some(3), .. }
+    ));
+    assert!(err.to_string().contains("broken.csv:3: invalid UTF-8"));
+    assert_eq!(io::Error::from(err).kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_compile_failure_shows_the_tex_errors() {
+    let err = EquationError::LatexCompileFailed {
+        engine: LatexEngine::Pdflatex,
+        tex: "out/energy.tex".into(),
+        log: "This is pdfTeX\n(./energy.tex\n! Undefined control sequence.\nl.5 \\foo\n\nNo pages of output."
+            .into(),
+    };
+    let message = err.to_string();
+    assert!(message.starts_with("pdflatex could not compile out/energy.tex:"));
+    assert!(message.contains("! Undefined control sequence.\nl.5 \\foo"));
+    assert!(!message.contains("This is pdfTeX"));
+}
+
+#[test]
+fn test_missing_tool_message_is_actionable() {
+    let err = EquationError::ToolNotFound {
+        tool: "pdftocairo".into(),
+    };
+    assert_eq!(
+        err.to_string(),
+        "pdftocairo was not found; install it or add it to PATH"
+    );
+    assert_eq!(io::Error::from(err).kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_batch_returns_the_renderer_error() {
+    let out = TempOutput::new("error_batch");
+    let equations = vec![EquationBuilder::new("energy").build()];
+    let failing = MockRenderer::new().fail_on("energy");
+    let err = render_equations_with(&equations, out.path(), &failing, false, None).unwrap_err();
+    match err {
+        EquationError::LatexCompileFailed { tex, .. } => assert!(tex.ends_with("energy.tex")),
+        other => panic!("expected a compile error, got {other:?}"),
+    }
+}
@@ -0,0 +1,58 @@
+use equation_processor::lint::lint_markdown;
+use equation_processor::parser::{parse_fence_attributes, parse_markdown};
+use equation_processor::EquationKind;
+
+#[test]
+fn test_fence_attributes_set_fields() {
+    let md = "# Fluids\n\n```math {name=\"bernoulli\", color=\"#223344\", tags=\"fluids,ch3\"}\np + \\frac{1}{2}\\rho v^2 = c\n```\n";
+    let eqs = parse_markdown(md);
+    assert_eq!(eqs.len(), 1);
+    let eq = &eqs[0];
+    assert_eq!(eq.name, "bernoulli");
+    assert_eq!(eq.body, "p + \\frac{1}{2}\\rho v^2 = c");
+    assert_eq!(eq.color.as_deref(), Some("#223344"));
+    assert_eq!(eq.tags, vec!["fluids", "ch3"]);
+    assert_eq!(eq.section.as_deref(), Some("Fluids"));
+    assert!(eq.active);
+}
+
+#[test]
+fn test_fences_mix_with_dollar_blocks_in_order() {
+    let md = "$$\na\n$$\n%%first%%\n\n```math {active=false chem}\nH2O\n```\n\n```math\nb\n```\n%%third%%\n\n$$\nc\n$$\n";
+    let eqs = parse_markdown(md);
+    let names: Vec<&str> = eqs.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["first", "default_equation", "third", "default_equation_1"]
+    );
+    assert!(!eqs[1].active);
+    assert_eq!(eqs[1].kind, EquationKind::Chemistry);
+    assert_eq!(eqs[2].body, "b");
+}
+
+#[test]
+fn test_other_fences_are_ignored() {
+    let eqs = parse_markdown("```rust\nlet x = 1;\n```\n");
+    assert!(eqs.is_empty());
+}
+
+#[test]
+fn test_parse_fence_attributes_quoted_bare_and_flags() {
+    let attrs = parse_fence_attributes(r#"name="a b", scale=1.2 tikz"#);
+    assert_eq!(
+        attrs,
+        vec![
+            ("name".to_string(), "a b".to_string()),
+            ("scale".to_string(), "1.2".to_string()),
+            ("tikz".to_string(), String::new()),
+        ]
+    );
+}
+
+#[test]
+fn test_lint_reports_unknown_fence_attributes() {
+    let diagnostics = lint_markdown("```math {name=\"x\", zoom=1.2, crop=maybe}\nx^2\n```\n");
+    let codes: Vec<&str> = diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(codes, ["unknown-tag", "invalid-tag"]);
+    assert!(diagnostics[0].message.contains("'zoom'"));
+}
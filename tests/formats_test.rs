@@ -0,0 +1,47 @@
+use equation_processor::render::{ImageFormat, OutputFormat, RenderOptions};
+use equation_processor::report::{build_index, skipped_stages};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+#[test]
+fn test_parse_output_formats() {
+    assert_eq!("pdf".parse::<OutputFormat>().unwrap(), OutputFormat::Pdf);
+    assert_eq!(
+        " PNG".parse::<OutputFormat>().unwrap(),
+        OutputFormat::Image(ImageFormat::Png)
+    );
+    assert!("eps"
+        .parse::<OutputFormat>()
+        .unwrap_err()
+        .contains("pdf, svg or png"));
+}
+
+#[test]
+fn test_default_formats_convert_to_svg_only() {
+    let options = RenderOptions::default();
+    assert_eq!(
+        options.image_formats().collect::<Vec<_>>(),
+        [ImageFormat::Svg]
+    );
+    assert!(!options.keeps_pdf());
+    assert_eq!(skipped_stages(&options), None);
+}
+
+#[test]
+fn test_pdf_only_skips_conversion_and_is_reported() {
+    let options = RenderOptions {
+        formats: vec![OutputFormat::Pdf],
+        ..Default::default()
+    };
+    assert_eq!(options.image_formats().count(), 0);
+    assert!(options.keeps_pdf());
+    assert!(skipped_stages(&options).unwrap().contains("only PDFs"));
+
+    let out = TempOutput::new("pdf_only_index");
+    let equations = vec![EquationBuilder::new("energy").build()];
+    fs::write(out.join("energy.pdf"), b"%PDF").unwrap();
+    let index = build_index(&equations, out.path());
+    assert_eq!(index.equations[0].status, "rendered");
+    assert_eq!(index.equations[0].pdf.as_deref(), Some("energy.pdf"));
+    assert_eq!(index.equations[0].svg, None);
+}
@@ -0,0 +1,31 @@
+use equation_processor::report::{write_gallery, GALLERY_FILE};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+#[test]
+fn test_gallery_lists_rendered_equations_with_dark_mode() {
+    let out = TempOutput::new("gallery");
+    let equations = vec![
+        EquationBuilder::new("energy")
+            .body("E < m c^2 & \"x\"")
+            .section("Physics")
+            .build(),
+        EquationBuilder::new("plot").section("Physics").build(),
+        EquationBuilder::new("unrendered").build(),
+        EquationBuilder::new("off").inactive().build(),
+    ];
+    fs::write(out.join("energy.svg"), "<svg/>").unwrap();
+    fs::write(out.join("plot.png"), "png").unwrap();
+    fs::write(out.join("off.svg"), "<svg/>").unwrap();
+
+    let path = write_gallery(&equations, out.path()).unwrap();
+    assert_eq!(path, out.join(GALLERY_FILE));
+    let html = fs::read_to_string(path).unwrap();
+    assert!(html.contains("@media (prefers-color-scheme: dark)"));
+    assert!(html.contains(r#"<meta name="color-scheme" content="light dark">"#));
+    assert_eq!(html.matches("<h2>Physics</h2>").count(), 1);
+    assert!(html.contains(r#"<img src="energy.svg" alt="E &lt; m c^2 &amp; &quot;x&quot;">"#));
+    assert!(html.contains(r#"<img src="plot.png""#));
+    assert!(!html.contains("unrendered"));
+    assert!(!html.contains("off.svg"));
+}
@@ -0,0 +1,46 @@
+use equation_processor::report::{archive_changed, history, write_index, HISTORY_DIR};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+#[test]
+fn test_changed_equation_is_archived() {
+    let out = TempOutput::new("history_changed");
+    let before = vec![
+        EquationBuilder::new("energy").body("E = mc^2").build(),
+        EquationBuilder::new("force").body("F = ma").build(),
+    ];
+    fs::write(out.join("energy.svg"), "<svg>old</svg>").unwrap();
+    fs::write(out.join("energy.pdf"), "%PDF old").unwrap();
+    fs::write(out.join("force.svg"), "<svg/>").unwrap();
+    write_index(&before, out.path()).unwrap();
+
+    let after = vec![
+        EquationBuilder::new("energy").body("E = m c^2").build(),
+        EquationBuilder::new("force").body("F = ma").build(),
+        EquationBuilder::new("new_one").body("x").build(),
+    ];
+    let archived = archive_changed(&after, out.path()).unwrap();
+    assert_eq!(archived.len(), 2);
+    assert!(!out.join("energy.svg").exists());
+    assert!(out.join("force.svg").exists());
+
+    let versions = history("energy", out.path()).unwrap();
+    assert_eq!(versions.len(), 1);
+    let stamp = &versions[0].timestamp;
+    assert_eq!(stamp.len(), "2024-05-01T10-00-00".len());
+    assert_eq!(&stamp[10..11], "T");
+    let svg = out.join(HISTORY_DIR).join(format!("energy.{stamp}.svg"));
+    assert!(versions[0].files.contains(&svg));
+    assert_eq!(fs::read_to_string(svg).unwrap(), "<svg>old</svg>");
+    assert!(history("force", out.path()).unwrap().is_empty());
+}
+
+#[test]
+fn test_nothing_archived_without_index() {
+    let out = TempOutput::new("history_no_index");
+    fs::write(out.join("energy.svg"), "<svg/>").unwrap();
+    let equations = vec![EquationBuilder::new("energy").body("E").build()];
+    assert!(archive_changed(&equations, out.path()).unwrap().is_empty());
+    assert!(out.join("energy.svg").exists());
+    assert!(history("energy", out.path()).unwrap().is_empty());
+}
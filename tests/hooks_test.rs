@@ -0,0 +1,80 @@
+use equation_processor::hooks::{run_hooks, PostRenderHook};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[test]
+fn test_hook_parsing_and_placeholders() {
+    let hook: PostRenderHook = "optipng -o7 '{png}' --log \"{dir}/opt log.txt\""
+        .parse()
+        .unwrap();
+    assert_eq!(hook.extensions(), ["png"]);
+    let out = Path::new("out");
+    let file = out.join("energy.png");
+    assert_eq!(
+        hook.command_line("energy", &file, out),
+        [
+            "optipng",
+            "-o7",
+            "out/energy.png",
+            "--log",
+            "out/opt log.txt"
+        ]
+    );
+
+    let every: PostRenderHook = "touch {file}".parse().unwrap();
+    assert_eq!(every.extensions(), ["svg", "png", "pdf"]);
+    assert!("svgo".parse::<PostRenderHook>().is_err());
+    assert!("svgo '{svg}".parse::<PostRenderHook>().is_err());
+    assert!("   ".parse::<PostRenderHook>().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hooks_run_per_artifact_and_report_failures() {
+    let out = TempOutput::new("hooks_run");
+    let equations = vec![
+        EquationBuilder::new("a").build(),
+        EquationBuilder::new("b").build(),
+        EquationBuilder::new("off").inactive().build(),
+    ];
+    for name in ["a", "b", "off"] {
+        fs::write(out.join(format!("{name}.svg")), "<svg/>").unwrap();
+    }
+    fs::write(out.join("a.pdf"), "%PDF").unwrap();
+
+    let hooks: Vec<PostRenderHook> = vec![
+        "sh -c 'echo {name} >> \"$0\"' {file}".parse().unwrap(),
+        "sh -c 'echo broken >&2; exit 3' {pdf}".parse().unwrap(),
+    ];
+    let failures = run_hooks(&equations, out.path(), &hooks, Duration::from_secs(10));
+    assert_eq!(fs::read_to_string(out.join("a.svg")).unwrap(), "<svg/>a\n");
+    assert_eq!(fs::read_to_string(out.join("a.pdf")).unwrap(), "%PDFa\n");
+    assert_eq!(fs::read_to_string(out.join("off.svg")).unwrap(), "<svg/>");
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].name, "a");
+    assert!(failures[0].reason.contains("broken"), "{}", failures[0]);
+
+    let both: PostRenderHook = "sh -c 'echo run >> \"$0\"' {pdf} {svg}".parse().unwrap();
+    assert!(run_hooks(
+        &equations[..1],
+        out.path(),
+        &[both],
+        Duration::from_secs(10)
+    )
+    .is_empty());
+    assert_eq!(
+        fs::read_to_string(out.join("a.pdf")).unwrap(),
+        "%PDFa\nrun\n"
+    );
+
+    let slow: PostRenderHook = "sh -c 'sleep 5' {svg}".parse().unwrap();
+    let failures = run_hooks(
+        &equations[..1],
+        out.path(),
+        &[slow],
+        Duration::from_millis(200),
+    );
+    assert!(failures[0].reason.contains("timed out"));
+}
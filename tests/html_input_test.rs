@@ -0,0 +1,68 @@
+use equation_processor::parser::{detect_file_type, parse_html, read_input, ParseOptions};
+use equation_processor::test_util::TempOutput;
+use equation_processor::Filetype;
+use std::fs;
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<script src="https://cdn.jsdelivr.net/npm/mathjax@2/MathJax.js"></script>
+<style>p::before { content: "\(x\)"; }</style>
+</head>
+<body>
+<h1>Waves &amp; <em>Fields</em></h1>
+<p>The speed is \(c = \lambda f\), and for \(x &lt; 0\) nothing moves.</p>
+<!-- \[ \text{draft} \] -->
+<pre>\[ \text{listing} \]</pre>
+<p>Write math as <code>\(...\)</code>.</p>
+<h2>Wave Equation</h2>
+<p>\[
+  \frac{\partial^2 u}{\partial t^2} = c^2 \nabla^2 u \label{eq:wave}
+\]</p>
+<SCRIPT type="math/tex; mode=display">E = \hbar \omega</SCRIPT>
+<script type="math/tex">k = 2\pi / \lambda</script>
+<p>An unclosed \( stays text.</p>
+</body>
+</html>
+"#;
+
+#[test]
+fn test_html_math_is_extracted_in_order() {
+    let equations = parse_html(PAGE);
+    let bodies: Vec<&str> = equations.iter().map(|e| e.body.as_str()).collect();
+    assert_eq!(
+        bodies,
+        [
+            "\\(c = \\lambda f\\)",
+            "\\(x < 0\\)",
+            "\\[\\frac{\\partial^2 u}{\\partial t^2} = c^2 \\nabla^2 u\\]",
+            "\\[E = \\hbar \\omega\\]",
+            "\\(k = 2\\pi / \\lambda\\)",
+        ]
+    );
+    assert_eq!(equations[2].name, "wave");
+    assert_eq!(equations[0].section.as_deref(), Some("Waves & Fields"));
+    assert_eq!(equations[4].section.as_deref(), Some("Wave Equation"));
+    assert!(equations.iter().all(|e| e.active));
+    assert_eq!(equations[0].name, "default_equation");
+    assert_eq!(equations[1].name, "default_equation_1");
+}
+
+#[test]
+fn test_html_repeated_labels_are_numbered() {
+    let page = "<p>\\[a \\label{x}\\]</p><p>\\[b \\label{x}\\]</p>";
+    let names: Vec<String> = parse_html(page).into_iter().map(|e| e.name).collect();
+    assert_eq!(names, ["x", "x_1"]);
+}
+
+#[test]
+fn test_html_files_are_detected_by_extension() {
+    let dir = TempOutput::new("html_input");
+    for name in ["post.html", "post.htm"] {
+        let path = dir.join(name);
+        fs::write(&path, PAGE).unwrap();
+        assert!(matches!(detect_file_type(&path), Filetype::Html));
+        let equations = read_input(&path, &ParseOptions::default()).unwrap();
+        assert_eq!(equations.len(), 5);
+    }
+}
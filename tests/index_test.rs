@@ -0,0 +1,54 @@
+use equation_processor::parser::parse_markdown;
+use equation_processor::report::{run_summary, svg_dimensions, write_index, INDEX_FILE};
+use equation_processor::test_util::TempOutput;
+use std::fs;
+
+#[test]
+fn test_markdown_sections_and_tags() {
+    let md = "# Mechanics\n\n$$F = ma$$\n%%force%%\n%%tags:newton, exam%%\n\n## Energy ##\n$$E = mc^2$$\n%%energy%%\n";
+    let equations = parse_markdown(md);
+    assert_eq!(equations[0].section.as_deref(), Some("Mechanics"));
+    assert_eq!(equations[0].tags, ["newton", "exam"]);
+    assert_eq!(equations[1].section.as_deref(), Some("Energy"));
+}
+
+#[test]
+fn test_index_lists_artifacts_and_dimensions() {
+    let dir = TempOutput::new("index");
+    fs::write(
+        dir.join("force.svg"),
+        r#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg" width="42.5pt" height="12pt" viewBox="0 0 42.5 12"></svg>"#,
+    )
+    .unwrap();
+    let equations = parse_markdown(
+        "# Mechanics\n$$F = ma$$\n%%force%%\n$$a$$\n%%missing%%\n%%no%%\n$$b$$\n%%inactive%%\n",
+    );
+
+    assert_eq!(svg_dimensions(&dir.join("force.svg")), Some((42.5, 12.0)));
+    let path = write_index(&equations, dir.path()).unwrap();
+    assert_eq!(path, dir.join(INDEX_FILE));
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(index["version"], 2);
+    let entries = index["equations"].as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0]["status"], "rendered");
+    assert_eq!(entries[0]["svg"], "force.svg");
+    assert_eq!(entries[0]["width"], 42.5);
+    assert_eq!(entries[0]["latex"], "F = ma");
+    assert_eq!(entries[0]["section"], "Mechanics");
+    assert!(entries[1]["svg"].is_null());
+    assert_eq!(entries[1]["status"], "missing");
+    assert_eq!(entries[2]["name"], "inactive");
+    assert_eq!(entries[2]["status"], "skipped");
+}
+
+#[test]
+fn test_run_summary_lists_skipped() {
+    let equations = parse_markdown("$$a$$\n%%on%%\n%%no%%\n$$b$$\n%%off%%\n");
+    assert_eq!(
+        run_summary(&equations),
+        "Rendered 1 equation(s), skipped 1 inactive: off"
+    );
+    assert_eq!(run_summary(&equations[..1]), "Rendered 1 equation(s).");
+}
@@ -0,0 +1,83 @@
+use equation_processor::lint::{lint_file, Severity};
+use equation_processor::parser::{ambiguous_tags, read_input, ParseOptions};
+use equation_processor::scaffold::{init_project, StarterFormat, STARTER_CONFIG};
+use equation_processor::settings::Settings;
+use equation_processor::test_util::TempOutput;
+use equation_processor::EquationKind;
+use std::fs;
+use std::io;
+
+#[test]
+fn test_markdown_starter_parses_without_errors() {
+    let out = TempOutput::new("init_markdown");
+    let written = init_project(out.path(), StarterFormat::Markdown, false).unwrap();
+    assert_eq!(written.len(), 3);
+
+    let input = out.join("equations.md");
+    let equations = read_input(&input, &ParseOptions::default()).unwrap();
+    let names: Vec<&str> = equations.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "mass_energy",
+            "integral_draft",
+            "small_angle",
+            "water",
+            "bernoulli"
+        ]
+    );
+    assert!(!equations[1].active);
+    assert_eq!(equations[2].color.as_deref(), Some("#1F4E79"));
+    assert_eq!(equations[3].kind, EquationKind::Chemistry);
+    assert!(ambiguous_tags(&fs::read_to_string(&input).unwrap()).is_empty());
+    let diagnostics = lint_file(&input, &ParseOptions::default()).unwrap();
+    assert!(
+        diagnostics.iter().all(|d| d.severity != Severity::Error),
+        "{diagnostics:?}"
+    );
+}
+
+#[test]
+fn test_csv_starter_parses_and_config_loads() {
+    let out = TempOutput::new("init_csv");
+    init_project(out.path(), StarterFormat::Csv, false).unwrap();
+
+    let equations = read_input(&out.join("equations.csv"), &ParseOptions::default()).unwrap();
+    assert_eq!(equations.len(), 4);
+    assert_eq!(equations.iter().filter(|e| e.active).count(), 3);
+    assert_eq!(equations[3].name, "bernoulli");
+
+    let settings = Settings::load(&out.join(STARTER_CONFIG)).unwrap();
+    assert_eq!(settings.output_dir, Some("output".into()));
+}
+
+#[test]
+fn test_init_refuses_to_overwrite_without_force() {
+    let out = TempOutput::new("init_existing");
+    fs::write(out.join("equations.md"), "mine").unwrap();
+
+    let err = init_project(out.path(), StarterFormat::Markdown, false).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    assert!(err.to_string().contains("equations.md"));
+    assert_eq!(
+        fs::read_to_string(out.join("equations.md")).unwrap(),
+        "mine"
+    );
+    assert!(!out.join(STARTER_CONFIG).exists());
+
+    init_project(out.path(), StarterFormat::Markdown, true).unwrap();
+    assert_ne!(
+        fs::read_to_string(out.join("equations.md")).unwrap(),
+        "mine"
+    );
+}
+
+#[test]
+fn test_starter_format_from_str() {
+    assert_eq!("CSV".parse(), Ok(StarterFormat::Csv));
+    assert_eq!("md".parse(), Ok(StarterFormat::Markdown));
+    assert!("yaml"
+        .parse::<StarterFormat>()
+        .unwrap_err()
+        .contains("'yaml'"));
+}
@@ -0,0 +1,72 @@
+use equation_processor::lint::lint_file;
+use equation_processor::parser::{
+    detect_file_type, read_input, register_input_format, ParseOptions,
+};
+use equation_processor::test_util::TempOutput;
+use equation_processor::{Equation, EquationError, Filetype};
+use std::fs;
+
+/// One `name = body` pair per line; `!` in front of the name marks it inactive.
+fn parse_pairs(content: &str, _options: &ParseOptions) -> Result<Vec<Equation>, String> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, body) = line
+                .split_once('=')
+                .ok_or_else(|| format!("missing '=' in '{line}'"))?;
+            let name = name.trim();
+            Ok(match name.strip_prefix('!') {
+                Some(name) => Equation::new(false, name, body.trim()),
+                None => Equation::new(true, name, body.trim()),
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn test_registered_format_is_detected_and_read() {
+    register_input_format(".Pairs", parse_pairs);
+    let out = TempOutput::new("input_format_read");
+    let path = out.join("lab.PAIRS");
+    fs::write(&path, "energy = E = mc^2\n!force = F = ma\n").unwrap();
+
+    assert!(matches!(detect_file_type(&path), Filetype::Registered(ext) if ext == "pairs"));
+    let eqs = read_input(&path, &ParseOptions::default()).unwrap();
+    assert_eq!(eqs.len(), 2);
+    assert_eq!(eqs[0].name, "energy");
+    assert_eq!(eqs[0].body, "E = mc^2");
+    assert!(!eqs[1].active);
+}
+
+#[test]
+fn test_parser_errors_name_the_file() {
+    register_input_format("pairs2", parse_pairs);
+    let out = TempOutput::new("input_format_error");
+    let path = out.join("broken.pairs2");
+    fs::write(&path, "no separator here\n").unwrap();
+
+    let err = read_input(&path, &ParseOptions::default()).unwrap_err();
+    assert!(matches!(err, EquationError::ParseError { line: None, .. }));
+    assert!(err.to_string().contains("broken.pairs2"));
+    assert!(err.to_string().contains("missing '='"));
+}
+
+#[test]
+fn test_lint_checks_registered_formats() {
+    register_input_format("pairs3", parse_pairs);
+    let out = TempOutput::new("input_format_lint");
+    let path = out.join("lab.pairs3");
+    fs::write(&path, "a = x\na = y\n").unwrap();
+
+    let diagnostics = lint_file(&path, &ParseOptions::default()).unwrap();
+    assert!(diagnostics.iter().any(|d| d.code == "duplicate-name"));
+}
+
+#[test]
+fn test_unregistered_extension_stays_unknown() {
+    assert!(matches!(
+        detect_file_type(std::path::Path::new("notes.unregistered")),
+        Filetype::Unknown
+    ));
+}
@@ -0,0 +1,59 @@
+use equation_processor::render::*;
+use equation_processor::test_util::TempOutput;
+use equation_processor::Equation;
+
+#[test]
+fn test_journal_survives_reopen() {
+    let dir = TempOutput::new("journal_reopen");
+    let mut journal = RenderJournal::create(dir.path()).unwrap();
+    journal.mark_completed("first", "k1").unwrap();
+    drop(journal);
+
+    let journal = RenderJournal::open(dir.path()).unwrap();
+    assert!(journal.is_completed("first", "k1"));
+    assert!(!journal.is_completed("first", "k2"));
+    assert!(!journal.is_completed("second", "k1"));
+    journal.finish().unwrap();
+    assert!(!dir.join(JOURNAL_FILE).exists());
+}
+
+#[test]
+fn test_resume_skips_completed_equations() {
+    use equation_processor::test_util::MockRenderer;
+
+    let dir = TempOutput::new("journal_resume");
+    let equations = vec![Equation::new(true, "done_eq", "x = 1")];
+    let options = RenderOptions::builder().resume(true).build();
+    let renderer = MockRenderer::new();
+    let key = renderer.cache_key(&equations[0]);
+    let mut journal = RenderJournal::create(dir.path()).unwrap();
+    journal
+        .mark_completed("done_eq", &journal_key(&equations[0], key.as_deref()))
+        .unwrap();
+
+    let tectonic = TectonicConfig::default();
+    render_batch_with(&equations, dir.path(), &tectonic, &options, &renderer).unwrap();
+    assert!(renderer.calls().is_empty());
+    assert!(!dir.join(JOURNAL_FILE).exists());
+}
+
+#[test]
+fn test_resume_renders_equations_edited_since() {
+    use equation_processor::test_util::MockRenderer;
+
+    let dir = TempOutput::new("journal_edited");
+    let before = vec![
+        Equation::new(true, "a", "x = 1"),
+        Equation::new(true, "b", "y"),
+    ];
+    let failing = MockRenderer::new().fail_on("b");
+    assert!(render_equations_with(&before, dir.path(), &failing, false, None).is_err());
+
+    let after = vec![
+        Equation::new(true, "a", "x = 2"),
+        Equation::new(true, "b", "y"),
+    ];
+    let renderer = MockRenderer::new();
+    render_equations_with(&after, dir.path(), &renderer, true, None).unwrap();
+    assert_eq!(renderer.calls(), ["a", "b"]);
+}
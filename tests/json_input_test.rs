@@ -0,0 +1,76 @@
+use equation_processor::parser::{detect_file_type, read_input, read_json_file, ParseOptions};
+use equation_processor::test_util::TempOutput;
+use equation_processor::{EquationError, EquationKind, Filetype};
+use std::fs;
+
+#[test]
+fn test_json_array_is_read_with_options() {
+    let out = TempOutput::new("json_input");
+    let path = out.join("equations.json");
+    fs::write(
+        &path,
+        r##"[
+  { "name": "energy", "body": "E = mc^2", "tags": ["physics", "exam"], "scale": 0.8 },
+  { "active": false, "name": "draft", "body": " \\int_0^1 x^2 \\, dx ", "description": "unfinished" },
+  { "name": "water", "body": "2H2 + O2 -> 2H2O", "chem": true, "crop": false, "color": "#1F4E79" }
+]"##,
+    )
+    .unwrap();
+    assert!(matches!(detect_file_type(&path), Filetype::Json));
+
+    let equations = read_input(&path, &ParseOptions::default()).unwrap();
+    assert_eq!(equations.len(), 3);
+    assert!(equations[0].active);
+    assert_eq!(equations[0].tags, ["physics", "exam"]);
+    assert_eq!(equations[0].display.scale, Some(0.8));
+    assert!(!equations[1].active);
+    assert_eq!(equations[1].body, r"\int_0^1 x^2 \, dx");
+    assert_eq!(equations[1].description.as_deref(), Some("unfinished"));
+    assert_eq!(equations[2].kind, EquationKind::Chemistry);
+    assert_eq!(equations[2].standalone.crop, Some(false));
+    assert_eq!(equations[2].color.as_deref(), Some("#1F4E79"));
+}
+
+#[test]
+fn test_json_errors_name_the_line_or_equation() {
+    let out = TempOutput::new("json_input_errors");
+    let path = out.join("bad.json");
+    let error = |content: &str| {
+        fs::write(&path, content).unwrap();
+        match read_json_file(&path) {
+            Err(EquationError::ParseError { line, reason, .. }) => (line, reason),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    };
+
+    let (line, reason) = error("[\n  { \"name\": \"energy\" }\n]");
+    assert_eq!(line, Some(2));
+    assert!(reason.contains("missing field `body`"), "{reason}");
+
+    let (line, reason) = error(r##"[{ "body": "x" }, { "body": "y", "colour": "#FF0000" }]"##);
+    assert_eq!(line, None);
+    assert_eq!(reason, "equation 2: unknown key 'colour'");
+
+    let (_, reason) = error(r#"[{ "body": "x", "width": "wide" }]"#);
+    assert_eq!(reason, "equation 1: invalid width 'wide'");
+}
+
+#[test]
+fn test_json_missing_and_repeated_names_are_numbered() {
+    let out = TempOutput::new("json_input_names");
+    let path = out.join("names.json");
+    fs::write(
+        &path,
+        r#"[{ "body": "a" }, { "body": "b" }, { "name": "x", "body": "c" }, { "name": "x", "body": "d" }]"#,
+    )
+    .unwrap();
+    let names: Vec<String> = read_json_file(&path)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert_eq!(
+        names,
+        ["default_equation", "default_equation_1", "x", "x_1"]
+    );
+}
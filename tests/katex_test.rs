@@ -0,0 +1,53 @@
+use equation_processor::katex::{is_supported, is_supported_environment, unsupported_commands};
+use equation_processor::lint::{check_katex, Severity};
+use equation_processor::parser::ParseOptions;
+use equation_processor::test_util::TempOutput;
+use std::fs;
+
+#[test]
+fn test_common_commands_are_supported() {
+    let body = r"\begin{pmatrix} \alpha & \mathbb{R} \\ \operatorname*{argmax}_x f & \xrightarrow{a} \end{pmatrix} \quad \left( \frac{1}{2} \right)";
+    assert!(unsupported_commands(body).is_empty());
+    assert!(is_supported("dfrac"));
+    assert!(is_supported_environment("aligned"));
+}
+
+#[test]
+fn test_unsupported_commands_and_environments_listed_once() {
+    let body = r"\begin{multline} a \intertext{x} b \intertext{y} \end{multline}";
+    assert_eq!(
+        unsupported_commands(body),
+        [r"\intertext", r"\begin{multline}"]
+    );
+}
+
+#[test]
+fn test_macros_defined_in_body_are_accepted() {
+    let body = r"\newcommand{\R}{\mathbb{R}} \def\half{\frac12} \R \half \myop";
+    assert_eq!(unsupported_commands(body), [r"\myop"]);
+}
+
+#[test]
+fn test_check_katex_reports_lines_and_kinds() {
+    let out = TempOutput::new("katex_check");
+    let file = out.join("eqs.md");
+    fs::write(
+        &file,
+        "$$\n\\sum_i x_i\n$$\n%%ok%%\n\n$$\na \\intertext{b}\n$$\n%%bad%%\n\n$$\n\\draw (0,0) -- (1,1);\n$$\n%%pic%%\n%%tikz%%\n\n$$\nH2O\n$$\n%%water%%\n%%chem%%\n",
+    )
+    .unwrap();
+    let diagnostics = check_katex(&file, &ParseOptions::default()).unwrap();
+    let found: Vec<(usize, &str, Severity)> = diagnostics
+        .iter()
+        .map(|d| (d.line, d.name.as_str(), d.severity))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            (6, "bad", Severity::Error),
+            (11, "pic", Severity::Error),
+            (17, "water", Severity::Warning),
+        ]
+    );
+    assert!(diagnostics[0].message.contains(r"\intertext"));
+}
@@ -0,0 +1,47 @@
+use equation_processor::parser::parse_markdown;
+use equation_processor::EquationKind;
+
+#[test]
+fn test_tikz_flag_and_libraries_tags() {
+    let md = "$$\n\\draw[->] (0,0) -- (1,1);\n$$\n%%tikz%%\n%%arrow%%\n%%tikzlibs:arrows.meta, positioning%%\n\n$$x$$\n%%plain%%\n";
+    let equations = parse_markdown(md);
+    assert_eq!(equations.len(), 2);
+    assert_eq!(equations[0].name, "arrow");
+    assert_eq!(equations[0].kind, EquationKind::TikZ);
+    assert_eq!(equations[0].tikz_libraries, ["arrows.meta", "positioning"]);
+    assert_eq!(equations[1].kind, EquationKind::Math);
+}
+
+#[test]
+fn test_tikz_body_is_wrapped_in_tikzpicture() {
+    let mut equations =
+        parse_markdown("$$\n\\draw (0,0) circle (1);\n$$\n%%kind:tikz%%\n%%tikzlibs:calc%%\n");
+    let tikz = equations.remove(0).generate_latex("#FF0000");
+    assert!(tikz.contains("\\usepackage{tikz}"));
+    assert!(tikz.contains("\\usetikzlibrary{calc}"));
+    assert!(tikz.contains("\\begin{tikzpicture}\n\\draw (0,0) circle (1);\n\\end{tikzpicture}"));
+    assert!(!tikz.contains("$\\draw"));
+
+    let math = parse_markdown("$$a+b$$")
+        .remove(0)
+        .generate_latex("#000000");
+    assert!(math.contains("{$a+b$}"));
+    assert!(!math.contains("tikz"));
+}
+
+#[test]
+fn test_chem_tag_wraps_body_in_ce() {
+    let mut equations = parse_markdown("$$\nH2O -> H+ + OH-\n$$\n%%water%%\n%%chem%%\n");
+    let eq = equations.remove(0);
+    assert_eq!(eq.name, "water");
+    assert_eq!(eq.kind, EquationKind::Chemistry);
+    let latex = eq.generate_latex("#000000");
+    assert!(latex.contains("\\usepackage[version=4]{mhchem}"));
+    assert!(latex.contains("{$\\ce{H2O -> H+ + OH-}$}"));
+}
+
+#[test]
+fn test_kind_tag_accepts_chem() {
+    let equations = parse_markdown("$$\nNaCl\n$$\n%%salt%%\n%%kind:chem%%\n");
+    assert_eq!(equations[0].kind, EquationKind::Chemistry);
+}
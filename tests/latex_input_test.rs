@@ -0,0 +1,74 @@
+use equation_processor::parser::{detect_file_type, parse_latex, read_input, ParseOptions};
+use equation_processor::test_util::TempOutput;
+use equation_processor::Filetype;
+use std::fs;
+
+const DOCUMENT: &str = r"\documentclass{article}
+\usepackage{amsmath}
+\begin{document}
+\section{Mechanics}
+Energy and mass are related by
+\begin{equation}
+  E = mc^2 \label{eq:energy}
+\end{equation}
+% \begin{equation} x^2 \end{equation}
+and the motion of a body by \[ F = ma \] for constant mass.
+\subsection*{Fields}
+\begin{align*}
+  \nabla \cdot \mathbf{E} &= \frac{\rho}{\varepsilon_0} \label{gauss} \\[2pt]
+  \nabla \cdot \mathbf{B} &= 0 \label{gauss_magnetism}
+\end{align*}
+\begin{gather}
+  p = 50\% \cdot q
+\end{gather}
+\begin{equation}
+  x = 1
+\end{document}
+";
+
+#[test]
+fn test_latex_environments_are_extracted_in_order() {
+    let equations = parse_latex(DOCUMENT);
+    let names: Vec<&str> = equations.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["energy", "default_equation", "gauss", "default_equation_1"]
+    );
+    assert!(equations.iter().all(|e| e.active));
+
+    assert_eq!(
+        equations[0].body,
+        "\\begin{equation}\n  E = mc^2 \n\\end{equation}"
+    );
+    assert_eq!(equations[0].section.as_deref(), Some("Mechanics"));
+    assert_eq!(equations[1].body, r"\[ F = ma \]");
+    // Line breaks with spacing stay in the body, all labels are removed.
+    assert!(equations[2].body.contains(r"\\[2pt]"));
+    assert!(!equations[2].body.contains(r"\label"));
+    assert_eq!(equations[2].section.as_deref(), Some("Fields"));
+    // An escaped percent sign is not a comment.
+    assert!(equations[3].body.contains(r"50\% \cdot q"));
+
+    // Repeated labels are numbered, so no two equations share files.
+    let repeated = parse_latex("\\[ a \\label{eq:x} \\]\n\\[ b \\label{x} \\]\n");
+    assert_eq!(repeated[1].name, "x_1");
+}
+
+#[test]
+fn test_tex_files_are_read_as_latex_documents() {
+    let out = TempOutput::new("latex_input");
+    let path = out.join("paper.tex");
+    fs::write(&path, DOCUMENT).unwrap();
+    assert!(matches!(detect_file_type(&path), Filetype::Latex));
+
+    let equations = read_input(&path, &ParseOptions::default()).unwrap();
+    assert_eq!(equations.len(), 4);
+    let latex = equations[2].generate_latex("#000000");
+    assert!(latex.contains(r"\begin{aligned}"), "{latex}");
+
+    // A directory of fragments is still read one file per equation.
+    assert!(matches!(
+        detect_file_type(out.path()),
+        Filetype::TexDirectory
+    ));
+}
@@ -0,0 +1,67 @@
+use equation_processor::lint::{lint_csv, lint_markdown, Severity};
+use equation_processor::parser::ParseOptions;
+
+fn codes(diagnostics: &[equation_processor::lint::Diagnostic]) -> Vec<&str> {
+    diagnostics.iter().map(|d| d.code).collect()
+}
+
+#[test]
+fn test_clean_markdown_has_no_diagnostics() {
+    let md = "# Intro\n\n$$\nE = mc^2\n$$\n%%energy%%\n%%tags:intro%%\n";
+    assert!(lint_markdown(md).is_empty());
+}
+
+#[test]
+fn test_markdown_duplicates_and_lines() {
+    let md = "$$\na\n$$\n%%energy%%\n\n$$\nb\n$$\n%%Energy%%\n";
+    let diagnostics = lint_markdown(md);
+    assert_eq!(codes(&diagnostics), ["duplicate-name", "duplicate-name"]);
+    assert_eq!(diagnostics[0].line, 1);
+    assert_eq!(diagnostics[1].line, 6);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert!(diagnostics[0].message.contains("lines 1, 6"));
+}
+
+#[test]
+fn test_markdown_missing_inactive_and_tag_problems() {
+    let md =
+        "$$\nx\n$$\n\n%%no%%\n$$\ny\n$$\n%%off%%\n\n$$\nz\n$$\n%%colour:#f00%%\n%%crop:maybe%%\n";
+    let diagnostics = lint_markdown(md);
+    assert_eq!(
+        codes(&diagnostics),
+        [
+            "missing-name",
+            "inactive-named",
+            "unknown-tag",
+            "invalid-tag"
+        ]
+    );
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+}
+
+#[test]
+fn test_body_checks() {
+    let md = "$$\nx^{2\n$$\n%%open%%\n\n$$\na \u{2212} b\n$$\n%%minus%%\n\n$$\n\\{ a \\}\n$$\n%%escaped%%\n";
+    let diagnostics = lint_markdown(md);
+    assert_eq!(
+        codes(&diagnostics),
+        ["unbalanced-braces", "suspicious-character"]
+    );
+    assert!(diagnostics[1].message.contains("U+2212"));
+}
+
+#[test]
+fn test_csv_rows() {
+    let csv = "active;equation;name\nyes;x;a\nyes;;b\nno only one field\nyes;y;a\n";
+    let diagnostics = lint_csv(csv, &ParseOptions::default());
+    assert_eq!(
+        codes(&diagnostics),
+        [
+            "duplicate-name",
+            "empty-body",
+            "malformed-row",
+            "duplicate-name"
+        ]
+    );
+    assert_eq!(diagnostics[2].line, 4);
+}
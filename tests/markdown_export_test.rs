@@ -0,0 +1,43 @@
+use equation_processor::report::{write_markdown, MARKDOWN_FILE};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+#[test]
+fn test_markdown_export_references_rendered_images() {
+    let out = TempOutput::new("markdown_export");
+    let mut energy = EquationBuilder::new("energy")
+        .body("E = m c^2 \\quad [J]")
+        .section("Physics")
+        .build();
+    energy.description = Some("rest energy".into());
+    let equations = vec![
+        energy,
+        EquationBuilder::new("plot").section("Physics").build(),
+        EquationBuilder::new("unrendered").build(),
+        EquationBuilder::new("off").inactive().build(),
+    ];
+    fs::write(
+        out.join("energy.svg"),
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"72pt\" height=\"18.2pt\"></svg>",
+    )
+    .unwrap();
+    fs::write(out.join("plot.png"), "png").unwrap();
+    fs::write(out.join("off.svg"), "<svg/>").unwrap();
+
+    let path = write_markdown(&equations, out.path(), false).unwrap();
+    assert_eq!(path, out.join(MARKDOWN_FILE));
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "# Equations\n\n## Physics\n\n\
+         ![E = m c^2 \\\\quad \\[J\\]](<energy.svg>)\n\n*rest energy*\n\n\
+         ![x](<plot.png>)\n"
+    );
+
+    write_markdown(&equations, out.path(), true).unwrap();
+    let markdown = fs::read_to_string(&path).unwrap();
+    assert!(markdown.contains(
+        "<img src=\"energy.svg\" alt=\"E = m c^2 \\quad [J]\" width=\"96\" height=\"24\">"
+    ));
+    // Dimensions come from the SVG, so a PNG stays a plain reference.
+    assert!(markdown.contains("![x](<plot.png>)"));
+}
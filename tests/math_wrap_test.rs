@@ -0,0 +1,37 @@
+use equation_processor::Equation;
+
+fn content(body: &str) -> String {
+    let latex = Equation::new(true, "eq", body).generate_latex("#000000");
+    let marker = "\\textcolor{equationcolor}{";
+    let start = latex.find(marker).unwrap() + marker.len();
+    let end = start + latex[start..].find("}}\n").unwrap();
+    latex[start..end].to_string()
+}
+
+#[test]
+fn test_plain_body_is_wrapped_once() {
+    assert_eq!(content("E = mc^2"), "$E = mc^2$");
+    assert_eq!(
+        content(r"\begin{pmatrix} a & b \end{pmatrix}"),
+        r"$\begin{pmatrix} a & b \end{pmatrix}$"
+    );
+}
+
+#[test]
+fn test_existing_delimiters_are_not_doubled() {
+    assert_eq!(content("$x$"), "$x$");
+    assert_eq!(content(r"\(x\)"), "$x$");
+    assert_eq!(content(r"\[ \sum_i x_i \]"), r"$\displaystyle \sum_i x_i$");
+}
+
+#[test]
+fn test_display_environments_become_inner_ones() {
+    assert_eq!(
+        content(r"\begin{align*} a &= b \\ c &= d \end{align*}"),
+        r"$\displaystyle \begin{aligned}a &= b \\ c &= d\end{aligned}$"
+    );
+    assert_eq!(
+        content(r"\begin{equation} x \end{equation}"),
+        r"$\displaystyle x$"
+    );
+}
@@ -0,0 +1,169 @@
+use equation_processor::render::{
+    render_equations_parallel, render_equations_with, BatchSummary, LinkStrategy, RenderJournal,
+    JOURNAL_FILE,
+};
+use equation_processor::report::write_index;
+use equation_processor::test_util::{EquationBuilder, MockRenderer, TempOutput};
+use std::fs;
+
+#[test]
+fn test_mock_pipeline_renders_active_and_writes_index() {
+    let out = TempOutput::new("mock_pipeline");
+    let equations = vec![
+        EquationBuilder::new("first")
+            .body("a^2")
+            .tag("intro")
+            .build(),
+        EquationBuilder::new("second").inactive().build(),
+        EquationBuilder::new("third").body("b^2").build(),
+    ];
+    let renderer = MockRenderer::new();
+    render_equations_with(&equations, out.path(), &renderer, false, None).unwrap();
+
+    assert_eq!(renderer.calls(), ["first", "third"]);
+    assert!(!out.join("second.svg").exists());
+    assert!(!out.join(JOURNAL_FILE).exists());
+
+    let index = fs::read_to_string(write_index(&equations, out.path()).unwrap()).unwrap();
+    assert!(index.contains("\"width\": 10.0"));
+    assert!(index.contains("intro"));
+}
+
+#[test]
+fn test_mock_failure_leaves_journal_for_resume() {
+    let out = TempOutput::new("mock_resume");
+    let equations = vec![
+        EquationBuilder::new("ok").build(),
+        EquationBuilder::new("broken").body("y").build(),
+    ];
+    let failing = MockRenderer::new().fail_on("broken");
+    assert!(render_equations_with(&equations, out.path(), &failing, false, None).is_err());
+    assert!(out.join(JOURNAL_FILE).exists());
+
+    let renderer = MockRenderer::new();
+    render_equations_with(&equations, out.path(), &renderer, true, None).unwrap();
+    assert_eq!(renderer.calls(), ["broken"]);
+}
+
+#[test]
+fn test_mock_dedup_renders_each_body_once() {
+    let out = TempOutput::new("mock_dedup");
+    let equations = vec![
+        EquationBuilder::new("a").body("x").build(),
+        EquationBuilder::new("b").body("x").build(),
+    ];
+    let renderer = MockRenderer::new();
+    render_equations_with(
+        &equations,
+        out.path(),
+        &renderer,
+        false,
+        Some(LinkStrategy::Copy),
+    )
+    .unwrap();
+    assert_eq!(renderer.calls(), ["a"]);
+    assert!(out.join("b.svg").exists());
+}
+
+#[test]
+fn test_parallel_batch_renders_every_equation_once() {
+    let out = TempOutput::new("mock_parallel");
+    let equations: Vec<_> = (0..20)
+        .map(|i| EquationBuilder::new(&format!("eq{i}")).build())
+        .collect();
+    let renderer = MockRenderer::new();
+    render_equations_parallel(&equations, out.path(), &renderer, false, None, 4).unwrap();
+
+    let mut calls = renderer.calls();
+    calls.sort();
+    let mut expected: Vec<String> = equations.iter().map(|e| e.name.clone()).collect();
+    expected.sort();
+    assert_eq!(calls, expected);
+    assert!(equations
+        .iter()
+        .all(|e| out.join(format!("{}.svg", e.name)).exists()));
+    assert!(!out.join(JOURNAL_FILE).exists());
+}
+
+#[test]
+fn test_parallel_failure_journals_finished_equations() {
+    let out = TempOutput::new("mock_parallel_failure");
+    let equations: Vec<_> = (0..8)
+        .map(|i| EquationBuilder::new(&format!("eq{i}")).build())
+        .collect();
+    let failing = MockRenderer::new().fail_on("eq3");
+    let err =
+        render_equations_parallel(&equations, out.path(), &failing, false, None, 3).unwrap_err();
+    assert!(err.to_string().contains("eq3"));
+
+    let journal = RenderJournal::open(out.path()).unwrap();
+    assert_eq!(journal.completed_count(), failing.calls().len() - 1);
+
+    let renderer = MockRenderer::new();
+    render_equations_parallel(&equations, out.path(), &renderer, true, None, 3).unwrap();
+    assert!(renderer.calls().contains(&"eq3".to_string()));
+    assert_eq!(
+        renderer.calls().len() + journal.completed_count(),
+        equations.len()
+    );
+}
+
+#[test]
+fn test_batch_summary_counts_each_outcome() {
+    let out = TempOutput::new("mock_summary");
+    let equations = vec![
+        EquationBuilder::new("a").body("x").build(),
+        EquationBuilder::new("b").body("x").build(),
+        EquationBuilder::new("c").body("y").build(),
+        EquationBuilder::new("d").inactive().build(),
+    ];
+    let copy = Some(LinkStrategy::Copy);
+    let renderer = MockRenderer::new();
+    let first = render_equations_parallel(&equations, out.path(), &renderer, false, copy, 2);
+    let expected = BatchSummary {
+        rendered: 2,
+        linked: 1,
+        ..BatchSummary::default()
+    };
+    assert_eq!(first.unwrap(), expected);
+
+    let again = render_equations_with(&equations, out.path(), &renderer, false, copy).unwrap();
+    let expected = BatchSummary {
+        cached: 2,
+        linked: 1,
+        ..BatchSummary::default()
+    };
+    assert_eq!(again, expected);
+}
+
+#[test]
+fn test_panicking_renderer_fails_the_batch() {
+    use equation_processor::render::Renderer;
+    use equation_processor::{Equation, EquationError};
+    use std::path::Path;
+
+    /// Renders like the mock, but panics on one equation.
+    struct Panicking(MockRenderer);
+
+    impl Renderer for Panicking {
+        fn render(&self, eq: &Equation, output_dir: &Path) -> Result<(), EquationError> {
+            if eq.name == "eq2" {
+                panic!("bad glyph");
+            }
+            self.0.render(eq, output_dir)
+        }
+    }
+
+    let out = TempOutput::new("mock_panic");
+    let equations: Vec<_> = (0..6)
+        .map(|i| EquationBuilder::new(&format!("eq{i}")).build())
+        .collect();
+    let panicking = Panicking(MockRenderer::new());
+    let err =
+        render_equations_parallel(&equations, out.path(), &panicking, false, None, 3).unwrap_err();
+    assert!(
+        err.to_string().contains("'eq2' panicked: bad glyph"),
+        "{err}"
+    );
+    assert!(out.join(JOURNAL_FILE).exists());
+}
@@ -0,0 +1,85 @@
+use equation_processor::parser::{detect_file_type, read_input, ParseOptions};
+use equation_processor::test_util::TempOutput;
+use equation_processor::Filetype;
+use std::fs;
+
+const NOTEBOOK: &str = r###"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": ["Intro\n", "\n", "$$\n", "a^2 + b^2 = c^2\n", "$$\n"]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [],
+   "source": ["x = '$$ y $$'"]
+  },
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": "## Heat Equation\n\n$$\n\\partial_t u = \\alpha \\Delta u\n$$\n\n$$\nu(0) = u_0\n$$\n%%initial%%\n"
+  },
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": ["$$\n", "q = -k \\nabla u\n", "$$\n"]
+  }
+ ],
+ "metadata": {},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"###;
+
+#[test]
+fn test_notebook_markdown_cells_are_read() {
+    let dir = TempOutput::new("notebook_input");
+    let path = dir.join("analysis.ipynb");
+    fs::write(&path, NOTEBOOK).unwrap();
+    assert!(matches!(detect_file_type(&path), Filetype::Notebook));
+
+    let equations = read_input(&path, &ParseOptions::default()).unwrap();
+    let names: Vec<&str> = equations.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["cell1_eq1", "heat_equation", "initial", "heat_equation_1"]
+    );
+    assert_eq!(equations[0].body.trim(), "a^2 + b^2 = c^2");
+    assert_eq!(equations[0].section, None);
+    assert_eq!(equations[3].body.trim(), "q = -k \\nabla u");
+    assert_eq!(equations[3].section.as_deref(), Some("Heat Equation"));
+}
+
+#[test]
+fn test_invalid_notebook_reports_position() {
+    let dir = TempOutput::new("notebook_invalid");
+    let path = dir.join("broken.ipynb");
+    fs::write(&path, "{\n \"cells\": [\n  {\"cell_type\": }\n ]\n}\n").unwrap();
+
+    let err = read_input(&path, &ParseOptions::default())
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("broken.ipynb"), "{err}");
+    assert!(err.contains('3'), "{err}");
+}
+
+#[test]
+fn test_repeated_names_are_numbered_across_cells() {
+    let cell = |source: &str| format!(r#"{{"cell_type": "markdown", "source": {source:?}}}"#);
+    let notebook = format!(
+        r#"{{"cells": [{}, {}, {}]}}"#,
+        cell("$$\nx\n$$\n%%foo%%\n"),
+        cell("# Foo\n\n$$\ny\n$$\n"),
+        cell("$$\nz\n$$\n%%foo%%\n"),
+    );
+    let dir = TempOutput::new("notebook_repeated");
+    let path = dir.join("repeated.ipynb");
+    fs::write(&path, notebook).unwrap();
+
+    let equations = read_input(&path, &ParseOptions::default()).unwrap();
+    let names: Vec<&str> = equations.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["foo", "foo_1", "foo_2"]);
+}
@@ -0,0 +1,54 @@
+use equation_processor::model::Color;
+use equation_processor::palette::{
+    batch_palette, check_palette, delta_e, ColorVision, MIN_DELTA_E, SAFE_PALETTE,
+};
+use equation_processor::test_util::EquationBuilder;
+
+fn color(hex: &str) -> Color {
+    hex.parse().unwrap()
+}
+
+#[test]
+fn test_safe_palette_has_no_conflicts() {
+    assert!(check_palette(&SAFE_PALETTE).is_empty());
+}
+
+#[test]
+fn test_red_green_conflicts_for_deuteranopia_only() {
+    let (red, green) = (color("#FF0000"), color("#00AA00"));
+    assert!(delta_e(red, green) > MIN_DELTA_E);
+
+    let conflicts = check_palette(&[red, green, red]);
+    assert_eq!(conflicts.len(), 1);
+    let conflict = &conflicts[0];
+    assert_eq!((conflict.first, conflict.second), (red, green));
+    assert_eq!(conflict.vision, ColorVision::Deuteranopia);
+    let suggestion = conflict.suggestion.unwrap();
+    assert!(SAFE_PALETTE.contains(&suggestion));
+    assert!(check_palette(&[red, suggestion]).is_empty());
+    assert!(conflict.to_string().contains("deuteranopia"));
+}
+
+#[test]
+fn test_simulation_keeps_grays() {
+    for vision in ColorVision::ALL {
+        let gray = vision.simulate(color("#808080"));
+        assert!(delta_e(gray, color("#808080")) < 1.0, "{vision}");
+    }
+    assert_eq!(
+        ColorVision::Typical.simulate(color("#123456")),
+        color("#123456")
+    );
+}
+
+#[test]
+fn test_batch_palette_uses_effective_colors() {
+    let mut tagged = EquationBuilder::new("b").build();
+    tagged.color = Some("#D55E00".into());
+    let mut inactive = EquationBuilder::new("c").inactive().build();
+    inactive.color = Some("#00FF00".into());
+    let equations = vec![EquationBuilder::new("a").build(), tagged, inactive];
+
+    let palette = batch_palette(&equations, color("#000000"));
+    assert_eq!(palette, [color("#000000"), color("#D55E00")]);
+}
@@ -0,0 +1,91 @@
+use equation_processor::parser::{fuzz_parse, is_ambiguous_tag, parse_markdown};
+use proptest::prelude::*;
+use std::collections::HashSet;
+use std::fs;
+
+/// One generated Markdown block: activation tag, optional name and body.
+fn block() -> impl Strategy<Value = (Option<bool>, Option<String>, String)> {
+    (
+        proptest::option::of(any::<bool>()),
+        proptest::option::of("[a-z][a-z0-9_]{0,10}"),
+        "[a-z0-9]([a-z0-9+= ^_]{0,18}[a-z0-9])?",
+    )
+}
+
+proptest! {
+    #[test]
+    fn prop_parsers_never_panic(data in proptest::collection::vec(any::<u8>(), 0..512)) {
+        fuzz_parse(&data);
+    }
+
+    #[test]
+    fn prop_parsers_never_panic_on_tag_soup(text in "(%%|\\$\\$|yes|no|[a-z]|\n|\r| ){0,64}") {
+        fuzz_parse(text.as_bytes());
+    }
+
+    #[test]
+    fn prop_well_formed_blocks_parse_back(blocks in proptest::collection::vec(block(), 0..8)) {
+        let mut seen = HashSet::new();
+        let blocks: Vec<_> = blocks
+            .into_iter()
+            .filter(|(_, name, _)| name.as_ref().is_none_or(|n| {
+                // `yes`/`no` before a block are activation tags, and markers
+                // such as `todo` are not taken as names
+                n != "default_equation" && !is_ambiguous_tag(n) && seen.insert(n.clone())
+            }))
+            .collect();
+        let mut md = String::new();
+        for (active, name, body) in &blocks {
+            match active {
+                Some(true) => md.push_str("%%yes%%\n"),
+                Some(false) => md.push_str("%%no%%\n"),
+                None => {}
+            }
+            md.push_str(&format!("$$\n{body}\n$$\n"));
+            if let Some(name) = name {
+                md.push_str(&format!("%%{name}%%\n"));
+            }
+            md.push('\n');
+        }
+
+        let equations = parse_markdown(&md);
+        prop_assert_eq!(equations.len(), blocks.len());
+        for (eq, (active, name, body)) in equations.iter().zip(&blocks) {
+            prop_assert_eq!(eq.active, active.unwrap_or(true));
+            prop_assert_eq!(&eq.body, body);
+            if let Some(name) = name {
+                prop_assert_eq!(&eq.name, name);
+            } else {
+                prop_assert!(eq.name.starts_with("default_equation"));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_corpus_parses_without_panicking() {
+    for entry in fs::read_dir("./tests/corpus").unwrap() {
+        let path = entry.unwrap().path();
+        fuzz_parse(&fs::read(&path).unwrap());
+    }
+}
+
+#[test]
+fn test_activation_tag_is_not_taken_as_previous_name() {
+    let content = fs::read_to_string("./tests/corpus/unnamed_then_prefix.md").unwrap();
+    let equations = parse_markdown(&content);
+    assert_eq!(equations.len(), 2);
+    assert_eq!(equations[0].name, "default_equation");
+    assert!(equations[0].active);
+    assert_eq!(equations[1].name, "default_equation_1");
+    assert!(!equations[1].active);
+}
+
+#[test]
+fn test_crlf_block_keeps_name() {
+    let content = fs::read_to_string("./tests/corpus/crlf.md").unwrap();
+    let equations = parse_markdown(&content);
+    assert_eq!(equations.len(), 1);
+    assert_eq!(equations[0].name, "crlf");
+    assert_eq!(equations[0].body, "x");
+}
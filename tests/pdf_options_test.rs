@@ -0,0 +1,54 @@
+use equation_processor::model::Equation;
+use equation_processor::render::{parse_pdf_version, PdfOptions, RenderOptions};
+use equation_processor::report::pdf_properties;
+use equation_processor::test_util::TempOutput;
+use std::fs;
+
+#[test]
+fn test_pdf_options_become_xdvipdfmx_specials() {
+    let options = RenderOptions {
+        pdf: PdfOptions {
+            compression: Some(9),
+            minor_version: Some(4),
+            ..PdfOptions::default()
+        },
+        ..Default::default()
+    };
+    let tex = Equation::new(true, "e", "x").generate_latex_with("#000000", &options);
+    assert!(tex
+        .contains("\\begin{document}\\special{dvipdfmx:config z 9}\\special{dvipdfmx:config V 4}"));
+    let plain = Equation::new(true, "e", "x").generate_latex("#000000");
+    assert!(!plain.contains("\\special"));
+}
+
+#[test]
+fn test_parse_pdf_version() {
+    assert_eq!(parse_pdf_version("1.4"), Ok(4));
+    assert!(parse_pdf_version("2.0").is_err());
+    assert!(parse_pdf_version("1.9").is_err());
+}
+
+#[test]
+fn test_pdf_properties_reads_version_and_fonts() {
+    let out = TempOutput::new("pdf_properties");
+    let path = out.join("e.pdf");
+    fs::write(
+        &path,
+        "%PDF-1.4\n1 0 obj << /Type /Font /BaseFont /ABCDEF+CMR10 >>\n\
+         2 0 obj << /BaseFont/GHIJKL+CMMI10 >>\n%%EOF\n",
+    )
+    .unwrap();
+    let props = pdf_properties(&path).unwrap();
+    assert_eq!(props.version.as_deref(), Some("1.4"));
+    assert_eq!(
+        props.fonts.as_deref(),
+        Some(&["ABCDEF+CMR10".to_string(), "GHIJKL+CMMI10".to_string()][..])
+    );
+    assert_eq!(props.fonts_subset, Some(true));
+    assert!(props.bytes > 0);
+
+    fs::write(&path, "%PDF-1.5\n<< /Type /ObjStm >>\n").unwrap();
+    let props = pdf_properties(&path).unwrap();
+    assert_eq!(props.fonts, None);
+    assert_eq!(props.fonts_subset, None);
+}
@@ -0,0 +1,21 @@
+use equation_processor::prelude::*;
+use std::path::Path;
+
+/// A downstream renderer written only against the prelude.
+struct Noop;
+
+impl Renderer for Noop {
+    fn render(&self, _eq: &Equation, _output_dir: &Path) -> Result<(), EquationError> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_prelude_covers_a_render_pipeline() {
+    let equations = parse_csv_with("active;equation;name\nyes;x;a\n", &ParseOptions::default());
+    assert_eq!(equations[0].kind, EquationKind::Math);
+    let out = std::env::temp_dir().join(format!("eqp_prelude_{}", std::process::id()));
+    render_equations_with(&equations, &out, &Noop, false, None).unwrap();
+    write_index(&equations, &out).unwrap();
+    std::fs::remove_dir_all(out).unwrap();
+}
@@ -0,0 +1,15 @@
+use equation_processor::report::unicode_preview;
+
+#[test]
+fn test_unicode_preview_symbols_and_scripts() {
+    assert_eq!(unicode_preview(r"E = mc^2"), "E = mc²");
+    assert_eq!(unicode_preview(r"\alpha_1 + \beta_{12}"), "α₁ + β₁₂");
+    assert_eq!(unicode_preview(r"\int_0^\infty x dx"), "∫₀^(∞) x dx");
+}
+
+#[test]
+fn test_unicode_preview_fractions_and_unknown_commands() {
+    assert_eq!(unicode_preview(r"\frac{a}{b}"), "(a)/(b)");
+    assert_eq!(unicode_preview(r"x \in \mathbb{R}"), "x ∈ R");
+    assert_eq!(unicode_preview(r"\foo x"), r"\foo x");
+}
@@ -0,0 +1,59 @@
+use equation_processor::process::{process_file_with, ProcessOptions};
+use equation_processor::report::INDEX_FILE;
+use equation_processor::test_util::{MockRenderer, TempOutput};
+use std::fs;
+
+const INPUT: &str = "$$\nE = mc^2\n$$\n%%energy%%\n\n%%no%%\n$$\na^2 + b^2 = c^2\n$$\n%%pythagoras%%\n\n$$\nx\n$$\n%%TODO%%\n";
+
+#[test]
+fn test_process_file_renders_and_reports_without_prompting() {
+    let out = TempOutput::new("process_file");
+    let input = out.join("equations.md");
+    fs::write(&input, INPUT).unwrap();
+    let options = ProcessOptions {
+        output_dir: out.join("output"),
+        ..Default::default()
+    };
+    let renderer = MockRenderer::new();
+    let report = process_file_with(&input, &options, &renderer).unwrap();
+
+    assert_eq!(renderer.calls(), ["energy", "default_equation"]);
+    assert_eq!(report.rendered, ["energy", "default_equation"]);
+    assert_eq!(report.skipped, ["pythagoras"]);
+    assert_eq!(report.outputs, [out.join("output").join(INDEX_FILE)]);
+    assert!(out.join("output/energy.svg").exists());
+    // `%%TODO%%` is dropped as a marker; the warning is collected, not printed.
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.warnings[0].contains("%%TODO%%"));
+}
+
+#[test]
+fn test_process_file_returns_render_and_color_errors() {
+    let out = TempOutput::new("process_file_errors");
+    let input = out.join("equations.md");
+    fs::write(&input, INPUT).unwrap();
+    let mut options = ProcessOptions {
+        output_dir: out.join("output"),
+        ..Default::default()
+    };
+    let renderer = MockRenderer::new().fail_on("energy");
+    let err = process_file_with(&input, &options, &renderer).unwrap_err();
+    assert!(err.to_string().contains("mock failure for energy"));
+
+    options.render.color = "not a color".into();
+    assert!(process_file_with(&input, &options, &MockRenderer::new()).is_err());
+}
+
+#[test]
+fn test_process_file_without_equations_is_empty() {
+    let out = TempOutput::new("process_file_empty");
+    let input = out.join("notes.md");
+    fs::write(&input, "# Just text\n").unwrap();
+    let options = ProcessOptions {
+        output_dir: out.join("output"),
+        ..Default::default()
+    };
+    let report = process_file_with(&input, &options, &MockRenderer::new()).unwrap();
+    assert!(report.equations.is_empty() && report.rendered.is_empty());
+    assert!(!out.join("output").exists());
+}
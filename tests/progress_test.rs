@@ -0,0 +1,28 @@
+use equation_processor::render::BatchProgress;
+use indicatif::{MultiProgress, ProgressDrawTarget};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_workers_report_concurrently() {
+    let multi = MultiProgress::with_draw_target(ProgressDrawTarget::hidden());
+    let progress = Arc::new(BatchProgress::with_target(8, 4, multi));
+    progress.set_position(2);
+    let handles: Vec<_> = (0..3)
+        .map(|worker| {
+            let progress = Arc::clone(&progress);
+            thread::spawn(move || {
+                for i in 0..2 {
+                    progress.start(worker, &format!("eq_{worker}_{i}"));
+                    progress.finish_one(worker, Duration::from_millis(5));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(progress.position(), 8);
+    progress.finish();
+}
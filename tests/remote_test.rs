@@ -0,0 +1,54 @@
+use equation_processor::parser::{read_input, ParseOptions};
+use equation_processor::remote::{extension_for_content_type, fetch_input, file_name_for};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+
+#[test]
+fn test_file_name_from_url_or_content_type() {
+    assert_eq!(
+        file_name_for("https://example.com/data/eqs.csv?token=1", "text/plain").as_deref(),
+        Some("eqs.csv")
+    );
+    assert_eq!(
+        file_name_for(
+            "https://example.com/api/equations",
+            "text/markdown; charset=utf-8"
+        )
+        .as_deref(),
+        Some("equations.md")
+    );
+    assert_eq!(
+        file_name_for("https://example.com/export", "text/html"),
+        None
+    );
+    assert_eq!(extension_for_content_type("Text/CSV"), Some("csv"));
+}
+
+/// Serve one HTTP response on a local port and return the URL to fetch.
+fn serve_once(content_type: &'static str, body: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+    format!("http://127.0.0.1:{port}/shared/equations")
+}
+
+#[test]
+fn test_fetch_input_detects_format_from_content_type() {
+    let url = serve_once("text/csv", "active,equation,name\nyes,E=mc^2,energy\n");
+    let path = fetch_input(&url).unwrap();
+    assert_eq!(path.file_name().unwrap(), "equations.csv");
+    let equations = read_input(&path, &ParseOptions::default()).unwrap();
+    assert_eq!(equations.len(), 1);
+    assert_eq!(equations[0].name, "energy");
+}
@@ -0,0 +1,86 @@
+use equation_processor::model::Equation;
+use equation_processor::render::{apply_renames, plan_renames, CaseTransform, RenameRule};
+
+fn equations() -> Vec<Equation> {
+    let mut eqs = vec![
+        Equation::new(true, "default_equation_1", "a"),
+        Equation::new(false, "default_equation_2", "b"),
+        Equation::new(true, "default_equation_3", "c"),
+    ];
+    eqs.push(Equation::new(true, "Energy", "E"));
+    eqs
+}
+
+#[test]
+fn test_plain_find_replace_only_touches_active() {
+    let mut eqs = equations();
+    let rule = RenameRule {
+        find: "default_equation".into(),
+        replace: "ch3".into(),
+        ..Default::default()
+    };
+    let (renames, warnings) = plan_renames(&eqs, &rule).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(renames.len(), 3);
+    apply_renames(&mut eqs, &renames);
+    let names: Vec<_> = eqs.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["ch3_1", "default_equation_2", "ch3_3", "Energy"]);
+}
+
+#[test]
+fn test_regex_numbering_and_case() {
+    let eqs = equations();
+    let rule = RenameRule {
+        find: r"^default_equation_(\d+)$".into(),
+        replace: "Eq{n}_was$1".into(),
+        regex: true,
+        case: CaseTransform::Lower,
+        start: 1,
+        width: 2,
+    };
+    let (renames, _) = plan_renames(&eqs, &rule).unwrap();
+    let new: Vec<_> = renames.iter().map(|r| r.new.as_str()).collect();
+    assert_eq!(new, ["eq01_was1", "eq02_was3", "energy"]);
+}
+
+#[test]
+fn test_empty_find_replaces_whole_name_and_sanitizes() {
+    let eqs = equations();
+    let rule = RenameRule {
+        replace: "eq {n}".into(),
+        ..Default::default()
+    };
+    let (renames, _) = plan_renames(&eqs, &rule).unwrap();
+    let new: Vec<_> = renames.iter().map(|r| r.new.as_str()).collect();
+    assert_eq!(new, ["eq_0", "eq_1", "eq_2"]);
+}
+
+#[test]
+fn test_collisions_are_warned_including_unselected_names() {
+    let eqs = equations();
+    let rule = RenameRule {
+        find: "3".into(),
+        replace: "2".into(),
+        ..Default::default()
+    };
+    let (_, warnings) = plan_renames(&eqs, &rule).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("default_equation_2"));
+
+    let rule = RenameRule {
+        replace: "same".into(),
+        ..Default::default()
+    };
+    let (_, warnings) = plan_renames(&eqs, &rule).unwrap();
+    assert_eq!(warnings, ["'same' would be used by 3 equations"]);
+}
+
+#[test]
+fn test_invalid_regex_is_an_error() {
+    let rule = RenameRule {
+        find: "(".into(),
+        regex: true,
+        ..Default::default()
+    };
+    assert!(plan_renames(&equations(), &rule).is_err());
+}
@@ -0,0 +1,160 @@
+use equation_processor::render::{
+    parse_font_package, LinkStrategy, OutputFormat, RenderOptions, Renderer, TectonicConfig,
+    TectonicRenderer,
+};
+use equation_processor::{Background, Equation};
+
+#[test]
+fn test_builder_starts_from_defaults() {
+    let options = RenderOptions::builder().build();
+    let defaults = RenderOptions::default();
+    assert_eq!(options.color, defaults.color);
+    assert_eq!(options.formats, defaults.formats);
+    assert!(!options.delete_intermediates && !options.resume);
+
+    let options = RenderOptions::builder()
+        .formats([OutputFormat::Pdf])
+        .dedup(LinkStrategy::Copy)
+        .resume(true)
+        .jobs(4)
+        .build();
+    assert!(options.keeps_pdf());
+    assert_eq!(options.image_formats().count(), 0);
+    assert_eq!(options.dedup, Some(LinkStrategy::Copy));
+    assert!(options.resume);
+    assert_eq!(options.jobs, 4);
+}
+
+#[test]
+fn test_font_size_replaces_the_default_size() {
+    let eq = Equation::new(true, "energy", "E = mc^2");
+    let default = eq.generate_latex_with("#000000", &RenderOptions::default());
+    assert!(default.contains(r"\Large"));
+
+    let options = RenderOptions::builder().font_size(12.0).build();
+    let sized = eq.generate_latex_with("#000000", &options);
+    assert!(sized.contains(r"\fontsize{12}{14.4}\selectfont"));
+    assert!(!sized.contains(r"\Large"));
+}
+
+#[test]
+fn test_renderer_takes_color_from_options() {
+    let eq = Equation::new(true, "energy", "E = mc^2");
+    let tectonic = TectonicConfig::default();
+    let black = RenderOptions::default();
+    let red = RenderOptions::builder()
+        .color("#FF0000".parse().unwrap())
+        .build();
+    let red_renderer = TectonicRenderer::new(&tectonic, &red);
+    assert_eq!(red_renderer.color, "#FF0000");
+    assert_ne!(
+        TectonicRenderer::new(&tectonic, &black).cache_key(&eq),
+        red_renderer.cache_key(&eq)
+    );
+}
+
+#[test]
+fn test_font_package_can_be_changed_or_dropped() {
+    let eq = Equation::new(true, "energy", "E = mc^2");
+    let latex = |options: &RenderOptions| eq.generate_latex_with("#000000", options);
+    assert!(latex(&RenderOptions::default()).contains(r"\usepackage{gfsneohellenicot}"));
+
+    let stix = latex(&RenderOptions::builder().font(Some("stix2")).build());
+    assert!(stix.contains(r"\usepackage{stix2}"));
+    assert!(!stix.contains("gfsneohellenicot"));
+
+    let plain = latex(&RenderOptions::builder().font(None).build());
+    assert!(!plain.contains("gfsneohellenicot"));
+    assert!(plain.contains(r"\usepackage{xcolor}"));
+
+    assert_eq!(parse_font_package(" newtxmath "), Ok("newtxmath".into()));
+    assert!(parse_font_package(r"x}\input{secret").is_err());
+}
+
+#[test]
+fn test_background_fills_the_page_only_when_set() {
+    let eq = Equation::new(true, "energy", "E = mc^2");
+    let plain = eq.generate_latex_with("#000000", &RenderOptions::default());
+    assert!(!plain.contains(r"\pagecolor"));
+
+    let background: Background = "#1e1e1e".parse().unwrap();
+    let dark = eq.generate_latex_with(
+        "#FFFFFF",
+        &RenderOptions::builder().background(background).build(),
+    );
+    assert!(dark.contains(r"\pagecolor[HTML]{1E1E1E}"));
+
+    assert_eq!("none".parse::<Background>(), Ok(Background::Transparent));
+    assert_eq!(background.to_string(), "#1E1E1E");
+    assert!("dark".parse::<Background>().is_err());
+}
+
+#[test]
+fn test_preamble_is_added_for_that_equation_only() {
+    let mut eq = Equation::new(true, "speed", r"v = \SI{3}{m/s}");
+    let plain = eq.generate_latex_with("#000000", &RenderOptions::default());
+    assert!(!plain.contains("siunitx"));
+
+    eq.preamble = Some("\\usepackage{siunitx}\n".into());
+    let tweaked = eq.generate_latex_with("#000000", &RenderOptions::default());
+    assert!(tweaked.contains("\\usepackage{siunitx}\n\\definecolor"));
+
+    eq.preamble = Some("  ".into());
+    assert_eq!(
+        eq.generate_latex_with("#000000", &RenderOptions::default()),
+        plain
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_render_keeps_the_baseline_signature() {
+    let output_dir = std::path::PathBuf::from("./output");
+    let eq = Equation::new(false, "idle", "x");
+    let rendered: std::io::Result<()> = eq.render(&output_dir, "#000000", false);
+    assert!(rendered.is_ok());
+
+    let err = Equation::new(true, "red", "x")
+        .render(&output_dir, "red", false)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_render_batch_applies_run_settings() {
+    use equation_processor::render::{render_batch, render_batch_with, Capabilities};
+    use equation_processor::report::{INDEX_FILE, SNIPPET_DIR};
+    use equation_processor::test_util::{MockRenderer, TempOutput};
+
+    let out = TempOutput::new("batch_run_settings");
+    let equations = vec![Equation::new(true, "energy", "E = mc^2")];
+    let mut options = RenderOptions::builder().build();
+    options.name_prefix = "lecture_".into();
+    options.tex_snippets = true;
+    let renderer = MockRenderer::new();
+    let tectonic = TectonicConfig::default();
+    render_batch_with(&equations, out.path(), &tectonic, &options, &renderer).unwrap();
+    assert_eq!(renderer.calls(), ["lecture_energy"]);
+    let index = std::fs::read_to_string(out.join(INDEX_FILE)).unwrap();
+    assert!(index.contains("lecture_energy"));
+    assert!(out.join(SNIPPET_DIR).is_dir());
+
+    let mut clashing = vec![equations[0].clone(), Equation::new(true, "mass", "m")];
+    clashing[0].color = Some("#FF0000".into());
+    clashing[1].color = Some("#FE0000".into());
+    options.check_palette = true;
+    let err = render_batch(&clashing, out.path(), &tectonic, &options)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("hard to tell apart"), "{err}");
+
+    let mut pdf_only = Capabilities::all("mock");
+    pdf_only.formats = vec![OutputFormat::Pdf];
+    let limited = MockRenderer::new().with_capabilities(pdf_only);
+    options.check_palette = false;
+    let err = render_batch_with(&equations, out.path(), &tectonic, &options, &limited)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("cannot write svg"), "{err}");
+    assert!(limited.calls().is_empty());
+}
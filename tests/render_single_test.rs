@@ -0,0 +1,59 @@
+use equation_processor::render::{
+    render_single_with, ImageFormat, OutputFormat, RenderOptions, Renderer,
+};
+use equation_processor::test_util::{MockRenderer, MOCK_SVG};
+use equation_processor::{Equation, EquationError};
+use std::fs;
+use std::path::Path;
+
+/// Writes an SVG of the built-in template's padded size: 17mm plus the
+/// default 1pt border above and below. Like any renderer that keeps the
+/// default capabilities, it is taken to compile the LaTeX template.
+struct PaddedRenderer;
+
+impl Renderer for PaddedRenderer {
+    fn render(&self, eq: &Equation, output_dir: &Path) -> Result<(), EquationError> {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="30pt" height="50.37pt"></svg>"#;
+        fs::write(output_dir.join(format!("{}.svg", eq.name)), svg)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_render_single_returns_the_requested_artifacts() {
+    let options = RenderOptions::builder()
+        .formats([OutputFormat::Pdf, OutputFormat::Image(ImageFormat::Svg)])
+        .build();
+    let renderer = MockRenderer::new();
+    let rendered = render_single_with("  E = mc^2\n", &options, &renderer).unwrap();
+
+    assert_eq!(renderer.calls(), ["equation"]);
+    assert_eq!(rendered.svg.as_deref(), Some(MOCK_SVG));
+    assert_eq!(rendered.pdf.as_deref(), Some(&b"%PDF-mock"[..]));
+    assert_eq!(rendered.png, None);
+    assert_eq!(rendered.dims, Some((10.0, 5.0)));
+    // The mock writes SVG directly, so there is no template baseline.
+    assert_eq!(rendered.baseline, None);
+
+    let failing = MockRenderer::new().fail_on("equation");
+    assert!(render_single_with("x", &options, &failing).is_err());
+    let bad_color = RenderOptions {
+        color: "teal".into(),
+        ..RenderOptions::default()
+    };
+    assert!(render_single_with("x", &bad_color, &renderer).is_err());
+}
+
+#[test]
+fn test_render_single_baseline_follows_the_template_padding() {
+    let rendered = render_single_with("x", &RenderOptions::default(), &PaddedRenderer).unwrap();
+    let baseline = rendered.baseline.unwrap();
+    assert!((baseline - (1.0 + 5.0 * 72.27 / 25.4)).abs() < 1e-9);
+
+    // The image no longer has the padded height, as if the equation reached
+    // below the reserved depth.
+    let mut options = RenderOptions::default();
+    options.standalone.border = Some("1pt 4pt 1pt 1pt".into());
+    let rendered = render_single_with("x", &options, &PaddedRenderer).unwrap();
+    assert_eq!(rendered.baseline, None);
+}
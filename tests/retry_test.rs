@@ -0,0 +1,61 @@
+use equation_processor::process::{process_file_with, ProcessOptions};
+use equation_processor::render::{Renderer, RetryPolicy, RetryingRenderer};
+use equation_processor::test_util::{EquationBuilder, MockRenderer, TempOutput};
+
+use std::fs;
+use std::time::Duration;
+
+const INPUT: &str = "$$\nE = mc^2\n$$\n%%energy%%\n\n$$\na^2 + b^2 = c^2\n$$\n%%pythagoras%%\n";
+
+fn options(out: &TempOutput, retries: u32) -> ProcessOptions {
+    let mut options = ProcessOptions {
+        output_dir: out.join("output"),
+        ..Default::default()
+    };
+    options.render.retry = RetryPolicy {
+        retries,
+        backoff: Duration::ZERO,
+    };
+    options
+}
+
+#[test]
+fn test_transient_failures_are_retried_and_reported() {
+    let out = TempOutput::new("retry_transient");
+    let input = out.join("equations.md");
+    fs::write(&input, INPUT).unwrap();
+    let renderer = MockRenderer::new().fail_transiently("energy", 2);
+    let report = process_file_with(&input, &options(&out, 3), &renderer).unwrap();
+
+    assert_eq!(
+        renderer.calls(),
+        ["energy", "energy", "energy", "pythagoras"]
+    );
+    assert_eq!(report.rendered, ["energy", "pythagoras"]);
+    assert_eq!(report.retries.len(), 2);
+    assert_eq!(report.retries[1].name, "energy");
+    assert_eq!(report.retries[1].retry, 2);
+    assert!(report.retries[0].reason.contains("connection reset"));
+}
+
+#[test]
+fn test_retries_stop_at_the_policy_limit_and_skip_other_failures() {
+    let out = TempOutput::new("retry_limit");
+    let input = out.join("equations.md");
+    fs::write(&input, INPUT).unwrap();
+    let flaky = MockRenderer::new().fail_transiently("energy", 2);
+    let err = process_file_with(&input, &options(&out, 1), &flaky).unwrap_err();
+    assert!(err.to_string().contains("energy"));
+    assert_eq!(flaky.calls(), ["energy", "energy"]);
+
+    let broken = MockRenderer::new().fail_on("energy");
+    let eq = EquationBuilder::new("energy").build();
+    let policy = RetryPolicy {
+        retries: 3,
+        backoff: Duration::ZERO,
+    };
+    let retrying = RetryingRenderer::new(&broken, policy);
+    assert!(retrying.render(&eq, &out.join("output")).is_err());
+    assert_eq!(broken.calls(), ["energy"]);
+    assert!(retrying.retries().is_empty());
+}
@@ -0,0 +1,78 @@
+use equation_processor::render::TectonicConfig;
+use equation_processor::rpc::{RpcServer, METHOD_NOT_FOUND, PARSE_ERROR};
+use equation_processor::test_util::{MockRenderer, TempOutput};
+use serde_json::{json, Value};
+use std::sync::Arc;
+
+/// Run the server on `requests` (one JSON value per line) and collect its messages.
+fn run(server: &RpcServer, requests: &[Value]) -> Vec<Value> {
+    let input: String = requests.iter().map(|r| format!("{r}\n")).collect();
+    let output = server.serve(input.as_bytes(), Vec::new()).unwrap();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_parse_returns_equations_with_spans() {
+    let server = RpcServer::new(TectonicConfig::default());
+    let content = "%%no%%\n$$\na\n$$\n%%first%%\n";
+    let out = run(
+        &server,
+        &[json!({"jsonrpc": "2.0", "id": 7, "method": "parse", "params": {"content": content}})],
+    );
+    assert_eq!(out.len(), 1);
+    assert_eq!(out[0]["id"], 7);
+    let eq = &out[0]["result"][0];
+    assert_eq!(eq["name"], "first");
+    assert_eq!(eq["active"], false);
+    assert_eq!(eq["bodySpan"], json!({"start": 10, "end": 11}));
+}
+
+#[test]
+fn test_errors_for_bad_json_and_unknown_methods() {
+    let server = RpcServer::new(TectonicConfig::default());
+    let output = server
+        .serve(
+            "not json\n{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"nope\"}\n".as_bytes(),
+            Vec::new(),
+        )
+        .unwrap();
+    let out: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(out[0]["error"]["code"], PARSE_ERROR);
+    assert_eq!(out[1]["error"]["code"], METHOD_NOT_FOUND);
+}
+
+#[test]
+fn test_render_batch_reports_progress() {
+    let dir = TempOutput::new("rpc_batch");
+    let server =
+        RpcServer::new(TectonicConfig::default()).with_renderer(Arc::new(MockRenderer::new()));
+    let content = "$$\na\n$$\n%%one%%\n\n%%no%%\n$$\nb\n$$\n%%off%%\n\n$$\nc\n$$\n%%two%%\n";
+    let out = run(
+        &server,
+        &[json!({
+            "jsonrpc": "2.0",
+            "id": "batch",
+            "method": "renderBatch",
+            "params": {"content": content, "outputDir": dir.path()},
+        })],
+    );
+    let progress: Vec<&Value> = out.iter().filter(|m| m["method"] == "$/progress").collect();
+    assert_eq!(progress.len(), 2);
+    assert_eq!(
+        progress[1]["params"],
+        json!({"id": "batch", "done": 2, "total": 2, "name": "two"})
+    );
+    let result = &out.last().unwrap()["result"];
+    assert_eq!(result["rendered"], json!(["one", "two"]));
+    assert_eq!(result["cancelled"], false);
+    assert!(dir.join("one.svg").exists());
+    assert!(dir.join("index.json").exists());
+}
@@ -0,0 +1,92 @@
+use equation_processor::report::{
+    fuzzy_score, search_outputs, write_index, SearchField, SearchQuery,
+};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+/// Two batches: `lecture1` at the root and `lecture2` in a subdirectory,
+/// plus a `history` folder that must not be searched.
+fn library(name: &str) -> TempOutput {
+    let out = TempOutput::new(name);
+    let first = vec![
+        EquationBuilder::new("energy")
+            .body("E = mc^2")
+            .tag("physics")
+            .build(),
+        EquationBuilder::new("half").body(r"\frac{1}{2}").build(),
+    ];
+    fs::write(out.join("energy.svg"), "<svg/>").unwrap();
+    write_index(&first, out.path()).unwrap();
+
+    let nested = out.join("lecture2");
+    fs::create_dir_all(&nested).unwrap();
+    let second = vec![EquationBuilder::new("kinetic")
+        .body(r"\frac{1}{2} m v^2")
+        .tag("Physics")
+        .build()];
+    fs::write(nested.join("kinetic.svg"), "<svg/>").unwrap();
+    write_index(&second, &nested).unwrap();
+
+    let history = out.join("history");
+    fs::create_dir_all(&history).unwrap();
+    write_index(&second, &history).unwrap();
+    out
+}
+
+fn names(out: &TempOutput, query: &str) -> Vec<String> {
+    let query: SearchQuery = query.parse().unwrap();
+    search_outputs(out.path(), &query)
+        .unwrap()
+        .into_iter()
+        .map(|hit| hit.name)
+        .collect()
+}
+
+#[test]
+fn test_search_matches_any_field_across_subdirectories() {
+    let out = library("search_any");
+    assert_eq!(names(&out, "FRAC"), ["half", "kinetic"]);
+    assert_eq!(names(&out, "physics"), ["energy", "kinetic"]);
+    assert!(names(&out, "nothing").is_empty());
+}
+
+#[test]
+fn test_search_restricted_fields() {
+    let out = library("search_fields");
+    assert_eq!(names(&out, "name:kin"), ["kinetic"]);
+    assert_eq!(names(&out, "body:mc"), ["energy"]);
+    // Tags match whole, so a prefix of one finds nothing.
+    assert!(names(&out, "tag:phys").is_empty());
+    assert_eq!(names(&out, "tag:PHYSICS"), ["energy", "kinetic"]);
+}
+
+#[test]
+fn test_search_hits_carry_artifact_paths() {
+    let out = library("search_paths");
+    let query: SearchQuery = "kinetic".parse().unwrap();
+    let hits = search_outputs(out.path(), &query).unwrap();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        hits[0].artifacts,
+        [out.join("lecture2").join("kinetic.svg")]
+    );
+    assert_eq!(hits[0].tags, ["Physics"]);
+}
+
+#[test]
+fn test_search_query_parsing() {
+    let query: SearchQuery = "unknown:field".parse().unwrap();
+    assert_eq!(query.field, SearchField::Any);
+    assert_eq!(query.text, "unknown:field");
+    assert!("tag:  ".parse::<SearchQuery>().is_err());
+}
+
+#[test]
+fn test_fuzzy_score_ranks_word_starts_and_runs_first() {
+    assert_eq!(fuzzy_score("", "Open input file…"), Some(0));
+    assert!(fuzzy_score("rsel", "Render selected equations").is_some());
+    assert!(fuzzy_score("SEL", "Render selected equations").is_some());
+    assert_eq!(fuzzy_score("xyz", "Render selected equations"), None);
+    assert!(fuzzy_score("of", "Open input folder…") > fuzzy_score("of", "Set output directory…"));
+    assert!(fuzzy_score("out", "Set output directory…") > fuzzy_score("out", "Toggle all rows"));
+}
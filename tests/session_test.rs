@@ -0,0 +1,52 @@
+use equation_processor::session::Session;
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use equation_processor::{EquationKind, LatexEngine};
+use std::fs;
+use std::io;
+
+#[test]
+fn test_session_round_trips_edits_and_settings() {
+    let out = TempOutput::new("session_round_trip");
+    let path = out.join("nested/gui_session.json");
+    let mut renamed = EquationBuilder::new("lecture3_energy")
+        .body("E = mc^2")
+        .kind(EquationKind::Chemistry)
+        .tag("exam")
+        .build();
+    renamed.engine = Some(LatexEngine::Lualatex);
+    renamed.standalone.border = Some("2pt".into());
+    let mut session = Session {
+        input_file: Some("notes.md".into()),
+        equations: vec![renamed, EquationBuilder::new("draft").inactive().build()],
+        page: 2,
+        ..Session::default()
+    };
+    session.settings.color = "#1A2B3C".into();
+    session.save(&path).unwrap();
+    assert!(!session.saved.is_empty());
+
+    let restored = Session::load(&path).unwrap();
+    assert_eq!(restored.saved, session.saved);
+    assert_eq!(restored.page, 2);
+    assert_eq!(restored.settings.color, "#1A2B3C");
+    assert_eq!(restored.equations.len(), 2);
+    let eq = &restored.equations[0];
+    assert_eq!(eq.name, "lecture3_energy");
+    assert_eq!(eq.kind, EquationKind::Chemistry);
+    assert_eq!(eq.engine, Some(LatexEngine::Lualatex));
+    assert_eq!(eq.tags, ["exam"]);
+    assert_eq!(eq.standalone.border.as_deref(), Some("2pt"));
+    assert!(!restored.equations[1].active);
+    assert!(!restored.is_empty());
+}
+
+#[test]
+fn test_empty_and_corrupt_sessions() {
+    assert!(Session::default().is_empty());
+    let out = TempOutput::new("session_corrupt");
+    let path = out.join("gui_session.json");
+    fs::write(&path, "{\"equations\": [").unwrap();
+    let err = Session::load(&path).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("gui_session.json"));
+}
@@ -0,0 +1,112 @@
+use equation_processor::render::TectonicConfig;
+use equation_processor::settings::{validate_config, Settings, ToolSettings};
+use equation_processor::test_util::TempOutput;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+#[test]
+fn test_settings_round_trip_through_toml() {
+    let out = TempOutput::new("settings_round_trip");
+    let tectonic = TectonicConfig {
+        cache_dir: Some(PathBuf::from("/shared/tectonic")),
+        bundle: Some("https://example.com/bundle.tar".into()),
+        offline: true,
+        env: vec![("SOURCE_DATE_EPOCH".into(), "0".into())],
+        source_date_epoch: Some(1_700_000_000),
+    };
+    let settings = Settings {
+        color: "#1A2B3C".into(),
+        border: "2pt".into(),
+        html_gallery: true,
+        zoom: 1.5,
+        tectonic: ToolSettings::from(&tectonic),
+        ..Settings::default()
+    };
+    let path = out.join("team.toml");
+    settings.save(&path).unwrap();
+    let loaded = Settings::load(&path).unwrap();
+    assert_eq!(loaded, settings);
+    let config = loaded.tectonic.to_config().unwrap();
+    assert_eq!(config.env, tectonic.env);
+    assert_eq!(config.cache_dir, tectonic.cache_dir);
+}
+
+#[test]
+fn test_settings_missing_keys_use_defaults() {
+    let out = TempOutput::new("settings_defaults");
+    let path = out.join("partial.toml");
+    fs::write(&path, "chemistry = true\n").unwrap();
+    let loaded = Settings::load(&path).unwrap();
+    assert!(loaded.chemistry);
+    assert_eq!(loaded.color, "#000000");
+    assert_eq!(loaded.zoom, 1.0);
+}
+
+#[test]
+fn test_settings_reject_invalid_values() {
+    let out = TempOutput::new("settings_invalid");
+    let path = out.join("bad.toml");
+    for content in [
+        "color = \"teal\"\n",
+        "[tectonic]\nenv = [\"NOEQUALS\"]\n",
+        "zoom = \"big\"\n",
+        "background = \"dark\"\n",
+    ] {
+        fs::write(&path, content).unwrap();
+        let err = Settings::load(&path).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData, "{content}");
+        assert!(err.to_string().contains("bad.toml"));
+    }
+}
+
+#[test]
+fn test_settings_font_key() {
+    let font = |toml: &str| toml::from_str::<Settings>(toml).unwrap().font_package();
+    assert_eq!(font(""), Some("gfsneohellenicot".into()));
+    assert_eq!(font("font = \"newtxmath\""), Some("newtxmath".into()));
+    assert_eq!(font("font = \"\""), None);
+
+    let out = TempOutput::new("settings_font");
+    let path = out.join("bad.toml");
+    fs::write(&path, "font = \"a b\"\n").unwrap();
+    assert!(Settings::load(&path).is_err());
+}
+
+#[test]
+fn test_validate_config_reports_every_problem() {
+    let content = "\
+color = \"#12345\"
+zom = 2.0
+offline = true
+
+[tectonic]
+env = [\"A=1\", 5]
+source_date_epoch = -1
+";
+    let issues: Vec<String> = validate_config(content)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            "line 2: zom: unknown key (did you mean 'zoom'?)",
+            "line 3: offline: unknown key (did you mean 'offline' in the [tectonic] table?)",
+            "line 6: tectonic.env: expected an array of strings, found an array with other values than strings",
+            "line 7: tectonic.source_date_epoch: expected a non-negative integer, found a negative integer",
+        ]
+    );
+
+    // Values are parsed once the keys and types are right.
+    let issues = validate_config("color = \"#12345\"\n[tectonic]\nenv = [\"NOEQUALS\"]\n");
+    let keys: Vec<_> = issues.iter().map(|i| i.key.as_deref()).collect();
+    assert_eq!(keys, [Some("color"), Some("tectonic.env[0]")]);
+    assert_eq!(issues[1].line, Some(3));
+
+    let issues = validate_config("color = \n");
+    assert_eq!(issues.len(), 1);
+    assert_eq!((issues[0].key.as_deref(), issues[0].line), (None, Some(1)));
+
+    assert!(validate_config(&toml::to_string(&Settings::default()).unwrap()).is_empty());
+}
@@ -0,0 +1,108 @@
+use equation_processor::process::{process_file_with, ProcessOptions};
+use equation_processor::report::INDEX_FILE;
+use equation_processor::shard::{merge_manifests, shard_of, Shard};
+use equation_processor::test_util::{MockRenderer, TempOutput};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const NAMES: [&str; 6] = ["energy", "force", "momentum", "entropy", "charge", "power"];
+
+/// Markdown with one active block per name in [`NAMES`] and an inactive one.
+fn input_text() -> String {
+    let mut md: String = NAMES
+        .iter()
+        .map(|name| format!("$$\n{name}\n$$\n%%{name}%%\n\n"))
+        .collect();
+    md.push_str("%%no%%\n$$\nx\n$$\n%%draft%%\n");
+    md
+}
+
+/// Render shard `index` of `count` into `out/shard<index>`.
+fn render_shard(out: &TempOutput, input: &Path, index: usize, count: usize) -> Vec<String> {
+    let mut options = ProcessOptions {
+        output_dir: out.join(format!("shard{index}")),
+        ..Default::default()
+    };
+    options.render.shard = Some(Shard::new(index, count).unwrap());
+    let renderer = MockRenderer::new();
+    process_file_with(input, &options, &renderer).unwrap();
+    renderer.calls()
+}
+
+#[test]
+fn test_shards_are_disjoint_and_cover_the_batch() {
+    assert!(Shard::new(0, 0).is_err());
+    assert_eq!(shard_of("energy", 3), shard_of("energy", 3));
+    let out = TempOutput::new("shard_disjoint");
+    let input = out.join("equations.md");
+    fs::write(&input, input_text()).unwrap();
+
+    let mut rendered: Vec<String> = (0..3)
+        .flat_map(|i| render_shard(&out, &input, i, 3))
+        .collect();
+    rendered.sort();
+    let mut expected: Vec<String> = NAMES.iter().map(|n| n.to_string()).collect();
+    expected.sort();
+    assert_eq!(rendered, expected);
+
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(out.join("shard1").join(INDEX_FILE)).unwrap())
+            .unwrap();
+    assert_eq!(index["shard"]["index"], 1);
+    assert_eq!(index["shard"]["count"], 3);
+
+    // A shard without equations still writes its index for merging.
+    let empty = (0..20).find(|&i| NAMES.iter().all(|n| shard_of(n, 20) != i));
+    let empty = empty.unwrap();
+    assert!(render_shard(&out, &input, empty, 20).is_empty());
+    assert!(out.join(format!("shard{empty}")).join(INDEX_FILE).exists());
+}
+
+#[test]
+fn test_merge_manifests_combines_indexes_and_artifacts() {
+    let out = TempOutput::new("shard_merge");
+    let input = out.join("equations.md");
+    fs::write(&input, input_text()).unwrap();
+    for i in 0..3 {
+        render_shard(&out, &input, i, 3);
+    }
+    let merged_dir = out.join("merged");
+    let inputs: Vec<PathBuf> = (0..3).map(|i| out.join(format!("shard{i}"))).collect();
+
+    let report = merge_manifests(&inputs, &merged_dir).unwrap();
+    assert_eq!(report.equations, NAMES.len() + 1);
+    assert_eq!(report.copied, NAMES.len() * 2);
+    assert!(report.missing_shards.is_empty());
+    let index: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&report.index).unwrap()).unwrap();
+    assert!(index.get("shard").is_none());
+    assert_eq!(
+        index["equations"].as_array().unwrap().len(),
+        NAMES.len() + 1
+    );
+    for name in NAMES {
+        assert!(merged_dir.join(format!("{name}.svg")).exists(), "{name}");
+    }
+
+    let report = merge_manifests(&inputs[..2], &out.join("partial")).unwrap();
+    assert_eq!(report.missing_shards, [2]);
+    let err =
+        merge_manifests(&[inputs[0].clone(), inputs[0].clone()], &out.join("twice")).unwrap_err();
+    assert!(err.to_string().contains("already listed"), "{err}");
+}
+
+#[test]
+fn test_merge_manifests_refuses_paths_outside_the_directories() {
+    let out = TempOutput::new("shard_merge_escape");
+    let shard = out.join("shard0");
+    fs::create_dir_all(&shard).unwrap();
+    fs::write(
+        shard.join(INDEX_FILE),
+        r#"{"version": 2, "equations": [{"name": "evil", "svg": "../../secret.svg"}]}"#,
+    )
+    .unwrap();
+
+    let err = merge_manifests(&[shard], &out.join("merged")).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("outside its directory"), "{err}");
+}
@@ -0,0 +1,50 @@
+use equation_processor::parser::parse_markdown;
+use equation_processor::report::{write_tex_snippets, SNIPPET_DIR, SNIPPET_MASTER};
+use equation_processor::test_util::TempOutput;
+use std::fs;
+
+#[test]
+fn test_snippet_forms() {
+    let eqs = parse_markdown(concat!(
+        "$$\nE = mc^2\n$$\n%%energy%%\n\n",
+        "$$\n\\[ a \\]\n$$\n%%display%%\n\n",
+        "$$\n\\begin{align}a &= b\\end{align}\n$$\n%%aligned%%\n\n",
+        "$$\nH2O\n$$\n%%water%%\n%%chem%%\n",
+    ));
+    assert_eq!(
+        eqs[0].latex_snippet(),
+        "\\begin{equation}\\label{eq:energy}\n  E = mc^2\n\\end{equation}\n"
+    );
+    assert!(eqs[1]
+        .latex_snippet()
+        .contains("\\label{eq:display}\n  a\n"));
+    assert_eq!(
+        eqs[2].latex_snippet(),
+        "\\begin{align}\\label{eq:aligned}\n  a &= b\n\\end{align}\n"
+    );
+    assert!(eqs[3].latex_snippet().contains("\\ce{H2O}"));
+}
+
+#[test]
+fn test_master_file_inputs_active_snippets() {
+    let dir = TempOutput::new("snippets");
+    let eqs = parse_markdown(
+        "$$\na\n$$\n%%first%%\n\n%%no%%\n$$\nb\n$$\n%%off%%\n\n$$\nc\n$$\n%%second%%\n",
+    );
+    let master = write_tex_snippets(&eqs, dir.path()).unwrap();
+    assert_eq!(master, dir.join(SNIPPET_DIR).join(SNIPPET_MASTER));
+    let content = fs::read_to_string(&master).unwrap();
+    let inputs: Vec<&str> = content
+        .lines()
+        .filter(|l| l.starts_with("\\input"))
+        .collect();
+    assert_eq!(
+        inputs,
+        [
+            "\\input{\\equationsnippets/first}",
+            "\\input{\\equationsnippets/second}"
+        ]
+    );
+    assert!(dir.join(SNIPPET_DIR).join("first.tex").exists());
+    assert!(!dir.join(SNIPPET_DIR).join("off.tex").exists());
+}
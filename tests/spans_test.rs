@@ -0,0 +1,32 @@
+use equation_processor::parser::{parse_markdown, parse_markdown_with_spans};
+
+#[test]
+fn test_spans_cover_blocks_and_bodies() {
+    let md = "# Intro\n\n%%yes%%\n$$\n  a + b  \n$$\n%%sum%%\n\ntext\n\n$$\nc\n$$\n";
+    let spanned = parse_markdown_with_spans(md);
+    assert_eq!(spanned.len(), 2);
+
+    assert_eq!(
+        &md[spanned[0].span.clone()],
+        "%%yes%%\n$$\n  a + b  \n$$\n%%sum%%"
+    );
+    assert_eq!(&md[spanned[0].body_span.clone()], "a + b");
+    assert_eq!(spanned[0].equation.name, "sum");
+    assert_eq!(&md[spanned[1].span.clone()], "$$\nc\n$$");
+    assert_eq!(&md[spanned[1].body_span.clone()], "c");
+}
+
+#[test]
+fn test_reparsing_a_region_matches_full_parse() {
+    let md = "$$\nx\n$$\n%%first%%\n\n$$\ny\n$$\n%%second%%\n";
+    let full = parse_markdown_with_spans(md);
+    let region = full[1].span.clone();
+    let partial = parse_markdown_with_spans(&md[region.clone()]);
+    assert_eq!(partial.len(), 1);
+    assert_eq!(partial[0].equation.name, "second");
+    assert_eq!(
+        region.start + partial[0].body_span.start,
+        full[1].body_span.start
+    );
+    assert_eq!(parse_markdown(md).len(), full.len());
+}
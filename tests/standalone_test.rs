@@ -0,0 +1,44 @@
+use equation_processor::parser::parse_markdown;
+use equation_processor::render::RenderOptions;
+use equation_processor::StandaloneOptions;
+
+#[test]
+fn test_default_border_is_1pt() {
+    let eq = &parse_markdown("$$\nx\n$$\n")[0];
+    assert!(eq
+        .generate_latex("#000000")
+        .starts_with("\\documentclass[border=1pt]{standalone}"));
+}
+
+#[test]
+fn test_tags_override_global_options() {
+    let md = "$$\nx\n$$\n%%padded%%\n%%border:2pt%%\n%%varwidth%%\n\n$$\ny\n$$\n%%plain%%\n";
+    let equations = parse_markdown(md);
+    assert_eq!(equations[0].standalone.border.as_deref(), Some("2pt"));
+
+    let options = RenderOptions {
+        standalone: StandaloneOptions {
+            border: Some("5pt".into()),
+            varwidth: None,
+            crop: Some(false),
+        },
+        ..Default::default()
+    };
+    let padded = equations[0].generate_latex_with("#000000", &options);
+    assert!(padded.starts_with("\\documentclass[border=2pt,varwidth,crop=false]{standalone}"));
+    let plain = equations[1].generate_latex_with("#000000", &options);
+    assert!(plain.starts_with("\\documentclass[border=5pt,crop=false]{standalone}"));
+}
+
+#[test]
+fn test_class_options_formatting() {
+    let options = StandaloneOptions {
+        border: Some("1pt 2pt 1pt 2pt".into()),
+        varwidth: Some("10cm".into()),
+        crop: None,
+    };
+    assert_eq!(
+        options.class_options(),
+        "border={1pt 2pt 1pt 2pt},varwidth=10cm"
+    );
+}
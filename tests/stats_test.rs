@@ -0,0 +1,123 @@
+use equation_processor::report::{
+    read_stats, record_run, stats_file, RunStats, StatsTrend, STATS_FILE,
+};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+use std::time::Duration;
+
+fn run(rendered: usize, failed: usize, seconds: f64) -> RunStats {
+    RunStats {
+        finished: "2024-05-01T10-00-00".into(),
+        rendered,
+        failed,
+        cached: 0,
+        skipped: 0,
+        seconds,
+    }
+}
+
+#[test]
+fn test_runs_are_appended_and_read_back() {
+    let data = TempOutput::new("stats_append_data");
+    let out = TempOutput::new("stats_append");
+    let path = stats_file(data.path(), out.path()).unwrap();
+    assert!(path.starts_with(data.join("stats")));
+    assert!(read_stats(&path).unwrap().is_empty());
+    let equations = vec![
+        EquationBuilder::new("a").build(),
+        EquationBuilder::new("b").inactive().build(),
+    ];
+    let first = RunStats::new(&equations, 1, 0, Duration::from_secs(2));
+    assert_eq!(first.skipped, 1);
+    assert_eq!(first.seconds_per_equation(), Some(2.0));
+    record_run(&path, &first).unwrap();
+    record_run(&path, &run(0, 1, 0.5)).unwrap();
+    // A torn line from an interrupted write is skipped.
+    let mut content = fs::read_to_string(&path).unwrap();
+    content.push_str("{\"finished\":");
+    fs::write(&path, content).unwrap();
+
+    let runs = read_stats(&path).unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0], first);
+    assert_eq!(runs[1].seconds_per_equation(), None);
+}
+
+#[test]
+fn test_stats_are_kept_per_output_directory() {
+    let data = TempOutput::new("stats_per_dir_data");
+    let first = TempOutput::new("stats_per_dir_a");
+    let second = TempOutput::new("stats_per_dir_b");
+    let a = stats_file(data.path(), first.path()).unwrap();
+    let b = stats_file(data.path(), second.path()).unwrap();
+    assert_ne!(a, b);
+    assert_eq!(a, stats_file(data.path(), first.path()).unwrap());
+}
+
+#[test]
+fn test_legacy_stats_file_is_migrated() {
+    let data = TempOutput::new("stats_migrate_data");
+    let out = TempOutput::new("stats_migrate");
+    let legacy = out.join(STATS_FILE);
+    record_run(&legacy, &run(1, 0, 1.0)).unwrap();
+    let path = stats_file(data.path(), out.path()).unwrap();
+    assert!(!legacy.exists());
+    record_run(&path, &run(2, 0, 1.0)).unwrap();
+    let rendered: Vec<usize> = read_stats(&path)
+        .unwrap()
+        .iter()
+        .map(|r| r.rendered)
+        .collect();
+    assert_eq!(rendered, [1, 2]);
+}
+
+#[test]
+fn test_trend_compares_recent_runs_with_earlier_ones() {
+    let runs = vec![
+        run(10, 0, 10.0),
+        run(10, 0, 10.0),
+        run(10, 0, 20.0),
+        run(8, 2, 16.0),
+    ];
+    let trend = StatsTrend::new(&runs, 2);
+    assert_eq!(trend.runs, 2);
+    assert_eq!(trend.seconds_per_equation, Some(2.0));
+    assert_eq!(trend.previous_seconds_per_equation, Some(1.0));
+    assert_eq!(trend.change(), Some(1.0));
+    assert_eq!(trend.failure_rate, Some(0.1));
+}
+
+#[test]
+fn test_trend_without_history() {
+    let trend = StatsTrend::new(&[], 10);
+    assert_eq!(trend.runs, 0);
+    assert_eq!(trend.seconds_per_equation, None);
+    assert_eq!(trend.change(), None);
+    assert_eq!(trend.failure_rate, None);
+
+    let only_failures = StatsTrend::new(&[run(0, 3, 1.0)], 10);
+    assert_eq!(only_failures.seconds_per_equation, None);
+    assert_eq!(only_failures.failure_rate, Some(1.0));
+}
+
+#[test]
+fn test_batch_stats_count_only_compiled_equations() {
+    use equation_processor::render::BatchSummary;
+
+    let equations = vec![
+        EquationBuilder::new("a").build(),
+        EquationBuilder::new("b").build(),
+        EquationBuilder::new("c").build(),
+        EquationBuilder::new("d").build(),
+    ];
+    let summary = BatchSummary {
+        rendered: 1,
+        cached: 1,
+        resumed: 1,
+        failed: 1,
+        ..BatchSummary::default()
+    };
+    let stats = RunStats::from_batch(&equations, &summary, Duration::from_secs(3));
+    assert_eq!((stats.rendered, stats.cached, stats.failed), (1, 2, 1));
+    assert_eq!(stats.seconds_per_equation(), Some(3.0));
+}
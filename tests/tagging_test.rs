@@ -0,0 +1,60 @@
+use equation_processor::parser::{parse_csv, read_input, ParseOptions};
+use equation_processor::tagging::{count_tags, edit_csv_tags, edit_tags_file, glob_match, TagEdit};
+use equation_processor::test_util::TempOutput;
+use std::fs;
+
+#[test]
+fn test_glob_match() {
+    assert!(glob_match("ch3_*", "ch3_energy"));
+    assert!(glob_match("*energy*", "ch3_energy_eq"));
+    assert!(glob_match("eq_?", "eq_1"));
+    assert!(!glob_match("eq_?", "eq_12"));
+    assert!(!glob_match("ch3_*", "ch4_energy"));
+    assert!(glob_match("*", ""));
+}
+
+#[test]
+fn test_add_and_remove_keep_other_rows_and_quotes() {
+    let csv =
+        "active;equation;name;assets;tags\r\nYes;\"a;b\";ch3_a;;exam\r\nNo;x;ch4_x;fig.tikz;\r\n";
+    let options = ParseOptions::default();
+    let (edited, changed) = edit_csv_tags(csv, &TagEdit::Add("review".into()), None, &options);
+    assert_eq!(changed, 2);
+    assert_eq!(
+        edited,
+        "active;equation;name;assets;tags\r\nYes;\"a;b\";ch3_a;;\"exam;review\"\r\nNo;x;ch4_x;fig.tikz;review\r\n"
+    );
+    let eqs = parse_csv(&edited);
+    assert_eq!(eqs[0].tags, ["exam", "review"]);
+    assert_eq!(eqs[0].body, "a;b");
+
+    let remove = TagEdit::Remove("exam".into());
+    let (edited, changed) = edit_csv_tags(&edited, &remove, Some("ch3_*"), &options);
+    assert_eq!(changed, 1);
+    assert!(edited.contains("ch3_a;;review\r\n"));
+    let (_, changed) = edit_csv_tags(&edited, &remove, None, &options);
+    assert_eq!(changed, 0);
+}
+
+#[test]
+fn test_edit_file_and_count() {
+    let out = TempOutput::new("tagging_file");
+    let path = out.join("eqs.csv");
+    fs::write(
+        &path,
+        "active,equation,name\nyes,a,ch3_a\nyes,b,ch3_b\nyes,c,ch4_c\n",
+    )
+    .unwrap();
+    let options = ParseOptions::default();
+    let changed =
+        edit_tags_file(&path, &TagEdit::Add("exam".into()), Some("ch3_*"), &options).unwrap();
+    assert_eq!(changed, 2);
+    let eqs = read_input(&path, &options).unwrap();
+    assert_eq!(count_tags(&eqs, None).get("exam"), Some(&2));
+    assert!(count_tags(&eqs, Some("ch4_*")).is_empty());
+    assert_eq!(fs::read_dir(out.path()).unwrap().count(), 1);
+
+    let md = out.join("eqs.md");
+    fs::write(&md, "$$\nx\n$$\n").unwrap();
+    assert!(edit_tags_file(&md, &TagEdit::Add("exam".into()), None, &options).is_err());
+}
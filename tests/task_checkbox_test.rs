@@ -0,0 +1,39 @@
+use equation_processor::parser::{parse_markdown, parse_markdown_with, ParseOptions};
+
+const NOTES: &str = "# Export\n\n- [x] Energy\n  $$\n  E = mc^2\n  $$\n  %%energy%%\n\n- [ ] Draft\n$$\nx\n$$\n%%draft%%\n\n* [X] $$y$$\n%%inline%%\n\n- [ ] Forced\n%%yes%%\n$$\nz\n$$\n%%forced%%\n\n$$\nw\n$$\n%%plain%%\n";
+
+fn options() -> ParseOptions {
+    ParseOptions {
+        task_checkboxes: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_checkboxes_set_active_flag() {
+    let eqs = parse_markdown_with(NOTES, &options());
+    let flags: Vec<_> = eqs.iter().map(|e| (e.name.as_str(), e.active)).collect();
+    assert_eq!(
+        flags,
+        [
+            ("energy", true),
+            ("draft", false),
+            ("inline", true),
+            ("forced", true),
+            ("plain", true),
+        ]
+    );
+}
+
+#[test]
+fn test_checkboxes_ignored_by_default() {
+    assert!(parse_markdown(NOTES).iter().all(|e| e.active));
+}
+
+#[test]
+fn test_unchecked_box_only_applies_to_next_block() {
+    let md = "1. [ ] skip\n\n$$\na\n$$\n%%a%%\n\n$$\nb\n$$\n%%b%%\n";
+    let eqs = parse_markdown_with(md, &options());
+    assert!(!eqs[0].active);
+    assert!(eqs[1].active);
+}
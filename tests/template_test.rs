@@ -0,0 +1,71 @@
+use equation_processor::render::{LatexTemplate, RenderOptions};
+use equation_processor::scaffold::{init_project, StarterFormat, STARTER_TEMPLATE};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use equation_processor::{EquationError, EquationKind};
+use std::path::Path;
+
+/// `latex` without comment lines and indentation.
+fn normalized(latex: &str) -> Vec<&str> {
+    latex
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('%'))
+        .collect()
+}
+
+#[test]
+fn test_template_placeholders_are_filled() {
+    let template = LatexTemplate::parse(
+        Path::new("mine.tex"),
+        "\\documentclass[{{CLASS_OPTIONS}}]{standalone}\n\\usepackage{mine}\n{{PACKAGES}}\
+         \\begin{document}% {{NAME}}\n{{SIZE}}\\color[HTML]{{{COLOR}}}{{BODY}}\n\\end{document}\n",
+    )
+    .unwrap();
+    let options = RenderOptions::builder()
+        .template(template)
+        .padding("3pt")
+        .font_size(10.0)
+        .build();
+    let eq = EquationBuilder::new("water")
+        .body("2H2 + O2 -> 2H2O")
+        .kind(EquationKind::Chemistry)
+        .build();
+    let latex = eq.generate_latex_with("#1F4E79", &options);
+    assert!(latex.starts_with("\\documentclass[border=3pt]{standalone}\n\\usepackage{mine}\n"));
+    assert!(latex.contains("\\usepackage[version=4]{mhchem}"));
+    assert!(latex.contains("% water\n"));
+    assert!(latex
+        .contains("\\fontsize{10}{12.0}\\selectfont\\color[HTML]{1F4E79}$\\ce{2H2 + O2 -> 2H2O}$"));
+}
+
+#[test]
+fn test_template_errors_name_the_problem() {
+    let path = Path::new("broken.tex");
+    match LatexTemplate::parse(path, "\\begin{document}\n{{BODY}} {{COLOUR}}\n") {
+        Err(EquationError::ParseError { line, reason, .. }) => {
+            assert_eq!(line, Some(2));
+            assert!(reason.contains("{{COLOUR}}"), "{reason}");
+        }
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+    match LatexTemplate::parse(path, "\\def\\x{{a}}\n{{COLOR}}\n") {
+        Err(EquationError::ParseError { line, reason, .. }) => {
+            assert_eq!(line, None);
+            assert!(reason.contains("{{BODY}}"));
+        }
+        other => panic!("expected a parse error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_starter_template_matches_the_built_in_document() {
+    let out = TempOutput::new("starter_template");
+    init_project(out.path(), StarterFormat::Markdown, false).unwrap();
+    let template = LatexTemplate::read(&out.join(STARTER_TEMPLATE)).unwrap();
+    let options = RenderOptions::builder().template(template).build();
+    let eq = EquationBuilder::new("energy").body("E = mc^2").build();
+    assert_eq!(
+        normalized(&eq.generate_latex_with("#000000", &options)),
+        normalized(&eq.generate_latex_with("#000000", &RenderOptions::default()))
+    );
+}
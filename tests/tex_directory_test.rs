@@ -0,0 +1,35 @@
+use equation_processor::lint::lint_file;
+use equation_processor::parser::{detect_file_type, read_tex_directory, ParseOptions};
+use equation_processor::test_util::TempOutput;
+use equation_processor::Filetype;
+use std::fs;
+
+#[test]
+fn test_fragments_become_equations_in_name_order() {
+    let out = TempOutput::new("tex_dir_read");
+    fs::write(out.join("b_force.tex"), "F = ma\n").unwrap();
+    fs::write(out.join("a energy.tex"), "\u{feff}E = mc^2").unwrap();
+    fs::write(out.join("notes.txt"), "not an equation").unwrap();
+    fs::create_dir(out.join("nested.tex")).unwrap();
+
+    assert!(matches!(
+        detect_file_type(out.path()),
+        Filetype::TexDirectory
+    ));
+    let eqs = read_tex_directory(out.path()).unwrap();
+    let names: Vec<&str> = eqs.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["a_energy", "b_force"]);
+    assert_eq!(eqs[0].body, "E = mc^2");
+    assert_eq!(eqs[1].body, "F = ma");
+    assert!(eqs.iter().all(|e| e.active));
+}
+
+#[test]
+fn test_lint_accepts_a_fragment_directory() {
+    let out = TempOutput::new("tex_dir_lint");
+    fs::write(out.join("empty.tex"), "  \n").unwrap();
+    fs::write(out.join("ok.tex"), "x^2").unwrap();
+    let diagnostics = lint_file(out.path(), &ParseOptions::default()).unwrap();
+    let codes: Vec<&str> = diagnostics.iter().map(|d| d.code).collect();
+    assert_eq!(codes, ["empty-body"]);
+}
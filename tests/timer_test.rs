@@ -0,0 +1,23 @@
+use equation_processor::render::{format_duration, RenderTimer};
+use std::time::Duration;
+
+#[test]
+fn test_timer_rate_and_eta() {
+    let mut timer = RenderTimer::default();
+    assert_eq!(timer.per_minute(), None);
+    assert_eq!(timer.summary(3), None);
+
+    timer.record(Duration::from_secs(2));
+    timer.record(Duration::from_secs(4));
+    assert_eq!(timer.mean(), Some(Duration::from_secs(3)));
+    assert_eq!(timer.per_minute(), Some(20.0));
+    assert_eq!(timer.eta(40), Some(Duration::from_secs(120)));
+    assert_eq!(timer.summary(40).unwrap(), "20.0 eq/min, ETA 2m 00s");
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(Duration::from_secs(45)), "45s");
+    assert_eq!(format_duration(Duration::from_secs(200)), "3m 20s");
+    assert_eq!(format_duration(Duration::from_secs(3720)), "1h 02m");
+}
@@ -0,0 +1,106 @@
+use equation_processor::parser::{
+    detect_file_type, read_input, read_toml_defaults, read_toml_file, ParseOptions,
+};
+use equation_processor::render::{ImageFormat, OutputFormat};
+use equation_processor::test_util::TempOutput;
+use equation_processor::{EquationError, Filetype};
+use std::fs;
+
+#[test]
+fn test_toml_defaults_apply_to_equations_without_their_own() {
+    let out = TempOutput::new("toml_input");
+    let path = out.join("job.toml");
+    fs::write(
+        &path,
+        r##"[defaults]
+color = "#1F4E79"
+scale = 0.8
+formats = ["svg", "pdf"]
+
+[[equation]]
+name = "energy"
+body = "E = mc^2"
+tags = ["physics"]
+
+[[equation]]
+name = "maxwell"
+active = false
+color = "#000000"
+scale = 1.5
+body = '''
+\nabla \cdot \mathbf{E} = \frac{\rho}{\varepsilon_0}
+'''
+"##,
+    )
+    .unwrap();
+    assert!(matches!(detect_file_type(&path), Filetype::Toml));
+
+    let equations = read_input(&path, &ParseOptions::default()).unwrap();
+    assert_eq!(equations.len(), 2);
+    assert_eq!(equations[0].color.as_deref(), Some("#1F4E79"));
+    assert_eq!(equations[0].display.scale, Some(0.8));
+    assert_eq!(equations[0].tags, ["physics"]);
+    assert!(!equations[1].active);
+    assert_eq!(equations[1].color.as_deref(), Some("#000000"));
+    assert_eq!(equations[1].display.scale, Some(1.5));
+    assert_eq!(
+        equations[1].body,
+        r"\nabla \cdot \mathbf{E} = \frac{\rho}{\varepsilon_0}"
+    );
+
+    let defaults = read_toml_defaults(&path).unwrap();
+    assert_eq!(
+        defaults.formats,
+        [OutputFormat::Image(ImageFormat::Svg), OutputFormat::Pdf]
+    );
+
+    // Without a [defaults] table the equations keep the batch settings.
+    fs::write(&path, "[[equation]]\nbody = \"x^2\"\n").unwrap();
+    let equations = read_toml_file(&path).unwrap();
+    assert_eq!(equations[0].name, "default_equation");
+    assert_eq!(equations[0].color, None);
+    assert!(read_toml_defaults(&path).unwrap().formats.is_empty());
+}
+
+#[test]
+fn test_toml_errors_name_the_line_defaults_or_equation() {
+    let out = TempOutput::new("toml_input_errors");
+    let path = out.join("bad.toml");
+    let error = |content: &str| {
+        fs::write(&path, content).unwrap();
+        match read_toml_file(&path) {
+            Err(EquationError::ParseError { line, reason, .. }) => (line, reason),
+            other => panic!("expected a parse error for {content:?}, got {other:?}"),
+        }
+    };
+
+    let (line, reason) = error("[[equation]]\nname = \"energy\"\nbody = E = mc^2\n");
+    assert_eq!(line, Some(3));
+    assert!(reason.contains("(column"), "{reason}");
+
+    let (line, reason) = error("[job]\nname = \"x\"\n");
+    assert_eq!(line, Some(1));
+    assert!(reason.contains("unknown field `job`"), "{reason}");
+
+    let (line, reason) = error("[defaults]\nformats = [\"gif\"]\n");
+    assert_eq!(line, None);
+    assert!(
+        reason.starts_with("defaults: unknown output format 'gif'"),
+        "{reason}"
+    );
+
+    let (_, reason) = error("[defaults]\ncolor = \"navy\"\n");
+    assert!(
+        reason.starts_with("defaults: invalid color 'navy'"),
+        "{reason}"
+    );
+
+    let (_, reason) = error("[defaults]\nscale = -1\n");
+    assert!(
+        reason.starts_with("defaults: invalid scale '-1'"),
+        "{reason}"
+    );
+
+    let (_, reason) = error("[[equation]]\nbody = \"x\"\nengine = \"troff\"\n");
+    assert_eq!(reason, "equation 1: invalid engine 'troff'");
+}
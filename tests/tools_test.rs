@@ -0,0 +1,25 @@
+use equation_processor::render::{find_tool, missing_tools, OutputFormat, RenderOptions};
+
+#[test]
+fn test_unknown_tool_is_not_found() {
+    assert!(find_tool("eqp-no-such-tool").is_none());
+}
+
+#[test]
+fn test_pdf_only_batches_do_not_need_pdftocairo() {
+    let options = RenderOptions {
+        formats: vec![OutputFormat::Pdf],
+        ..Default::default()
+    };
+    assert!(!missing_tools(&options).contains(&"pdftocairo"));
+    let missing = missing_tools(&RenderOptions::default());
+    assert!(missing
+        .iter()
+        .all(|tool| ["tectonic", "pdftocairo"].contains(tool)));
+}
+
+#[cfg(feature = "poppler")]
+#[test]
+fn test_poppler_builds_do_not_need_pdftocairo() {
+    assert!(!missing_tools(&RenderOptions::default()).contains(&"pdftocairo"));
+}
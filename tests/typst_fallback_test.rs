@@ -0,0 +1,39 @@
+#![cfg(feature = "typst-fallback")]
+
+use equation_processor::render::{RenderOptions, Renderer};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use equation_processor::typst::TypstRenderer;
+use equation_processor::EquationError;
+use std::fs;
+
+#[test]
+fn test_typst_fallback_writes_svg_without_latex() {
+    let out = TempOutput::new("typst_fallback");
+    let options = RenderOptions::builder().build();
+    let renderer = TypstRenderer::new(&options);
+    let mut eq = EquationBuilder::new("energy")
+        .body(r"E = \frac{m c^2}{\sqrt{1 - v^2}}")
+        .build();
+    eq.description = Some("mass-energy".into());
+    renderer.render(&eq, out.path()).unwrap();
+    let svg = fs::read_to_string(out.join("energy.svg")).unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("<desc>mass-energy</desc>"));
+    assert!(!out.join("energy.pdf").exists());
+}
+
+#[test]
+fn test_typst_fallback_rejects_untranslated_latex() {
+    let out = TempOutput::new("typst_fallback_untranslated");
+    let options = RenderOptions::default();
+    let eq = EquationBuilder::new("text")
+        .body(r"\intertext{so} x")
+        .build();
+    match TypstRenderer::new(&options).render(&eq, out.path()) {
+        Err(EquationError::TypstFailed { name, reason }) => {
+            assert_eq!(name, "text");
+            assert!(reason.contains(r"\intertext"), "{reason}");
+        }
+        other => panic!("expected a Typst failure, got {other:?}"),
+    }
+}
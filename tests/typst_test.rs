@@ -0,0 +1,78 @@
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use equation_processor::typst::{latex_to_typst, write_typst_snippets, TYPST_DIR, TYPST_MASTER};
+use equation_processor::EquationKind;
+use std::fs;
+
+#[test]
+fn test_latex_math_is_translated_to_typst() {
+    let cases = [
+        (r"E = mc^2", "E = m c^2"),
+        (
+            r"\[ \sum_{i=1}^{n} x_i^2 = \int_0^\infty e^{-t} \, dt \]",
+            "sum_(i=1)^n x_i^2 = integral_0^infinity e^(-t) thin d t",
+        ),
+        (
+            r"\left( \frac{a}{b} \right)^{10} \neq \sqrt[3]{\hat{y}}",
+            "(frac(a, b))^10 != root(3, hat(y))",
+        ),
+        (
+            r"\begin{pmatrix} a & b \\ c & d \end{pmatrix}",
+            r#"mat(delim: "(", a, b; c, d)"#,
+        ),
+        (
+            r"|x| = \begin{cases} x & \text{if } x \geq 0 \\ -x & \text{otherwise} \end{cases}",
+            r#"|x| = cases(x & "if " x >= 0, -x & "otherwise")"#,
+        ),
+        (
+            r"\begin{align} a &= b + c \label{eq:sum} \\ &= \mathbf{v} \cdot \mathrm{d}x \end{align}",
+            r"a &= b + c \ &= bold(v) dot.op upright(d) x",
+        ),
+        (
+            r"\operatorname{sgn}(x) \varphi \epsilon a/b",
+            r#"op("sgn") (x) phi epsilon.alt a slash b"#,
+        ),
+    ];
+    for (latex, typst) in cases {
+        let math = latex_to_typst(latex);
+        assert_eq!(math.source, typst, "{latex}");
+        assert!(math.untranslated.is_empty(), "{latex}");
+    }
+}
+
+#[test]
+fn test_untranslatable_commands_are_kept_as_text_and_listed() {
+    let math = latex_to_typst(r"\foo{x} + \begin{tabular} a \end{tabular} + \foo");
+    assert_eq!(math.source, r#""\\foo" x + a + "\\foo""#);
+    assert_eq!(math.untranslated, [r"\foo", r"\begin{tabular}"]);
+}
+
+#[test]
+fn test_typst_snippets_are_written_for_active_math_equations() {
+    let out = TempOutput::new("typst_export");
+    let equations = vec![
+        EquationBuilder::new("energy").body("E = mc^2").build(),
+        EquationBuilder::new("draft").inactive().build(),
+        EquationBuilder::new("water")
+            .body("2H2 + O2 -> 2H2O")
+            .kind(EquationKind::Chemistry)
+            .build(),
+        EquationBuilder::new("odd").body(r"\foo x").build(),
+    ];
+    let export = write_typst_snippets(&equations, out.path()).unwrap();
+    let dir = out.join(TYPST_DIR);
+    assert_eq!(export.master, dir.join(TYPST_MASTER));
+    assert_eq!(export.written, ["energy", "odd"]);
+    assert_eq!(export.skipped, ["water"]);
+    assert_eq!(
+        export.untranslated,
+        [("odd".to_string(), vec![r"\foo".to_string()])]
+    );
+
+    assert_eq!(
+        fs::read_to_string(dir.join("energy.typ")).unwrap(),
+        "$ E = m c^2 $ <eq:energy>\n"
+    );
+    let master = fs::read_to_string(&export.master).unwrap();
+    assert!(master.ends_with("#include \"energy.typ\"\n#include \"odd.typ\"\n"));
+    assert!(!dir.join("draft.typ").exists());
+}
@@ -0,0 +1,61 @@
+use equation_processor::report::{content_address, verify_outputs, write_index, Integrity};
+use equation_processor::test_util::{EquationBuilder, TempOutput};
+use std::fs;
+
+const SVG: &str = "<svg xmlns=\"http://www.w3.org/2000/svg\"><path d=\"M0 0\"/></svg>\n";
+
+#[test]
+fn test_verify_reports_missing_modified_and_corrupted() {
+    let out = TempOutput::new("verify_index");
+    let equations = vec![
+        EquationBuilder::new("a").build(),
+        EquationBuilder::new("b").build(),
+        EquationBuilder::new("c").build(),
+        EquationBuilder::new("d").build(),
+    ];
+    for name in ["a", "b", "c", "d"] {
+        fs::write(out.join(format!("{name}.svg")), SVG).unwrap();
+    }
+    fs::write(out.join("d.pdf"), "%PDF-1.5\n...\n%%EOF\n").unwrap();
+    write_index(&equations, out.path()).unwrap();
+    assert!(verify_outputs(out.path()).unwrap().is_empty());
+
+    fs::remove_file(out.join("a.svg")).unwrap();
+    fs::write(out.join("b.svg"), SVG.replace("M0 0", "M1 1")).unwrap();
+    fs::write(out.join("c.svg"), &SVG[..20]).unwrap();
+    let problems = verify_outputs(out.path()).unwrap();
+    let found: Vec<(&str, Integrity)> = problems
+        .iter()
+        .map(|p| (p.file.as_str(), p.problem))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("a.svg", Integrity::Missing),
+            ("b.svg", Integrity::Modified),
+            ("c.svg", Integrity::Corrupted),
+        ]
+    );
+    assert_eq!(problems[0].name, "a");
+}
+
+#[test]
+fn test_verify_content_addressed_names() {
+    let out = TempOutput::new("verify_manifest");
+    let equations = vec![EquationBuilder::new("a").build()];
+    fs::write(out.join("a.svg"), SVG).unwrap();
+    let manifest = content_address(&equations, out.path()).unwrap();
+    assert!(verify_outputs(out.path()).unwrap().is_empty());
+
+    let file = manifest.artifact("a", "svg").unwrap();
+    fs::write(out.join(file), SVG.replace("M0 0", "M2 2")).unwrap();
+    let problems = verify_outputs(out.path()).unwrap();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].problem, Integrity::Modified);
+}
+
+#[test]
+fn test_verify_needs_an_index() {
+    let out = TempOutput::new("verify_empty");
+    assert!(verify_outputs(out.path()).is_err());
+}
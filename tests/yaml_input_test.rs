@@ -0,0 +1,64 @@
+use equation_processor::parser::{detect_file_type, read_input, read_yaml_file, ParseOptions};
+use equation_processor::test_util::TempOutput;
+use equation_processor::{EquationError, Filetype};
+use std::fs;
+
+#[test]
+fn test_yaml_list_is_read_with_multiline_bodies() {
+    let out = TempOutput::new("yaml_input");
+    let path = out.join("equations.yaml");
+    fs::write(
+        &path,
+        r"- name: energy
+  body: E = mc^2
+  tags: [physics, exam]
+- name: maxwell
+  active: false
+  body: |
+    \nabla \cdot \mathbf{E}
+      = \frac{\rho}{\varepsilon_0}
+  width: 8cm
+- body: x^2
+  chem: false
+",
+    )
+    .unwrap();
+    assert!(matches!(detect_file_type(&path), Filetype::Yaml));
+    assert!(matches!(
+        detect_file_type(&out.join("more.yml")),
+        Filetype::Yaml
+    ));
+
+    let equations = read_input(&path, &ParseOptions::default()).unwrap();
+    assert_eq!(equations.len(), 3);
+    assert_eq!(equations[0].tags, ["physics", "exam"]);
+    assert!(!equations[1].active);
+    assert_eq!(
+        equations[1].body,
+        "\\nabla \\cdot \\mathbf{E}\n  = \\frac{\\rho}{\\varepsilon_0}"
+    );
+    assert_eq!(equations[1].display.width.as_deref(), Some("8cm"));
+    assert!(equations[2].active);
+    assert_eq!(equations[2].name, "default_equation");
+}
+
+#[test]
+fn test_yaml_errors_name_the_line_or_equation() {
+    let out = TempOutput::new("yaml_input_errors");
+    let path = out.join("bad.yml");
+    let error = |content: &str| {
+        fs::write(&path, content).unwrap();
+        match read_yaml_file(&path) {
+            Err(EquationError::ParseError { line, reason, .. }) => (line, reason),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    };
+
+    let (line, reason) = error("- name: energy\n  body: E = mc^2\n- name: force\n");
+    assert_eq!(line, Some(3));
+    assert!(reason.contains("missing field `body`"), "{reason}");
+
+    let (line, reason) = error("- body: x\n  engine: troff\n");
+    assert_eq!(line, None);
+    assert_eq!(reason, "equation 1: invalid engine 'troff'");
+}